@@ -77,6 +77,36 @@ pub struct SymbolStats {
     pub loaded_symbols: bool,
     /// If we tried to parse the symbols, but failed.
     pub corrupt_symbols: bool,
+    /// The outcome of looking up the module's symbols.
+    ///
+    /// Unlike `loaded_symbols` this distinguishes symbols that couldn't be found
+    /// from symbols that were found but couldn't be used.
+    pub status: SymbolStatus,
+}
+
+/// The outcome of looking up the symbols of a module.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum SymbolStatus {
+    /// No symbol file could be found for the module.
+    #[default]
+    NotFound,
+    /// A symbol file was found, but it couldn't be read or parsed.
+    ///
+    /// Contains a description of what went wrong.
+    LoadError(String),
+    /// The symbol file was found and loaded.
+    Loaded,
+}
+
+impl SymbolStatus {
+    /// A short, stable name for the status, suitable for machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolStatus::NotFound => "not_found",
+            SymbolStatus::LoadError(_) => "load_error",
+            SymbolStatus::Loaded => "loaded",
+        }
+    }
 }
 
 /// Statistics on pending symbols.
@@ -206,6 +236,19 @@ pub fn breakpad_sym_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
     })
 }
 
+/// Get the relative path at which a symbol store should hold the symbols for `module`.
+///
+/// This is the `<debug filename>/<debug identifier>/<debug filename>.sym` path
+/// described in [`breakpad_sym_lookup`], always using `/` as the separator. It's
+/// the same on every platform: the debug identifier is the PDB GUID and age for PE
+/// modules, and is derived from the build id or UUID for ELF and Mach-O modules
+/// (see [`Module::debug_identifier`]).
+///
+/// Returns `None` if the module has no debug filename or debug identifier.
+pub fn breakpad_sym_path(module: &(dyn Module + Sync)) -> Option<String> {
+    breakpad_sym_lookup(module).map(|lookup| lookup.cache_rel)
+}
+
 /// Returns a lookup for this module's extra debuginfo (pdb)
 pub fn extra_debuginfo_lookup(module: &(dyn Module + Sync)) -> Option<FileLookup> {
     let debug_file = module.debug_file()?;
@@ -404,6 +447,16 @@ impl SymbolSupplier for SimpleSymbolSupplier {
                     trace!("SimpleSymbolSupplier found file {}", test_path.display());
                     return Ok(test_path);
                 }
+                // Symbol stores may keep symbol files compressed.
+                if cfg!(feature = "gzip") && file_kind == FileKind::BreakpadSym {
+                    let mut gz_path = test_path.into_os_string();
+                    gz_path.push(".gz");
+                    let gz_path = PathBuf::from(gz_path);
+                    if fs::metadata(&gz_path).map(|m| m.is_file()).unwrap_or(false) {
+                        trace!("SimpleSymbolSupplier found file {}", gz_path.display());
+                        return Ok(gz_path);
+                    }
+                }
             }
         } else {
             trace!("SimpleSymbolSupplier could not build symbol_path");
@@ -467,6 +520,17 @@ pub trait FrameSymbolizer {
     /// Add an inline frame. This method can be called multiple times, in the
     /// order "outside to inside".
     fn add_inline_frame(&mut self, _name: &str, _file: Option<&str>, _line: Option<u32>) {}
+    /// Note that the function given to `set_function` came from a PUBLIC record
+    /// because no FUNC record covered the address. PUBLIC records have no size,
+    /// so this is a less reliable result.
+    fn set_function_is_public(&mut self) {}
+    /// Note that no symbol covered the address, so the function given to
+    /// `set_function` is just the nearest one before it. The address is `distance`
+    /// bytes past the end of that function.
+    ///
+    /// This only happens if the [`Symbolizer`] was configured with
+    /// [`Symbolizer::with_nearest_symbol`].
+    fn set_function_is_nearest(&mut self, _distance: u64) {}
 }
 
 pub trait FrameWalker {
@@ -503,6 +567,11 @@ pub struct SimpleFrame {
     pub function_base: Option<u64>,
     /// The size, in bytes, that this function's parameters take up on the stack.
     pub parameter_size: Option<u32>,
+    /// Whether `function` came from a PUBLIC record instead of a FUNC record.
+    pub function_is_public: bool,
+    /// If `function` is only the nearest FUNC before the instruction, how many bytes
+    /// past its end the instruction is.
+    pub nearest_function_distance: Option<u64>,
     /// The name of the source file in which the current instruction is executing.
     pub source_file: Option<String>,
     /// The 1-based index of the line number in `source_file` in which the current instruction is
@@ -536,6 +605,12 @@ impl FrameSymbolizer for SimpleFrame {
         self.source_line = Some(line);
         self.source_line_base = Some(base);
     }
+    fn set_function_is_public(&mut self) {
+        self.function_is_public = true;
+    }
+    fn set_function_is_nearest(&mut self, distance: u64) {
+        self.nearest_function_distance = Some(distance);
+    }
 }
 
 /// A type of file related to a module that you might want downloaded.
@@ -590,6 +665,10 @@ fn module_key(module: &(dyn Module + Sync)) -> ModuleKey {
 
 type CachedOperation<T, E> = Arc<tokio::sync::OnceCell<Result<T, E>>>;
 
+/// A function that rewrites the source file paths of symbols, see
+/// [`Symbolizer::with_file_path_mapper`].
+pub type FilePathMapper = Box<dyn Fn(&str) -> String + Send + Sync>;
+
 pub struct Symbolizer {
     /// Symbol supplier for locating symbols.
     supplier: Box<dyn SymbolSupplier + Send + Sync + 'static>,
@@ -600,6 +679,13 @@ pub struct Symbolizer {
     // way messier but not impossible.
     symbols: Mutex<HashMap<ModuleKey, CachedOperation<SymbolFile, SymbolError>>>,
     pending_stats: Mutex<PendingSymbolStats>,
+    /// Rewrites the source file paths of symbols.
+    file_path_mapper: Option<FilePathMapper>,
+    /// Cache of the results of `file_path_mapper`.
+    mapped_file_paths: Mutex<HashMap<String, String>>,
+    /// How far past the end of a function an address can be to be attributed to
+    /// it when no symbol covers it, if at all.
+    nearest_symbol_max_distance: Option<u64>,
 }
 
 impl Symbolizer {
@@ -609,9 +695,39 @@ impl Symbolizer {
             supplier: Box::new(supplier),
             symbols: Mutex::new(HashMap::new()),
             pending_stats: Mutex::default(),
+            file_path_mapper: None,
+            mapped_file_paths: Mutex::default(),
+            nearest_symbol_max_distance: None,
         }
     }
 
+    /// Rewrite the source file paths of symbols with `mapper`.
+    ///
+    /// Symbol files usually refer to source files by their path on the machine that built
+    /// them. This can be used to map them to something more useful, like a path relative
+    /// to the root of a repository. The mapper is applied to the source file of every
+    /// frame and inline frame this `Symbolizer` fills in, and is only called once for
+    /// each distinct path.
+    pub fn with_file_path_mapper<F>(mut self, mapper: F) -> Symbolizer
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.file_path_mapper = Some(Box::new(mapper));
+        self
+    }
+
+    /// Attribute addresses that no symbol covers to the nearest function before them.
+    ///
+    /// An address in a gap in the symbols (such as code the symbol dumper missed)
+    /// is then reported as being in the function that ends right before it, as long
+    /// as it's at most `max_distance` bytes past the end of that function. Such
+    /// results are approximate, so they're flagged with
+    /// [`FrameSymbolizer::set_function_is_nearest`].
+    pub fn with_nearest_symbol(mut self, max_distance: u64) -> Symbolizer {
+        self.nearest_symbol_max_distance = Some(max_distance);
+        self
+    }
+
     /// Helper method for non-minidump-using callers.
     ///
     /// Pass `debug_file` and `debug_id` describing a specific module,
@@ -679,7 +795,20 @@ impl Symbolizer {
             .unwrap()
             .as_ref()
             .map_err(|_| FillSymbolError {})?;
-        sym.fill_symbol(module, frame);
+        let fill = |frame: &mut dyn FrameSymbolizer| match self.nearest_symbol_max_distance {
+            Some(max_distance) => sym.fill_nearest_symbol(module, frame, max_distance),
+            None => sym.fill_symbol(module, frame),
+        };
+        if let Some(mapper) = &self.file_path_mapper {
+            let mut frame = MappedFrame {
+                frame,
+                mapper,
+                cache: &self.mapped_file_paths,
+            };
+            fill(&mut frame);
+        } else {
+            fill(frame);
+        }
         Ok(())
     }
 
@@ -699,6 +828,7 @@ impl Symbolizer {
                         stats.symbol_url = sym.url.clone();
                         stats.loaded_symbols = true;
                         stats.corrupt_symbols = false;
+                        stats.status = SymbolStatus::Loaded;
                     }
                     Err(SymbolError::NotFound) => {
                         stats.loaded_symbols = false;
@@ -706,12 +836,14 @@ impl Symbolizer {
                     Err(SymbolError::MissingDebugFileOrId) => {
                         stats.loaded_symbols = false;
                     }
-                    Err(SymbolError::LoadError(_)) => {
+                    Err(e @ SymbolError::LoadError(_)) => {
                         stats.loaded_symbols = false;
+                        stats.status = SymbolStatus::LoadError(e.to_string());
                     }
-                    Err(SymbolError::ParseError(..)) => {
+                    Err(e @ SymbolError::ParseError(..)) => {
                         stats.loaded_symbols = true;
                         stats.corrupt_symbols = true;
+                        stats.status = SymbolStatus::LoadError(e.to_string());
                     }
                 }
                 (leafname(&k.0).to_string(), stats)
@@ -777,6 +909,53 @@ impl Symbolizer {
     }
 }
 
+/// A `FrameSymbolizer` that rewrites source file paths before forwarding them to another one.
+struct MappedFrame<'a> {
+    frame: &'a mut (dyn FrameSymbolizer + Send),
+    mapper: &'a FilePathMapper,
+    cache: &'a Mutex<HashMap<String, String>>,
+}
+
+impl MappedFrame<'_> {
+    fn map_path(&self, file: &str) -> String {
+        if let Some(mapped) = self.cache.lock().unwrap().get(file) {
+            return mapped.clone();
+        }
+        // Don't hold the lock while the mapper runs, it may be slow. If another
+        // thread maps the same path meanwhile, whichever result is inserted first wins.
+        let mapped = (self.mapper)(file);
+        self.cache
+            .lock()
+            .unwrap()
+            .entry(file.to_owned())
+            .or_insert(mapped)
+            .clone()
+    }
+}
+
+impl FrameSymbolizer for MappedFrame<'_> {
+    fn get_instruction(&self) -> u64 {
+        self.frame.get_instruction()
+    }
+    fn set_function(&mut self, name: &str, base: u64, parameter_size: u32) {
+        self.frame.set_function(name, base, parameter_size);
+    }
+    fn set_source_file(&mut self, file: &str, line: u32, base: u64) {
+        let file = self.map_path(file);
+        self.frame.set_source_file(&file, line, base);
+    }
+    fn add_inline_frame(&mut self, name: &str, file: Option<&str>, line: Option<u32>) {
+        let file = file.map(|file| self.map_path(file));
+        self.frame.add_inline_frame(name, file.as_deref(), line);
+    }
+    fn set_function_is_public(&mut self) {
+        self.frame.set_function_is_public();
+    }
+    fn set_function_is_nearest(&mut self, distance: u64) {
+        self.frame.set_function_is_nearest(distance);
+    }
+}
+
 #[test]
 fn test_leafname() {
     assert_eq!(leafname("c:\\foo\\bar\\test.pdb"), "test.pdb");
@@ -886,6 +1065,35 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_breakpad_sym_path() {
+        // PE: the debug file is the pdb and the identifier includes its age.
+        let debug_id = DebugId::from_str("3249d99d-0c40-4931-8610-f4e4fb0b6936-1").unwrap();
+        let m = SimpleModule::new("C:\\build\\firefox.pdb", debug_id);
+        assert_eq!(
+            breakpad_sym_path(&m).unwrap(),
+            "firefox.pdb/3249D99D0C4049318610F4E4FB0B69361/firefox.sym"
+        );
+
+        // ELF: the identifier comes from the build id and has no age.
+        let debug_id = DebugId::from_str("e58f3d2c-6af1-31b8-4bbf-5ba5b4b9e5d7").unwrap();
+        let m = SimpleModule::new("/usr/lib/libxul.so", debug_id);
+        assert_eq!(
+            breakpad_sym_path(&m).unwrap(),
+            "libxul.so/E58F3D2C6AF131B84BBF5BA5B4B9E5D70/libxul.so.sym"
+        );
+
+        // Mach-O: the identifier is the LC_UUID and has no age.
+        let debug_id = DebugId::from_str("9c5ad4d7-4cb8-3a4e-8e4e-b0d6c1a2f1e3").unwrap();
+        let m = SimpleModule::new("/Applications/Firefox.app/Contents/MacOS/XUL", debug_id);
+        assert_eq!(
+            breakpad_sym_path(&m).unwrap(),
+            "XUL/9C5AD4D74CB83A4E8E4EB0D6C1A2F1E30/XUL.sym"
+        );
+
+        assert!(breakpad_sym_path(&SimpleModule::default()).is_none());
+    }
+
     fn mksubdirs(path: &Path, dirs: &[&str]) -> Vec<PathBuf> {
         dirs.iter()
             .map(|dir| {
@@ -1038,4 +1246,151 @@ FUNC 1000 30 10 another func
             .await
             .is_none());
     }
+
+    #[tokio::test]
+    async fn test_symbolizer_file_path_mapper() {
+        let t = tempfile::tempdir().unwrap();
+        let path = t.path();
+
+        let calls = Arc::new(Mutex::new(0));
+        let mapper_calls = calls.clone();
+        let supplier = SimpleSymbolSupplier::new(vec![PathBuf::from(path)]);
+        let symbolizer = Symbolizer::new(supplier).with_file_path_mapper(move |file| {
+            *mapper_calls.lock().unwrap() += 1;
+            match file.strip_prefix("/builds/worker/checkouts/") {
+                Some(relative) => relative.to_owned(),
+                None => file.to_owned(),
+            }
+        });
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let m1 = SimpleModule::new("foo.pdb", debug_id);
+        write_symbol_file(
+            &path.join("foo.pdb/ABCD1234ABCD1234ABCDABCD12345678a/foo.sym"),
+            b"MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a foo
+FILE 1 /builds/worker/checkouts/src/foo.c
+FILE 2 /usr/include/stdio.h
+FUNC 1000 30 10 some func
+1000 10 100 1
+1010 20 7 2
+",
+        );
+        let mut f1 = SimpleFrame::with_instruction(0x1000);
+        symbolizer.fill_symbol(&m1, &mut f1).await.unwrap();
+        assert_eq!(f1.source_file.unwrap(), "src/foo.c");
+        assert_eq!(f1.source_line.unwrap(), 100);
+
+        let mut f2 = SimpleFrame::with_instruction(0x1010);
+        symbolizer.fill_symbol(&m1, &mut f2).await.unwrap();
+        assert_eq!(f2.source_file.unwrap(), "/usr/include/stdio.h");
+
+        // The mapping of each path is cached.
+        let mut f3 = SimpleFrame::with_instruction(0x1004);
+        symbolizer.fill_symbol(&m1, &mut f3).await.unwrap();
+        assert_eq!(f3.source_file.unwrap(), "src/foo.c");
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_symbolizer_nearest_symbol() {
+        let t = tempfile::tempdir().unwrap();
+        let path = t.path();
+
+        let supplier = SimpleSymbolSupplier::new(vec![PathBuf::from(path)]);
+        let symbolizer = Symbolizer::new(supplier).with_nearest_symbol(0x100);
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let m1 = SimpleModule::new("foo.pdb", debug_id);
+        write_symbol_file(
+            &path.join("foo.pdb/ABCD1234ABCD1234ABCDABCD12345678a/foo.sym"),
+            b"MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a foo
+FUNC 1000 30 10 some func
+",
+        );
+        let mut f1 = SimpleFrame::with_instruction(0x1050);
+        symbolizer.fill_symbol(&m1, &mut f1).await.unwrap();
+        assert_eq!(f1.function.unwrap(), "some func");
+        assert_eq!(f1.nearest_function_distance, Some(0x20));
+
+        let mut f2 = SimpleFrame::with_instruction(0x1200);
+        symbolizer.fill_symbol(&m1, &mut f2).await.unwrap();
+        assert_eq!(f2.function, None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_symbolizer_gzip() {
+        use flate2::write::GzEncoder;
+
+        let t = tempfile::tempdir().unwrap();
+        let path = t.path();
+
+        let supplier = SimpleSymbolSupplier::new(vec![PathBuf::from(path)]);
+        let symbolizer = Symbolizer::new(supplier);
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        let m1 = SimpleModule::new("foo.pdb", debug_id);
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(
+                b"MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a foo
+FILE 1 foo.c
+FUNC 1000 30 10 some func
+1000 30 100 1
+",
+            )
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+        write_symbol_file(
+            &path.join("foo.pdb/ABCD1234ABCD1234ABCDABCD12345678a/foo.sym.gz"),
+            &compressed,
+        );
+
+        let mut f1 = SimpleFrame::with_instruction(0x1010);
+        symbolizer.fill_symbol(&m1, &mut f1).await.unwrap();
+        assert_eq!(f1.function.unwrap(), "some func");
+        assert_eq!(f1.source_file.unwrap(), "foo.c");
+        assert_eq!(f1.source_line.unwrap(), 100);
+
+        // The magic is what matters, not the extension.
+        let symbols = SymbolFile::from_bytes(&compressed).unwrap();
+        assert_eq!(symbols.functions.ranges_values().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_symbolizer_stats_status() {
+        let t = tempfile::tempdir().unwrap();
+        let path = t.path();
+
+        let supplier = SimpleSymbolSupplier::new(vec![PathBuf::from(path)]);
+        let symbolizer = Symbolizer::new(supplier);
+        let debug_id = DebugId::from_str("abcd1234-abcd-1234-abcd-abcd12345678-a").unwrap();
+        // Stats are keyed on the code file.
+        let module = |name: &str| SimpleModule {
+            code_file: Some(name.replace(".pdb", ".dll")),
+            ..SimpleModule::new(name, debug_id)
+        };
+        let good = module("good.pdb");
+        let missing = module("missing.pdb");
+        let corrupt = module("corrupt.pdb");
+        write_symbol_file(
+            &path.join("good.pdb/ABCD1234ABCD1234ABCDABCD12345678a/good.sym"),
+            b"MODULE Linux x86 ABCD1234ABCD1234ABCDABCD12345678a good\n",
+        );
+        write_bad_symbol_file(
+            &path.join("corrupt.pdb/ABCD1234ABCD1234ABCDABCD12345678a/corrupt.sym"),
+        );
+
+        for module in [&good, &missing, &corrupt] {
+            let mut frame = SimpleFrame::with_instruction(0x1010);
+            let _ = symbolizer.fill_symbol(module, &mut frame).await;
+        }
+
+        let stats = symbolizer.stats();
+        assert_eq!(stats["good.dll"].status, SymbolStatus::Loaded);
+        assert_eq!(stats["missing.dll"].status, SymbolStatus::NotFound);
+        assert!(
+            matches!(stats["corrupt.dll"].status, SymbolStatus::LoadError(_)),
+            "{:?}",
+            stats["corrupt.dll"].status
+        );
+        assert!(stats["corrupt.dll"].corrupt_symbols);
+    }
 }
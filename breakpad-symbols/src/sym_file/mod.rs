@@ -4,8 +4,9 @@ use crate::{FrameSymbolizer, FrameWalker, Module, SymbolError};
 
 pub use crate::sym_file::types::*;
 pub use parser::SymbolParser;
+use range_map::Range;
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read};
 use std::ops::Deref;
 use std::path::Path;
 use tracing::trace;
@@ -70,6 +71,13 @@ pub mod walker;
 static MAX_BUFFER_CAPACITY: usize = 1024 * 160;
 static INITIAL_BUFFER_CAPACITY: usize = 1024 * 10;
 
+/// The magic bytes gzip-compressed data starts with.
+static GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
 impl SymbolFile {
     /// Parse a SymbolFile from the given Reader.
     ///
@@ -323,21 +331,92 @@ impl SymbolFile {
     }
 
     // Parse a SymbolFile from bytes.
+    //
+    // With the `gzip` feature, gzip-compressed bytes are decompressed first.
     pub fn from_bytes(bytes: &[u8]) -> Result<SymbolFile, SymbolError> {
+        if is_gzip(bytes) {
+            return Self::parse_gzip(bytes);
+        }
         Self::parse(bytes, |_| ())
     }
 
     // Parse a SymbolFile from a file.
+    //
+    // With the `gzip` feature, gzip-compressed files (like `.sym.gz`) are
+    // decompressed first.
     pub fn from_file(path: &Path) -> Result<SymbolFile, SymbolError> {
-        let file = File::open(path)?;
+        let mut file = BufReader::new(File::open(path)?);
+        if is_gzip(file.fill_buf()?) {
+            return Self::parse_gzip(file);
+        }
         Self::parse(file, |_| ())
     }
 
+    #[cfg(feature = "gzip")]
+    fn parse_gzip<R: Read>(input_reader: R) -> Result<SymbolFile, SymbolError> {
+        Self::parse(flate2::read::GzDecoder::new(input_reader), |_| ())
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn parse_gzip<R: Read>(_input_reader: R) -> Result<SymbolFile, SymbolError> {
+        Err(SymbolError::ParseError(
+            "SymbolFile is gzip-compressed (enable the gzip feature to read it)",
+            0,
+        ))
+    }
+
     /// Fill in as much source information for `frame` as possible.
+    ///
+    /// Addresses in the symbol file are module-relative, so the frame's instruction is
+    /// looked up at `instruction - module.base_address()`, the module's actual load
+    /// address. The addresses reported back to `frame` are likewise rebased onto it.
     pub fn fill_symbol(&self, module: &dyn Module, frame: &mut dyn FrameSymbolizer) {
+        self.fill_exact_symbol(module, frame);
+    }
+
+    /// Like [`SymbolFile::fill_symbol`], but if no symbol covers the address, settle
+    /// for the nearest FUNC before it.
+    ///
+    /// This is only done if the address is at most `max_distance` bytes past the end
+    /// of that FUNC, and the frame is told the distance with
+    /// [`FrameSymbolizer::set_function_is_nearest`].
+    pub fn fill_nearest_symbol(
+        &self,
+        module: &dyn Module,
+        frame: &mut dyn FrameSymbolizer,
+        max_distance: u64,
+    ) {
+        if self.fill_exact_symbol(module, frame) || frame.get_instruction() < module.base_address()
+        {
+            return;
+        }
+        let addr = frame.get_instruction() - module.base_address();
+
+        let funcs_slice = self.functions.ranges_values().as_slice();
+        let func = match funcs_slice
+            .partition_point(|(range, _)| range.start <= addr)
+            .checked_sub(1)
+        {
+            Some(idx) => &funcs_slice[idx].1,
+            None => return,
+        };
+        let distance = match addr.checked_sub(func.address + func.size as u64) {
+            Some(distance) if distance <= max_distance => distance,
+            _ => return,
+        };
+        frame.set_function(
+            &func.name,
+            func.address + module.base_address(),
+            func.parameter_size,
+        );
+        frame.set_function_is_nearest(distance);
+    }
+
+    /// Fill in the symbol that covers the frame's address, returning whether there was one.
+    fn fill_exact_symbol(&self, module: &dyn Module, frame: &mut dyn FrameSymbolizer) -> bool {
         // Look for a FUNC covering the address first.
         if frame.get_instruction() < module.base_address() {
-            return;
+            return false;
         }
         let addr = frame.get_instruction() - module.base_address();
         if let Some(func) = self.functions.get(addr) {
@@ -431,6 +510,7 @@ impl SymbolFile {
                     }
                 }
             }
+            true
         } else if let Some(public) = self.find_nearest_public(addr) {
             // We couldn't find a valid FUNC record, but we could find a PUBLIC record.
             // Unfortauntely, PUBLIC records don't have end-points, so this could be
@@ -475,7 +555,7 @@ impl SymbolFile {
                 if public.address <= prev_func.1.address {
                     // This PUBLIC is truncated by a FUNC before it gets to `addr`,
                     // so we shouldn't use it.
-                    return;
+                    return false;
                 }
             }
 
@@ -485,9 +565,17 @@ impl SymbolFile {
                 public.address + module.base_address(),
                 public.parameter_size,
             );
+            frame.set_function_is_public();
+            true
+        } else {
+            false
         }
     }
 
+    /// Unwind `walker`'s frame with the STACK records of the symbol file.
+    ///
+    /// Like [`SymbolFile::fill_symbol`], this looks up the instruction relative to the
+    /// module's load address.
     pub fn walk_frame(&self, module: &dyn Module, walker: &mut dyn FrameWalker) -> Option<()> {
         if walker.get_instruction() < module.base_address() {
             return None;
@@ -525,6 +613,27 @@ impl SymbolFile {
     pub fn find_nearest_public(&self, addr: u64) -> Option<&PublicSymbol> {
         self.publics.iter().rev().find(|&p| p.address <= addr)
     }
+
+    /// Get all the `SourceLine`s that intersect the address range `start..end`, in address order.
+    ///
+    /// Lines that only partially overlap the range are included. An empty range has no lines.
+    pub fn lines_for_range(&self, start: u64, end: u64) -> impl Iterator<Item = &SourceLine> {
+        // `RangeMap` ranges are inclusive, so convert to that up front.
+        let range = (start < end).then(|| Range::new(start, end - 1));
+        self.functions
+            .ranges_values()
+            .filter(
+                move |(func_range, _)| matches!(range, Some(range) if range.intersects(func_range)),
+            )
+            .flat_map(move |(_, func)| {
+                func.lines
+                    .ranges_values()
+                    .filter(move |(line_range, _)| {
+                        matches!(range, Some(range) if range.intersects(line_range))
+                    })
+                    .map(|(_, line)| line)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -602,6 +711,115 @@ mod test {
         assert_eq!(sym.functions.get(0x1001).unwrap().name, "another func");
     }
 
+    #[test]
+    fn test_fill_symbol_public_fallback() {
+        let sym = SymbolFile::from_bytes(
+            b"MODULE Linux x86 ffff0000 bar
+FUNC 1000 30 10 first func
+PUBLIC 1100 8 public in gap
+FUNC 1200 30 10 second func
+",
+        )
+        .unwrap();
+        let module = crate::SimpleModule::default();
+
+        // Covered by a FUNC.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1010);
+        sym.fill_symbol(&module, &mut frame);
+        assert_eq!(frame.function.as_deref(), Some("first func"));
+        assert!(!frame.function_is_public);
+
+        // In the gap between the FUNCs, after the PUBLIC.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1150);
+        sym.fill_symbol(&module, &mut frame);
+        assert_eq!(frame.function.as_deref(), Some("public in gap"));
+        assert_eq!(frame.function_base, Some(0x1100));
+        assert_eq!(frame.parameter_size, Some(8));
+        assert!(frame.function_is_public);
+
+        // In the gap between the FUNCs, before the PUBLIC.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1050);
+        sym.fill_symbol(&module, &mut frame);
+        assert_eq!(frame.function, None);
+        assert!(!frame.function_is_public);
+    }
+
+    #[test]
+    fn test_fill_nearest_symbol() {
+        let sym = SymbolFile::from_bytes(
+            b"MODULE Linux x86 ffff0000 bar
+FUNC 1000 30 10 first func
+FUNC 1200 30 4 last func
+",
+        )
+        .unwrap();
+        let module = crate::SimpleModule::default();
+
+        // Exact matches aren't approximate.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1210);
+        sym.fill_nearest_symbol(&module, &mut frame, 0x200);
+        assert_eq!(frame.function.as_deref(), Some("last func"));
+        assert_eq!(frame.nearest_function_distance, None);
+
+        // In the gap after the last FUNC.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1350);
+        sym.fill_nearest_symbol(&module, &mut frame, 0x200);
+        assert_eq!(frame.function.as_deref(), Some("last func"));
+        assert_eq!(frame.function_base, Some(0x1200));
+        assert_eq!(frame.parameter_size, Some(4));
+        assert_eq!(frame.nearest_function_distance, Some(0x120));
+        assert!(!frame.function_is_public);
+
+        // Too far past the end.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1431);
+        sym.fill_nearest_symbol(&module, &mut frame, 0x200);
+        assert_eq!(frame.function, None);
+        assert_eq!(frame.nearest_function_distance, None);
+
+        // Before every FUNC.
+        let mut frame = crate::SimpleFrame::with_instruction(0x800);
+        sym.fill_nearest_symbol(&module, &mut frame, 0x200);
+        assert_eq!(frame.function, None);
+
+        // Plain `fill_symbol` leaves gaps alone.
+        let mut frame = crate::SimpleFrame::with_instruction(0x1350);
+        sym.fill_symbol(&module, &mut frame);
+        assert_eq!(frame.function, None);
+    }
+
+    #[test]
+    fn test_lines_for_range() {
+        let sym = SymbolFile::from_bytes(
+            b"MODULE Linux x86 ffff0000 bar
+FILE 0 foo.c
+FUNC 1000 30 0 first func
+1000 10 1 0
+1010 10 2 0
+1020 10 3 0
+FUNC 2000 20 0 second func
+2000 10 10 0
+2010 10 11 0
+",
+        )
+        .unwrap();
+        let lines = |start, end| {
+            sym.lines_for_range(start, end)
+                .map(|line| line.line)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lines(0x1000, 0x1030), [1, 2, 3]);
+        // Partial overlaps at either end are included.
+        assert_eq!(lines(0x1018, 0x1021), [2, 3]);
+        // Across functions, and past the end of the last one.
+        assert_eq!(lines(0x1020, 0x3000), [3, 10, 11]);
+        // In the gap between functions.
+        assert_eq!(lines(0x1030, 0x2000), Vec::<u32>::new());
+        // Empty ranges.
+        assert_eq!(lines(0x1010, 0x1010), Vec::<u32>::new());
+        assert_eq!(lines(0x1020, 0x1010), Vec::<u32>::new());
+    }
+
     #[test]
     fn test_symbolfile_from_bytes_with_lf() {
         test_symbolfile_from_bytes(
@@ -77,9 +77,46 @@ pub struct MINIDUMP_HEADER {
     pub stream_directory_rva: RVA,
     pub checksum: u32,
     pub time_date_stamp: u32,
+    /// The kind of information the minidump was asked to capture, see [`MinidumpType`].
     pub flags: u64,
 }
 
+bitflags! {
+    /// Values for [`MINIDUMP_HEADER::flags`], which describe what the minidump captured.
+    ///
+    /// An empty set means `MiniDumpNormal`: just the basics that are needed for a
+    /// stack trace. This matches the [Microsoft enum][msdn] `MINIDUMP_TYPE`.
+    ///
+    /// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ne-minidumpapiset-minidump_type
+    pub struct MinidumpType: u64 {
+        const MiniDumpWithDataSegs                   = 0x00000001;
+        const MiniDumpWithFullMemory                 = 0x00000002;
+        const MiniDumpWithHandleData                 = 0x00000004;
+        const MiniDumpFilterMemory                   = 0x00000008;
+        const MiniDumpScanMemory                     = 0x00000010;
+        const MiniDumpWithUnloadedModules            = 0x00000020;
+        const MiniDumpWithIndirectlyReferencedMemory = 0x00000040;
+        const MiniDumpFilterModulePaths              = 0x00000080;
+        const MiniDumpWithProcessThreadData          = 0x00000100;
+        const MiniDumpWithPrivateReadWriteMemory     = 0x00000200;
+        const MiniDumpWithoutOptionalData            = 0x00000400;
+        const MiniDumpWithFullMemoryInfo             = 0x00000800;
+        const MiniDumpWithThreadInfo                 = 0x00001000;
+        const MiniDumpWithCodeSegs                   = 0x00002000;
+        const MiniDumpWithoutAuxiliaryState          = 0x00004000;
+        const MiniDumpWithFullAuxiliaryState         = 0x00008000;
+        const MiniDumpWithPrivateWriteCopyMemory     = 0x00010000;
+        const MiniDumpIgnoreInaccessibleMemory       = 0x00020000;
+        const MiniDumpWithTokenInformation           = 0x00040000;
+        const MiniDumpWithModuleHeaders              = 0x00080000;
+        const MiniDumpFilterTriage                   = 0x00100000;
+        const MiniDumpWithAvxXStateContext           = 0x00200000;
+        const MiniDumpWithIptTrace                   = 0x00400000;
+        const MiniDumpScanInaccessiblePartialPages   = 0x00800000;
+        const MiniDumpFilterWriteCombinedMemory      = 0x01000000;
+    }
+}
+
 /// A location within a minidump file comprised of an offset and a size.
 ///
 /// This struct matches the [Microsoft struct][msdn] of the same name.
@@ -210,6 +247,9 @@ pub enum MINIDUMP_STREAM_TYPE {
     CommentStreamA = 10,
     CommentStreamW = 11,
     HandleDataStream = 12,
+    /// Dynamic function tables (unwinding information) of the process
+    ///
+    /// See [`MINIDUMP_FUNCTION_TABLE_STREAM`].
     FunctionTable = 13,
     /// The list of executable modules from the process that were unloaded by the time of the crash
     ///
@@ -231,8 +271,17 @@ pub enum MINIDUMP_STREAM_TYPE {
     ///
     /// See ['MINIDUMP_MEMORY_INFO_LIST'].
     MemoryInfoListStream = 16,
+    /// Extra information about threads
+    ///
+    /// See [`MINIDUMP_THREAD_INFO_LIST`].
     ThreadInfoListStream = 17,
+    /// Handle operations recorded by Application Verifier
+    ///
+    /// See ['MINIDUMP_HANDLE_OPERATION_LIST'].
     HandleOperationListStream = 18,
+    /// Access tokens of the process
+    ///
+    /// See [`MINIDUMP_TOKEN_INFO_LIST`].
     TokenStream = 19,
     JavaScriptDataStream = 20,
     SystemMemoryInfoStream = 21,
@@ -319,6 +368,53 @@ impl From<MINIDUMP_STREAM_TYPE> for u32 {
     }
 }
 
+/// The header of a list of extra information about threads in a minidump
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::ThreadInfoListStream`]. The individual
+/// [`MINIDUMP_THREAD_INFO`] entries follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info_list
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO_LIST {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each entry in the list
+    pub size_of_entry: u32,
+    /// The number of entries in the list
+    pub number_of_entries: u32,
+}
+
+/// Extra information about a thread, found in the ThreadInfoListStream
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_thread_info
+#[derive(Debug, Clone, Default, PartialEq, Eq, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_THREAD_INFO {
+    /// The identifier of the thread
+    pub thread_id: u32,
+    /// Flags describing the state of the thread when it was dumped
+    pub dump_flags: u32,
+    /// An `HRESULT` describing why the thread couldn't be dumped, if it couldn't
+    pub dump_error: u32,
+    /// The exit code of the thread
+    pub exit_status: u32,
+    /// When the thread was created, as a Windows `FILETIME`
+    pub create_time: u64,
+    /// When the thread exited, as a Windows `FILETIME`
+    pub exit_time: u64,
+    /// The time spent executing in kernel mode, in 100-nanosecond units
+    pub kernel_time: u64,
+    /// The time spent executing in user mode, in 100-nanosecond units
+    pub user_time: u64,
+    /// The address the thread started executing at
+    pub start_address: u64,
+    /// The processor affinity mask of the thread
+    pub affinity: u64,
+}
+
 /// The name of a thread, found in the ThreadNamesStream.
 #[derive(Debug, Clone, Default, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_THREAD_NAME {
@@ -416,6 +512,88 @@ pub const VS_FFI_SIGNATURE: u32 = 0xfeef04bd;
 /// The expected value of `VS_FIXEDFILEINFO.struct_version`
 pub const VS_FFI_STRUCVERSION: u32 = 0x00010000;
 
+bitflags! {
+    /// Flags in [`VS_FIXEDFILEINFO::file_flags`]
+    ///
+    /// Only the bits set in [`VS_FIXEDFILEINFO::file_flags_mask`] are meaningful.
+    pub struct VersionFileFlags: u32 {
+        /// The file contains debugging information or is compiled with debugging features enabled
+        const VS_FF_DEBUG = 0x00000001;
+        /// The file is a development version, not a commercially released product
+        const VS_FF_PRERELEASE = 0x00000002;
+        /// The file has been modified and is not identical to the original shipping file
+        const VS_FF_PATCHED = 0x00000004;
+        /// The file was not built using standard release procedures
+        const VS_FF_PRIVATEBUILD = 0x00000008;
+        /// The file's version structure was created dynamically
+        const VS_FF_INFOINFERRED = 0x00000010;
+        /// The file is a variation of the normal file of the same version number
+        const VS_FF_SPECIALBUILD = 0x00000020;
+    }
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_os`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum VersionFileOs {
+    VOS_UNKNOWN = 0x00000000,
+    VOS_DOS = 0x00010000,
+    VOS_OS216 = 0x00020000,
+    VOS_OS232 = 0x00030000,
+    VOS_NT = 0x00040000,
+    VOS__WINDOWS16 = 0x00000001,
+    VOS__PM16 = 0x00000002,
+    VOS__PM32 = 0x00000003,
+    VOS__WINDOWS32 = 0x00000004,
+    VOS_DOS_WINDOWS16 = 0x00010001,
+    VOS_DOS_WINDOWS32 = 0x00010004,
+    VOS_OS216_PM16 = 0x00020002,
+    VOS_OS232_PM32 = 0x00030003,
+    VOS_NT_WINDOWS32 = 0x00040004,
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_type`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum VersionFileType {
+    VFT_UNKNOWN = 0x00000000,
+    VFT_APP = 0x00000001,
+    VFT_DLL = 0x00000002,
+    VFT_DRV = 0x00000003,
+    VFT_FONT = 0x00000004,
+    VFT_VXD = 0x00000005,
+    VFT_STATIC_LIB = 0x00000007,
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_subtype`] for [`VersionFileType::VFT_DRV`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum VersionFileSubtypeDriver {
+    VFT2_UNKNOWN = 0x00000000,
+    VFT2_DRV_PRINTER = 0x00000001,
+    VFT2_DRV_KEYBOARD = 0x00000002,
+    VFT2_DRV_LANGUAGE = 0x00000003,
+    VFT2_DRV_DISPLAY = 0x00000004,
+    VFT2_DRV_MOUSE = 0x00000005,
+    VFT2_DRV_NETWORK = 0x00000006,
+    VFT2_DRV_SYSTEM = 0x00000007,
+    VFT2_DRV_INSTALLABLE = 0x00000008,
+    VFT2_DRV_SOUND = 0x00000009,
+    VFT2_DRV_COMM = 0x0000000a,
+    VFT2_DRV_INPUTMETHOD = 0x0000000b,
+    VFT2_DRV_VERSIONED_PRINTER = 0x0000000c,
+}
+
+/// Known values of [`VS_FIXEDFILEINFO::file_subtype`] for [`VersionFileType::VFT_FONT`]
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum VersionFileSubtypeFont {
+    VFT2_UNKNOWN = 0x00000000,
+    VFT2_FONT_RASTER = 0x00000001,
+    VFT2_FONT_VECTOR = 0x00000002,
+    VFT2_FONT_TRUETYPE = 0x00000003,
+}
+
 /// Known values for the `signature` field of CodeView records
 ///
 /// In addition to the two CodeView record formats used for linking
@@ -1932,11 +2110,11 @@ bitflags! {
 /// [fmt]: https://chromium.googlesource.com/breakpad/breakpad/+/88d8114fda3e4a7292654bd6ac0c34d6c88a8121/src/google_breakpad/common/minidump_format.h#998
 #[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
 pub struct MINIDUMP_ASSERTION_INFO {
-    /// The assertion that failed, as a 0-terminated UTF16-LE string
+    /// The assertion that failed, as a 0-terminated UTF-16 string
     pub expression: [u16; 128],
-    /// The function containing the assertion, as a 0-terminated UTF16-LE string
+    /// The function containing the assertion, as a 0-terminated UTF-16 string
     pub function: [u16; 128],
-    /// The source file containing the assertion, as a 0-terminated UTF16-LE string
+    /// The source file containing the assertion, as a 0-terminated UTF-16 string
     pub file: [u16; 128],
     /// The line number in [`file`] containing the assertion
     pub line: u32,
@@ -1956,6 +2134,184 @@ pub enum AssertionType {
     PureVirtualCall = 2,
 }
 
+/// The header of a list of handle operations in a minidump
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::HandleOperationListStream`]. The individual
+/// [`AVRF_HANDLE_OPERATION`] entries follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_handle_operation_list
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_HANDLE_OPERATION_LIST {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each entry in the list
+    pub size_of_entry: u32,
+    /// The number of entries in the list
+    pub number_of_entries: u32,
+    pub reserved: u32,
+}
+
+/// The maximum number of return addresses recorded in an [`AVRF_BACKTRACE_INFORMATION`].
+///
+/// The struct spells this out as a literal because the scroll derives can't take a constant.
+pub const AVRF_MAX_TRACES: usize = 32;
+
+/// A backtrace captured by Application Verifier
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/avrfsdk/ns-avrfsdk-avrf_backtrace_information
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_BACKTRACE_INFORMATION {
+    /// The number of valid entries in `return_addresses`
+    pub depth: u32,
+    /// The index of this backtrace in Application Verifier's trace database
+    pub index: u32,
+    /// The return addresses of the backtrace, innermost first
+    pub return_addresses: [u64; 32], // AVRF_MAX_TRACES
+}
+
+/// An operation on a handle, as recorded by Application Verifier
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/avrfsdk/ns-avrfsdk-avrf_handle_operation
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct AVRF_HANDLE_OPERATION {
+    /// The handle that was operated on
+    pub handle: u64,
+    /// The id of the process that performed the operation
+    pub process_id: u32,
+    /// The id of the thread that performed the operation
+    pub thread_id: u32,
+    /// The kind of operation
+    ///
+    /// See [`HandleTraceOperation`] for valid values.
+    pub operation_type: u32,
+    pub spare0: u32,
+    /// The backtrace of the thread at the time of the operation
+    pub back_trace_information: AVRF_BACKTRACE_INFORMATION,
+}
+
+/// Known values of [`AVRF_HANDLE_OPERATION::operation_type`]
+///
+/// This matches the Microsoft `eHANDLE_TRACE_OPERATIONS` enum.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Primitive)]
+pub enum HandleTraceOperation {
+    OperationDbUnused = 0,
+    /// The handle was opened
+    OperationDbOpen = 1,
+    /// The handle was closed
+    OperationDbClose = 2,
+    /// The handle was referenced while invalid
+    OperationDbBadRef = 3,
+}
+
+/// The header of a list of access tokens in a minidump
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::TokenStream`]. Variable-length entries,
+/// each starting with a [`MINIDUMP_TOKEN_INFO_HEADER`], follow this header in the stream.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_token_info_list
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_LIST {
+    /// The size of the whole list, in bytes
+    pub token_list_size: u32,
+    /// The number of entries in the list
+    pub token_list_entries: u32,
+    /// The size of this header
+    pub list_header_size: u32,
+    /// The size of the header of each entry
+    pub element_header_size: u32,
+}
+
+/// The header of an access token entry in a minidump
+///
+/// The token's data immediately follows this header.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_token_info_header
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_TOKEN_INFO_HEADER {
+    /// The size of this entry (header and data), in bytes
+    pub token_size: u32,
+    /// The id of the token
+    pub token_id: u32,
+    /// The handle of the token
+    pub token_handle: u64,
+}
+
+/// The header of a list of function tables in a minidump
+///
+/// This is the format of the [`MINIDUMP_STREAM_TYPE::FunctionTable`] stream. `size_of_align_pad`
+/// bytes of padding follow this header, and then `number_of_descriptors` function tables. Each
+/// table consists of a [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`], an OS-specific native descriptor
+/// (`DYNAMIC_FUNCTION_TABLE` on Windows), the table's function entries, and finally the
+/// descriptor's own alignment padding.
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_function_table_stream
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_STREAM {
+    /// The size of this header
+    pub size_of_header: u32,
+    /// The size of each [`MINIDUMP_FUNCTION_TABLE_DESCRIPTOR`]
+    pub size_of_descriptor: u32,
+    /// The size of each native descriptor
+    pub size_of_native_descriptor: u32,
+    /// The size of each function entry
+    ///
+    /// On x86-64 the function entries are [`IMAGE_RUNTIME_FUNCTION_ENTRY`].
+    pub size_of_function_entry: u32,
+    /// The number of function tables in the stream
+    pub number_of_descriptors: u32,
+    /// The number of bytes of padding following this header
+    pub size_of_align_pad: u32,
+}
+
+/// A function table in a minidump
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/minidumpapiset/ns-minidumpapiset-minidump_function_table_descriptor
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct MINIDUMP_FUNCTION_TABLE_DESCRIPTOR {
+    /// The lowest address of the functions in the table
+    pub minimum_address: u64,
+    /// The highest address of the functions in the table
+    pub maximum_address: u64,
+    /// The address that the function entries' addresses are relative to
+    pub base_address: u64,
+    /// The number of function entries in the table
+    pub entry_count: u32,
+    /// The number of bytes of padding following this table
+    pub size_of_align_pad: u32,
+}
+
+/// An x86-64 function entry of a function table or of a PE image's `.pdata` section
+///
+/// All addresses are relative to the base address of the table (or image).
+///
+/// This struct matches the [Microsoft struct][msdn] of the same name.
+///
+/// [msdn]: https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-image_runtime_function_entry
+#[derive(Debug, Clone, Pread, Pwrite, SizeWith)]
+pub struct IMAGE_RUNTIME_FUNCTION_ENTRY {
+    /// The address of the start of the function
+    pub begin_address: u32,
+    /// The address of the end of the function
+    pub end_address: u32,
+    /// The address of the function's `UNWIND_INFO`
+    pub unwind_info_address: u32,
+}
+
 /// Dynamic linker information for a shared library on 32-bit Linux
 ///
 /// This is functionally equivalent to the data in `struct link_map` defined in <link.h>.
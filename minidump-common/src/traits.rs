@@ -10,6 +10,11 @@ use std::fmt::Debug;
 /// An executable or shared library loaded in a process.
 pub trait Module {
     /// The base address of this code module as it was loaded by the process.
+    ///
+    /// This is the actual load address, which for position-independent or ASLR'd
+    /// images generally differs from the preferred base recorded in the image itself.
+    /// Addresses in symbol files are relative to the start of the image, so they are
+    /// always translated to and from process addresses with this value.
     fn base_address(&self) -> u64;
     /// The size of the code module.
     fn size(&self) -> u64;
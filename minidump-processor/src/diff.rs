@@ -0,0 +1,277 @@
+//! Structural comparison of processed minidumps.
+//!
+//! This compares only what matters for telling whether two processors agree on a
+//! crash: the crash reason, the crashing thread, and the function, module and offset
+//! of each frame of the crashing thread. Everything else (timestamps, registers,
+//! symbol file statistics, ...) is ignored, which makes it suitable for regression
+//! tests against golden outputs, including ones from other tools.
+
+use std::fmt;
+
+use minidump_common::utils::basename;
+use serde_json::Value;
+
+use crate::ProcessState;
+
+/// A field of a frame that [`diff_process_states`] compares.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameField {
+    /// The name of the function.
+    Function,
+    /// The file name (without directories) of the module.
+    Module,
+    /// The offset of the instruction in the module, or its address if the frame
+    /// isn't in a module.
+    Offset,
+}
+
+impl fmt::Display for FrameField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FrameField::Function => "function",
+            FrameField::Module => "module",
+            FrameField::Offset => "offset",
+        })
+    }
+}
+
+/// A difference between two processed minidumps, as found by [`diff_process_states`]
+/// or [`diff_json`].
+///
+/// In all of these, `left` is the value from the first state and `right` the value
+/// from the second one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateDifference {
+    /// The crash reasons (e.g. `EXCEPTION_ACCESS_VIOLATION_READ`) differ.
+    CrashReason {
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// The index of the crashing thread differs.
+    CrashingThread {
+        left: Option<usize>,
+        right: Option<usize>,
+    },
+    /// The crashing thread has a different number of frames.
+    ///
+    /// The frames both threads have are still compared.
+    FrameCount { left: usize, right: usize },
+    /// A frame of the crashing thread differs.
+    Frame {
+        /// The index of the frame, 0 being the innermost one.
+        index: usize,
+        field: FrameField,
+        left: Option<String>,
+        right: Option<String>,
+    },
+}
+
+impl fmt::Display for StateDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn opt<T: fmt::Display>(value: &Option<T>) -> String {
+            match value {
+                Some(value) => value.to_string(),
+                None => String::from("<none>"),
+            }
+        }
+
+        match self {
+            StateDifference::CrashReason { left, right } => {
+                write!(f, "crash reason: {} != {}", opt(left), opt(right))
+            }
+            StateDifference::CrashingThread { left, right } => {
+                write!(f, "crashing thread: {} != {}", opt(left), opt(right))
+            }
+            StateDifference::FrameCount { left, right } => {
+                write!(f, "frame count: {left} != {right}")
+            }
+            StateDifference::Frame {
+                index,
+                field,
+                left,
+                right,
+            } => write!(f, "frame {index} {field}: {} != {}", opt(left), opt(right)),
+        }
+    }
+}
+
+/// The parts of a processed minidump that are compared.
+#[derive(Debug, Default)]
+struct CrashSummary {
+    reason: Option<String>,
+    crashing_thread: Option<usize>,
+    frames: Vec<FrameSummary>,
+}
+
+#[derive(Debug)]
+struct FrameSummary {
+    function: Option<String>,
+    module: Option<String>,
+    offset: Option<u64>,
+}
+
+impl CrashSummary {
+    fn from_state(state: &ProcessState) -> CrashSummary {
+        let frames = state
+            .requesting_thread
+            .and_then(|idx| state.threads.get(idx))
+            .map(|thread| {
+                thread
+                    .frames
+                    .iter()
+                    .map(|frame| FrameSummary {
+                        function: frame.function_name.clone(),
+                        module: frame
+                            .module
+                            .as_ref()
+                            .map(|module| basename(&module.name).to_string()),
+                        offset: Some(match &frame.module {
+                            Some(module) => frame.instruction - module.raw.base_of_image,
+                            None => frame.instruction,
+                        }),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        CrashSummary {
+            reason: state
+                .exception_info
+                .as_ref()
+                .map(|info| info.reason.to_string()),
+            crashing_thread: state.requesting_thread,
+            frames,
+        }
+    }
+
+    fn from_json(json: &Value) -> CrashSummary {
+        let crash_info = &json["crash_info"];
+        let crashing_thread = crash_info["crashing_thread"]
+            .as_u64()
+            .map(|idx| idx as usize);
+        // Prefer the top-level copy of the crashing thread, which is what tools that
+        // only output that thread have.
+        let thread = if json["crashing_thread"].is_object() {
+            &json["crashing_thread"]
+        } else {
+            crashing_thread
+                .and_then(|idx| json["threads"].get(idx))
+                .unwrap_or(&Value::Null)
+        };
+        let frames = thread["frames"]
+            .as_array()
+            .map(|frames| {
+                frames
+                    .iter()
+                    .map(|frame| {
+                        let module = json_string(&frame["module"]);
+                        let offset = if module.is_some() {
+                            json_address(&frame["module_offset"])
+                        } else {
+                            None
+                        };
+                        FrameSummary {
+                            function: json_string(&frame["function"]),
+                            module: module.map(|module| basename(&module).to_string()),
+                            offset: offset.or_else(|| json_address(&frame["offset"])),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        CrashSummary {
+            reason: json_string(&crash_info["type"]),
+            crashing_thread,
+            frames,
+        }
+    }
+
+    fn diff(&self, other: &CrashSummary) -> Vec<StateDifference> {
+        let mut differences = Vec::new();
+        if self.reason != other.reason {
+            differences.push(StateDifference::CrashReason {
+                left: self.reason.clone(),
+                right: other.reason.clone(),
+            });
+        }
+        if self.crashing_thread != other.crashing_thread {
+            differences.push(StateDifference::CrashingThread {
+                left: self.crashing_thread,
+                right: other.crashing_thread,
+            });
+        }
+        if self.frames.len() != other.frames.len() {
+            differences.push(StateDifference::FrameCount {
+                left: self.frames.len(),
+                right: other.frames.len(),
+            });
+        }
+
+        for (index, (left, right)) in self.frames.iter().zip(&other.frames).enumerate() {
+            let mut compare = |field, left: &Option<String>, right: &Option<String>| {
+                if left != right {
+                    differences.push(StateDifference::Frame {
+                        index,
+                        field,
+                        left: left.clone(),
+                        right: right.clone(),
+                    });
+                }
+            };
+            compare(FrameField::Function, &left.function, &right.function);
+            compare(FrameField::Module, &left.module, &right.module);
+            compare(
+                FrameField::Offset,
+                &left.offset.map(|offset| format!("{offset:#x}")),
+                &right.offset.map(|offset| format!("{offset:#x}")),
+            );
+        }
+        differences
+    }
+}
+
+/// A non-empty string value.
+fn json_string(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .filter(|string| !string.is_empty())
+        .map(String::from)
+}
+
+/// An address, either as a number or as a hex string, with or without a `0x`
+/// prefix and leading zeros.
+fn json_address(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => {
+            let string = string.trim();
+            let digits = string
+                .strip_prefix("0x")
+                .or_else(|| string.strip_prefix("0X"))
+                .unwrap_or(string);
+            u64::from_str_radix(digits, 16).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Compare the crash reason, crashing thread, and frames of the crashing thread of
+/// two processed minidumps.
+///
+/// Returns an empty list if they agree.
+pub fn diff_process_states(left: &ProcessState, right: &ProcessState) -> Vec<StateDifference> {
+    CrashSummary::from_state(left).diff(&CrashSummary::from_state(right))
+}
+
+/// Like [`diff_process_states`], but comparing JSON output.
+///
+/// The JSON should follow the schema of [`ProcessState::print_json`], but only
+/// `crash_info.type`, `crash_info.crashing_thread` and the `function`, `module`,
+/// `module_offset` and `offset` of the crashing thread's frames are used, so the
+/// output of other tools that share those fields can be compared too. The crashing
+/// thread is taken from the top-level `crashing_thread` if there is one. Addresses
+/// may be numbers or hex strings in any format.
+pub fn diff_json(left: &Value, right: &Value) -> Vec<StateDifference> {
+    CrashSummary::from_json(left).diff(&CrashSummary::from_json(right))
+}
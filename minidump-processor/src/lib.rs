@@ -77,16 +77,20 @@
 doc_comment::doctest!("../README.md");
 
 mod arg_recovery;
+mod diff;
 mod evil;
 mod op_analysis;
 mod process_state;
 mod processor;
+mod sanitizer;
 mod stackwalker;
 pub mod symbols;
 mod system_info;
 
+pub use crate::diff::*;
 pub use crate::process_state::*;
 pub use crate::processor::*;
+pub use crate::sanitizer::SanitizerReport;
 pub use crate::stackwalker::*;
 pub use crate::symbols::*;
 pub use crate::system_info::*;
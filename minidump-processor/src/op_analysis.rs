@@ -109,6 +109,50 @@ pub fn analyze_thread_context(
     }
 }
 
+/// Read the code bytes at the given thread's instruction pointer
+///
+/// At most `max_len` bytes are returned, or fewer if the memory containing the
+/// instruction pointer ends first.
+///
+/// # Errors
+///
+/// This may fail if there are no bytes at the instruction pointer.
+pub fn read_thread_instruction_bytes<'a>(
+    context: &MinidumpContext,
+    memory_list: &'a minidump::UnifiedMemoryList<'a>,
+    max_len: usize,
+) -> Result<&'a [u8], OpAnalysisError> {
+    let bytes = get_thread_instruction_bytes(context, memory_list)?;
+    Ok(&bytes[..bytes.len().min(max_len)])
+}
+
+/// Disassemble the instructions being run by the given thread
+///
+/// Up to `count` instructions are disassembled, starting with the one at the instruction
+/// pointer. Instructions before it aren't included, as there's no reliable way to find
+/// where they start. Disassembly stops early if the memory runs out or an instruction
+/// can't be decoded.
+///
+/// # Errors
+///
+/// This fails if disassembly of the target CPU architecture is not supported, if there
+/// are no bytes at the instruction pointer, or if the first instruction can't be decoded.
+pub fn disassemble_thread_context(
+    context: &MinidumpContext,
+    memory_list: &minidump::UnifiedMemoryList,
+    count: usize,
+) -> Result<Vec<String>, OpAnalysisError> {
+    let instruction_bytes = get_thread_instruction_bytes(context, memory_list)?;
+
+    match context.raw {
+        #[cfg(feature = "disasm_amd64")]
+        MinidumpRawContext::Amd64(_) => {
+            self::amd64::disassemble_instructions(instruction_bytes, count)
+        }
+        _ => Err(OpAnalysisError::UnsupportedCpuArch),
+    }
+}
+
 /// Helper to read the instruction bytes that were being run by the given thread
 ///
 /// Use the given `context` to attempt to read `1 <= n <= MAX_INSTRUCTION_LENGTH`
@@ -166,6 +210,27 @@ mod amd64 {
         })
     }
 
+    /// Disassemble up to `count` consecutive instructions from `instruction_bytes`
+    pub fn disassemble_instructions(
+        mut instruction_bytes: &[u8],
+        count: usize,
+    ) -> Result<Vec<String>, OpAnalysisError> {
+        use yaxpeax_arch::LengthedInstruction;
+
+        let mut instructions = Vec::new();
+        while instructions.len() < count {
+            let decoded_instruction = match decode_instruction(instruction_bytes) {
+                Ok(decoded_instruction) => decoded_instruction,
+                Err(e) if instructions.is_empty() => return Err(e),
+                Err(_) => break,
+            };
+            instructions.push(decoded_instruction.to_string());
+            let len = decoded_instruction.len().to_const() as usize;
+            instruction_bytes = &instruction_bytes[len..];
+        }
+        Ok(instructions)
+    }
+
     fn get_registers(i: Instruction) -> BTreeSet<&'static str> {
         let mut ret = BTreeSet::new();
         for op in 0..i.operand_count() {
@@ -3,21 +3,31 @@
 
 //! The state of a process.
 
+use std::any::Any;
 use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
 use std::io::prelude::*;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use crate::op_analysis::MemoryAccess;
+use crate::sanitizer::SanitizerReport;
 use crate::system_info::SystemInfo;
-use crate::{FrameSymbolizer, SymbolStats};
+use crate::{FrameSymbolizer, SymbolStats, SymbolStatus};
 use minidump::system_info::PointerWidth;
 use minidump::*;
 use minidump_common::utils::basename;
 use serde_json::json;
 
+/// The version of the JSON schema produced by [`ProcessState::print_json`].
+///
+/// This is only bumped for incompatible changes to the schema (removing or renaming
+/// fields, or changing their type or meaning). Backwards compatible additions don't
+/// change it. See `json-schema.md` for the details of the schema.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
 /// Indicates how well the instruction pointer derived during
 /// stack walking is trusted. Since the stack walker can resort to
 /// stack scanning, it can wind up with dubious frames.
@@ -29,12 +39,19 @@ pub enum FrameTrust {
     Scan,
     /// Found while scanning stack using call frame info.
     CfiScan,
+    /// Taken from the link register of the context frame, which had no usable
+    /// frame record.
+    LinkRegister,
     /// Derived from frame pointer.
     FramePointer,
+    /// Derived from an [`UnwindHint`][crate::UnwindHint] given for the module.
+    UnwindHint,
     /// Derived from call frame info.
     CallFrameInfo,
     /// Explicitly provided by some external stack walker.
     PreWalked,
+    /// Restored from the context the kernel saved when delivering a signal.
+    SignalContext,
     /// Given as instruction pointer in a context.
     Context,
 }
@@ -219,6 +236,19 @@ pub struct StackFrame {
     /// WIN STACK unwinding needs this value to work; it's otherwise uninteresting.
     pub parameter_size: Option<u32>,
 
+    /// Whether `function_name` came from a PUBLIC symbol because no FUNC covered
+    /// the address.
+    ///
+    /// PUBLIC symbols have no size, so the function name is a lower confidence guess.
+    pub function_is_public: bool,
+
+    /// If `function_name` is only the nearest function before the address, because no
+    /// symbol covered it, how many bytes past the end of that function the address is.
+    ///
+    /// This is only filled in by a [`Symbolizer`][crate::Symbolizer] configured with
+    /// [`with_nearest_symbol`][crate::Symbolizer::with_nearest_symbol].
+    pub nearest_function_distance: Option<u64>,
+
     /// The source file name, may be omitted if debug symbols are not available.
     pub source_file_name: Option<String>,
 
@@ -266,6 +296,16 @@ pub struct StackFrame {
 
     /// Any function args we recovered.
     pub arguments: Option<FunctionArgs>,
+
+    /// The valid registers of [`StackFrame::context`][], by name.
+    ///
+    /// For the top frame these are the registers of the thread's context, for the
+    /// others they're the registers the stack walker recovered for the caller (always
+    /// the instruction and stack pointers, plus whatever the unwinding rules restored).
+    ///
+    /// This is only populated if [`ProcessorOptions::frame_registers`][crate::ProcessorOptions::frame_registers]
+    /// is set.
+    pub registers: Option<BTreeMap<String, u64>>,
 }
 
 /// Information about the results of unwinding a thread's stack.
@@ -283,6 +323,111 @@ pub enum CallStackInfo {
     DumpThreadSkipped,
 }
 
+/// Why the stack walker stopped looking for more frames.
+///
+/// See [`CallStack::walk_end`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackWalkEnd {
+    /// None of the unwinding methods could find a caller.
+    ///
+    /// This is how most stacks end, as there's nothing left to find past the
+    /// thread's entry point.
+    NoCaller,
+    /// The thread's stack memory wasn't captured, so only the context frame
+    /// could be recovered.
+    MissingStackMemory,
+    /// The caller's instruction pointer was null (or close enough to it, within
+    /// the first page of memory), which marks the end of the stack.
+    NullInstructionPointer,
+    /// The caller's stack pointer wasn't above the callee's.
+    ///
+    /// Stacks grow down, so unwinding must move the stack pointer up to make
+    /// progress. Continuing would produce garbage frames, or loop forever.
+    StackPointerNotIncreasing,
+    /// The caller's stack pointer was outside of the thread's stack memory.
+    LeftStack,
+    /// The caller had the same instruction and stack pointers as one of the recent
+    /// frames, so unwinding it would repeat the same frames forever.
+    ///
+    /// This only happens with corrupt stacks (or bad unwinding info).
+    Loop,
+    /// The stack already had as many frames as a thread's stack is unwound to (1024).
+    ///
+    /// This only happens with corrupt stacks, which keep producing plausible frames.
+    FrameLimit,
+    /// The stack walker doesn't support the thread's CPU.
+    UnsupportedCpu,
+}
+
+/// An operation on a handle (such as opening or closing it) recorded by Application
+/// Verifier, along with a backtrace of the thread that performed it.
+///
+/// See [`MinidumpHandleOperation`].
+#[derive(Debug, Clone)]
+pub struct HandleOperationInfo {
+    /// The handle that was operated on.
+    pub handle: u64,
+    /// The id of the process that performed the operation.
+    pub process_id: u32,
+    /// The id of the thread that performed the operation.
+    pub thread_id: u32,
+    /// The kind of operation, if it is a known one.
+    pub operation_type: Option<format::HandleTraceOperation>,
+    /// The backtrace of the operation, innermost first.
+    pub frames: Vec<BacktraceFrame>,
+}
+
+/// A frame of a backtrace that was recorded by the process itself, instead of being
+/// unwound by the processor.
+///
+/// Frames are only symbolicated if [`ProcessorOptions::symbolicate_handle_operations`][crate::ProcessorOptions::symbolicate_handle_operations]
+/// is set, and the frame is inside a module.
+#[derive(Debug, Clone)]
+pub struct BacktraceFrame {
+    /// The return address recorded in the backtrace.
+    pub return_address: u64,
+    /// The module the return address is in, if any.
+    pub module: Option<MinidumpModule>,
+    /// The function containing the call, if known.
+    pub function_name: Option<String>,
+    /// The start address of `function_name`.
+    pub function_base: Option<u64>,
+    /// The source file containing the call, if known.
+    pub source_file_name: Option<String>,
+    /// The (1-based) line of the call in `source_file_name`.
+    pub source_line: Option<u32>,
+}
+
+impl BacktraceFrame {
+    /// Create an unsymbolicated frame for `return_address`, which is in `module` (if any).
+    pub fn new(return_address: u64, module: Option<MinidumpModule>) -> Self {
+        BacktraceFrame {
+            return_address,
+            module,
+            function_name: None,
+            function_base: None,
+            source_file_name: None,
+            source_line: None,
+        }
+    }
+}
+
+impl FrameSymbolizer for BacktraceFrame {
+    fn get_instruction(&self) -> u64 {
+        // Look up the call instruction rather than the one it returns to, which
+        // may be in a different function (see [`StackFrame::resume_address`]).
+        self.return_address.saturating_sub(1)
+    }
+    fn set_function(&mut self, name: &str, base: u64, _parameter_size: u32) {
+        self.function_name = Some(String::from(name));
+        self.function_base = Some(base);
+    }
+    fn set_source_file(&mut self, file: &str, line: u32, _base: u64) {
+        self.source_file_name = Some(String::from(file));
+        self.source_line = Some(line);
+    }
+}
+
 /// A stack of `StackFrame`s produced as a result of unwinding a thread.
 #[derive(Debug, Clone)]
 pub struct CallStack {
@@ -299,6 +444,34 @@ pub struct CallStack {
     pub thread_name: Option<String>,
     /// The GetLastError() value stored in the TEB.
     pub last_error_value: Option<CrashReason>,
+    /// The size of the thread's stack memory region, if it was captured.
+    pub stack_size: Option<u64>,
+    /// How much of the thread's stack was in use, measured from the top of the
+    /// stack memory region down to the thread's stack pointer.
+    ///
+    /// This is `None` if the stack wasn't captured or the stack pointer isn't in it.
+    pub stack_used: Option<u64>,
+    /// Whether any of the thread's stack memory was captured.
+    ///
+    /// Without it the stack walker can only recover the context frame, so a
+    /// single-frame stack with this unset is truncated, not genuinely shallow.
+    pub stack_memory_present: bool,
+    /// Why the stack walker stopped unwinding this thread.
+    ///
+    /// The frame that would have come after the last one in [`CallStack::frames`][] is
+    /// discarded. This is `None` if the stack hasn't been walked.
+    pub walk_end: Option<StackWalkEnd>,
+    /// The thread's priority class, if known.
+    ///
+    /// See [Scheduling Priorities](https://docs.microsoft.com/en-us/windows/win32/ProcThread/scheduling-priorities).
+    pub priority_class: Option<u32>,
+    /// The thread's priority level, if known.
+    pub priority: Option<u32>,
+    /// The thread's processor affinity mask.
+    ///
+    /// This comes from the thread info list, so it's `None` if that has no
+    /// entry for this thread.
+    pub affinity: Option<u64>,
 }
 
 impl CallStack {
@@ -312,6 +485,13 @@ impl CallStack {
             thread_id: 0,
             thread_name: None,
             last_error_value: None,
+            stack_size: None,
+            stack_used: None,
+            stack_memory_present: false,
+            walk_end: None,
+            priority_class: None,
+            priority: None,
+            affinity: None,
         }
     }
 }
@@ -345,12 +525,80 @@ pub struct ExceptionInfo {
     pub adjusted_address: Option<AdjustedAddress>,
     /// A string representing the crashing instruction (if available)
     pub instruction_str: Option<String>,
+    /// The code bytes at the crashing instruction (if available)
+    ///
+    /// These are read from the crashing thread's instruction pointer, and are
+    /// only present if the minidump includes the memory containing the code.
+    pub instruction_bytes: Option<Vec<u8>>,
+    /// The crashing instruction and the few instructions that follow it (if available)
+    pub disassembly: Vec<String>,
     /// A list of memory accesses performed by crashing instruction (if available)
     pub memory_accesses: Option<Vec<MemoryAccess>>,
     /// Possible valid addresses which are one flipped bit away from the crashing address or adjusted address.
     ///
     /// The original address was possibly the result of faulty hardware, alpha particles, etc.
     pub possible_bit_flips: Vec<PossibleBitFlip>,
+    /// Whether the crashing instruction is inside a loaded module.
+    ///
+    /// If it isn't, the crash likely happened in JIT code, or the module list is
+    /// incomplete or corrupt. This is checked after the top frame of the crashing
+    /// thread has been unwound, so it's `None` if there's no such frame.
+    pub crash_address_in_module: Option<bool>,
+    /// If the crashing instruction isn't inside a loaded module, the module closest to it.
+    pub nearest_module: Option<NearestModule>,
+    /// What kind of memory an access violation touched, if it is notable.
+    pub crash_classification: Option<CrashClassification>,
+}
+
+/// A classification of an access violation by the memory it touched.
+///
+/// These conditions are typical of exploitation attempts (or of memory corruption
+/// that went far enough to look like one), so they are worth telling apart from
+/// ordinary bugs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashClassification {
+    /// The crashing instruction is in memory that isn't executable (a DEP/W^X violation).
+    ExecuteNonExecutable,
+    /// The crash is a write to memory that is readable but not writable.
+    ///
+    /// This is only detected when the crash reason says the access was a write,
+    /// which is currently only the case on Windows.
+    WriteReadOnly,
+    /// The crashing address is in the first 64KB of the address space, which is never mapped.
+    NullPage,
+}
+
+impl CrashClassification {
+    /// A short identifier for this classification, as used in the JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExecuteNonExecutable => "execute-non-executable",
+            Self::WriteReadOnly => "write-read-only",
+            Self::NullPage => "null-page",
+        }
+    }
+}
+
+impl std::fmt::Display for CrashClassification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::ExecuteNonExecutable => "execution of non-executable memory",
+            Self::WriteReadOnly => "write to read-only memory",
+            Self::NullPage => "null-page access",
+        };
+        f.write_str(description)
+    }
+}
+
+/// The loaded module closest to an address that isn't in any module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearestModule {
+    /// The name of the module's code file.
+    pub name: String,
+    /// The base address of the module.
+    pub base_address: Address,
+    /// The distance between the address and the module, in bytes.
+    pub gap: u64,
 }
 
 /// Info about a memory address that was adjusted from its reported value
@@ -544,21 +792,65 @@ impl PossibleBitFlip {
     }
 }
 
+/// Replace the template arguments in a C++ function name with `<T>`.
+///
+/// Angle brackets that are part of an operator name (`operator<`, `operator->`, ...)
+/// are left alone.
+fn strip_template_args_from(name: &str) -> Cow<'_, str> {
+    if !name.contains('<') {
+        return Cow::Borrowed(name);
+    }
+
+    let mut output = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    for c in name.chars() {
+        let in_operator = depth == 0
+            && (output.ends_with("operator")
+                || output.ends_with("operator<")
+                || output.ends_with("operator>")
+                || output.ends_with("operator-"));
+        match c {
+            '<' | '>' if in_operator => output.push(c),
+            '<' => {
+                if depth == 0 {
+                    output.push_str("<T>");
+                }
+                depth += 1;
+            }
+            '>' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ => output.push(c),
+        }
+    }
+    Cow::Owned(output)
+}
+
 /// The state of a process as recorded by a `Minidump`.
 #[derive(Debug, Clone)]
 pub struct ProcessState {
     /// The PID of the process.
+    ///
+    /// See [`ProcessState::process_id`] for where this comes from.
     pub process_id: Option<u32>,
     /// When the minidump was written.
     pub time: SystemTime,
     /// When the process started, if available
+    ///
+    /// See [`ProcessState::process_create_time`] for where this comes from.
     pub process_create_time: Option<SystemTime>,
+    /// The speed of the system's processors in MHz, if available.
+    ///
+    /// See [`ProcessState::cpu_mhz`] for where this comes from.
+    pub cpu_mhz: Option<u32>,
     /// Known code signing certificates (module name => cert name)
     pub cert_info: HashMap<String, String>,
     /// Info about the exception that triggered the dump (if one did)
     pub exception_info: Option<ExceptionInfo>,
     /// A string describing an assertion that was hit, if present.
     pub assertion: Option<String>,
+    /// A report from a sanitizer (ASan, UBSan, ...) describing the crash, if one was
+    /// found in the minidump's comment streams. It is also appended to the crash reason.
+    pub sanitizer_report: Option<SanitizerReport>,
     /// The index of the thread that requested a dump be written.
     /// If a dump was produced as a result of a crash, this
     /// will point to the thread that crashed.  If the dump was produced as
@@ -568,8 +860,18 @@ pub struct ProcessState {
     /// extended Breakpad information is present, this field will be
     /// `None`.
     pub requesting_thread: Option<usize>,
-    /// Stacks for each thread (except possibly the exception handler
-    /// thread) at the time of the crash.
+    /// Whether the dump was explicitly requested rather than written for a crash.
+    ///
+    /// This is the case when there's no exception but the minidump still says which
+    /// thread asked for it to be written (see `requesting_thread`).
+    pub requested_dump: bool,
+    /// Stacks for each thread at the time of the crash.
+    ///
+    /// These are in the same order as the minidump's [`MinidumpThreadList`]:
+    /// `threads[i]` is always the thread at `MinidumpThreadList::threads[i]`, and
+    /// has the same [`CallStack::thread_id`]. Threads are never reordered or
+    /// dropped; the thread that wrote the dump (if known) is kept, but isn't walked
+    /// (its `info` is [`CallStackInfo::DumpThreadSkipped`]).
     pub threads: Vec<CallStack>,
     // TODO:
     // thread_memory_regions
@@ -577,17 +879,37 @@ pub struct ProcessState {
     pub system_info: SystemInfo,
     /// Linux Standard Base Info
     pub linux_standard_base: Option<LinuxStandardBase>,
+    /// The process's command line (argv), from Linux's /proc/self/cmdline.
+    pub command_line: Option<Vec<String>>,
+    /// The process's environment variables, from Linux's /proc/self/environ.
+    ///
+    /// These are reported in the order they were recorded, and are not redacted.
+    pub environment: Option<Vec<(String, String)>>,
     pub mac_crash_info: Option<Vec<RawMacCrashInfo>>,
     /// The modules that were loaded into the process represented by the
     /// `ProcessState`.
     pub modules: MinidumpModuleList,
     pub unloaded_modules: MinidumpUnloadedModuleList,
+    /// The handle operations recorded by Application Verifier, if any.
+    pub handle_operations: Vec<HandleOperationInfo>,
     // modules_without_symbols
     // modules_with_corrupt_symbols
     // exploitability
     pub unknown_streams: Vec<MinidumpUnknownStream>,
     pub unimplemented_streams: Vec<MinidumpUnimplementedStream>,
+    /// Streams parsed by the parsers in [`ProcessorOptions::custom_streams`][crate::ProcessorOptions::custom_streams],
+    /// by stream type.
+    ///
+    /// [`ProcessState::custom_stream`] is a more convenient way to get at these.
+    pub custom_streams: HashMap<u32, Arc<dyn Any + Send + Sync>>,
     pub symbol_stats: HashMap<String, SymbolStats>,
+    /// Errors the symbol provider had, by module.
+    ///
+    /// See [`IsolatedSymbolProvider::errors`][crate::IsolatedSymbolProvider::errors]
+    /// for how modules are keyed. Modules without symbols aren't errors.
+    ///
+    /// This is only filled in by [`process_minidump_isolated`][crate::process_minidump_isolated].
+    pub symbol_provider_errors: HashMap<String, String>,
 }
 
 impl FrameTrust {
@@ -597,8 +919,11 @@ impl FrameTrust {
         match *self {
             FrameTrust::Context => "given as instruction pointer in context",
             FrameTrust::PreWalked => "recovered by external stack walker",
+            FrameTrust::SignalContext => "context saved for a signal handler",
             FrameTrust::CallFrameInfo => "call frame info",
             FrameTrust::CfiScan => "call frame info with scanning",
+            FrameTrust::LinkRegister => "link register",
+            FrameTrust::UnwindHint => "unwind hint for the module",
             FrameTrust::FramePointer => "previous frame's frame pointer",
             FrameTrust::Scan => "stack scanning",
             FrameTrust::None => "unknown",
@@ -610,8 +935,11 @@ impl FrameTrust {
         match *self {
             FrameTrust::Context => "context",
             FrameTrust::PreWalked => "prewalked",
+            FrameTrust::SignalContext => "signal_context",
             FrameTrust::CallFrameInfo => "cfi",
             FrameTrust::CfiScan => "cfi_scan",
+            FrameTrust::LinkRegister => "link_register",
+            FrameTrust::UnwindHint => "unwind_hint",
             FrameTrust::FramePointer => "frame_pointer",
             FrameTrust::Scan => "scan",
             FrameTrust::None => "non",
@@ -619,6 +947,93 @@ impl FrameTrust {
     }
 }
 
+/// How many frames in a module were recovered by each unwinding method.
+///
+/// See [`ProcessState::unwind_method_counts`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UnwindMethodCounts {
+    /// Frames whose instruction pointer was given by a thread context, or by the
+    /// context saved for a signal handler.
+    pub context: usize,
+    /// Frames recovered with call frame information.
+    pub cfi: usize,
+    /// Frames recovered with the callee's frame pointer.
+    pub frame_pointer: usize,
+    /// Frames found by scanning the stack, with or without call frame information.
+    pub scan: usize,
+    /// Frames provided by an external stack walker, recovered with an unwind hint
+    /// or from the link register, or found in some unknown way.
+    pub other: usize,
+}
+
+impl UnwindMethodCounts {
+    fn add(&mut self, trust: FrameTrust) {
+        let count = match trust {
+            FrameTrust::Context | FrameTrust::SignalContext => &mut self.context,
+            FrameTrust::CallFrameInfo => &mut self.cfi,
+            FrameTrust::FramePointer => &mut self.frame_pointer,
+            FrameTrust::Scan | FrameTrust::CfiScan => &mut self.scan,
+            FrameTrust::PreWalked
+            | FrameTrust::UnwindHint
+            | FrameTrust::LinkRegister
+            | FrameTrust::None => &mut self.other,
+        };
+        *count += 1;
+    }
+
+    /// The total number of frames.
+    pub fn total(&self) -> usize {
+        self.context + self.cfi + self.frame_pointer + self.scan + self.other
+    }
+}
+
+/// How many frames symbols could describe.
+///
+/// See [`ProcessState::symbolication_stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolicationStats {
+    /// The total number of frames.
+    pub total_frames: usize,
+    /// Frames with a function name.
+    pub symbolized_frames: usize,
+    /// Frames with a source file and line.
+    pub frames_with_line_info: usize,
+    /// Frames whose function name came from a `FUNC` record, or anything else that
+    /// isn't a `PUBLIC` record (like [`ProcessorOptions::fallback_resolver`][crate::ProcessorOptions::fallback_resolver]).
+    pub func_frames: usize,
+    /// Frames whose function name came from a `PUBLIC` record.
+    pub public_frames: usize,
+    /// Frames without a function name.
+    pub unresolved_frames: usize,
+}
+
+impl SymbolicationStats {
+    fn add(&mut self, frame: &StackFrame) {
+        self.total_frames += 1;
+        if frame.function_name.is_none() {
+            self.unresolved_frames += 1;
+            return;
+        }
+        self.symbolized_frames += 1;
+        if frame.function_is_public {
+            self.public_frames += 1;
+        } else {
+            self.func_frames += 1;
+        }
+        if frame.source_file_name.is_some() && frame.source_line.is_some() {
+            self.frames_with_line_info += 1;
+        }
+    }
+
+    /// The fraction of frames with a function name, or `None` if there are no frames.
+    pub fn hit_rate(&self) -> Option<f64> {
+        if self.total_frames == 0 {
+            return None;
+        }
+        Some(self.symbolized_frames as f64 / self.total_frames as f64)
+    }
+}
+
 impl StackFrame {
     /// Create a `StackFrame` from a `MinidumpContext`.
     pub fn from_context(context: MinidumpContext, trust: FrameTrust) -> StackFrame {
@@ -631,11 +1046,14 @@ impl StackFrame {
             function_name: None,
             function_base: None,
             parameter_size: None,
+            function_is_public: false,
+            nearest_function_distance: None,
             source_file_name: None,
             source_line: None,
             source_line_base: None,
             inlines: Vec::new(),
             arguments: None,
+            registers: None,
             trust,
             context,
         }
@@ -656,6 +1074,12 @@ impl FrameSymbolizer for StackFrame {
         self.source_line = Some(line);
         self.source_line_base = Some(base);
     }
+    fn set_function_is_public(&mut self) {
+        self.function_is_public = true;
+    }
+    fn set_function_is_nearest(&mut self, distance: u64) {
+        self.nearest_function_distance = Some(distance);
+    }
     /// This function can be called multiple times, for the inlines that cover the
     /// address at various levels of inlining. The call order is from outside to
     /// inside.
@@ -718,6 +1142,15 @@ fn json_registers(ctx: &MinidumpContext) -> serde_json::Value {
     json!(output)
 }
 
+impl<'a> IntoIterator for &'a CallStack {
+    type Item = &'a StackFrame;
+    type IntoIter = std::slice::Iter<'a, StackFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl CallStack {
     /// Create a `CallStack` with `info` and no frames.
     pub fn with_info(id: u32, info: CallStackInfo) -> CallStack {
@@ -727,9 +1160,61 @@ impl CallStack {
             thread_id: id,
             thread_name: None,
             last_error_value: None,
+            stack_size: None,
+            stack_used: None,
+            stack_memory_present: false,
+            walk_end: None,
+            priority_class: None,
+            priority: None,
+            affinity: None,
         }
     }
 
+    /// Iterate over the frames, from the innermost callee to the outermost caller.
+    ///
+    /// Inlined functions aren't separate frames, they're in [`StackFrame::inlines`][].
+    ///
+    /// ```
+    /// use minidump::format::CONTEXT_AMD64;
+    /// use minidump::{MinidumpContext, MinidumpRawContext};
+    /// use minidump_processor::{CallStack, FrameTrust};
+    ///
+    /// let raw = CONTEXT_AMD64 { rip: 0x1000, ..Default::default() };
+    /// let context = MinidumpContext::from_raw(MinidumpRawContext::Amd64(raw));
+    /// let stack = CallStack::with_context(context);
+    ///
+    /// assert_eq!(stack.len(), 1);
+    /// for frame in stack.iter() {
+    ///     assert_eq!(frame.instruction, 0x1000);
+    ///     assert_eq!(frame.trust, FrameTrust::Context);
+    ///     // Nothing has been symbolicated yet.
+    ///     assert!(frame.module.is_none());
+    ///     assert!(frame.function_name.is_none());
+    ///     assert!(frame.source_file_name.is_none());
+    ///     assert!(frame.source_line.is_none());
+    /// }
+    /// assert_eq!(stack.get(0).map(|frame| frame.instruction), Some(0x1000));
+    /// assert!(stack.get(1).is_none());
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, StackFrame> {
+        self.frames.iter()
+    }
+
+    /// The number of frames, not counting inlined functions.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether there are no frames at all.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get the frame at `index`, 0 being the innermost callee.
+    pub fn get(&self, index: usize) -> Option<&StackFrame> {
+        self.frames.get(index)
+    }
+
     /// Write a human-readable description of the call stack to `f`.
     ///
     /// This is very verbose, it implements the output format used by
@@ -800,6 +1285,10 @@ impl CallStack {
                         // We didn't have source info, so just give a byte offset from the func
                         write!(f, " + {:#x}", addr - func_base)?;
                     }
+                    if frame.nearest_function_distance.is_some() {
+                        // The function doesn't actually cover the address.
+                        write!(f, " (nearest)")?;
+                    }
                 } else {
                     // We didn't have a function name, so just give a byte offset from the module
                     write!(f, " + {:#x}", addr - module.base_address())?;
@@ -885,6 +1374,146 @@ impl ProcessState {
     pub fn crashed(&self) -> bool {
         self.exception_info.is_some()
     }
+
+    /// The id of the process that the minidump was written for.
+    ///
+    /// This is taken from the `MINIDUMP_MISC_INFO` stream. If that stream is absent
+    /// or doesn't have a process id, it falls back to the `Tgid` line of the Linux
+    /// `/proc/self/status` stream. Returns `None` if neither is present.
+    pub fn process_id(&self) -> Option<u32> {
+        self.process_id
+    }
+
+    /// When the process that the minidump was written for was started.
+    ///
+    /// This is only recorded in the `MINIDUMP_MISC_INFO` stream, and returns `None`
+    /// if that stream is absent or doesn't have process times.
+    pub fn process_create_time(&self) -> Option<SystemTime> {
+        self.process_create_time
+    }
+
+    /// A single human-readable label for the operating system, like
+    /// "Windows 10.0.19041.1 (WinBuild.160101.0800)" or "Ubuntu 22.04.3 LTS".
+    ///
+    /// See [`SystemInfo::os_pretty_name`] for which streams this comes from.
+    pub fn os_pretty_name(&self) -> &str {
+        &self.system_info.os_pretty_name
+    }
+
+    /// The number of processors in the system.
+    ///
+    /// This is taken from the `MINIDUMP_SYSTEM_INFO` stream, and returns `None` if
+    /// the count recorded there is zero.
+    pub fn cpu_count(&self) -> Option<usize> {
+        Some(self.system_info.cpu_count).filter(|&count| count > 0)
+    }
+
+    /// How the frames of each module were recovered, by the module's file name.
+    ///
+    /// This counts the frames of all threads that are in a module, and is useful to
+    /// find the modules whose symbols or CFI need improving: frames found by
+    /// scanning are much less reliable than frames found with CFI. Inline frames
+    /// aren't counted, since they aren't unwound.
+    pub fn unwind_method_counts(&self) -> BTreeMap<String, UnwindMethodCounts> {
+        let mut counts = BTreeMap::<String, UnwindMethodCounts>::new();
+        let frames = self.threads.iter().flat_map(|thread| &thread.frames);
+        for frame in frames {
+            if let Some(module) = &frame.module {
+                counts
+                    .entry(basename(&module.code_file()).to_string())
+                    .or_default()
+                    .add(frame.trust);
+            }
+        }
+        counts
+    }
+
+    /// How well the frames of all threads were symbolicated.
+    ///
+    /// Inline frames aren't counted. With
+    /// [`defer_symbolication`][crate::ProcessorOptions::defer_symbolication], this
+    /// reflects the frames as they are now, so call it after symbolicating them.
+    pub fn symbolication_stats(&self) -> SymbolicationStats {
+        let mut stats = SymbolicationStats::default();
+        for frame in self.threads.iter().flat_map(|thread| &thread.frames) {
+            stats.add(frame);
+        }
+        stats
+    }
+
+    /// The speed of the system's processors in MHz.
+    ///
+    /// This is the maximum speed from the processor power info in the
+    /// `MINIDUMP_MISC_INFO` stream (falling back to the current speed if the maximum
+    /// is missing). Only Windows minidumps have it, and only with a `MINIDUMP_MISC_INFO_2`
+    /// or later stream, so this returns `None` otherwise.
+    pub fn cpu_mhz(&self) -> Option<u32> {
+        self.cpu_mhz
+    }
+
+    /// Build a signature for the crash from the top frames of the crashing thread.
+    ///
+    /// This is meant for grouping crashes with the same cause, in the style of Socorro's
+    /// signatures: up to `max_frames` frames (inlined functions count as frames), joined
+    /// with `" | "`. Each frame is its function name if it was symbolicated, or otherwise
+    /// `module@0xoffset` (or `@0xaddress` if it isn't in a module). Frames found by stack
+    /// scanning (with or without call frame info) are skipped, as they're likely to be
+    /// noise.
+    ///
+    /// If `strip_template_args` is set, C++ template arguments are replaced with `<T>`,
+    /// so `std::vector<int>::at` becomes `std::vector<T>::at`.
+    ///
+    /// Returns `None` if there's no crashing thread, or it has no usable frames.
+    pub fn crash_signature(&self, max_frames: usize, strip_template_args: bool) -> Option<String> {
+        let thread = self.threads.get(self.requesting_thread?)?;
+        let normalize = |name: &str| {
+            if strip_template_args {
+                strip_template_args_from(name).into_owned()
+            } else {
+                name.to_owned()
+            }
+        };
+
+        let parts: Vec<String> = thread
+            .frames
+            .iter()
+            .filter(|frame| !matches!(frame.trust, FrameTrust::Scan | FrameTrust::CfiScan))
+            .flat_map(|frame| {
+                let inlines = frame
+                    .inlines
+                    .iter()
+                    .map(|inline| normalize(&inline.function_name));
+                let name = match (&frame.function_name, &frame.module) {
+                    (Some(name), _) => normalize(name),
+                    (None, Some(module)) => format!(
+                        "{}@{:#x}",
+                        basename(&module.code_file()),
+                        frame.instruction - module.base_address()
+                    ),
+                    (None, None) => format!("@{:#x}", frame.instruction),
+                };
+                inlines.chain(std::iter::once(name)).collect::<Vec<_>>()
+            })
+            .take(max_frames)
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" | "))
+        }
+    }
+
+    /// Get the value a custom parser produced for the stream of type `stream_type`.
+    ///
+    /// Returns `None` if there was no such stream, it failed to parse, or the parser
+    /// produced something other than a `T`.
+    pub fn custom_stream<T: Any>(&self, stream_type: u32) -> Option<&T> {
+        self.custom_streams
+            .get(&stream_type)
+            .and_then(|value| value.downcast_ref())
+    }
+
     /// Write a human-readable description of the process state to `f`.
     ///
     /// This is very verbose, it implements the output format used by
@@ -931,7 +1560,11 @@ impl ProcessState {
         writeln!(f)?;
 
         if let Some(ref crash_info) = self.exception_info {
-            writeln!(f, "Crash reason:  {}", crash_info.reason)?;
+            write!(f, "Crash reason:  {}", crash_info.reason)?;
+            if let Some(ref report) = self.sanitizer_report {
+                write!(f, " ({report})")?;
+            }
+            writeln!(f)?;
 
             if let Some(adjusted_address) = &crash_info.adjusted_address {
                 writeln!(f, "Crash address: {} **", crash_info.address)?;
@@ -947,6 +1580,21 @@ impl ProcessState {
                 writeln!(f, "Crash address: {}", crash_info.address)?;
             }
 
+            if let Some(classification) = crash_info.crash_classification {
+                writeln!(f, "Crash classification: {classification}")?;
+            }
+
+            if crash_info.crash_address_in_module == Some(false) {
+                match &crash_info.nearest_module {
+                    Some(nearest) => writeln!(
+                        f,
+                        "Crashing instruction is not in any module (nearest module: {} at {}, {:#x} bytes away)",
+                        nearest.name, nearest.base_address, nearest.gap
+                    )?,
+                    None => writeln!(f, "Crashing instruction is not in any module")?,
+                }
+            }
+
             if let Some(ref crashing_instruction_str) = crash_info.instruction_str {
                 writeln!(f, "Crashing instruction: `{crashing_instruction_str}`")?;
             }
@@ -993,6 +1641,8 @@ impl ProcessState {
                     )?;
                 }
             }
+        } else if self.requested_dump {
+            writeln!(f, "No crash (requested dump)")?;
         } else {
             writeln!(f, "No crash")?;
         }
@@ -1000,6 +1650,17 @@ impl ProcessState {
         if let Some(ref assertion) = self.assertion {
             writeln!(f, "Assertion: {assertion}")?;
         }
+        if let Some(ref report) = self.sanitizer_report {
+            write!(
+                f,
+                "Sanitizer report: {} {}",
+                report.sanitizer, report.bug_type
+            )?;
+            if let Some(address) = report.address {
+                write!(f, " on address {address:#x}")?;
+            }
+            writeln!(f)?;
+        }
         if let Some(ref info) = self.mac_crash_info {
             writeln!(f, "Mac Crash Info:")?;
             for (idx, record) in info.iter().enumerate() {
@@ -1166,6 +1827,21 @@ Unknown streams encountered:
     ///
     /// See the top level documentation of this library for the stable JSON schema.
     pub fn print_json<T: Write>(&self, f: &mut T, pretty: bool) -> Result<(), serde_json::Error> {
+        self.print_json_with_frame_limit(f, pretty, None)
+    }
+
+    /// Like [`ProcessState::print_json`], but with at most `frame_limit` frames per thread.
+    ///
+    /// Threads with more frames than that only output their top `frame_limit` frames,
+    /// and are marked with `"truncated": true`. Their `frame_count` is still the
+    /// number of frames that were walked. This keeps the output of deeply recursive
+    /// stacks bounded.
+    pub fn print_json_with_frame_limit<T: Write>(
+        &self,
+        f: &mut T,
+        pretty: bool,
+        frame_limit: Option<usize>,
+    ) -> Result<(), serde_json::Error> {
         // See ../json-schema.md for details on this format.
 
         self.set_print_context();
@@ -1180,6 +1856,9 @@ Unknown streams encountered:
             // Currently unused, we either produce no output or successful output.
             // OK | ERROR_* | SYMBOL_SUPPLIER_INTERRUPTED
             "status": "OK",
+            // The version of minidump-processor that produced this output
+            "processor_version": env!("CARGO_PKG_VERSION"),
+            "schema_version": JSON_SCHEMA_VERSION,
             "system_info": {
                 // Linux | Windows NT | Mac OS X
                 "os": sys.os.long_name(),
@@ -1188,12 +1867,18 @@ Unknown streams encountered:
                 "cpu_arch": sys.cpu.to_string(),
                 "cpu_info": sys.cpu_info,
                 "cpu_count": sys.cpu_count,
+                // optional
+                "cpu_mhz": self.cpu_mhz(),
                 // optional, print as hex string
                 "cpu_microcode_version": sys.cpu_microcode_version.map(|num| format!("{num:#x}")),
             },
             "crash_info": {
-                "type": self.exception_info.as_ref().map(|info| info.reason).map(|reason| reason.to_string()),
+                "type": self.exception_info.as_ref().map(|info| match self.sanitizer_report {
+                    Some(ref report) => format!("{} ({report})", info.reason),
+                    None => info.reason.to_string(),
+                }),
                 "address": self.exception_info.as_ref().map(|info| info.address),
+                "requested_dump": self.requested_dump,
                 "adjusted_address": self.exception_info.as_ref().map(|info| {
                     info.adjusted_address.as_ref().map(|adjusted| match adjusted {
                         AdjustedAddress::NonCanonical(address) => json!({
@@ -1220,9 +1905,25 @@ Unknown streams encountered:
                 "possible_bit_flips": self.exception_info.as_ref().and_then(|info| {
                     (!info.possible_bit_flips.is_empty()).then_some(&info.possible_bit_flips)
                 }),
+                "crash_address_in_module": self.exception_info.as_ref().and_then(|info| info.crash_address_in_module),
+                "crash_classification": self.exception_info.as_ref().and_then(|info| {
+                    info.crash_classification.map(|classification| classification.as_str())
+                }),
+                "nearest_module": self.exception_info.as_ref().and_then(|info| {
+                    info.nearest_module.as_ref().map(|nearest| json!({
+                        "filename": basename(&nearest.name),
+                        "base_addr": nearest.base_address,
+                        "gap": json_hex(nearest.gap),
+                    }))
+                }),
                 // thread index | null
                 "crashing_thread": self.requesting_thread,
                 "assertion": self.assertion,
+                "sanitizer_report": self.sanitizer_report.as_ref().map(|report| json!({
+                    "sanitizer": report.sanitizer,
+                    "bug_type": report.bug_type,
+                    "address": report.address.map(json_hex),
+                })),
             },
             // optional
             "lsb_release": self.linux_standard_base.as_ref().map(|lsb| json!({
@@ -1252,100 +1953,82 @@ Unknown streams encountered:
             "main_module": 0,
             // [UNSTABLE:evil_json]
             "modules_contains_cert_info": !self.cert_info.is_empty(),
-            "modules": self.modules.iter().map(|module| {
-                let full_name = module.code_file();
-                let name = basename(&full_name);
-
-                // Gather statistics on the module's symbols
-                let stats = self.symbol_stats.get(name);
-                let had_stats = stats.is_some();
-                let default = SymbolStats::default();
-                let stats = stats.unwrap_or(&default);
-                // Only consider the symbols "missing" if the symbolizer
-                // actually has statistics on them (implying it *tried* to
-                // get the symbols but failed.)
-                let missing_symbols = had_stats && !stats.loaded_symbols;
-                json!({
-                    "base_addr": json_hex(module.raw.base_of_image),
-                    // filename | empty string
-                    "debug_file": basename(module.debug_file().unwrap_or(Cow::Borrowed("")).borrow()),
-                    // [[:xdigit:]]{33} | empty string
-                    "debug_id": module.debug_identifier().unwrap_or_default().breakpad().to_string(),
-                    "end_addr": json_hex(module.raw.base_of_image + module.raw.size_of_image as u64),
-                    "filename": &name,
-                    "code_id": module.code_identifier().unwrap_or_default().as_str(),
-                    "version": module.version(),
-                    // [UNSTABLE:evil_json]
-                    "cert_subject": self.cert_info.get(name),
-
-                    // These are all just metrics for debugging minidump-processor's execution
-
-                    // optional, if mdsw looked for the file and it doesn't exist
-                    "missing_symbols": missing_symbols,
-                    // optional, if mdsw looked for the file and it does exist
-                    "loaded_symbols": stats.loaded_symbols,
-                    // optional, if mdsw found a file that has parse errors
-                    "corrupt_symbols": stats.corrupt_symbols,
-                    // optional, url of symbol file
-                    "symbol_url": stats.symbol_url,
-                })
-            }).collect::<Vec<_>>(),
+            "modules": self.modules_json(),
             "pid": self.process_id,
             "thread_count": self.threads.len(),
-            "threads": self.threads.iter().map(|thread| json!({
-                "frame_count": thread.frames.len(),
-                // optional
-                "last_error_value": thread.last_error_value.map(|error| error.to_string()),
-                // optional
-                "thread_name": thread.thread_name,
-                "frames": thread.frames.iter().enumerate().map(|(idx, frame)| json!({
-                    "frame": idx,
+            "threads": self.threads.iter().map(|thread| {
+                let frames = match frame_limit {
+                    Some(limit) if limit < thread.frames.len() => &thread.frames[..limit],
+                    _ => &thread.frames[..],
+                };
+                json!({
+                    "frame_count": thread.frames.len(),
                     // optional
-                    "module": frame.module.as_ref().map(|module| basename(&module.name)),
+                    "truncated": (frames.len() < thread.frames.len()).then_some(true),
                     // optional
-                    "function": frame.function_name,
+                    "last_error_value": thread.last_error_value.map(|error| error.to_string()),
                     // optional
-                    "file": frame.source_file_name,
+                    "thread_name": thread.thread_name,
                     // optional
-                    "line": frame.source_line,
-                    "offset": json_hex(frame.instruction),
+                    "stack_size": thread.stack_size,
                     // optional
-                    "inlines": if !frame.inlines.is_empty() {
-                        Some(frame.inlines.iter().map(|frame| {
-                            json!({
-                                "function": frame.function_name,
-                                "file": frame.source_file_name,
-                                "line": frame.source_line,
-                            })
-                        }).collect::<Vec<_>>())
-                    } else {
-                        None
-                    },
+                    "stack_used": thread.stack_used,
+                    "stack_memory_present": thread.stack_memory_present,
                     // optional
-                    "module_offset": frame
-                        .module
-                        .as_ref()
-                        .map(|module| frame.instruction - module.raw.base_of_image)
-                        .map(json_hex),
+                    "priority_class": thread.priority_class,
                     // optional
-                    "unloaded_modules": if frame.unloaded_modules.is_empty() {
-                        None
-                    } else {
-                        Some(frame.unloaded_modules.iter().map(|(module, offsets)| json!({
-                            "module": module,
-                            "offsets": offsets.iter().copied().map(json_hex).collect::<Vec<_>>(),
-                        })).collect::<Vec<_>>())
-                    },
+                    "priority": thread.priority,
                     // optional
-                    "function_offset": frame
-                        .function_base
-                        .map(|func_base| frame.instruction - func_base)
-                        .map(json_hex),
-                    "missing_symbols": frame.function_name.is_none(),
-                    // none | scan | cfi_scan | frame_pointer | cfi | context | prewalked
-                    "trust": frame.trust.as_str()
-                })).collect::<Vec<_>>(),
-            })).collect::<Vec<_>>(),
+                    "affinity": thread.affinity.map(json_hex),
+                    "frames": frames.iter().enumerate().map(|(idx, frame)| json!({
+                        "frame": idx,
+                        // optional
+                        "module": frame.module.as_ref().map(|module| basename(&module.name)),
+                        // optional
+                        "function": frame.function_name,
+                        // optional
+                        "file": frame.source_file_name,
+                        // optional
+                        "line": frame.source_line,
+                        "offset": json_hex(frame.instruction),
+                        // optional
+                        "inlines": if !frame.inlines.is_empty() {
+                            Some(frame.inlines.iter().map(|frame| {
+                                json!({
+                                    "function": frame.function_name,
+                                    "file": frame.source_file_name,
+                                    "line": frame.source_line,
+                                })
+                            }).collect::<Vec<_>>())
+                        } else {
+                            None
+                        },
+                        // optional
+                        "module_offset": frame
+                            .module
+                            .as_ref()
+                            .map(|module| frame.instruction - module.raw.base_of_image)
+                            .map(json_hex),
+                        // optional
+                        "unloaded_modules": if frame.unloaded_modules.is_empty() {
+                            None
+                        } else {
+                            Some(frame.unloaded_modules.iter().map(|(module, offsets)| json!({
+                                "module": module,
+                                "offsets": offsets.iter().copied().map(json_hex).collect::<Vec<_>>(),
+                            })).collect::<Vec<_>>())
+                        },
+                        // optional
+                        "function_offset": frame
+                            .function_base
+                            .map(|func_base| frame.instruction - func_base)
+                            .map(json_hex),
+                        "missing_symbols": frame.function_name.is_none(),
+                        // none | scan | cfi_scan | link_register | frame_pointer | cfi | context | prewalked
+                        "trust": frame.trust.as_str()
+                    })).collect::<Vec<_>>(),
+                })
+            }).collect::<Vec<_>>(),
 
             "unloaded_modules": self.unloaded_modules.iter().map(|module| json!({
                 "base_addr": json_hex(module.raw.base_of_image),
@@ -1379,9 +2062,11 @@ Unknown streams encountered:
                     .unwrap()
                     .as_array_mut()
                     .unwrap();
-                let frame = frames[0].as_object_mut().unwrap();
-
-                frame.insert(String::from("registers"), registers);
+                // The frame limit may have left no frames at all.
+                if let Some(frame) = frames.get_mut(0) {
+                    let frame = frame.as_object_mut().unwrap();
+                    frame.insert(String::from("registers"), registers);
+                }
                 thread_obj.insert(String::from("threads_index"), json!(requesting_thread));
 
                 output
@@ -1398,6 +2083,85 @@ Unknown streams encountered:
         }
     }
 
+    /// Serializes the state to compact json, as with [`ProcessState::print_json`].
+    ///
+    /// This doesn't consume the state, so it can still be used afterwards.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_json(&self) -> Result<String, serde_json::Error> {
+        self.json_string(false)
+    }
+
+    /// Serializes the state to pretty-printed json, as with [`ProcessState::print_json`].
+    ///
+    /// This doesn't consume the state, so it can still be used afterwards.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_json_pretty(&self) -> Result<String, serde_json::Error> {
+        self.json_string(true)
+    }
+
+    fn json_string(&self, pretty: bool) -> Result<String, serde_json::Error> {
+        let mut output = Vec::new();
+        self.print_json(&mut output, pretty)?;
+        // serde_json only ever writes valid UTF-8
+        Ok(String::from_utf8(output).expect("serde_json produced invalid UTF-8"))
+    }
+
+    /// The per-module section of [`ProcessState::print_json`], as a JSON array.
+    ///
+    /// There's one object per loaded module, in the order the minidump lists them
+    /// (which is load order, in practice). See `json-schema.md` for details on the
+    /// format, which is as stable as the rest of the JSON output.
+    pub fn modules_json(&self) -> serde_json::Value {
+        self.set_print_context();
+
+        let modules = self.modules.iter().map(|module| {
+            let full_name = module.code_file();
+            let name = basename(&full_name);
+
+            // Gather statistics on the module's symbols
+            let stats = self.symbol_stats.get(name);
+            let had_stats = stats.is_some();
+            let default = SymbolStats::default();
+            let stats = stats.unwrap_or(&default);
+            // Only consider the symbols "missing" if the symbolizer
+            // actually has statistics on them (implying it *tried* to
+            // get the symbols but failed.)
+            let missing_symbols = had_stats && !stats.loaded_symbols;
+            json!({
+                "base_addr": Address(module.raw.base_of_image).to_string(),
+                // filename | empty string
+                "debug_file": basename(module.debug_file().unwrap_or(Cow::Borrowed("")).borrow()),
+                // [[:xdigit:]]{33} | empty string
+                "debug_id": module.debug_identifier().unwrap_or_default().breakpad().to_string(),
+                "end_addr": Address(module.raw.base_of_image + module.raw.size_of_image as u64).to_string(),
+                "filename": &name,
+                "code_id": module.code_identifier().unwrap_or_default().as_str(),
+                "version": module.version(),
+                // [UNSTABLE:evil_json]
+                "cert_subject": self.cert_info.get(name),
+
+                // These are all just metrics for debugging minidump-processor's execution
+
+                // optional, if mdsw looked for the file and it doesn't exist
+                "missing_symbols": missing_symbols,
+                // optional, if mdsw looked for the file and it does exist
+                "loaded_symbols": stats.loaded_symbols,
+                // optional, if mdsw found a file that has parse errors
+                "corrupt_symbols": stats.corrupt_symbols,
+                // optional, "not_found" | "load_error" | "loaded" if mdsw looked for the file
+                "symbol_status": had_stats.then(|| stats.status.as_str()),
+                // optional, why the symbol file couldn't be loaded
+                "symbol_load_error": match &stats.status {
+                    SymbolStatus::LoadError(details) => Some(details),
+                    _ => None,
+                },
+                // optional, url of symbol file
+                "symbol_url": stats.symbol_url,
+            })
+        });
+        json!(modules.collect::<Vec<_>>())
+    }
+
     fn set_print_context(&self) {
         SERIALIZATION_CONTEXT.with(|ctx| {
             ctx.borrow_mut().pointer_width = Some(self.system_info.cpu.pointer_width());
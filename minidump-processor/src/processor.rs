@@ -1,23 +1,35 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
 use std::ops::{Deref, RangeInclusive};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+use debugid::DebugId;
 use minidump::system_info::PointerWidth;
 use minidump::{self, *};
+use minidump_common::utils::basename;
 
 use crate::op_analysis::MemoryAccess;
-use crate::process_state::{CallStack, CallStackInfo, LinuxStandardBase, ProcessState};
+use crate::process_state::{
+    CallStack, CallStackInfo, CrashClassification, LinuxStandardBase, ProcessState,
+};
+use crate::sanitizer;
 use crate::stackwalker;
 use crate::symbols::*;
-use crate::system_info::SystemInfo;
+use crate::system_info::{SystemInfo, SystemInfoOverride};
 use crate::{arg_recovery, FrameTrust, StackFrame};
 use crate::{evil, AdjustedAddress};
 
+/// How many code bytes to save from the crashing instruction onwards.
+const CRASH_INSTRUCTION_BYTES: usize = 32;
+/// How many instructions to disassemble from the crashing instruction onwards.
+const CRASH_DISASSEMBLY_INSTRUCTIONS: usize = 4;
+
 /// Configuration of the processor's exact behaviour.
 ///
 /// This can be used to either:
@@ -70,6 +82,396 @@ pub struct ProcessorOptions<'a> {
     ///
     /// See [`PendingProcessorStats`] and [`PendingProcessorStatSubscriptions`].
     pub stat_reporter: Option<&'a PendingProcessorStats>,
+
+    /// **\[UNSTABLE\]** Interpret the minidump as if it came from a different OS/CPU.
+    ///
+    /// This is an escape hatch for reprocessing minidumps whose system info is
+    /// wrong or misleading. The overridden values are used for decoding the crash
+    /// reason and address, selecting unwinding heuristics, and are what will be
+    /// reported in [`ProcessState::system_info`][].
+    ///
+    /// This only affects interpretation: the minidump's streams (including the
+    /// contexts of threads, whose layout is determined by the raw system info)
+    /// are still parsed exactly as they were written.
+    pub override_system_info: Option<SystemInfoOverride>,
+
+    /// Unwind every thread, but don't look up function names or source lines.
+    ///
+    /// Each [`StackFrame`][] will still have its `module` filled in, so it can later be
+    /// symbolicated on demand with [`symbolicate_frame`][crate::symbolicate_frame]. This is
+    /// useful for interactive tools that only need to symbolicate what they display.
+    ///
+    /// The symbol provider passed to the processor is still used for unwinding (CFI),
+    /// so passing one without symbols makes unwinding cheap at the cost of its accuracy.
+    pub defer_symbolication: bool,
+
+    /// **\[UNSTABLE\]** Unwind Windows x86-64 frames with the unwinding information of their modules.
+    ///
+    /// Windows x86-64 images describe how to unwind each of their functions in their `.pdata`
+    /// and `.xdata` sections. If those sections were captured in the minidump's memory,
+    /// they are used to unwind frames that have no CFI in their symbols. This makes it possible
+    /// to correctly unwind code that doesn't use frame pointers without symbols.
+    pub use_pdata_unwinding: bool,
+
+    /// Parsers for streams that this library doesn't know about.
+    ///
+    /// Streams in the minidump with a registered type are handed to their parser
+    /// instead of being reported in [`ProcessState::unknown_streams`][], and the
+    /// parsed values can be retrieved with [`ProcessState::custom_stream`][].
+    ///
+    /// See [`CustomStreamRegistry`].
+    pub custom_streams: Option<&'a CustomStreamRegistry>,
+
+    /// Look up function names and source lines for the backtraces of handle operations.
+    ///
+    /// Application Verifier records a backtrace for each operation on a handle, which is
+    /// reported in [`ProcessState::handle_operations`][]. This symbolicates those
+    /// backtraces with the same symbol provider as the threads, which may require
+    /// fetching symbols for modules that aren't on any thread's stack.
+    pub symbolicate_handle_operations: bool,
+
+    /// Record the registers of every frame in [`StackFrame::registers`][].
+    ///
+    /// This is useful for debugging the stack walker, or for finding out why a
+    /// stack looks wrong.
+    pub frame_registers: bool,
+
+    /// Extra modules to consider when mapping addresses to modules.
+    ///
+    /// This is useful when the minidump's own module list is missing or incomplete,
+    /// but the modules are known from elsewhere (e.g. another minidump of the same
+    /// process). These modules are used for unwinding and symbol lookup as if they
+    /// were in the minidump, but a module in the minidump always takes precedence
+    /// over an additional module with the same base address.
+    pub additional_modules: Option<MinidumpModuleList>,
+
+    /// A last resort for naming frames that symbols couldn't name.
+    ///
+    /// After all threads have been unwound and symbolicated, this is called for every
+    /// frame that still has no function name (e.g. because it is in JIT code), with
+    /// the processed state for context. It isn't used with
+    /// [`defer_symbolication`][Self::defer_symbolication], since no frame is named then.
+    ///
+    /// See [`FallbackResolver`].
+    pub fallback_resolver: Option<&'a FallbackResolver>,
+
+    /// Drop threads whose id already appeared earlier in the thread list.
+    ///
+    /// Some buggy producers write the same thread more than once. Duplicates are always
+    /// warned about, and the first of them is always the one that is considered to
+    /// have requested the dump, but they are only removed from
+    /// [`ProcessState::threads`][] with this option.
+    pub deduplicate_threads: bool,
+
+    /// The number of bytes at the base of each stack that stack scanning ignores.
+    ///
+    /// Some platforms keep data that isn't part of any frame (e.g. guard or TLS
+    /// areas) at the base of a thread's stack, where it started growing down from,
+    /// which can contain values that look like return addresses. Scanning won't
+    /// consider anything in the last (highest) `scan_skip_bytes` of a thread's
+    /// stack memory, although other unwinding methods still read it.
+    pub scan_skip_bytes: u64,
+
+    /// Only consider pointer-aligned stack slots when scanning for return addresses.
+    ///
+    /// By default scanning starts at the stack pointer, whatever its alignment is.
+    /// With this option, it starts at the first pointer-aligned address instead.
+    pub scan_align_to_pointer: bool,
+
+    /// Hints for unwinding modules that have neither symbols nor CFI.
+    ///
+    /// See [`UnwindHints`].
+    pub unwind_hints: Option<&'a UnwindHints>,
+
+    /// Which modules to look up function names and source lines for.
+    ///
+    /// Frames in the other modules are left as module+offset, which saves the time
+    /// spent symbolicating libraries nobody is going to look at. Their symbols may
+    /// still be loaded to get the CFI that unwinding needs. By default every module
+    /// is symbolicated, see [`ModuleFilter`].
+    pub symbolicate_modules: ModuleFilter,
+}
+
+/// A parser for a custom stream, see [`CustomStreamRegistry::register`].
+pub type CustomStreamParser =
+    Box<dyn Fn(&[u8], Endian) -> Result<Box<dyn Any + Send + Sync>, minidump::Error> + Send + Sync>;
+
+/// A set of parsers for stream types this library doesn't know about.
+///
+/// This allows vendor-specific streams to be parsed during processing without
+/// having to teach this library about them. Pass it into
+/// [`ProcessorOptions::custom_streams`] to use it.
+///
+/// ## Example:
+///
+/// ```
+/// use minidump_processor::{CustomStreamRegistry, ProcessorOptions};
+///
+/// let mut custom_streams = CustomStreamRegistry::new();
+/// custom_streams.register(0x76650001, |bytes, _endian| Ok(Box::new(bytes.len())));
+///
+/// let mut options = ProcessorOptions::default();
+/// options.custom_streams = Some(&custom_streams);
+/// ```
+#[derive(Default)]
+pub struct CustomStreamRegistry {
+    parsers: HashMap<u32, CustomStreamParser>,
+}
+
+impl CustomStreamRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a parser for streams of type `stream_type`.
+    ///
+    /// The parser is given the raw contents of the stream and the endianness of the
+    /// minidump. If it fails, the stream is reported as unknown instead.
+    ///
+    /// Registering a second parser for the same type replaces the first one.
+    pub fn register<F>(&mut self, stream_type: u32, parser: F)
+    where
+        F: Fn(&[u8], Endian) -> Result<Box<dyn Any + Send + Sync>, minidump::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.parsers.insert(stream_type, Box::new(parser));
+    }
+
+    /// Get the parser registered for `stream_type`, if any.
+    pub fn get(&self, stream_type: u32) -> Option<&CustomStreamParser> {
+        self.parsers.get(&stream_type)
+    }
+}
+
+impl fmt::Debug for CustomStreamRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stream_types = self.parsers.keys().collect::<Vec<_>>();
+        stream_types.sort();
+        f.debug_struct("CustomStreamRegistry")
+            .field("stream_types", &stream_types)
+            .finish()
+    }
+}
+
+/// The name of a frame, as found by a [`FallbackResolver`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameName {
+    /// The name of the function.
+    pub function_name: String,
+    /// The address of the start of the function, if known.
+    pub function_base: Option<u64>,
+    /// The source file of the frame's instruction, if known.
+    pub source_file_name: Option<String>,
+    /// The source line of the frame's instruction, if known.
+    pub source_line: Option<u32>,
+}
+
+impl FrameName {
+    /// Create a `FrameName` with just a function name.
+    pub fn new(function_name: impl Into<String>) -> Self {
+        FrameName {
+            function_name: function_name.into(),
+            ..FrameName::default()
+        }
+    }
+}
+
+/// A custom way of naming frames that symbols can't name.
+///
+/// This is useful for code that has no symbol files but whose layout is known some
+/// other way, like JIT code or generated thunks. Pass it into
+/// [`ProcessorOptions::fallback_resolver`] to use it.
+///
+/// ## Example:
+///
+/// ```
+/// use minidump_processor::{FallbackResolver, FrameName, ProcessorOptions};
+///
+/// let resolver = FallbackResolver::new(|address, _state| {
+///     (0x1000..0x2000)
+///         .contains(&address)
+///         .then(|| FrameName::new("jit_function"))
+/// });
+///
+/// let mut options = ProcessorOptions::default();
+/// options.fallback_resolver = Some(&resolver);
+/// ```
+pub struct FallbackResolver {
+    resolver: FallbackResolverFn,
+}
+
+type FallbackResolverFn = Box<dyn Fn(u64, &ProcessState) -> Option<FrameName> + Send + Sync>;
+
+impl FallbackResolver {
+    /// Create a resolver from a function.
+    ///
+    /// The function is given the address of a frame's instruction and the
+    /// processed state, and returns the name of the frame if it knows it.
+    pub fn new<F>(resolver: F) -> Self
+    where
+        F: Fn(u64, &ProcessState) -> Option<FrameName> + Send + Sync + 'static,
+    {
+        FallbackResolver {
+            resolver: Box::new(resolver),
+        }
+    }
+
+    /// Name the frame at `address`, if possible.
+    pub fn resolve(&self, address: u64, state: &ProcessState) -> Option<FrameName> {
+        (self.resolver)(address, state)
+    }
+}
+
+impl fmt::Debug for FallbackResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackResolver").finish_non_exhaustive()
+    }
+}
+
+/// How the functions of a module set up their stack frames.
+///
+/// See [`UnwindHints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnwindHint {
+    /// The functions use the standard frame pointer prologue (`push ebp; mov ebp, esp`).
+    ///
+    /// Frames unwound with this are reported as [`FrameTrust::UnwindHint`], since
+    /// only the hint says that `ebp` holds a frame pointer.
+    FramePointer {
+        /// The number of bytes of arguments the functions pop off the stack when they
+        /// return, as with `stdcall`. This is 0 for `cdecl`.
+        parameter_size: u32,
+    },
+    /// The functions don't use a frame pointer, and their return address is
+    /// `frame_size` bytes above the stack pointer.
+    ///
+    /// Frames unwound with this are reported as [`FrameTrust::UnwindHint`], since
+    /// nothing in the minidump confirms the frame size.
+    Frameless {
+        /// The distance from the stack pointer to the return address.
+        frame_size: u32,
+        /// The number of bytes of arguments the functions pop off the stack when they
+        /// return, as with `stdcall`. This is 0 for `cdecl`.
+        parameter_size: u32,
+    },
+}
+
+/// Hints for unwinding modules that have neither symbols nor CFI, by debug id.
+///
+/// Without CFI the stack walker has to guess with frame pointers and stack scanning,
+/// which goes wrong for functions that don't follow the usual conventions. When the
+/// conventions of a module are known, a hint for it is used to unwind its frames
+/// instead (CFI from symbols still takes precedence). Pass it into
+/// [`ProcessorOptions::unwind_hints`] to use it.
+///
+/// Hints are currently only used for x86.
+///
+/// ## Example:
+///
+/// ```
+/// use minidump_processor::{ProcessorOptions, UnwindHint, UnwindHints};
+///
+/// let debug_id = "e28da0a2b8cf4bae93ee5c6b1c2c8f3b1".parse().unwrap();
+/// let mut unwind_hints = UnwindHints::new();
+/// unwind_hints.register(debug_id, UnwindHint::FramePointer { parameter_size: 8 });
+///
+/// let mut options = ProcessorOptions::default();
+/// options.unwind_hints = Some(&unwind_hints);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct UnwindHints {
+    hints: HashMap<DebugId, UnwindHint>,
+}
+
+impl UnwindHints {
+    /// Create an empty set of hints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `hint` to unwind the frames of the module with the debug id `debug_id`.
+    ///
+    /// Registering a second hint for the same module replaces the first one.
+    pub fn register(&mut self, debug_id: DebugId, hint: UnwindHint) {
+        self.hints.insert(debug_id, hint);
+    }
+
+    /// Get the hint registered for the module with the debug id `debug_id`, if any.
+    pub fn get(&self, debug_id: DebugId) -> Option<UnwindHint> {
+        self.hints.get(&debug_id).copied()
+    }
+}
+
+/// Which modules to look up symbols for, see [`ProcessorOptions::symbolicate_modules`].
+///
+/// Modules are selected by the file name of their code or debug file (compared
+/// ignoring ASCII case, since that's how Windows compares them), or by debug id. A
+/// filter is either an allowlist, which only selects the modules added to it, or a
+/// denylist, which selects every module except those. The default filter is an
+/// empty denylist, which selects every module.
+///
+/// ## Example:
+///
+/// ```
+/// use minidump_processor::{ModuleFilter, ProcessorOptions};
+///
+/// let mut system_libraries = ModuleFilter::denylist();
+/// system_libraries.add_name("ntdll.dll");
+/// system_libraries.add_name("kernelbase.dll");
+///
+/// let mut options = ProcessorOptions::default();
+/// options.symbolicate_modules = system_libraries;
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ModuleFilter {
+    allowlist: bool,
+    names: Vec<String>,
+    debug_ids: HashSet<DebugId>,
+}
+
+impl ModuleFilter {
+    /// Create a filter that only selects the modules added to it.
+    pub fn allowlist() -> Self {
+        Self {
+            allowlist: true,
+            ..Self::default()
+        }
+    }
+
+    /// Create a filter that selects every module except the ones added to it.
+    pub fn denylist() -> Self {
+        Self::default()
+    }
+
+    /// Add the modules whose code or debug file is named `name` (without any directories).
+    pub fn add_name(&mut self, name: &str) {
+        self.names.push(name.to_owned());
+    }
+
+    /// Add the module with the debug id `debug_id`.
+    pub fn add_debug_id(&mut self, debug_id: DebugId) {
+        self.debug_ids.insert(debug_id);
+    }
+
+    /// Whether this filter selects `module`.
+    pub fn selects(&self, module: &MinidumpModule) -> bool {
+        let file_names = [module.code_file(), module.debug_file().unwrap_or_default()];
+        let has_name = file_names.iter().any(|file| {
+            let file_name = basename(file);
+            !file_name.is_empty()
+                && self
+                    .names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(file_name))
+        });
+        let has_debug_id = module
+            .debug_identifier()
+            .is_some_and(|debug_id| self.debug_ids.contains(&debug_id));
+        (has_name || has_debug_id) == self.allowlist
+    }
 }
 
 /// A subscription to various live updates during minidump processing.
@@ -292,6 +694,19 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: false,
             stat_reporter: None,
+            override_system_info: None,
+            defer_symbolication: false,
+            use_pdata_unwinding: false,
+            custom_streams: None,
+            symbolicate_handle_operations: false,
+            frame_registers: false,
+            additional_modules: None,
+            fallback_resolver: None,
+            deduplicate_threads: false,
+            scan_skip_bytes: 0,
+            scan_align_to_pointer: false,
+            unwind_hints: None,
+            symbolicate_modules: ModuleFilter::default(),
         }
     }
 
@@ -311,6 +726,19 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: false,
             stat_reporter: None,
+            override_system_info: None,
+            defer_symbolication: false,
+            use_pdata_unwinding: false,
+            custom_streams: None,
+            symbolicate_handle_operations: false,
+            frame_registers: false,
+            additional_modules: None,
+            fallback_resolver: None,
+            deduplicate_threads: false,
+            scan_skip_bytes: 0,
+            scan_align_to_pointer: false,
+            unwind_hints: None,
+            symbolicate_modules: ModuleFilter::default(),
         }
     }
 
@@ -318,6 +746,7 @@ impl ProcessorOptions<'_> {
     ///
     /// * `evil_json: None`
     /// * `recover_function_args: true`
+    /// * `use_pdata_unwinding: true`
     ///
     /// (evil_json is still "disabled" because you need to give it needs a path.)
     ///
@@ -327,6 +756,19 @@ impl ProcessorOptions<'_> {
             evil_json: None,
             recover_function_args: true,
             stat_reporter: None,
+            override_system_info: None,
+            defer_symbolication: false,
+            use_pdata_unwinding: true,
+            custom_streams: None,
+            symbolicate_handle_operations: false,
+            frame_registers: false,
+            additional_modules: None,
+            fallback_resolver: None,
+            deduplicate_threads: false,
+            scan_skip_bytes: 0,
+            scan_align_to_pointer: false,
+            unwind_hints: None,
+            symbolicate_modules: ModuleFilter::default(),
         }
     }
 
@@ -453,21 +895,47 @@ where
 
     if let Some(details) = &mut exception_details {
         info.check_for_bitflips(details);
+        info.classify_crash(details);
     }
     info.into_process_state(dump, symbol_provider, exception_details)
         .await
 }
 
+/// Like [`process_minidump_with_options`], but a symbol provider that fails on some
+/// modules only leaves those modules unsymbolicated.
+///
+/// The provider is wrapped in an [`IsolatedSymbolProvider`], so panics in it are
+/// caught instead of aborting processing. The errors it had are recorded in
+/// [`ProcessState::symbol_provider_errors`].
+pub async fn process_minidump_isolated<'a, T, P>(
+    dump: &Minidump<'a, T>,
+    symbol_provider: &P,
+    options: ProcessorOptions<'_>,
+) -> Result<ProcessState, ProcessError>
+where
+    T: Deref<Target = [u8]> + 'a,
+    P: SymbolProvider + Sync,
+{
+    let provider = IsolatedSymbolProvider::new(symbol_provider);
+    let mut state = process_minidump_with_options(dump, &provider, options).await?;
+    state.symbol_provider_errors = provider.errors();
+    Ok(state)
+}
+
 struct MinidumpInfo<'a> {
     options: ProcessorOptions<'a>,
     evil: crate::evil::Evil,
     thread_list: MinidumpThreadList<'a>,
     thread_names: MinidumpThreadNames,
+    thread_infos: MinidumpThreadInfoList,
     dump_system_info: MinidumpSystemInfo,
     linux_standard_base: Option<LinuxStandardBase>,
+    command_line: Option<Vec<String>>,
+    environment: Option<Vec<(String, String)>>,
     system_info: SystemInfo,
     mac_crash_info: Option<Vec<RawMacCrashInfo>>,
     misc_info: Option<MinidumpMiscInfo>,
+    linux_process_id: Option<u32>,
     dump_thread_id: Option<u32>,
     requesting_thread_id: Option<u32>,
     modules: MinidumpModuleList,
@@ -496,9 +964,12 @@ impl<'a> MinidumpInfo<'a> {
             .unwrap_or_default();
 
         // Thread list is required for processing.
-        let thread_list = dump
+        let mut thread_list = dump
             .get_stream::<MinidumpThreadList>()
             .or(Err(ProcessError::MissingThreadList))?;
+        if options.deduplicate_threads {
+            thread_list.deduplicate();
+        }
 
         let num_threads = thread_list.threads.len() as u64;
         if let Some(reporter) = options.stat_reporter {
@@ -510,6 +981,11 @@ impl<'a> MinidumpInfo<'a> {
             .get_stream::<MinidumpThreadNames>()
             .unwrap_or_else(|_| MinidumpThreadNames::default());
 
+        // Extra info about threads (e.g. their affinity) is also optional.
+        let thread_infos = dump
+            .get_stream::<MinidumpThreadInfoList>()
+            .unwrap_or_default();
+
         // System info is required for processing.
         let dump_system_info = dump
             .get_stream::<MinidumpSystemInfo>()
@@ -521,8 +997,9 @@ impl<'a> MinidumpInfo<'a> {
         let linux_cpu_info = dump
             .get_stream::<MinidumpLinuxCpuInfo>()
             .unwrap_or_default();
-        let _linux_environ = dump.get_stream::<MinidumpLinuxEnviron>().ok();
-        let _linux_proc_status = dump.get_stream::<MinidumpLinuxProcStatus>().ok();
+        let linux_cmd_line = dump.get_stream::<MinidumpLinuxCmdLine>().ok();
+        let linux_environ = dump.get_stream::<MinidumpLinuxEnviron>().ok();
+        let linux_proc_status = dump.get_stream::<MinidumpLinuxProcStatus>().ok();
 
         // Extract everything we care about from linux streams here.
         // We don't eagerly process them in the minidump crate because there's just
@@ -533,37 +1010,99 @@ impl<'a> MinidumpInfo<'a> {
         let cpu_microcode_version = get_microcode_version(&linux_cpu_info, &evil);
 
         let linux_standard_base = linux_standard_base.map(|linux_standard_base| {
-            let mut lsb = LinuxStandardBase::default();
-            for (key, val) in linux_standard_base.iter() {
-                match key.as_bytes() {
-                    b"DISTRIB_ID" | b"ID" => lsb.id = val.to_string_lossy().into_owned(),
-                    b"DISTRIB_RELEASE" | b"VERSION_ID" => {
-                        lsb.release = val.to_string_lossy().into_owned()
-                    }
-                    b"DISTRIB_CODENAME" | b"VERSION_CODENAME" => {
-                        lsb.codename = val.to_string_lossy().into_owned()
-                    }
-                    b"DISTRIB_DESCRIPTION" | b"PRETTY_NAME" => {
-                        lsb.description = val.to_string_lossy().into_owned()
-                    }
-                    _ => {}
-                }
+            let to_string = |val: Option<&minidump::strings::LinuxOsStr>| {
+                val.map(|val| val.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            LinuxStandardBase {
+                id: to_string(linux_standard_base.id()),
+                release: to_string(linux_standard_base.release()),
+                codename: to_string(linux_standard_base.codename()),
+                description: to_string(linux_standard_base.description()),
             }
-            lsb
+        });
+
+        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+
+        let command_line = linux_cmd_line.map(|cmd_line| {
+            cmd_line
+                .args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        });
+        let environment = linux_environ.map(|environ| {
+            environ
+                .iter()
+                .map(|(key, val)| {
+                    (
+                        key.to_string_lossy().into_owned(),
+                        val.to_string_lossy().into_owned(),
+                    )
+                })
+                .collect()
+        });
+
+        // The dump is written by a thread of the crashing process reading
+        // /proc/self/status, so Tgid is the process id (Pid is that thread's id).
+        let linux_process_id = linux_proc_status.and_then(|status| {
+            status
+                .iter()
+                .find(|(key, _)| key.as_bytes() == b"Tgid")
+                .and_then(|(_, val)| val.to_string_lossy().trim().parse().ok())
         });
 
         let cpu_info = dump_system_info
             .cpu_info()
             .map(|string| string.into_owned());
 
+        // The override has to be applied before anything is derived from the OS.
+        let (os, cpu) = match options.override_system_info {
+            Some(SystemInfoOverride { os, cpu }) => {
+                tracing::info!(
+                    "overriding system info: {} {} -> {} {}",
+                    dump_system_info.os,
+                    dump_system_info.cpu,
+                    os,
+                    cpu
+                );
+                (os, cpu)
+            }
+            None => (dump_system_info.os, dump_system_info.cpu),
+        };
+
+        // Prefer the distro's own description, falling back to "id release".
+        let os_flavor = linux_standard_base.as_ref().and_then(|lsb| {
+            if !lsb.description.is_empty() {
+                Some(lsb.description.clone())
+            } else if !lsb.id.is_empty() {
+                Some(format!("{} {}", lsb.id, lsb.release).trim_end().to_owned())
+            } else {
+                None
+            }
+        });
+
+        // The most specific name for the OS: Windows' build string, or the Linux distro.
+        let windows_build_string = misc_info.as_ref().and_then(|info| info.build_string());
+        let os_pretty_name = match (os, windows_build_string, &os_flavor) {
+            (system_info::Os::Windows, Some(build_string), _) => format!("Windows {build_string}"),
+            (system_info::Os::Linux, _, Some(distro)) => distro.clone(),
+            _ => {
+                let mut overridden = dump_system_info.clone();
+                overridden.os = os;
+                overridden.os_version_string()
+            }
+        };
+
         let system_info = SystemInfo {
-            os: dump_system_info.os,
+            os,
             os_version: Some(os_version),
             os_build,
-            cpu: dump_system_info.cpu,
+            cpu,
             cpu_info,
             cpu_microcode_version,
             cpu_count: dump_system_info.raw.number_of_processors as usize,
+            os_flavor,
+            os_pretty_name,
         };
 
         let mac_crash_info = dump
@@ -571,7 +1110,6 @@ impl<'a> MinidumpInfo<'a> {
             .ok()
             .map(|info| info.raw);
 
-        let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
         // If Breakpad info exists in dump, get dump and requesting thread ids.
         let breakpad_info = dump.get_stream::<MinidumpBreakpadInfo>();
         let (dump_thread_id, requesting_thread_id) = if let Ok(info) = breakpad_info {
@@ -580,11 +1118,23 @@ impl<'a> MinidumpInfo<'a> {
             (None, None)
         };
         // Get assertion
-        let modules = match dump.get_stream::<MinidumpModuleList>() {
+        let mut modules = match dump.get_stream::<MinidumpModuleList>() {
             Ok(module_list) => module_list,
             // Just give an empty list, simplifies things.
             Err(_) => MinidumpModuleList::new(),
         };
+        if let Some(additional) = &options.additional_modules {
+            modules = merge_modules(modules, additional);
+        }
+        let linux_maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
+        if let Some(maps) = &linux_maps {
+            if matches!(
+                system_info.os,
+                system_info::Os::Linux | system_info::Os::Android
+            ) {
+                modules = with_linux_map_paths(modules, maps);
+            }
+        }
         let unloaded_modules = match dump.get_stream::<MinidumpUnloadedModuleList>() {
             Ok(module_list) => module_list,
             // Just give an empty list, simplifies things.
@@ -592,7 +1142,6 @@ impl<'a> MinidumpInfo<'a> {
         };
         let memory_list = dump.get_memory().unwrap_or_default();
         let memory_info_list = dump.get_stream::<MinidumpMemoryInfoList>().ok();
-        let linux_maps = dump.get_stream::<MinidumpLinuxMaps>().ok();
         let memory_info =
             UnifiedMemoryInfoList::new(memory_info_list, linux_maps).unwrap_or_default();
 
@@ -604,11 +1153,15 @@ impl<'a> MinidumpInfo<'a> {
             evil,
             thread_list,
             thread_names,
+            thread_infos,
             dump_system_info,
             linux_standard_base,
+            command_line,
+            environment,
             system_info,
             mac_crash_info,
             misc_info,
+            linux_process_id,
             dump_thread_id,
             requesting_thread_id,
             modules,
@@ -631,12 +1184,21 @@ impl<'a> MinidumpInfo<'a> {
         let reason = exception.get_crash_reason(self.system_info.os, self.system_info.cpu);
         let address = exception.get_crash_address(self.system_info.os, self.system_info.cpu);
 
-        let stack_memory_ref = self
+        let thread_stack = self
             .thread_list
             .get_thread(exception.get_crashing_thread_id())
             .and_then(|thread| thread.stack_memory(&self.memory_list));
 
-        let context = exception.context(&self.dump_system_info, self.misc_info.as_ref());
+        let context = exception.context_with_memory(
+            &self.dump_system_info,
+            self.misc_info.as_ref(),
+            &self.memory_list,
+        );
+
+        let stack_memory_ref = match context.as_deref() {
+            Some(context) => context_stack_memory(context, thread_stack, &self.memory_list),
+            None => thread_stack,
+        };
 
         let mut exception_info: Option<crate::ExceptionInfo> = None;
         let mut instruction_registers: BTreeSet<&'static str> = Default::default();
@@ -668,8 +1230,13 @@ impl<'a> MinidumpInfo<'a> {
                         address: address.into(),
                         adjusted_address,
                         instruction_str: Some(op_analysis.instruction_str),
+                        instruction_bytes: None,
+                        disassembly: Vec::new(),
                         memory_accesses: op_analysis.memory_accesses,
                         possible_bit_flips: Default::default(),
+                        crash_address_in_module: None,
+                        nearest_module: None,
+                        crash_classification: None,
                     });
                     instruction_registers = op_analysis.registers;
                 }
@@ -679,15 +1246,36 @@ impl<'a> MinidumpInfo<'a> {
             }
         }
 
-        let info = exception_info.unwrap_or_else(|| crate::ExceptionInfo {
+        let mut info = exception_info.unwrap_or_else(|| crate::ExceptionInfo {
             reason,
             address: address.into(),
             adjusted_address: None,
             instruction_str: None,
+            instruction_bytes: None,
+            disassembly: Vec::new(),
             memory_accesses: None,
             possible_bit_flips: Default::default(),
+            crash_address_in_module: None,
+            nearest_module: None,
+            crash_classification: None,
         });
 
+        if let Some(context) = context.as_ref() {
+            info.instruction_bytes = crate::op_analysis::read_thread_instruction_bytes(
+                context,
+                &self.memory_list,
+                CRASH_INSTRUCTION_BYTES,
+            )
+            .ok()
+            .map(<[u8]>::to_vec);
+            info.disassembly = crate::op_analysis::disassemble_thread_context(
+                context,
+                &self.memory_list,
+                CRASH_DISASSEMBLY_INSTRUCTIONS,
+            )
+            .unwrap_or_default();
+        }
+
         Some(ExceptionDetails {
             info,
             context,
@@ -695,6 +1283,46 @@ impl<'a> MinidumpInfo<'a> {
         })
     }
 
+    /// Classify access violations by the memory they touched.
+    ///
+    /// The classification will be added to `exception_details`.
+    pub fn classify_crash(&self, exception_details: &mut ExceptionDetails<'a>) {
+        let info = &mut exception_details.info;
+        if !is_access_violation(&info.reason) {
+            return;
+        }
+
+        let usable = |mi: &UnifiedMemoryInfo| mi.is_committed() && !mi.is_guard_page();
+        let address = info.address.0;
+        let memory_op = bitflip::MemoryOperation::from_crash_reason(&info.reason);
+
+        let executed_non_executable = exception_details
+            .context
+            .as_deref()
+            .and_then(|context| {
+                self.memory_info
+                    .memory_info_at_address(context.get_instruction_pointer())
+            })
+            .map(|mi| !(usable(&mi) && mi.is_executable()))
+            .unwrap_or(false);
+        let wrote_read_only = memory_op == bitflip::MemoryOperation::Write
+            && self
+                .memory_info
+                .memory_info_at_address(address)
+                .map(|mi| usable(&mi) && mi.is_readable() && !mi.is_writable())
+                .unwrap_or(false);
+
+        info.crash_classification = if executed_non_executable {
+            Some(CrashClassification::ExecuteNonExecutable)
+        } else if wrote_read_only {
+            Some(CrashClassification::WriteReadOnly)
+        } else if address < NULL_PAGE_SIZE {
+            Some(CrashClassification::NullPage)
+        } else {
+            None
+        };
+    }
+
     /// Check for bit-flips of the exception address/instruction.
     ///
     /// Additional bit flip information will be added to `exception_details`.
@@ -784,9 +1412,21 @@ impl<'a> MinidumpInfo<'a> {
             .map(|(i, thread)| {
                 let id = thread.raw.thread_id;
 
+                let priority_class = Some(thread.raw.priority_class);
+                let priority = Some(thread.raw.priority);
+                let affinity = self
+                    .thread_infos
+                    .get_thread_info(id)
+                    .map(|info| info.affinity());
+
                 // If this is the thread that wrote the dump, skip processing it.
                 if self.dump_thread_id == Some(id) {
-                    return CallStack::with_info(id, CallStackInfo::DumpThreadSkipped);
+                    return CallStack {
+                        priority_class,
+                        priority,
+                        affinity,
+                        ..CallStack::with_info(id, CallStackInfo::DumpThreadSkipped)
+                    };
                 }
 
                 let thread_context =
@@ -794,8 +1434,25 @@ impl<'a> MinidumpInfo<'a> {
                 // If this thread requested the dump then try to use the exception
                 // context if it exists. (prefer the exception stream's thread id over
                 // the breakpad info stream's thread id.)
-                let context = if crashing_thread_id.or(self.requesting_thread_id) == Some(id) {
+                let is_requesting_thread = requesting_thread.is_none()
+                    && crashing_thread_id.or(self.requesting_thread_id) == Some(id);
+                let context = if is_requesting_thread {
                     requesting_thread = Some(i);
+                    // Some dumpers (notably Breakpad on Linux) record the context of the
+                    // signal handler in the thread list, so the two contexts may disagree.
+                    // The exception's context is the one that actually crashed.
+                    if let (Some(exception_context), Some(thread_context)) =
+                        (exception_context.as_deref(), thread_context.as_deref())
+                    {
+                        if exception_context.get_instruction_pointer()
+                            != thread_context.get_instruction_pointer()
+                        {
+                            tracing::debug!(
+                                "thread {id:#x} context differs from the exception context, \
+                                 preferring the exception context"
+                            );
+                        }
+                    }
                     exception_context.as_deref().or(thread_context.as_deref())
                 } else {
                     thread_context.as_deref()
@@ -817,24 +1474,68 @@ impl<'a> MinidumpInfo<'a> {
                     (CallStackInfo::MissingContext, vec![])
                 };
 
+                let (stack_size, stack_used) = stack_usage(
+                    thread,
+                    context.into_iter().chain(thread_context.as_deref()),
+                    &self.memory_list,
+                );
+
                 CallStack {
                     frames,
                     info,
                     thread_id: id,
                     thread_name: name,
                     last_error_value: thread.last_error(self.system_info.cpu, &self.memory_list),
+                    stack_size,
+                    stack_used,
+                    stack_memory_present: matches!(stack_size, Some(size) if size > 0),
+                    walk_end: None,
+                    priority_class,
+                    priority,
+                    affinity,
                 }
             })
             .collect();
 
-        // Collect up info on unimplemented/unknown modules
-        let unknown_streams = dump.unknown_streams().collect();
+        // Collect up info on unimplemented/unknown modules, giving any custom
+        // parsers a chance to claim the unknown ones first.
+        let mut custom_streams = HashMap::new();
+        let unknown_streams = dump
+            .unknown_streams()
+            .filter(|stream| {
+                let parser = match self
+                    .options
+                    .custom_streams
+                    .and_then(|registry| registry.get(stream.stream_type))
+                {
+                    Some(parser) => parser,
+                    None => return true,
+                };
+                let parsed = dump
+                    .get_raw_stream(stream.stream_type)
+                    .and_then(|bytes| parser(bytes, dump.endian));
+                match parsed {
+                    Ok(value) => {
+                        custom_streams.insert(stream.stream_type, Arc::from(value));
+                        false
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "failed to parse custom stream 0x{:08x}: {e}",
+                            stream.stream_type
+                        );
+                        true
+                    }
+                }
+            })
+            .collect();
         let unimplemented_streams = dump.unimplemented_streams().collect();
 
         // Get symbol stats from the symbolizer
         let symbol_stats = symbol_provider.stats();
 
-        // Process create time is optional.
+        // Process id and create time are optional. The id is preferably taken from
+        // MiscInfo, falling back to Linux's /proc/self/status.
         let (process_id, process_create_time) = if let Some(misc_info) = self.misc_info.as_ref() {
             (
                 misc_info.raw.process_id().cloned(),
@@ -843,24 +1544,65 @@ impl<'a> MinidumpInfo<'a> {
         } else {
             (None, None)
         };
+        let process_id = process_id.or(self.linux_process_id);
+
+        // The processor speed is only in the power info of newer MiscInfo streams.
+        let cpu_mhz = self.misc_info.as_ref().and_then(|misc_info| {
+            let max_mhz = misc_info.raw.processor_max_mhz().copied();
+            let current_mhz = misc_info.raw.processor_current_mhz().copied();
+            max_mhz
+                .filter(|&mhz| mhz != 0)
+                .or(current_mhz)
+                .filter(|&mhz| mhz != 0)
+        });
 
+        let handle_operations = dump
+            .get_stream::<MinidumpHandleOperationList>()
+            .map(|list| {
+                list.iter()
+                    .map(|operation| crate::HandleOperationInfo {
+                        handle: operation.handle(),
+                        process_id: operation.process_id(),
+                        thread_id: operation.thread_id(),
+                        operation_type: operation.operation_type(),
+                        frames: operation
+                            .backtrace_modules(&self.modules)
+                            .map(|(address, module)| {
+                                crate::BacktraceFrame::new(address, module.cloned())
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // No exception, but some thread asked for the dump (e.g. via BreakpadInfo).
+        let requested_dump = exception_info.is_none() && requesting_thread.is_some();
         let mut state = ProcessState {
             process_id,
             time: SystemTime::UNIX_EPOCH + Duration::from_secs(dump.header.time_date_stamp as u64),
             process_create_time,
+            cpu_mhz,
             cert_info: self.evil.certs,
             exception_info,
             assertion: None,
+            sanitizer_report: sanitizer::find_sanitizer_report(dump),
             requesting_thread,
+            requested_dump,
             system_info: self.system_info,
             linux_standard_base: self.linux_standard_base,
+            command_line: self.command_line,
+            environment: self.environment,
             mac_crash_info: self.mac_crash_info,
             threads,
             modules: self.modules,
             unloaded_modules: self.unloaded_modules,
+            handle_operations,
             unknown_streams,
             unimplemented_streams,
+            custom_streams,
             symbol_stats,
+            symbol_provider_errors: HashMap::new(),
         };
 
         // Report the unwalked result
@@ -882,30 +1624,23 @@ impl<'a> MinidumpInfo<'a> {
                     .zip(self.thread_list.threads.iter())
                     .enumerate()
                     .map(|(i, (stack, thread))| async move {
-                        let mut stack_memory = thread.stack_memory(memory_list);
-                        // Always choose the memory region that is referenced by the context,
-                        // as the `exception_context` may refer to a different memory region than
-                        // the `thread_context`, which in turn would fail to stack walk.
-                        let stack_ptr = stack
-                            .frames
-                            .get(0)
-                            .map(|ctx_frame| ctx_frame.context.get_stack_pointer());
-                        if let Some(stack_ptr) = stack_ptr {
-                            let contains_stack_ptr = stack_memory
-                                .as_ref()
-                                .and_then(|memory| memory.get_memory_at_address::<u64>(stack_ptr))
-                                .is_some();
-                            if !contains_stack_ptr {
-                                stack_memory =
-                                    memory_list.memory_at_address(stack_ptr).or(stack_memory);
+                        let thread_stack = thread.stack_memory(memory_list);
+                        // The walker moves back over to the thread's stack once the frames
+                        // leave the region referenced by the context.
+                        let stack_memory = match stack.frames.first() {
+                            Some(frame) => {
+                                context_stack_memory(&frame.context, thread_stack, memory_list)
                             }
-                        }
+                            None => thread_stack,
+                        };
 
-                        stackwalker::walk_stack(
+                        stackwalker::walk_stack_with_thread_stack(
                             i,
                             options,
                             stack,
                             stack_memory,
+                            thread_stack,
+                            Some(memory_list),
                             modules,
                             system_info,
                             symbol_provider,
@@ -936,6 +1671,18 @@ impl<'a> MinidumpInfo<'a> {
                             arg_recovery::fill_arguments(stack, stack_memory);
                         }
 
+                        if options.frame_registers {
+                            for frame in &mut stack.frames {
+                                frame.registers = Some(
+                                    frame
+                                        .context
+                                        .valid_registers()
+                                        .map(|(reg, val)| (reg.to_owned(), val))
+                                        .collect(),
+                                );
+                            }
+                        }
+
                         // Report the unwalked result
                         if let Some(reporter) = options.stat_reporter {
                             reporter.inc_processed_threads();
@@ -947,6 +1694,40 @@ impl<'a> MinidumpInfo<'a> {
             .await
         };
 
+        // Now that the crashing thread's top frame has been unwound (and so has
+        // had its module looked up), check whether the crash happened in a module.
+        let threads = &state.threads;
+        let crash_frame = state
+            .requesting_thread
+            .and_then(|idx| threads.get(idx))
+            .and_then(|thread| thread.frames.first());
+        if let (Some(info), Some(frame)) = (state.exception_info.as_mut(), crash_frame) {
+            info.crash_address_in_module = Some(frame.module.is_some());
+            if frame.module.is_none() {
+                info.nearest_module = nearest_module(&state.modules, frame.instruction);
+            }
+        }
+
+        if self.options.symbolicate_handle_operations {
+            for operation in &mut state.handle_operations {
+                for frame in &mut operation.frames {
+                    if let Some(module) = frame.module.take() {
+                        // Frames without symbols are left unsymbolicated.
+                        if self.options.symbolicate_modules.selects(&module) {
+                            let _ = symbol_provider.fill_symbol(&module, frame).await;
+                        }
+                        frame.module = Some(module);
+                    }
+                }
+            }
+        }
+
+        if let Some(resolver) = self.options.fallback_resolver {
+            if !self.options.defer_symbolication {
+                resolve_unnamed_frames(&mut state, resolver);
+            }
+        }
+
         let symbol_stats = symbol_provider.stats();
         state.symbol_stats = symbol_stats;
 
@@ -954,6 +1735,125 @@ impl<'a> MinidumpInfo<'a> {
     }
 }
 
+/// Name the frames of `state`'s threads that have no function name with `resolver`.
+fn resolve_unnamed_frames(state: &mut ProcessState, resolver: &FallbackResolver) {
+    // The resolver gets to look at the whole state, so only apply its results at the end.
+    let names = state
+        .threads
+        .iter()
+        .enumerate()
+        .flat_map(|(thread_idx, thread)| {
+            thread
+                .frames
+                .iter()
+                .enumerate()
+                .filter(|(_, frame)| frame.function_name.is_none())
+                .map(move |(frame_idx, frame)| (thread_idx, frame_idx, frame.instruction))
+        })
+        .filter_map(|(thread_idx, frame_idx, address)| {
+            let name = resolver.resolve(address, state)?;
+            Some((thread_idx, frame_idx, name))
+        })
+        .collect::<Vec<_>>();
+
+    for (thread_idx, frame_idx, name) in names {
+        let frame = &mut state.threads[thread_idx].frames[frame_idx];
+        frame.function_name = Some(name.function_name);
+        frame.function_base = name.function_base;
+        frame.source_file_name = name.source_file_name;
+        frame.source_line = name.source_line;
+    }
+}
+
+/// Add the modules of `additional` that don't share a base address with a module of `modules`.
+fn merge_modules(
+    modules: MinidumpModuleList,
+    additional: &MinidumpModuleList,
+) -> MinidumpModuleList {
+    let bases: HashSet<u64> = modules.iter().map(|module| module.base_address()).collect();
+    let extra = additional
+        .iter()
+        .filter(|module| !bases.contains(&module.base_address()))
+        .cloned();
+    let merged: Vec<MinidumpModule> = modules.iter().cloned().chain(extra).collect();
+    MinidumpModuleList::from_modules(merged)
+}
+
+/// Give modules that are only named by a file name the full path of the file that
+/// is mapped over them in `maps`.
+///
+/// Some producers only record the file name of a module, but /proc/self/maps always
+/// has the full path.
+fn with_linux_map_paths(
+    modules: MinidumpModuleList,
+    maps: &MinidumpLinuxMaps,
+) -> MinidumpModuleList {
+    let modules: Vec<MinidumpModule> = modules
+        .iter()
+        .cloned()
+        .map(|mut module| {
+            if module.name.contains('/') {
+                return module;
+            }
+            let base = module.base_address();
+            let end = base.saturating_add(module.size());
+            // The end of a mapping in /proc/self/maps is exclusive.
+            let path = maps
+                .by_addr()
+                .filter(|map| map.base_address < end && base < map.final_address)
+                .find_map(|map| match &map.kind {
+                    MinidumpLinuxMapKind::File(path) => Some(path.to_string_lossy().into_owned()),
+                    _ => None,
+                });
+            if let Some(path) = path {
+                tracing::debug!("naming module {} after its mapping {}", module.name, path);
+                module.name = path;
+            }
+            module
+        })
+        .collect();
+    MinidumpModuleList::from_modules(modules)
+}
+
+/// Addresses below this are in the null page, which is never mapped.
+const NULL_PAGE_SIZE: u64 = 0x10000;
+
+/// Whether `reason` is an access to memory that wasn't allowed.
+fn is_access_violation(reason: &CrashReason) -> bool {
+    use minidump_common::errors::{ExceptionCodeLinux, ExceptionCodeMac};
+    matches!(
+        reason,
+        CrashReason::WindowsAccessViolation(_)
+            | CrashReason::LinuxSigsegv(_)
+            | CrashReason::LinuxGeneral(ExceptionCodeLinux::SIGSEGV, _)
+            | CrashReason::MacBadAccessKern(_)
+            | CrashReason::MacBadAccessArm(_)
+            | CrashReason::MacBadAccessPpc(_)
+            | CrashReason::MacBadAccessX86(_)
+            | CrashReason::MacGeneral(ExceptionCodeMac::EXC_BAD_ACCESS, _)
+    )
+}
+
+/// Find the module closest to `address`, which isn't in any module.
+fn nearest_module(modules: &MinidumpModuleList, address: u64) -> Option<crate::NearestModule> {
+    modules
+        .iter()
+        .map(|module| {
+            let gap = if address < module.base_address() {
+                module.base_address() - address
+            } else {
+                address.saturating_sub(module.base_address().saturating_add(module.size()))
+            };
+            (gap, module)
+        })
+        .min_by_key(|(gap, _)| *gap)
+        .map(|(gap, module)| crate::NearestModule {
+            name: module.code_file().into_owned(),
+            base_address: module.base_address().into(),
+            gap,
+        })
+}
+
 struct ExceptionDetails<'a> {
     info: crate::ExceptionInfo,
     context: Option<std::borrow::Cow<'a, MinidumpContext>>,
@@ -1082,6 +1982,55 @@ fn try_detect_null_pointer_in_disguise(memory_accesses: Option<&[MemoryAccess]>)
     None
 }
 
+/// Get the size of a thread's stack memory, and how much of it is in use.
+///
+/// Stacks grow down on all supported platforms, so the used part of the stack
+/// is everything between the stack pointer and the top of the stack.
+fn stack_usage<'c>(
+    thread: &MinidumpThread,
+    contexts: impl IntoIterator<Item = &'c MinidumpContext>,
+    memory_list: &UnifiedMemoryList,
+) -> (Option<u64>, Option<u64>) {
+    let stack = match thread.stack_memory(memory_list) {
+        Some(stack) => stack,
+        None => return (None, None),
+    };
+    let base = stack.base_address();
+    let top = base.checked_add(stack.size());
+    // Use the first context whose stack pointer is actually on the thread's stack.
+    let stack_used = top.and_then(|top| {
+        contexts
+            .into_iter()
+            .map(|context| context.get_stack_pointer())
+            .find(|&sp| base <= sp && sp <= top)
+            .map(|sp| top - sp)
+    });
+    (Some(stack.size()), stack_used)
+}
+
+/// Choose the memory region that is referenced by `context`'s stack pointer.
+///
+/// The exception context may refer to a different memory region than the thread's
+/// stack, which in turn would fail to stack walk. This happens when a signal is
+/// handled on an alt-stack, or when the thread list and exception stream disagree
+/// about a thread's context.
+fn context_stack_memory<'a, 'mdmp>(
+    context: &MinidumpContext,
+    thread_stack: Option<UnifiedMemory<'a, 'mdmp>>,
+    memory_list: &'a UnifiedMemoryList<'mdmp>,
+) -> Option<UnifiedMemory<'a, 'mdmp>> {
+    let stack_ptr = context.get_stack_pointer();
+    let contains_stack_ptr = thread_stack
+        .as_ref()
+        .and_then(|memory| memory.get_memory_at_address::<u64>(stack_ptr))
+        .is_some();
+    if contains_stack_ptr {
+        thread_stack
+    } else {
+        memory_list.memory_at_address(stack_ptr).or(thread_stack)
+    }
+}
+
 /// Bit-flip detection.
 mod bitflip {
     use super::*;
@@ -1111,12 +2060,15 @@ mod bitflip {
         }
 
         /// Return whether this memory operation is allowed in the given memory region.
+        ///
+        /// Guard pages and memory that isn't committed can't be accessed at all.
         pub fn allowed_for(&self, memory_info: &UnifiedMemoryInfo) -> bool {
+            let accessible = memory_info.is_committed() && !memory_info.is_guard_page();
             match self {
                 Self::Unknown => true,
-                Self::Read => memory_info.is_readable(),
-                Self::Write => memory_info.is_writable(),
-                Self::Execute => memory_info.is_executable(),
+                Self::Read => accessible && memory_info.is_readable(),
+                Self::Write => accessible && memory_info.is_writable(),
+                Self::Execute => accessible && memory_info.is_executable(),
             }
         }
     }
@@ -0,0 +1,184 @@
+//! Detection of sanitizer (ASan, UBSan, ...) reports stashed in a minidump.
+//!
+//! Sanitizer-instrumented builds sometimes save the report the sanitizer printed
+//! in a comment stream of the minidump. The report usually explains the crash far
+//! better than the exception does (which is typically just a SIGABRT), so we pull
+//! out the important bits of it.
+//!
+//! Vendor-specific streams are not scanned: their contents are binary data of an
+//! unknown format, and decoding them as text would invite false positives.
+
+use std::fmt;
+use std::ops::Deref;
+
+use minidump::format::MINIDUMP_STREAM_TYPE;
+use minidump::{Endian, Minidump};
+
+/// The sanitizers whose reports we recognize.
+static SANITIZERS: &[&str] = &[
+    "AddressSanitizer",
+    "HWAddressSanitizer",
+    "LeakSanitizer",
+    "MemorySanitizer",
+    "ThreadSanitizer",
+    "UndefinedBehaviorSanitizer",
+];
+
+/// A report from a sanitizer that was found in the minidump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizerReport {
+    /// The sanitizer that wrote the report, e.g. "AddressSanitizer".
+    pub sanitizer: String,
+    /// The kind of bug that was reported, e.g. "heap-use-after-free".
+    pub bug_type: String,
+    /// The address the report is about, if it named one.
+    pub address: Option<u64>,
+}
+
+impl SanitizerReport {
+    /// Find a sanitizer report in `text`.
+    ///
+    /// To avoid false positives, this only recognizes the header line that sanitizers
+    /// start their reports with, which looks like:
+    ///
+    /// ```text
+    /// ==12345==ERROR: AddressSanitizer: heap-use-after-free on address 0x602000000010 at pc ...
+    /// ```
+    pub fn parse(text: &str) -> Option<SanitizerReport> {
+        text.lines().find_map(Self::parse_header)
+    }
+
+    fn parse_header(line: &str) -> Option<SanitizerReport> {
+        let (pid, rest) = line.trim().strip_prefix("==")?.split_once("==")?;
+        if pid.is_empty() || !pid.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let (sanitizer, details) = rest.strip_prefix("ERROR: ")?.split_once(": ")?;
+        if !SANITIZERS.contains(&sanitizer) {
+            return None;
+        }
+
+        let bug_type = if details.starts_with("detected memory leaks") {
+            "memory-leak"
+        } else {
+            details.split_whitespace().next()?
+        };
+        let address = details.split_once("address 0x").and_then(|(_, hex)| {
+            let end = hex
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(hex.len());
+            u64::from_str_radix(&hex[..end], 16).ok()
+        });
+
+        Some(SanitizerReport {
+            sanitizer: sanitizer.to_owned(),
+            bug_type: bug_type.to_owned(),
+            address,
+        })
+    }
+}
+
+impl fmt::Display for SanitizerReport {
+    /// Formats the report the way it is appended to the crash reason, e.g.
+    /// "AddressSanitizer: heap-use-after-free".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.sanitizer, self.bug_type)
+    }
+}
+
+/// Look for a sanitizer report in the comment streams of `dump`.
+pub(crate) fn find_sanitizer_report<'a, T>(dump: &'a Minidump<'a, T>) -> Option<SanitizerReport>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let comment_a = dump
+        .get_raw_stream(MINIDUMP_STREAM_TYPE::CommentStreamA as u32)
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+    let comment_w = dump
+        .get_raw_stream(MINIDUMP_STREAM_TYPE::CommentStreamW as u32)
+        .ok()
+        .map(|bytes| decode_utf16(bytes, dump.endian));
+
+    comment_a
+        .into_iter()
+        .chain(comment_w)
+        .find_map(|text| SanitizerReport::parse(&text))
+}
+
+fn decode_utf16(bytes: &[u8], endian: Endian) -> String {
+    let units = bytes.chunks_exact(2).map(|unit| {
+        let unit = [unit[0], unit[1]];
+        match endian {
+            Endian::Little => u16::from_le_bytes(unit),
+            Endian::Big => u16::from_be_bytes(unit),
+        }
+    });
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asan_report() {
+        let report = "\
+=================================================================
+==4102==ERROR: AddressSanitizer: heap-use-after-free on address 0x602000000010 at pc 0x55d4c8a4c1a3 bp 0x7ffd7d0d0a50 sp 0x7ffd7d0d0a48
+READ of size 4 at 0x602000000010 thread T0
+    #0 0x55d4c8a4c1a2 in main /tmp/uaf.c:7:10
+    #1 0x7f1c2b6a6d8f in __libc_start_call_main
+
+SUMMARY: AddressSanitizer: heap-use-after-free /tmp/uaf.c:7:10 in main
+";
+        assert_eq!(
+            SanitizerReport::parse(report),
+            Some(SanitizerReport {
+                sanitizer: "AddressSanitizer".into(),
+                bug_type: "heap-use-after-free".into(),
+                address: Some(0x602000000010),
+            })
+        );
+    }
+
+    #[test]
+    fn test_other_reports() {
+        let segv = "==1==ERROR: UndefinedBehaviorSanitizer: SEGV on unknown address 0x000000000000 (pc 0x1 bp 0x2 sp 0x3 T1)";
+        assert_eq!(
+            SanitizerReport::parse(segv),
+            Some(SanitizerReport {
+                sanitizer: "UndefinedBehaviorSanitizer".into(),
+                bug_type: "SEGV".into(),
+                address: Some(0),
+            })
+        );
+
+        let leak =
+            "\n==77==ERROR: LeakSanitizer: detected memory leaks\n\nDirect leak of 7 byte(s)";
+        assert_eq!(
+            SanitizerReport::parse(leak),
+            Some(SanitizerReport {
+                sanitizer: "LeakSanitizer".into(),
+                bug_type: "memory-leak".into(),
+                address: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_a_report() {
+        for text in [
+            "",
+            "ERROR: AddressSanitizer: heap-use-after-free on address 0x10",
+            "==abc==ERROR: AddressSanitizer: heap-use-after-free on address 0x10",
+            "==1==ERROR: MadeUpSanitizer: heap-use-after-free on address 0x10",
+            "==1==WARNING: AddressSanitizer: heap-use-after-free on address 0x10",
+            "user comment mentioning ==1==ERROR: AddressSanitizer: in passing",
+        ] {
+            assert_eq!(SanitizerReport::parse(text), None, "{}", text);
+        }
+    }
+}
@@ -6,16 +6,20 @@
 // so that it can be copied between the two with minimal changes. It's not
 // worth the effort to *actually* unify the implementations.
 
-use crate::process_state::{FrameTrust, StackFrame};
+use crate::process_state::{FrameTrust, StackFrame, StackWalkEnd};
 use crate::stackwalker::unwind::Unwind;
-use crate::stackwalker::CfiStackWalker;
-use crate::{SymbolProvider, SystemInfo};
-use minidump::format::CONTEXT_AMD64;
+use crate::stackwalker::ScanOptions;
+use crate::stackwalker::{function_name_at, CfiStackWalker};
+use crate::{SymbolProvider, SystemInfo, UnwindHints};
+use minidump::format::{CONTEXT_AMD64, IMAGE_RUNTIME_FUNCTION_ENTRY};
 use minidump::system_info::Os;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, UnifiedMemory,
+    CpuContext, Endian, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
+    MinidumpRawContext, Module, UnifiedMemory, UnifiedMemoryList,
 };
+use scroll::ctx::TryFromCtx;
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use tracing::trace;
 
 type Pointer = u64;
@@ -25,6 +29,13 @@ const STACK_POINTER_REGISTER: &str = "rsp";
 const FRAME_POINTER_REGISTER: &str = "rbp";
 // FIXME: rdi and rsi are also preserved on windows (but not in sysv) -- we should handle that?
 const CALLEE_SAVED_REGS: &[&str] = &["rbx", "rbp", "r12", "r13", "r14", "r15"];
+const WINDOWS_CALLEE_SAVED_REGS: &[&str] =
+    &["rbx", "rbp", "rdi", "rsi", "r12", "r13", "r14", "r15"];
+/// The general purpose registers, in the order they're numbered by Windows unwinding info.
+const WINDOWS_REGISTERS: &[&str] = &[
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
 
 async fn get_caller_by_cfi<P>(
     ctx: &CONTEXT_AMD64,
@@ -94,6 +105,110 @@ where
     Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
 }
 
+/// The trampolines that Linux signal handlers return into.
+///
+/// glibc and musl both call theirs `__restore_rt`, a vDSO would provide
+/// `__kernel_rt_sigreturn`. All they do is invoke `rt_sigreturn`, which restores
+/// the registers the signal interrupted from the signal frame on the stack.
+///
+/// Signal frames are only recognized on amd64 so far. On the other architectures,
+/// unwinding through a signal handler relies on the trampoline's CFI (if the
+/// symbol files have any), and otherwise falls back to scanning.
+const SIGRETURN_TRAMPOLINES: &[&str] = &["__restore_rt", "__kernel_rt_sigreturn"];
+/// The offset of `uc_link` in the `ucontext_t` of a signal frame.
+const UCONTEXT_LINK_OFFSET: u64 = 8;
+/// The offset of `uc_mcontext` in the `ucontext_t` of a signal frame.
+const UCONTEXT_MCONTEXT_OFFSET: u64 = 40;
+/// The registers saved in a `sigcontext`, in order.
+const SIGCONTEXT_REGISTERS: &[&str] = &[
+    "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15", "rdi", "rsi", "rbp", "rbx", "rdx", "rax",
+    "rcx", "rsp", "rip",
+];
+
+/// Unwind through a Linux signal handler's sigreturn trampoline.
+///
+/// When a signal handler returns into the trampoline, the stack pointer points at
+/// the `ucontext_t` that the kernel saved when delivering the signal. The caller
+/// is whatever the signal interrupted, so all of its registers can be restored
+/// from there.
+///
+/// The handler may have run on a signal alt-stack, so the caller's stack pointer
+/// can be anywhere relative to the callee's. Instead, the `ucontext_t` itself is
+/// checked for plausibility, and the stack walker only lets one signal frame per
+/// stack move to a lower stack pointer.
+async fn get_caller_by_sigreturn<P>(
+    ctx: &CONTEXT_AMD64,
+    callee: &StackFrame,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    system_info: &SystemInfo,
+    symbol_provider: &P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    if !matches!(system_info.os, Os::Linux | Os::Android) {
+        return None;
+    }
+    if let MinidumpContextValidity::Some(ref which) = callee.context.valid {
+        if !which.contains(STACK_POINTER_REGISTER) {
+            return None;
+        }
+    }
+
+    // The trampoline is returned into rather than called, so the callee's
+    // instruction (the return address minus one) may be in whatever precedes it.
+    // Check the return address itself too.
+    let is_trampoline =
+        |name: Option<&str>| matches!(name, Some(name) if SIGRETURN_TRAMPOLINES.contains(&name));
+    let mut found = is_trampoline(callee.function_name.as_deref());
+    if !found && callee.instruction != ctx.rip {
+        let name = function_name_at(ctx.rip, modules, symbol_provider).await;
+        found = is_trampoline(name.as_deref());
+    }
+    if !found {
+        return None;
+    }
+
+    trace!("trying sigreturn frame");
+
+    // The kernel always clears uc_link.
+    let uc_link: Pointer =
+        stack_memory.get_memory_at_address(ctx.rsp.checked_add(UCONTEXT_LINK_OFFSET)?)?;
+    if uc_link != 0 {
+        trace!("sigreturn frame has a non-null uc_link, rejecting it");
+        return None;
+    }
+
+    let mcontext = ctx.rsp.checked_add(UCONTEXT_MCONTEXT_OFFSET)?;
+    let mut caller_ctx = CONTEXT_AMD64::default();
+    let mut valid = HashSet::new();
+    for (i, &reg) in SIGCONTEXT_REGISTERS.iter().enumerate() {
+        let val = stack_memory.get_memory_at_address(mcontext + i as u64 * POINTER_WIDTH)?;
+        caller_ctx.set_register(reg, val)?;
+        valid.insert(reg);
+    }
+
+    if caller_ctx.rsp == 0
+        || !instruction_seems_valid(caller_ctx.rip, modules, symbol_provider).await
+    {
+        trace!("sigreturn frame has an invalid rsp or rip, rejecting it");
+        return None;
+    }
+
+    trace!(
+        "sigreturn frame seems valid -- caller_ip: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_ctx.rip,
+        caller_ctx.rsp,
+    );
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Amd64(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::SignalContext))
+}
+
 fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static str> {
     match valid {
         MinidumpContextValidity::All => CALLEE_SAVED_REGS.iter().copied().collect(),
@@ -105,6 +220,449 @@ fn callee_forwarded_regs(valid: &MinidumpContextValidity) -> HashSet<&'static st
     }
 }
 
+/// Unwind with the `RUNTIME_FUNCTION`/`UNWIND_INFO` of the callee's module.
+///
+/// Every non-leaf function of a Windows x64 image has a `RUNTIME_FUNCTION` entry in the
+/// image's `.pdata` section, pointing to an `UNWIND_INFO` (in `.xdata`) which describes
+/// everything the function's prologue did to the stack. Undoing that recovers the caller,
+/// so this is exactly as good as CFI, as long as the image is in the minidump's memory.
+///
+/// See <https://docs.microsoft.com/en-us/cpp/build/exception-handling-x64>.
+fn get_caller_by_pdata(
+    ctx: &CONTEXT_AMD64,
+    callee: &StackFrame,
+    stack_memory: UnifiedMemory<'_, '_>,
+    memory_list: Option<&UnifiedMemoryList<'_>>,
+    modules: &MinidumpModuleList,
+    system_info: &SystemInfo,
+) -> Option<StackFrame> {
+    if system_info.os != Os::Windows {
+        return None;
+    }
+    let memory_list = memory_list?;
+
+    trace!("trying pdata");
+    let valid = &callee.context.valid;
+    let mut rsp = ctx.get_register(STACK_POINTER_REGISTER, valid)?;
+
+    let module = modules.module_at_address(callee.instruction)?;
+    let image_base = module.base_address();
+    let image = PeImage {
+        base: image_base,
+        memory: memory_list,
+    };
+    let rva = u32::try_from(callee.instruction - image_base).ok()?;
+
+    let mut caller_ctx = ctx.clone();
+    let mut caller_validity: HashSet<&'static str> = WINDOWS_CALLEE_SAVED_REGS
+        .iter()
+        .filter(|reg| ctx.register_is_valid(reg, valid))
+        .copied()
+        .collect();
+
+    let machine_frame = match image.runtime_function(rva) {
+        Some(function) => {
+            // The context frame may have stopped in the middle of an epilogue, which
+            // has already undone part of the prologue. Epilogues have a strict format,
+            // so they can be recognized and simulated instead.
+            let epilogue = if callee.trust == FrameTrust::Context {
+                image.simulate_epilogue(ctx, valid, stack_memory)
+            } else {
+                None
+            };
+            match epilogue {
+                Some(epilogue) => {
+                    trace!("callee is in an epilogue");
+                    rsp = epilogue.rsp;
+                    for (reg, val) in epilogue.restored {
+                        caller_ctx.set_register(reg, val)?;
+                        caller_validity.insert(reg);
+                    }
+                    false
+                }
+                None => {
+                    let prolog_offset = rva - function.begin_address;
+                    let mut unwinder = UnwindCodeInterpreter {
+                        ctx,
+                        valid,
+                        stack_memory,
+                        rsp,
+                        caller_ctx: &mut caller_ctx,
+                        caller_validity: &mut caller_validity,
+                    };
+                    let machine_frame = unwinder.unwind(&image, function, prolog_offset)?;
+                    rsp = unwinder.rsp;
+                    machine_frame
+                }
+            }
+        }
+        // Leaf functions don't need a RUNTIME_FUNCTION, because they don't touch
+        // the stack: the return address is right at the top of it. Only the context
+        // frame can be a leaf function, every other frame called something.
+        None if callee.trust == FrameTrust::Context && image.has_pdata() => false,
+        None => return None,
+    };
+
+    let caller_ip: u64 = stack_memory.get_memory_at_address(rsp)?;
+    let caller_sp = if machine_frame {
+        // The CPU pushed a full machine frame (e.g. for an interrupt), which
+        // contains the interrupted stack pointer.
+        stack_memory.get_memory_at_address(rsp.checked_add(POINTER_WIDTH * 3)?)?
+    } else {
+        rsp.checked_add(POINTER_WIDTH)?
+    };
+
+    trace!(
+        "pdata evaluation was successful -- caller_ip: 0x{:016x}, caller_sp: 0x{:016x}",
+        caller_ip,
+        caller_sp,
+    );
+
+    caller_ctx.rip = caller_ip;
+    caller_ctx.rsp = caller_sp;
+    caller_validity.insert(INSTRUCTION_REGISTER);
+    caller_validity.insert(STACK_POINTER_REGISTER);
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Amd64(caller_ctx),
+        valid: MinidumpContextValidity::Some(caller_validity),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::CallFrameInfo))
+}
+
+// Flags of an UNWIND_INFO
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+
+// Operations of an UNWIND_CODE
+const UWOP_PUSH_NONVOL: u8 = 0;
+const UWOP_ALLOC_LARGE: u8 = 1;
+const UWOP_ALLOC_SMALL: u8 = 2;
+const UWOP_SET_FPREG: u8 = 3;
+const UWOP_SAVE_NONVOL: u8 = 4;
+const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+const UWOP_EPILOG: u8 = 6;
+const UWOP_SPARE_CODE: u8 = 7;
+const UWOP_SAVE_XMM128: u8 = 8;
+const UWOP_SAVE_XMM128_FAR: u8 = 9;
+const UWOP_PUSH_MACHFRAME: u8 = 10;
+
+/// How many `UNWIND_INFO`s may be chained together before we give up.
+const MAX_CHAINED_UNWIND_INFOS: usize = 32;
+
+/// A PE image mapped into the memory of the process.
+struct PeImage<'a, 'mdmp> {
+    base: u64,
+    memory: &'a UnifiedMemoryList<'mdmp>,
+}
+
+impl PeImage<'_, '_> {
+    fn read<T>(&self, rva: u64) -> Option<T>
+    where
+        T: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error>,
+    {
+        let address = self.base.checked_add(rva)?;
+        self.memory
+            .memory_at_address(address)?
+            .get_memory_at_address(address)
+    }
+
+    /// Like `read`, but also advances `rva` past the value.
+    fn read_next<T>(&self, rva: &mut u64) -> Option<T>
+    where
+        T: for<'b> TryFromCtx<'b, Endian, [u8], Error = scroll::Error>,
+    {
+        let val = self.read(*rva)?;
+        *rva += std::mem::size_of::<T>() as u64;
+        Some(val)
+    }
+
+    /// The address and size of the `.pdata` section (the exception directory).
+    fn pdata(&self) -> Option<(u32, u32)> {
+        const IMAGE_DOS_SIGNATURE: u16 = 0x5a4d; // "MZ"
+        const IMAGE_NT_SIGNATURE: u32 = 0x4550; // "PE\0\0"
+        const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20b;
+        const IMAGE_DIRECTORY_ENTRY_EXCEPTION: u32 = 3;
+
+        if self.read::<u16>(0)? != IMAGE_DOS_SIGNATURE {
+            return None;
+        }
+        let nt_headers = u64::from(self.read::<u32>(0x3c)?);
+        if self.read::<u32>(nt_headers)? != IMAGE_NT_SIGNATURE {
+            return None;
+        }
+        // The optional header follows the signature and the 20-byte file header.
+        let optional_header = nt_headers + 4 + 20;
+        if self.read::<u16>(optional_header)? != IMAGE_NT_OPTIONAL_HDR64_MAGIC {
+            return None;
+        }
+        let number_of_rva_and_sizes = self.read::<u32>(optional_header + 108)?;
+        if number_of_rva_and_sizes <= IMAGE_DIRECTORY_ENTRY_EXCEPTION {
+            return None;
+        }
+        let directory = optional_header + 112 + 8 * u64::from(IMAGE_DIRECTORY_ENTRY_EXCEPTION);
+        Some((self.read(directory)?, self.read(directory + 4)?))
+    }
+
+    fn has_pdata(&self) -> bool {
+        self.pdata().is_some_and(|(_, size)| size != 0)
+    }
+
+    /// Find the `RUNTIME_FUNCTION` of the function containing `rva`.
+    fn runtime_function(&self, rva: u32) -> Option<IMAGE_RUNTIME_FUNCTION_ENTRY> {
+        const ENTRY_SIZE: u32 = 12;
+        let (pdata_rva, pdata_size) = self.pdata()?;
+        let entry = |idx: u32| -> Option<IMAGE_RUNTIME_FUNCTION_ENTRY> {
+            self.read(u64::from(pdata_rva) + u64::from(idx) * u64::from(ENTRY_SIZE))
+        };
+
+        // The entries are sorted by address.
+        let (mut low, mut high) = (0, pdata_size / ENTRY_SIZE);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let function = entry(mid)?;
+            if rva < function.begin_address {
+                high = mid;
+            } else if rva >= function.end_address {
+                low = mid + 1;
+            } else {
+                return Some(function);
+            }
+        }
+        None
+    }
+
+    /// If the instruction pointer of `ctx` is in an epilogue, simulate the rest of it
+    /// up to (but not including) the final `ret`.
+    ///
+    /// A Windows x64 epilogue is an optional `add rsp, imm` or `lea rsp, [reg + disp]`,
+    /// followed by any number of `pop reg`, followed by a `ret`.
+    fn simulate_epilogue(
+        &self,
+        ctx: &CONTEXT_AMD64,
+        valid: &MinidumpContextValidity,
+        stack_memory: UnifiedMemory<'_, '_>,
+    ) -> Option<Epilogue> {
+        let mut address = ctx.rip.checked_sub(self.base)?;
+        let mut rsp = ctx.get_register(STACK_POINTER_REGISTER, valid)?;
+        let mut restored = Vec::new();
+
+        let mut b = self.read_next::<u8>(&mut address)?;
+        if b & 0xf8 == 0x48 {
+            // An instruction with a REX.W prefix, the only one allowed is the stack adjustment.
+            let rex_b = usize::from(b & 1) << 3;
+            let opcode = self.read_next::<u8>(&mut address)?;
+            let modrm = self.read_next::<u8>(&mut address)?;
+            let (md, reg, rm) = (modrm >> 6, (modrm >> 3) & 7, usize::from(modrm & 7));
+            match (opcode, md, reg) {
+                // add rsp, imm8
+                (0x83, 3, 0) if rm == 4 && rex_b == 0 => {
+                    rsp = rsp.checked_add(u64::from(self.read_next::<u8>(&mut address)?))?;
+                }
+                // add rsp, imm32
+                (0x81, 3, 0) if rm == 4 && rex_b == 0 => {
+                    rsp = rsp.checked_add(u64::from(self.read_next::<u32>(&mut address)?))?;
+                }
+                // lea rsp, [reg + disp8/disp32]
+                (0x8d, 1 | 2, 4) if rm != 4 => {
+                    let base = ctx.get_register(WINDOWS_REGISTERS[rm | rex_b], valid)?;
+                    let disp = if md == 1 {
+                        i64::from(self.read_next::<u8>(&mut address)? as i8)
+                    } else {
+                        i64::from(self.read_next::<u32>(&mut address)? as i32)
+                    };
+                    rsp = base.checked_add_signed(disp)?;
+                }
+                _ => return None,
+            }
+            b = self.read_next::<u8>(&mut address)?;
+        }
+
+        loop {
+            let mut rex_b = 0;
+            if b == 0x41 {
+                rex_b = 8;
+                b = self.read_next::<u8>(&mut address)?;
+            }
+            match b {
+                // pop reg
+                0x58..=0x5f => {
+                    let reg = WINDOWS_REGISTERS[usize::from(b - 0x58) | rex_b];
+                    restored.push((reg, stack_memory.get_memory_at_address(rsp)?));
+                    rsp = rsp.checked_add(POINTER_WIDTH)?;
+                }
+                // ret (optionally with a meaningless rep prefix)
+                0xc3 if rex_b == 0 => break,
+                0xf3 if rex_b == 0 && self.read_next::<u8>(&mut address)? == 0xc3 => break,
+                _ => return None,
+            }
+            b = self.read_next::<u8>(&mut address)?;
+        }
+
+        Some(Epilogue { rsp, restored })
+    }
+}
+
+/// The state of the stack after simulating an epilogue.
+struct Epilogue {
+    /// The stack pointer, pointing at the return address.
+    rsp: u64,
+    /// The registers restored by the epilogue.
+    restored: Vec<(&'static str, u64)>,
+}
+
+/// Undoes the effects of a function's prologue on a context, as described by its `UNWIND_CODE`s.
+struct UnwindCodeInterpreter<'a, 'b, 'c> {
+    ctx: &'a CONTEXT_AMD64,
+    valid: &'a MinidumpContextValidity,
+    stack_memory: UnifiedMemory<'b, 'c>,
+    /// The stack pointer as of the codes processed so far.
+    rsp: u64,
+    caller_ctx: &'a mut CONTEXT_AMD64,
+    caller_validity: &'a mut HashSet<&'static str>,
+}
+
+impl UnwindCodeInterpreter<'_, '_, '_> {
+    /// Process the `UNWIND_INFO` of `function` (and any it is chained to).
+    ///
+    /// Only the codes of prologue instructions before `prolog_offset` are processed.
+    /// Returns whether a machine frame was found on the stack.
+    fn unwind(
+        &mut self,
+        image: &PeImage,
+        mut function: IMAGE_RUNTIME_FUNCTION_ENTRY,
+        mut prolog_offset: u32,
+    ) -> Option<bool> {
+        for _ in 0..MAX_CHAINED_UNWIND_INFOS {
+            let info = u64::from(function.unwind_info_address);
+            let version_and_flags: u8 = image.read(info)?;
+            let flags = version_and_flags >> 3;
+            let count_of_codes: u8 = image.read(info + 2)?;
+            let frame: u8 = image.read(info + 3)?;
+            let (frame_register, frame_offset) = (frame & 0xf, u64::from(frame >> 4) * 16);
+
+            let codes = (0..u64::from(count_of_codes))
+                .map(|i| image.read::<u16>(info + 4 + i * 2))
+                .collect::<Option<Vec<u16>>>()?;
+
+            // Saved registers are addressed relative to the frame pointer if the
+            // function has one, and it has already been established.
+            let fp_established = frame_register != 0
+                && codes.iter().any(|&code| {
+                    let (offset, op) = (code as u8, (code >> 8) as u8 & 0xf);
+                    op == UWOP_SET_FPREG && u32::from(offset) <= prolog_offset
+                });
+            let frame_base = if fp_established {
+                let fp = WINDOWS_REGISTERS[usize::from(frame_register)];
+                self.ctx
+                    .get_register(fp, self.valid)?
+                    .checked_sub(frame_offset)?
+            } else {
+                self.rsp
+            };
+
+            if let Some(true) = self.process_codes(&codes, prolog_offset, frame_base) {
+                return Some(true);
+            }
+
+            if flags & UNW_FLAG_CHAININFO == 0 {
+                return Some(false);
+            }
+            // The chained RUNTIME_FUNCTION follows the (evenly padded) codes. Its
+            // prologue has been fully executed.
+            let chained = info + 4 + 2 * ((u64::from(count_of_codes) + 1) & !1);
+            function = image.read(chained)?;
+            prolog_offset = u32::MAX;
+        }
+        trace!("too many chained unwind infos");
+        None
+    }
+
+    /// Returns `Some(true)` if a machine frame was found, and `None` on failure.
+    fn process_codes(
+        &mut self,
+        codes: &[u16],
+        prolog_offset: u32,
+        frame_base: u64,
+    ) -> Option<bool> {
+        let slot = |idx: usize| -> Option<u64> { codes.get(idx).copied().map(u64::from) };
+        let mut i = 0;
+        while i < codes.len() {
+            let code = codes[i];
+            let (offset, op, info) = (code as u8, (code >> 8) as u8 & 0xf, (code >> 12) as u8);
+            let applies = u32::from(offset) <= prolog_offset;
+            let slots = match op {
+                UWOP_PUSH_NONVOL => {
+                    if applies {
+                        let val = self.stack_memory.get_memory_at_address(self.rsp)?;
+                        self.restore(info, val)?;
+                        self.rsp = self.rsp.checked_add(POINTER_WIDTH)?;
+                    }
+                    1
+                }
+                UWOP_ALLOC_LARGE => {
+                    let (size, slots) = if info == 0 {
+                        (slot(i + 1)? * 8, 2)
+                    } else {
+                        (slot(i + 1)? | slot(i + 2)? << 16, 3)
+                    };
+                    if applies {
+                        self.rsp = self.rsp.checked_add(size)?;
+                    }
+                    slots
+                }
+                UWOP_ALLOC_SMALL => {
+                    if applies {
+                        self.rsp = self.rsp.checked_add(u64::from(info) * 8 + 8)?;
+                    }
+                    1
+                }
+                UWOP_SET_FPREG => {
+                    if applies {
+                        self.rsp = frame_base;
+                    }
+                    1
+                }
+                UWOP_SAVE_NONVOL | UWOP_SAVE_NONVOL_FAR => {
+                    let (offset, slots) = if op == UWOP_SAVE_NONVOL {
+                        (slot(i + 1)? * 8, 2)
+                    } else {
+                        (slot(i + 1)? | slot(i + 2)? << 16, 3)
+                    };
+                    if applies {
+                        let address = frame_base.checked_add(offset)?;
+                        let val = self.stack_memory.get_memory_at_address(address)?;
+                        self.restore(info, val)?;
+                    }
+                    slots
+                }
+                // We don't restore xmm registers, and epilogue codes are only hints.
+                UWOP_EPILOG | UWOP_SAVE_XMM128 => 2,
+                UWOP_SPARE_CODE | UWOP_SAVE_XMM128_FAR => 3,
+                UWOP_PUSH_MACHFRAME => {
+                    if applies {
+                        // Skip the error code, if the CPU pushed one.
+                        self.rsp = self.rsp.checked_add(u64::from(info) * POINTER_WIDTH)?;
+                        return Some(true);
+                    }
+                    1
+                }
+                _ => {
+                    trace!("unknown unwind code {}", op);
+                    return None;
+                }
+            };
+            i += slots;
+        }
+        Some(false)
+    }
+
+    fn restore(&mut self, register: u8, val: u64) -> Option<()> {
+        let reg = *WINDOWS_REGISTERS.get(usize::from(register))?;
+        self.caller_ctx.set_register(reg, val)?;
+        self.caller_validity.insert(reg);
+        Some(())
+    }
+}
+
 fn get_caller_by_frame_pointer<P>(
     ctx: &CONTEXT_AMD64,
     callee: &StackFrame,
@@ -224,6 +782,7 @@ async fn get_caller_by_scan<P>(
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -264,8 +823,13 @@ where
         default_scan_range
     };
 
+    let first_address = scan.first_address(last_sp, POINTER_WIDTH)?;
+
     for i in 0..scan_range {
-        let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_ip = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_ip, stack_memory, POINTER_WIDTH) {
+            return None;
+        }
         let caller_ip = stack_memory.get_memory_at_address(address_of_ip)?;
         if instruction_seems_valid(caller_ip, modules, symbol_provider).await {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
@@ -427,44 +991,59 @@ impl Unwind for CONTEXT_AMD64 {
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
+        scan: ScanOptions,
+        _unwind_hints: Option<&UnwindHints>,
         syms: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = stack_memory.ok_or(StackWalkEnd::MissingStackMemory)?;
+
+        // A frame interrupted by a signal has a precise instruction pointer,
+        // rather than a return address, so it needs no further adjustment.
+        if let Some(mut frame) =
+            get_caller_by_sigreturn(self, callee, stack, modules, system_info, syms).await
+        {
+            frame.instruction = frame.context.get_instruction_pointer();
+            return Ok(frame);
+        }
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
         if frame.is_none() {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
+        if frame.is_none() {
+            frame = get_caller_by_pdata(self, callee, stack, memory_list, modules, system_info);
+        }
         if frame.is_none() {
             frame = get_caller_by_frame_pointer(self, callee, stack, modules, system_info, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, stack, modules, scan, syms).await;
         }
-        let mut frame = frame?;
+        let mut frame = frame.ok_or(StackWalkEnd::NoCaller)?;
 
         // We now check the frame to see if it looks like unwinding is complete,
         // based on the frame we computed having a nonsense value. Returning
-        // None signals to the unwinder to stop unwinding.
+        // an error signals to the unwinder to stop unwinding.
 
         // if the instruction is within the first ~page of memory, it's basically
         // null, and we can assume unwinding is complete.
         if frame.context.get_instruction_pointer() < 4096 {
             trace!("instruction pointer was nullish, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::NullInstructionPointer);
         }
         // If the new stack pointer is at a lower address than the old,
         // then that's clearly incorrect. Treat this as end-of-stack to
         // enforce progress and avoid infinite loops.
         if frame.context.get_stack_pointer() <= self.rsp {
             trace!("stack pointer went backwards, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::StackPointerNotIncreasing);
         }
 
         // Ok, the frame now seems well and truly valid, do final cleanup.
@@ -477,6 +1056,6 @@ impl Unwind for CONTEXT_AMD64 {
         let ip = frame.context.get_instruction_pointer();
         frame.instruction = ip - 1;
 
-        Some(frame)
+        Ok(frame)
     }
 }
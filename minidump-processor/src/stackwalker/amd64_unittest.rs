@@ -1,7 +1,7 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
-use crate::stackwalker::walk_stack;
+use crate::stackwalker::{walk_stack, walk_stack_with_thread_stack};
 use crate::{process_state::*, ProcessorOptions};
 use crate::{string_symbol_supplier, Symbolizer, SystemInfo};
 use minidump::format::CONTEXT_AMD64;
@@ -35,6 +35,8 @@ impl TestFixture {
                 cpu_info: None,
                 cpu_microcode_version: None,
                 cpu_count: 1,
+                os_flavor: None,
+                os_pretty_name: String::new(),
             },
             symbols: HashMap::new(),
         }
@@ -73,6 +75,111 @@ impl TestFixture {
         stack
     }
 
+    /// Walk the stack with pdata unwinding enabled, and `image` mapped into memory.
+    pub async fn walk_stack_with_image(&self, stack: Section, image: Section) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Amd64(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+        };
+        let memory = |section: Section| {
+            let base = section.start().value().unwrap();
+            let size = section.size();
+            (base, size, section.get_contents().unwrap())
+        };
+        let (stack_base, stack_size, stack) = memory(stack);
+        let (image_base, image_size, image) = memory(image);
+        let memory_list = UnifiedMemoryList::Memory(MinidumpMemoryList::from_regions(vec![
+            MinidumpMemory {
+                desc: Default::default(),
+                base_address: stack_base,
+                size: stack_size,
+                bytes: &stack,
+                endian: scroll::LE,
+            },
+            MinidumpMemory {
+                desc: Default::default(),
+                base_address: image_base,
+                size: image_size,
+                bytes: &image,
+                endian: scroll::LE,
+            },
+        ]));
+        let stack_memory = memory_list.memory_at_address(stack_base);
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let options = ProcessorOptions {
+            use_pdata_unwinding: true,
+            ..ProcessorOptions::default()
+        };
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack_with_thread_stack(
+            0,
+            &options,
+            &mut stack,
+            stack_memory,
+            None,
+            Some(&memory_list),
+            &self.modules,
+            &self.system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
+    /// Walk the stack starting out on `stack` (such as a signal alt-stack), then
+    /// moving over to `thread_stack`.
+    pub async fn walk_stack_with_thread_stack(
+        &self,
+        stack: Section,
+        thread_stack: Section,
+    ) -> CallStack {
+        let context = MinidumpContext {
+            raw: MinidumpRawContext::Amd64(self.raw.clone()),
+            valid: MinidumpContextValidity::All,
+        };
+        let memory = |section: Section| {
+            let base = section.start().value().unwrap();
+            let size = section.size();
+            (base, size, section.get_contents().unwrap())
+        };
+        let (stack_base, stack_size, stack) = memory(stack);
+        let (thread_stack_base, thread_stack_size, thread_stack) = memory(thread_stack);
+        let stack_memory = &MinidumpMemory {
+            desc: Default::default(),
+            base_address: stack_base,
+            size: stack_size,
+            bytes: &stack,
+            endian: scroll::LE,
+        };
+        let thread_stack_memory = &MinidumpMemory {
+            desc: Default::default(),
+            base_address: thread_stack_base,
+            size: thread_stack_size,
+            bytes: &thread_stack,
+            endian: scroll::LE,
+        };
+        let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
+        let options = ProcessorOptions::default();
+        let mut stack = CallStack::with_context(context);
+
+        walk_stack_with_thread_stack(
+            0,
+            &options,
+            &mut stack,
+            Some(UnifiedMemory::Memory(stack_memory)),
+            Some(UnifiedMemory::Memory(thread_stack_memory)),
+            None,
+            &self.modules,
+            &self.system_info,
+            &symbolizer,
+        )
+        .await;
+
+        stack
+    }
+
     pub fn add_symbols(&mut self, name: String, symbols: String) {
         self.symbols.insert(name, symbols);
     }
@@ -92,6 +199,7 @@ async fn test_simple() {
 
     let s = f.walk_stack(stack).await;
     assert_eq!(s.frames.len(), 1);
+    assert_eq!(s.walk_end, Some(StackWalkEnd::NoCaller));
     let f = &s.frames[0];
     let m = f.module.as_ref().unwrap();
     assert_eq!(m.code_file(), "module1");
@@ -649,6 +757,81 @@ async fn test_cfi_at_4006() {
     check_cfi(f, stack, expected, expected_valid).await;
 }
 
+#[tokio::test]
+async fn test_null_instruction_pointer() {
+    // The outermost function's return address is null.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 4000 1000 10 thread_start\n",
+        "STACK CFI INIT 4000 1000 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    stack.start().set_const(stack_start);
+    let stack = stack
+        .D64(0) // return address
+        .append_repeated(0, 0x100);
+
+    f.raw.rip = 0x00007400c0004010;
+    f.raw.rsp = stack_start;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    assert_eq!(s.walk_end, Some(StackWalkEnd::NullInstructionPointer));
+}
+
+#[tokio::test]
+async fn test_stack_pointer_not_increasing() {
+    // CFI that claims the caller's stack pointer is the same as the callee's.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 4000 1000 10 spin\n",
+        "STACK CFI INIT 4000 1000 .cfa: $rsp .ra: .cfa ^\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    stack.start().set_const(stack_start);
+    let stack = stack
+        .D64(0x00007400c0004020) // return address
+        .append_repeated(0, 0x100);
+
+    f.raw.rip = 0x00007400c0004010;
+    f.raw.rsp = stack_start;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    assert_eq!(s.walk_end, Some(StackWalkEnd::StackPointerNotIncreasing));
+}
+
+#[tokio::test]
+async fn test_left_stack() {
+    // CFI that puts the caller's stack pointer far past the end of the stack.
+    let mut f = TestFixture::new();
+    let symbols = [
+        "FUNC 4000 1000 10 leap\n",
+        "STACK CFI INIT 4000 1000 .cfa: $rsp 65536 + .ra: $rsp ^\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+
+    let stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    stack.start().set_const(stack_start);
+    let stack = stack
+        .D64(0x00007400c0004020) // return address
+        .append_repeated(0, 0x100);
+
+    f.raw.rip = 0x00007400c0004010;
+    f.raw.rsp = stack_start;
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    assert_eq!(s.walk_end, Some(StackWalkEnd::LeftStack));
+}
+
 #[tokio::test]
 async fn test_frame_pointer_overflow() {
     // Make sure we don't explode when trying frame pointer analysis on a value
@@ -812,3 +995,414 @@ async fn test_scan_walk_overflow() {
         }
     }
 }
+
+/// A minimal PE image for module1, with two functions that have unwinding info.
+///
+/// * 0x2000..0x2100 pushes rbx, allocates 0x28 bytes, and saves rsi at rsp+0x20.
+///   It has an epilogue at 0x20f0.
+/// * 0x2100..0x2200 pushes rbp, allocates 0x40 bytes, and sets rbp to rsp+0x20.
+fn pdata_image() -> Section {
+    let image = Section::new();
+    image.start().set_const(0x00007400c0000000);
+    image
+        // DOS header
+        .D16(0x5a4d)
+        .append_repeated(0, 0x3a)
+        .D32(0x80)
+        .append_repeated(0, 0x40)
+        // NT headers, with an empty file header
+        .D32(0x4550)
+        .append_repeated(0, 20)
+        // optional header
+        .D16(0x20b)
+        .append_repeated(0, 106)
+        .D32(16)
+        // data directories, the exception directory is the 4th
+        .append_repeated(0, 3 * 8)
+        .D32(0x1000)
+        .D32(2 * 12)
+        .append_repeated(0, 12 * 8)
+        .append_repeated(0, 0x1000 - 0x188)
+        // .pdata
+        .D32(0x2000)
+        .D32(0x2100)
+        .D32(0x3000)
+        .D32(0x2100)
+        .D32(0x2200)
+        .D32(0x3010)
+        .append_repeated(0, 0x1000 - 2 * 12)
+        // .text
+        .append_repeated(0xcc, 0xf0)
+        // epilogue: add rsp, 0x28; pop rbx; ret
+        .append_bytes(&[0x48, 0x83, 0xc4, 0x28, 0x5b, 0xc3])
+        .append_repeated(0xcc, 0x1000 - 0xf6)
+        // .xdata for the first function
+        .D8(1) // version 1, no flags
+        .D8(11) // size of prologue
+        .D8(4) // count of codes
+        .D8(0) // no frame register
+        .D16(0x640a) // offset 10: UWOP_SAVE_NONVOL rsi
+        .D16(0x20 / 8)
+        .D16(0x4205) // offset 5: UWOP_ALLOC_SMALL 0x28
+        .D16(0x3001) // offset 1: UWOP_PUSH_NONVOL rbx
+        .append_repeated(0, 4)
+        // .xdata for the second function
+        .D8(1) // version 1, no flags
+        .D8(15) // size of prologue
+        .D8(3) // count of codes
+        .D8(0x25) // frame register rbp, at offset 0x20
+        .D16(0x030f) // offset 15: UWOP_SET_FPREG
+        .D16(0x7205) // offset 5: UWOP_ALLOC_SMALL 0x40
+        .D16(0x5001) // offset 1: UWOP_PUSH_NONVOL rbp
+        .D16(0) // padding
+        .append_repeated(0, 0x1000 - 0x1c)
+}
+
+#[tokio::test]
+async fn test_pdata() {
+    let mut f = TestFixture::new();
+    f.system_info.os = Os::Windows;
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address1 = 0x00007400c0002180;
+    let return_address2 = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 0x20) // locals
+        .D64(0x1234) // saved rsi
+        .D64(0x5678) // saved rbx
+        .D64(return_address1)
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 0x40) // locals
+        .D64(0xabcd) // saved rbp
+        .D64(return_address2)
+        // frame 2
+        .mark(&frame2_sp)
+        .append_repeated(0, 16);
+
+    f.raw.rip = 0x00007400c0002050;
+    f.raw.rsp = stack_start;
+    f.raw.rbp = frame1_sp.value().unwrap() + 0x20;
+    f.raw.rsi = 1;
+    f.raw.rbx = 2;
+
+    let s = f.walk_stack_with_image(stack, pdata_image()).await;
+    assert_eq!(s.frames.len(), 3);
+
+    {
+        // To avoid reusing locals by mistake
+        let f1 = &s.frames[1];
+        assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpContextValidity::Some(ref which) = f1.context.valid {
+            for reg in ["rip", "rsp", "rbp", "rbx", "rsi"] {
+                assert!(which.contains(reg), "{} should be valid", reg);
+            }
+        } else {
+            unreachable!();
+        }
+
+        if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+            assert_eq!(ctx.rip, return_address1);
+            assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+            assert_eq!(ctx.rbp, frame1_sp.value().unwrap() + 0x20);
+            assert_eq!(ctx.rsi, 0x1234);
+            assert_eq!(ctx.rbx, 0x5678);
+        } else {
+            unreachable!();
+        }
+    }
+
+    {
+        // To avoid reusing locals by mistake
+        let f2 = &s.frames[2];
+        assert_eq!(f2.trust, FrameTrust::CallFrameInfo);
+        if let MinidumpRawContext::Amd64(ctx) = &f2.context.raw {
+            assert_eq!(ctx.rip, return_address2);
+            assert_eq!(ctx.rsp, frame2_sp.value().unwrap());
+            assert_eq!(ctx.rbp, 0xabcd);
+            assert_eq!(ctx.rsi, 0x1234);
+            assert_eq!(ctx.rbx, 0x5678);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_pdata_epilogue() {
+    let mut f = TestFixture::new();
+    f.system_info.os = Os::Windows;
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    let frame1_sp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 0x20) // locals
+        .D64(0x1234) // saved rsi, already restored by now
+        .D64(0x5678) // saved rbx
+        .D64(return_address)
+        // frame 1
+        .mark(&frame1_sp)
+        .append_repeated(0, 16);
+
+    f.raw.rip = 0x00007400c00020f0;
+    f.raw.rsp = stack_start;
+    f.raw.rsi = 1;
+    f.raw.rbx = 2;
+
+    let s = f.walk_stack_with_image(stack, pdata_image()).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let f1 = &s.frames[1];
+    assert_eq!(f1.trust, FrameTrust::CallFrameInfo);
+    if let MinidumpRawContext::Amd64(ctx) = &f1.context.raw {
+        assert_eq!(ctx.rip, return_address);
+        assert_eq!(ctx.rsp, frame1_sp.value().unwrap());
+        assert_eq!(ctx.rsi, 1);
+        assert_eq!(ctx.rbx, 0x5678);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_pdata_not_windows() {
+    // Without Windows, the unwinding info isn't used, so we scan
+    // (right past the saved registers) instead.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    let return_address = 0x00007500b0000110;
+    stack.start().set_const(stack_start);
+
+    stack = stack
+        .append_repeated(0, 0x30)
+        .D64(return_address)
+        .append_repeated(0, 16);
+
+    f.raw.rip = 0x00007400c0002050;
+    f.raw.rsp = stack_start;
+
+    let s = f.walk_stack_with_image(stack, pdata_image()).await;
+    assert_eq!(s.frames.len(), 2);
+    assert_eq!(s.frames[1].trust, FrameTrust::Scan);
+}
+
+#[tokio::test]
+async fn test_sigreturn_frame() {
+    let mut f = TestFixture::new();
+    let handler = 0x00007400c0001000;
+    let trampoline = 0x00007400c0002000;
+    let interrupted_rip = 0x00007500b0003008;
+    // The trampoline is preceded by a nop, so its return address minus one
+    // isn't inside of it.
+    let symbols = [
+        "FUNC 1000 100 0 handler\n",
+        "STACK CFI INIT 1000 100 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+        "FUNC 2000 10 0 __restore_rt\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+    f.add_symbols(
+        String::from("module2"),
+        String::from("FUNC 3000 100 0 busy\n"),
+    );
+
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000;
+    stack.start().set_const(stack_start);
+    let ucontext = Label::new();
+    let resumed_sp = Label::new();
+    stack = stack
+        // The handler's return address
+        .D64(trampoline)
+        // ucontext_t: uc_flags, uc_link, uc_stack
+        .mark(&ucontext)
+        .append_repeated(0, 40)
+        // uc_mcontext: r8 - r15
+        .D64(8)
+        .D64(9)
+        .D64(10)
+        .D64(11)
+        .D64(12)
+        .D64(13)
+        .D64(14)
+        .D64(15)
+        // rdi, rsi, rbp, rbx, rdx, rax, rcx, rsp, rip
+        .D64(0x1d1)
+        .D64(0x151)
+        .D64(&resumed_sp)
+        .D64(0xb)
+        .D64(0xd)
+        .D64(0xa)
+        .D64(0xc)
+        .D64(&resumed_sp)
+        .D64(interrupted_rip)
+        .append_repeated(0, 64)
+        .mark(&resumed_sp)
+        .append_repeated(0, 64);
+
+    f.raw.rip = handler + 0x10;
+    f.raw.rsp = stack_start;
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    let frame = &s.frames[1];
+    assert_eq!(frame.trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frame.context.get_stack_pointer(), ucontext.value().unwrap());
+
+    let frame = &s.frames[2];
+    assert_eq!(frame.trust, FrameTrust::SignalContext);
+    assert_eq!(frame.instruction, interrupted_rip);
+    assert_eq!(frame.function_name.as_deref(), Some("busy"));
+    if let MinidumpRawContext::Amd64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.rip, interrupted_rip);
+        assert_eq!(ctx.rsp, resumed_sp.value().unwrap());
+        assert_eq!(ctx.rbp, resumed_sp.value().unwrap());
+        assert_eq!(ctx.rbx, 0xb);
+        assert_eq!(ctx.r8, 8);
+        assert_eq!(ctx.r15, 15);
+        assert_eq!(ctx.rdi, 0x1d1);
+        assert_eq!(ctx.rsi, 0x151);
+    } else {
+        unreachable!();
+    }
+}
+
+/// Add the symbols of a signal handler, the sigreturn trampoline that it returns
+/// into, and the function that the signal interrupted.
+fn add_sigreturn_symbols(f: &mut TestFixture) {
+    let symbols = [
+        "FUNC 1000 100 0 handler\n",
+        "STACK CFI INIT 1000 100 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+        "FUNC 2000 10 0 __restore_rt\n",
+    ];
+    f.add_symbols(String::from("module1"), symbols.concat());
+    f.add_symbols(
+        String::from("module2"),
+        String::from("FUNC 3000 100 0 busy\n"),
+    );
+}
+
+/// A signal handler's frame: its return address into the trampoline, followed by
+/// a `ucontext_t` that resumes at `rip` and `rsp`.
+fn sigreturn_frame(stack: Section, uc_link: u64, rip: u64, rsp: &Label) -> Section {
+    stack
+        .D64(0x00007400c0002000)
+        // uc_flags, uc_link, uc_stack
+        .D64(0)
+        .D64(uc_link)
+        .append_repeated(0, 24)
+        // r8 - r15, rdi, rsi, rbp, rbx, rdx, rax, rcx
+        .append_repeated(0, 15 * 8)
+        // rsp, rip
+        .D64(rsp)
+        .D64(rip)
+}
+
+#[tokio::test]
+async fn test_sigreturn_frame_on_alt_stack() {
+    // Crash handlers usually run on a signal alt-stack, which may be anywhere
+    // relative to the stack the signal interrupted.
+    let mut f = TestFixture::new();
+    add_sigreturn_symbols(&mut f);
+    let interrupted_rip = 0x00007500b0003008;
+
+    let thread_stack = Section::new();
+    let thread_stack_start = 0x7000000080000000u64;
+    thread_stack.start().set_const(thread_stack_start);
+    let resumed_sp = Label::new();
+    let thread_stack = thread_stack
+        .append_repeated(0, 64)
+        .mark(&resumed_sp)
+        .append_repeated(0, 64);
+
+    let alt_stack = Section::new();
+    let alt_stack_start = 0x8000000080000000u64;
+    alt_stack.start().set_const(alt_stack_start);
+    let alt_stack =
+        sigreturn_frame(alt_stack, 0, interrupted_rip, &resumed_sp).append_repeated(0, 64);
+
+    f.raw.rip = 0x00007400c0001010;
+    f.raw.rsp = alt_stack_start;
+    let s = f
+        .walk_stack_with_thread_stack(alt_stack, thread_stack)
+        .await;
+    assert_eq!(s.frames.len(), 3);
+
+    let frame = &s.frames[2];
+    assert_eq!(frame.trust, FrameTrust::SignalContext);
+    assert_eq!(frame.instruction, interrupted_rip);
+    assert_eq!(frame.context.get_stack_pointer(), thread_stack_start + 64);
+    assert_eq!(frame.function_name.as_deref(), Some("busy"));
+}
+
+#[tokio::test]
+async fn test_sigreturn_frame_invalid_ucontext() {
+    let mut f = TestFixture::new();
+    add_sigreturn_symbols(&mut f);
+    let stack_start = 0x8000000080000000u64;
+
+    // The kernel always clears uc_link, and the interrupted code must be code.
+    for (uc_link, rip) in [(0x1000, 0x00007500b0003008), (0, 0x00007600b0003008)] {
+        let stack = Section::new();
+        stack.start().set_const(stack_start);
+        let resumed_sp = Label::new();
+        let stack = sigreturn_frame(stack, uc_link, rip, &resumed_sp)
+            .append_repeated(0, 64)
+            .mark(&resumed_sp)
+            .append_repeated(0, 64);
+
+        f.raw.rip = 0x00007400c0001010;
+        f.raw.rsp = stack_start;
+        let s = f.walk_stack(stack).await;
+        assert!(s.frames.len() >= 2);
+        assert!(s.frames[2..]
+            .iter()
+            .all(|frame| frame.trust != FrameTrust::SignalContext));
+    }
+}
+
+#[tokio::test]
+async fn test_sigreturn_frame_cycle() {
+    // Each ucontext resumes at the trampoline, on the stack of the next one, and the
+    // last one resumes on the first. Only one signal frame may move to a lower stack.
+    let mut f = TestFixture::new();
+    add_sigreturn_symbols(&mut f);
+    let trampoline = 0x00007400c0002004;
+
+    let mut stack = Section::new();
+    let stack_start = 0x8000000080000000u64;
+    stack.start().set_const(stack_start);
+    let ucontexts: Vec<Label> = (0..10).map(|_| Label::new()).collect();
+    for (i, ucontext) in ucontexts.iter().enumerate() {
+        stack = stack
+            .mark(ucontext)
+            // uc_flags, uc_link, uc_stack
+            .append_repeated(0, 40)
+            // r8 - r15, rdi, rsi, rbp, rbx, rdx, rax, rcx
+            .append_repeated(0, 15 * 8)
+            // rsp, rip
+            .D64(&ucontexts[(i + 1) % ucontexts.len()])
+            .D64(trampoline);
+    }
+    stack = stack.append_repeated(0, 64);
+
+    f.raw.rip = trampoline;
+    f.raw.rsp = stack_start;
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 20);
+    assert!(s.frames[1..]
+        .iter()
+        .all(|frame| frame.trust == FrameTrust::SignalContext));
+    assert_eq!(s.walk_end, Some(StackWalkEnd::StackPointerNotIncreasing));
+}
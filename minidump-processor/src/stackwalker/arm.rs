@@ -4,16 +4,18 @@
 // NOTE: arm64_old.rs and arm64.rs should be identical except for the names of
 // their context types.
 
-use crate::process_state::{FrameTrust, StackFrame};
+use crate::process_state::{FrameTrust, StackFrame, StackWalkEnd};
 use crate::stackwalker::unwind::Unwind;
-use crate::stackwalker::CfiStackWalker;
-use crate::{SymbolProvider, SystemInfo};
+use crate::stackwalker::ScanOptions;
+use crate::stackwalker::{function_name_at, CfiStackWalker};
+use crate::{SymbolProvider, SystemInfo, UnwindHints};
 use minidump::system_info::Os;
 use minidump::{
     CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
-    UnifiedMemory,
+    UnifiedMemory, UnifiedMemoryList,
 };
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use tracing::trace;
 
 type ArmContext = minidump::format::CONTEXT_ARM;
@@ -24,7 +26,7 @@ const POINTER_WIDTH: Pointer = std::mem::size_of::<Pointer>() as Pointer;
 const FRAME_POINTER: &str = Registers::FramePointer.name();
 const STACK_POINTER: &str = Registers::StackPointer.name();
 const PROGRAM_COUNTER: &str = Registers::ProgramCounter.name();
-const _LINK_REGISTER: &str = Registers::LinkRegister.name();
+const LINK_REGISTER: &str = Registers::LinkRegister.name();
 const CALLEE_SAVED_REGS: &[&str] = &["r4", "r5", "r6", "r7", "r8", "r9", "r10", "fp"];
 
 async fn get_caller_by_cfi<P>(
@@ -183,11 +185,130 @@ where
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
 
+async fn get_caller_by_link_register<P>(
+    ctx: &ArmContext,
+    callee: &StackFrame,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    // A leaf function doesn't need to save lr (the return address) to the stack,
+    // so if we crashed in one, the caller's pc is still sitting in lr. lr is only
+    // meaningful for the context frame, every other frame's lr is just whatever
+    // its callee left behind.
+    if callee.trust != FrameTrust::Context {
+        return None;
+    }
+    trace!("trying link register");
+
+    let valid = &callee.context.valid;
+    let last_lr = ctx.get_register(LINK_REGISTER, valid)?;
+    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+
+    // lr can be used as a general purpose register by non-leaf functions, so only
+    // trust it if it actually points into some code.
+    if !instruction_seems_valid(last_lr, modules, symbol_provider).await {
+        return None;
+    }
+
+    // If lr points back into the function that crashed, it's a return address
+    // left behind by a call that already returned.
+    let caller_function = function_name_at(last_lr as u64, modules, symbol_provider).await;
+    if caller_function.is_some()
+        && caller_function == function_name_at(callee.instruction, modules, symbol_provider).await
+    {
+        trace!("link register is in the crashing function, not using it");
+        return None;
+    }
+
+    // If the frame record agrees with lr, we're in a normal frame that just hasn't
+    // clobbered lr yet, so leave it to the methods that can restore sp properly.
+    let last_fp = ctx.get_register(FRAME_POINTER, valid);
+    let saved_lr = last_fp
+        .and_then(|last_fp| last_fp.checked_add(POINTER_WIDTH))
+        .and_then(|address| stack_memory.get_memory_at_address::<Pointer>(address as u64));
+    if saved_lr == Some(last_lr) {
+        trace!("link register matches the frame record, not using it");
+        return None;
+    }
+
+    // A leaf and a non-leaf function that already returned from its last call look
+    // the same, so only fall back to lr if the frame record is unusable.
+    if let Some(last_fp) = last_fp {
+        if frame_record_seems_valid(last_fp, last_sp, stack_memory, modules, symbol_provider).await
+        {
+            trace!("frame record seems valid, not using the link register");
+            return None;
+        }
+    }
+
+    // A leaf doesn't have to touch sp, so assume it didn't. The callee-saved
+    // registers are likewise untouched.
+    trace!(
+        "link register seems valid -- caller_pc: 0x{:08x}, caller_sp: 0x{:08x}",
+        last_lr,
+        last_sp,
+    );
+
+    let mut caller_ctx = ArmContext::default();
+    caller_ctx.set_register(PROGRAM_COUNTER, last_lr);
+    caller_ctx.set_register(STACK_POINTER, last_sp);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+    for &reg in CALLEE_SAVED_REGS {
+        if let Some(val) = ctx.get_register(reg, &callee.context.valid) {
+            caller_ctx.set_register(reg, val);
+            valid.insert(reg);
+        }
+    }
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Arm(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::LinkRegister))
+}
+
+/// Whether `fp` points to a plausible frame record (the caller's fp and the return
+/// address) for a frame with the stack pointer `sp`.
+async fn frame_record_seems_valid<P>(
+    fp: Pointer,
+    sp: Pointer,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> bool
+where
+    P: SymbolProvider + Sync,
+{
+    // The frame record is pushed onto the frame's own stack, and records are
+    // linked from the innermost frame outwards.
+    if fp < sp {
+        return false;
+    }
+    let (saved_fp, saved_lr) = match (
+        stack_memory.get_memory_at_address::<Pointer>(fp as u64),
+        fp.checked_add(POINTER_WIDTH)
+            .and_then(|address| stack_memory.get_memory_at_address::<Pointer>(address as u64)),
+    ) {
+        (Some(saved_fp), Some(saved_lr)) => (saved_fp, saved_lr),
+        _ => return false,
+    };
+    (saved_fp == 0 || saved_fp > fp)
+        && instruction_seems_valid(saved_lr, modules, symbol_provider).await
+}
+
 async fn get_caller_by_scan<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -215,8 +336,14 @@ where
         default_scan_range
     };
 
+    let first_address = scan.first_address(last_sp as u64, POINTER_WIDTH as u64)?;
+    let first_address = Pointer::try_from(first_address).ok()?;
+
     for i in 0..scan_range {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_pc = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_pc as u64, stack_memory, POINTER_WIDTH as u64) {
+            return None;
+        }
         let caller_pc = stack_memory.get_memory_at_address(address_of_pc as u64)?;
         if instruction_seems_valid(caller_pc, modules, symbol_provider).await {
             // pc is pushed by CALL, so sp is just address_of_pc + ptr
@@ -311,37 +438,43 @@ impl Unwind for ArmContext {
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        _memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
+        scan: ScanOptions,
+        _unwind_hints: Option<&UnwindHints>,
         syms: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = stack_memory.ok_or(StackWalkEnd::MissingStackMemory)?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
         if frame.is_none() {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
+        if frame.is_none() {
+            frame = get_caller_by_link_register(self, callee, stack, modules, syms).await;
+        }
         if frame.is_none() {
             frame = get_caller_by_frame_pointer(self, callee, stack, modules, system_info, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, stack, modules, scan, syms).await;
         }
-        let mut frame = frame?;
+        let mut frame = frame.ok_or(StackWalkEnd::NoCaller)?;
 
         // We now check the frame to see if it looks like unwinding is complete,
         // based on the frame we computed having a nonsense value. Returning
-        // None signals to the unwinder to stop unwinding.
+        // an error signals to the unwinder to stop unwinding.
 
         // if the instruction is within the first ~page of memory, it's basically
         // null, and we can assume unwinding is complete.
         if frame.context.get_instruction_pointer() < 4096 {
             trace!("instruction pointer was nullish, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::NullInstructionPointer);
         }
         // If the new stack pointer is at a lower address than the old,
         // then that's clearly incorrect. Treat this as end-of-stack to
@@ -357,7 +490,7 @@ impl Unwind for ArmContext {
             let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
             if !is_leaf {
                 trace!("stack pointer went backwards, assuming unwind complete");
-                return None;
+                return Err(StackWalkEnd::StackPointerNotIncreasing);
             }
         }
 
@@ -371,6 +504,6 @@ impl Unwind for ArmContext {
         let ip = frame.context.get_instruction_pointer();
         frame.instruction = ip - 2;
 
-        Some(frame)
+        Ok(frame)
     }
 }
@@ -4,13 +4,14 @@
 // NOTE: arm64_old.rs and arm64.rs should be identical except for the names of
 // their context types.
 
-use crate::process_state::{FrameTrust, StackFrame};
+use crate::process_state::{FrameTrust, StackFrame, StackWalkEnd};
 use crate::stackwalker::unwind::Unwind;
-use crate::stackwalker::CfiStackWalker;
-use crate::{SymbolProvider, SystemInfo};
+use crate::stackwalker::ScanOptions;
+use crate::stackwalker::{function_name_at, CfiStackWalker};
+use crate::{SymbolProvider, SystemInfo, UnwindHints};
 use minidump::{
     CpuContext, MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext,
-    Module, UnifiedMemory,
+    Module, UnifiedMemory, UnifiedMemoryList,
 };
 use std::collections::HashSet;
 use tracing::trace;
@@ -201,6 +202,9 @@ where
     // to be the correct fp of the next frame. This will effectively result in us unwinding
     // our caller instead of ourselves, causing the caller to be omitted from the backtrace
     // but otherwise perfectly syncing up for the rest of the frames.
+    //
+    // The one exception is a context frame without a usable frame record, where lr is
+    // still intact for a leaf, which get_caller_by_link_register handles before we get here.
     let valid = &callee.context.valid;
     let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
     let last_sp = ctx.get_register(STACK_POINTER, valid)?;
@@ -257,6 +261,93 @@ where
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
 
+async fn get_caller_by_link_register<P>(
+    ctx: &ArmContext,
+    callee: &StackFrame,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Option<StackFrame>
+where
+    P: SymbolProvider + Sync,
+{
+    // If we crashed in a leaf function, its return address was never saved to the
+    // stack: it's still sitting in lr (see get_caller_by_frame_pointer for details).
+    // lr is only meaningful for the context frame, every other frame's lr is
+    // just whatever its callee left behind.
+    if callee.trust != FrameTrust::Context {
+        return None;
+    }
+    trace!("trying link register");
+
+    let valid = &callee.context.valid;
+    let last_lr = ptr_auth_strip(modules, ctx.get_register(LINK_REGISTER, valid)?);
+    let last_fp = ctx.get_register(FRAME_POINTER, valid)?;
+    let last_sp = ctx.get_register(STACK_POINTER, valid)?;
+
+    // lr is freely used as a general purpose register by non-leaf functions, so only
+    // trust it if it actually points into some code.
+    if !instruction_seems_valid(last_lr, modules, symbol_provider).await {
+        return None;
+    }
+
+    // If lr points back into the function that crashed, it's a return address
+    // left behind by a call that already returned.
+    let caller_function = function_name_at(last_lr, modules, symbol_provider).await;
+    if caller_function.is_some()
+        && caller_function == function_name_at(callee.instruction, modules, symbol_provider).await
+    {
+        trace!("link register is in the crashing function, not using it");
+        return None;
+    }
+
+    // If the frame record agrees with lr, we're in a normal frame that just hasn't
+    // clobbered lr yet, and frame pointer unwinding will also restore sp properly.
+    let saved_lr = last_fp
+        .checked_add(POINTER_WIDTH)
+        .and_then(|address| stack_memory.get_memory_at_address(address))
+        .map(|saved_lr| ptr_auth_strip(modules, saved_lr));
+    if saved_lr == Some(last_lr) {
+        trace!("link register matches the frame record, deferring to frame pointer");
+        return None;
+    }
+
+    // A leaf and a non-leaf function that already returned from its last call look
+    // the same, so only fall back to lr if frame pointer unwinding can't work.
+    if frame_record_seems_valid(last_fp, last_sp, stack_memory, modules, symbol_provider).await {
+        trace!("frame record seems valid, deferring to frame pointer");
+        return None;
+    }
+
+    // A leaf doesn't have to touch sp, so assume it didn't. The callee-saved
+    // registers (including fp) are likewise untouched.
+    trace!(
+        "link register seems valid -- caller_pc: 0x{:016x}, caller_sp: 0x{:016x}",
+        last_lr,
+        last_sp,
+    );
+
+    let mut caller_ctx = ArmContext::default();
+    caller_ctx.set_register(PROGRAM_COUNTER, last_lr);
+    caller_ctx.set_register(STACK_POINTER, last_sp);
+
+    let mut valid = HashSet::new();
+    valid.insert(PROGRAM_COUNTER);
+    valid.insert(STACK_POINTER);
+    for &reg in CALLEE_SAVED_REGS {
+        if let Some(val) = ctx.get_register(reg, &callee.context.valid) {
+            caller_ctx.set_register(reg, val);
+            valid.insert(reg);
+        }
+    }
+
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::Arm64(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::LinkRegister))
+}
+
 fn ptr_auth_strip(modules: &MinidumpModuleList, ptr: Pointer) -> Pointer {
     // ARMv8.3 introduced a code hardening system called "Pointer Authentication"
     // which is used on Apple platforms. It adds some extra high bits to the
@@ -315,11 +406,43 @@ fn ptr_auth_strip(modules: &MinidumpModuleList, ptr: Pointer) -> Pointer {
     ptr & mask
 }
 
+/// Whether `fp` points to a plausible frame record (the caller's fp and the return
+/// address) for a frame with the stack pointer `sp`.
+async fn frame_record_seems_valid<P>(
+    fp: Pointer,
+    sp: Pointer,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> bool
+where
+    P: SymbolProvider + Sync,
+{
+    // The frame record is pushed onto the frame's own stack, and records are
+    // linked from the innermost frame outwards.
+    if fp < sp {
+        return false;
+    }
+    let (saved_fp, saved_lr) = match (
+        stack_memory.get_memory_at_address::<Pointer>(fp),
+        fp.checked_add(POINTER_WIDTH)
+            .and_then(|address| stack_memory.get_memory_at_address::<Pointer>(address)),
+    ) {
+        (Some(saved_fp), Some(saved_lr)) => (saved_fp, saved_lr),
+        _ => return false,
+    };
+    let saved_fp = ptr_auth_strip(modules, saved_fp);
+    let saved_lr = ptr_auth_strip(modules, saved_lr);
+    (saved_fp == 0 || saved_fp > fp)
+        && instruction_seems_valid(saved_lr, modules, symbol_provider).await
+}
+
 async fn get_caller_by_scan<P>(
     ctx: &ArmContext,
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -347,8 +470,13 @@ where
         default_scan_range
     };
 
+    let first_address = scan.first_address(last_sp, POINTER_WIDTH)?;
+
     for i in 0..scan_range {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_pc = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_pc, stack_memory, POINTER_WIDTH) {
+            return None;
+        }
         let caller_pc = stack_memory.get_memory_at_address(address_of_pc)?;
         if instruction_seems_valid(caller_pc, modules, symbol_provider).await {
             // pc is pushed by CALL, so sp is just address_of_pc + ptr
@@ -452,37 +580,43 @@ impl Unwind for ArmContext {
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        _memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
+        scan: ScanOptions,
+        _unwind_hints: Option<&UnwindHints>,
         syms: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = stack_memory.ok_or(StackWalkEnd::MissingStackMemory)?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
         if frame.is_none() {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
+        if frame.is_none() {
+            frame = get_caller_by_link_register(self, callee, stack, modules, syms).await;
+        }
         if frame.is_none() {
             frame = get_caller_by_frame_pointer(self, callee, grand_callee, stack, modules, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, stack, modules, scan, syms).await;
         }
-        let mut frame = frame?;
+        let mut frame = frame.ok_or(StackWalkEnd::NoCaller)?;
 
         // We now check the frame to see if it looks like unwinding is complete,
         // based on the frame we computed having a nonsense value. Returning
-        // None signals to the unwinder to stop unwinding.
+        // an error signals to the unwinder to stop unwinding.
 
         // if the instruction is within the first ~page of memory, it's basically
         // null, and we can assume unwinding is complete.
         if frame.context.get_instruction_pointer() < 4096 {
             trace!("instruction pointer was nullish, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::NullInstructionPointer);
         }
 
         // If the new stack pointer is at a lower address than the old,
@@ -500,7 +634,7 @@ impl Unwind for ArmContext {
             let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
             if !is_leaf {
                 trace!("stack pointer went backwards, assuming unwind complete");
-                return None;
+                return Err(StackWalkEnd::StackPointerNotIncreasing);
             }
         }
 
@@ -514,6 +648,6 @@ impl Unwind for ArmContext {
         let ip = frame.context.get_instruction_pointer();
         frame.instruction = ip - 4;
 
-        Some(frame)
+        Ok(frame)
     }
 }
@@ -84,6 +84,8 @@ impl TestFixture {
             cpu_info: None,
             cpu_microcode_version: None,
             cpu_count: 1,
+            os_flavor: None,
+            os_pretty_name: String::new(),
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         let options = ProcessorOptions::default();
@@ -541,6 +543,32 @@ async fn test_frame_pointer_stackless_leaf() {
     }
 }
 
+#[tokio::test]
+async fn test_frame_pointer_loop() {
+    // Same as test_frame_pointer_stackless_leaf, but the link register points back
+    // at the leaf itself. Its caller would be the very same frame, again and again.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let frame1_fp = Label::new();
+    stack = stack
+        .append_repeated(0, 64) // space
+        .mark(&frame1_fp)
+        .D64(0)
+        .D64(0);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", 0x40005510);
+    f.raw.set_register("fp", frame1_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 1);
+    assert_eq!(s.frames[0].trust, FrameTrust::Context);
+    assert_eq!(s.walk_end, Some(StackWalkEnd::Loop));
+}
+
 #[tokio::test]
 async fn test_frame_pointer_stackful_leaf() {
     // Same as test_frame_pointer but frame0 is a stackful leaf.
@@ -620,6 +648,149 @@ async fn test_frame_pointer_stackful_leaf() {
     }
 }
 
+#[tokio::test]
+async fn test_link_register_without_frame_record() {
+    // frame0 is a stackless leaf in code built without frame pointers, so the
+    // only way to find its caller is the link register.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    stack = stack.append_repeated(0, 64);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", return_address1);
+    f.raw.set_register("fp", 0);
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert!(s.frames.len() >= 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::LinkRegister);
+    assert_eq!(frame.instruction, return_address1 - 4);
+    if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+        assert_eq!(ctx.get_register("sp", valid).unwrap(), 0x80000000);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_link_register_after_call() {
+    // frame0 is a normal frame that crashed after a call returned, so lr points
+    // back into frame0's own function. That's not a caller, the frame record is.
+    let mut f = TestFixture::new();
+    f.add_symbols(
+        String::from("module1"),
+        String::from("FUNC 5500 100 0 crashing\n"),
+    );
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let frame1_sp = Label::new();
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // space
+        .mark(&frame0_fp)
+        .D64(&frame1_fp)
+        .D64(return_address1)
+        .mark(&frame1_sp)
+        // frame 1
+        .append_repeated(0, 64) // space
+        .mark(&frame1_fp)
+        .D64(0)
+        .D64(0);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", 0x40005508);
+    f.raw.set_register("fp", frame0_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+        assert_eq!(
+            ctx.get_register("sp", valid).unwrap(),
+            frame1_sp.value().unwrap()
+        );
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_frame_pointer_link_register_matches() {
+    // Same as test_frame_pointer but frame0 is a normal frame that hasn't
+    // clobbered lr yet, so lr agrees with the saved frame record. We must not
+    // treat this as a leaf, or we'd get frame 1's sp wrong.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u64;
+    let return_address2 = 0x50000900u64;
+    let frame1_sp = Label::new();
+    let frame2_sp = Label::new();
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+    let frame2_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 64) // space
+        .mark(&frame0_fp)
+        .D64(&frame1_fp)
+        .D64(return_address1)
+        .mark(&frame1_sp)
+        // frame 1
+        .append_repeated(0, 64) // space
+        .mark(&frame1_fp)
+        .D64(&frame2_fp)
+        .D64(return_address2)
+        .mark(&frame2_sp)
+        // frame 2
+        .append_repeated(0, 64) // Whatever values on the stack.
+        .mark(&frame2_fp)
+        .D64(0)
+        .D64(0);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", return_address1);
+    f.raw.set_register("fp", frame0_fp.value().unwrap());
+    f.raw.set_register("sp", stack.start().value().unwrap());
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 3);
+
+    let expected = [
+        (return_address1, frame1_sp.value().unwrap()),
+        (return_address2, frame2_sp.value().unwrap()),
+    ];
+    for (frame, (pc, sp)) in s.frames[1..].iter().zip(expected) {
+        let valid = &frame.context.valid;
+        assert_eq!(frame.trust, FrameTrust::FramePointer);
+        if let MinidumpRawContext::Arm64(ctx) = &frame.context.raw {
+            assert_eq!(ctx.get_register("pc", valid).unwrap(), pc);
+            assert_eq!(ctx.get_register("sp", valid).unwrap(), sp);
+        } else {
+            unreachable!();
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_frame_pointer_ptr_auth_strip() {
     // Same as the basic frame pointer test but extra high bits have been set which
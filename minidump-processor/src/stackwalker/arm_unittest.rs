@@ -35,6 +35,8 @@ impl TestFixture {
                 cpu_info: None,
                 cpu_microcode_version: None,
                 cpu_count: 1,
+                os_flavor: None,
+                os_pretty_name: String::new(),
             },
             symbols: HashMap::new(),
         }
@@ -625,6 +627,91 @@ async fn test_frame_pointer_stackful_leaf() {
     }
 }
 
+#[tokio::test]
+async fn test_link_register_without_frame_record() {
+    // frame0 is a stackless leaf in code built without frame pointers, so the
+    // only way to find its caller is the link register.
+    let mut f = TestFixture::new();
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    stack = stack.append_repeated(0, 32);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", return_address1);
+    f.raw.set_register("fp", 0);
+    f.raw
+        .set_register("sp", stack.start().value().unwrap() as u32);
+
+    let s = f.walk_stack(stack).await;
+    assert!(s.frames.len() >= 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::LinkRegister);
+    assert_eq!(frame.instruction, return_address1 as u64 - 2);
+    if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+        assert_eq!(ctx.get_register("sp", valid).unwrap(), 0x80000000);
+    } else {
+        unreachable!();
+    }
+}
+
+#[tokio::test]
+async fn test_link_register_after_call() {
+    // frame0 is a normal frame that crashed after a call returned, so lr points
+    // back into frame0's own function. That's not a caller, the frame record is.
+    let mut f = TestFixture::new();
+    f.add_symbols(
+        String::from("module1"),
+        String::from("FUNC 5500 100 0 crashing\n"),
+    );
+    let mut stack = Section::new();
+    stack.start().set_const(0x80000000);
+
+    let return_address1 = 0x50000100u32;
+    let frame1_sp = Label::new();
+    let frame0_fp = Label::new();
+    let frame1_fp = Label::new();
+
+    stack = stack
+        // frame 0
+        .append_repeated(0, 32) // space
+        .mark(&frame0_fp)
+        .D32(&frame1_fp)
+        .D32(return_address1)
+        .mark(&frame1_sp)
+        // frame 1
+        .append_repeated(0, 32) // space
+        .mark(&frame1_fp)
+        .D32(0)
+        .D32(0);
+
+    f.raw.set_register("pc", 0x40005510);
+    f.raw.set_register("lr", 0x40005508);
+    f.raw.set_register("fp", frame0_fp.value().unwrap() as u32);
+    f.raw
+        .set_register("sp", stack.start().value().unwrap() as u32);
+
+    let s = f.walk_stack(stack).await;
+    assert_eq!(s.frames.len(), 2);
+
+    let frame = &s.frames[1];
+    let valid = &frame.context.valid;
+    assert_eq!(frame.trust, FrameTrust::FramePointer);
+    if let MinidumpRawContext::Arm(ctx) = &frame.context.raw {
+        assert_eq!(ctx.get_register("pc", valid).unwrap(), return_address1);
+        assert_eq!(
+            ctx.get_register("sp", valid).unwrap(),
+            frame1_sp.value().unwrap() as u32
+        );
+    } else {
+        unreachable!();
+    }
+}
+
 #[tokio::test]
 async fn test_frame_pointer_infinite_equality() {
     // Leaf functions on Arm are allowed to not update the stack pointer, so
@@ -4,14 +4,14 @@ use std::convert::TryFrom;
 use minidump::format::ContextFlagsCpu;
 use minidump::{
     CpuContext, Endian, MinidumpContext, MinidumpContextValidity, MinidumpModuleList,
-    MinidumpRawContext, UnifiedMemory,
+    MinidumpRawContext, UnifiedMemory, UnifiedMemoryList,
 };
 use scroll::ctx::{SizeWith, TryFromCtx};
 use tracing::trace;
 
 use crate::stackwalker::unwind::Unwind;
-use crate::stackwalker::CfiStackWalker;
-use crate::{FrameTrust, StackFrame, SymbolProvider, SystemInfo};
+use crate::stackwalker::{CfiStackWalker, ScanOptions};
+use crate::{FrameTrust, StackFrame, StackWalkEnd, SymbolProvider, SystemInfo, UnwindHints};
 
 type MipsContext = minidump::format::CONTEXT_MIPS;
 type Pointer = <MipsContext as CpuContext>::Register;
@@ -100,6 +100,7 @@ async fn get_caller_by_scan32<P>(
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -130,8 +131,14 @@ where
         count -= MIN_ARGS;
     }
 
+    let first_address = scan.first_address(last_sp as u64, POINTER_WIDTH as u64)?;
+    let first_address = u32::try_from(first_address).ok()?;
+
     for i in 0..count {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_pc = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_pc as u64, stack_memory, POINTER_WIDTH as u64) {
+            return None;
+        }
         let caller_pc: u32 = stack_memory.get_memory_at_address(address_of_pc as u64)?;
         //trace!("unwind: trying addr 0x{address_of_pc:08x}: 0x{caller_pc:08x}");
         if instruction_seems_valid(caller_pc as u64, modules, symbol_provider).await {
@@ -170,6 +177,7 @@ async fn get_caller_by_scan64<P>(
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -189,8 +197,13 @@ where
 
     let count = MAX_STACK_SIZE / POINTER_WIDTH;
 
+    let first_address = scan.first_address(last_sp, POINTER_WIDTH)?;
+
     for i in 0..count {
-        let address_of_pc = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_pc = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_pc, stack_memory, POINTER_WIDTH) {
+            return None;
+        }
         let caller_pc = stack_memory.get_memory_at_address(address_of_pc)?;
         if instruction_seems_valid(caller_pc, modules, symbol_provider).await {
             // `ra` is usually saved directly at the bottom of the frame,
@@ -245,15 +258,18 @@ impl Unwind for MipsContext {
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        _memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
+        scan: ScanOptions,
+        _unwind_hints: Option<&UnwindHints>,
         syms: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync,
     {
         let ctx = Mips32Context::try_from(self.clone());
-        let stack = stack_memory?;
+        let stack = stack_memory.ok_or(StackWalkEnd::MissingStackMemory)?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
@@ -272,24 +288,24 @@ impl Unwind for MipsContext {
         if frame.is_none() {
             match &ctx {
                 Ok(mips32) => {
-                    frame = get_caller_by_scan32(mips32, callee, stack, modules, syms).await
+                    frame = get_caller_by_scan32(mips32, callee, stack, modules, scan, syms).await
                 }
                 Err(mips64) => {
-                    frame = get_caller_by_scan64(mips64, callee, stack, modules, syms).await
+                    frame = get_caller_by_scan64(mips64, callee, stack, modules, scan, syms).await
                 }
             }
         }
-        let mut frame = frame?;
+        let mut frame = frame.ok_or(StackWalkEnd::NoCaller)?;
 
         // We now check the frame to see if it looks like unwinding is complete,
         // based on the frame we computed having a nonsense value. Returning
-        // None signals to the unwinder to stop unwinding.
+        // an error signals to the unwinder to stop unwinding.
 
         // if the instruction is within the first ~page of memory, it's basically
         // null, and we can assume unwinding is complete.
         if frame.context.get_instruction_pointer() < 4096 {
             trace!("instruction pointer was nullish, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::NullInstructionPointer);
         }
 
         // If the new stack pointer is at a lower address than the old,
@@ -307,7 +323,7 @@ impl Unwind for MipsContext {
             let is_leaf = callee.trust == FrameTrust::Context && sp == last_sp;
             if !is_leaf {
                 trace!("stack pointer went backwards, assuming unwind complete");
-                return None;
+                return Err(StackWalkEnd::StackPointerNotIncreasing);
             }
         }
 
@@ -317,7 +333,7 @@ impl Unwind for MipsContext {
         let ip = frame.context.get_instruction_pointer();
         frame.instruction = ip - 8;
 
-        Some(frame)
+        Ok(frame)
     }
 }
 
@@ -11,8 +11,8 @@ mod mips;
 mod unwind;
 mod x86;
 
-use crate::{process_state::*, ProcessorOptions};
-use crate::{FrameWalker, SymbolProvider, SystemInfo};
+use crate::{process_state::*, ModuleFilter, ProcessorOptions, UnwindHints};
+use crate::{FillSymbolError, FrameWalker, SymbolProvider, SystemInfo};
 use minidump::*;
 use scroll::ctx::{SizeWith, TryFromCtx};
 use tracing::trace;
@@ -21,6 +21,62 @@ use self::unwind::Unwind;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 
+/// How many of the most recent frames a new frame is checked against for
+/// [`StackWalkEnd::Loop`].
+const LOOP_DETECTION_FRAMES: usize = 8;
+
+/// The most frames a single thread's stack is unwound to, see
+/// [`StackWalkEnd::FrameLimit`].
+const MAX_FRAMES_PER_THREAD: usize = 1024;
+
+/// Restrictions on which stack slots stack scanning considers.
+///
+/// See [`ProcessorOptions::scan_skip_bytes`] and
+/// [`ProcessorOptions::scan_align_to_pointer`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ScanOptions {
+    skip_bytes: u64,
+    align_to_pointer: bool,
+}
+
+impl ScanOptions {
+    fn new(options: &ProcessorOptions<'_>) -> Self {
+        ScanOptions {
+            skip_bytes: options.scan_skip_bytes,
+            align_to_pointer: options.scan_align_to_pointer,
+        }
+    }
+
+    /// The first address that a scan from `sp` should look at.
+    fn first_address(&self, sp: u64, pointer_width: u64) -> Option<u64> {
+        let mut address = sp;
+        if self.align_to_pointer {
+            let misalignment = address % pointer_width;
+            if misalignment != 0 {
+                address = address.checked_add(pointer_width - misalignment)?;
+            }
+        }
+        Some(address)
+    }
+
+    /// Whether a scan through `stack_memory` may look at the pointer at `address`.
+    ///
+    /// The stack grows down, so the skipped bytes are at the end of `stack_memory`,
+    /// furthest away from the stack pointer.
+    fn includes(
+        &self,
+        address: u64,
+        stack_memory: UnifiedMemory<'_, '_>,
+        pointer_width: u64,
+    ) -> bool {
+        let end = stack_memory
+            .base_address()
+            .saturating_add(stack_memory.size())
+            .saturating_sub(self.skip_bytes);
+        matches!(address.checked_add(pointer_width), Some(slot_end) if slot_end <= end)
+    }
+}
+
 struct CfiStackWalker<'a, C: CpuContext> {
     instruction: u64,
     has_grand_callee: bool,
@@ -85,16 +141,20 @@ where
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tracing::instrument(name = "unwind_frame", level = "trace", skip_all, fields(idx = _frame_idx, fname = callee_frame.function_name.as_deref().unwrap_or("")))]
 async fn get_caller_frame<P>(
     _frame_idx: usize,
     callee_frame: &StackFrame,
     grand_callee_frame: Option<&StackFrame>,
     stack_memory: Option<UnifiedMemory<'_, '_>>,
+    memory_list: Option<&UnifiedMemoryList<'_>>,
     modules: &MinidumpModuleList,
     system_info: &SystemInfo,
+    scan: ScanOptions,
+    unwind_hints: Option<&UnwindHints>,
     symbol_provider: &P,
-) -> Option<StackFrame>
+) -> Result<StackFrame, StackWalkEnd>
 where
     P: SymbolProvider + Sync,
 {
@@ -109,8 +169,11 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
@@ -120,8 +183,11 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
@@ -131,8 +197,11 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
@@ -142,8 +211,11 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
@@ -153,8 +225,11 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
@@ -164,20 +239,24 @@ where
                 callee_frame,
                 grand_callee_frame,
                 stack_memory,
+                memory_list,
                 modules,
                 system_info,
+                scan,
+                unwind_hints,
                 symbol_provider,
             )
             .await
         }
-        _ => None,
+        _ => Err(StackWalkEnd::UnsupportedCpu),
     }
 }
 
 async fn fill_source_line_info<P>(
     frame: &mut StackFrame,
     modules: &MinidumpModuleList,
-    symbol_provider: &P,
+    symbol_provider: Option<&P>,
+    symbolicate_modules: &ModuleFilter,
 ) where
     P: SymbolProvider + Sync,
 {
@@ -187,18 +266,135 @@ async fn fill_source_line_info<P>(
         // the same lifetime as the module list that's passed in.
         frame.module = Some(module.clone());
 
-        // This is best effort, so ignore any errors.
-        let _ = symbol_provider.fill_symbol(module, frame).await;
+        let symbol_provider = symbol_provider.filter(|_| symbolicate_modules.selects(module));
+        if let Some(symbol_provider) = symbol_provider {
+            // This is best effort, so ignore any errors.
+            let _ = symbolicate_frame(frame, symbol_provider).await;
+        }
+    }
+}
+
+/// Fill in the function name, source line, and inlines of a single frame.
+///
+/// This is the second half of processing with [`ProcessorOptions::defer_symbolication`]:
+/// the stackwalker has already found the frame's module, so this only needs to do
+/// the (potentially expensive) symbol lookup. It can be called on frames in any order,
+/// and symbolicating a frame more than once is harmless.
+///
+/// An error is returned if `symbol_provider` has no symbols for the frame's module,
+/// or if the frame isn't in a module at all.
+///
+/// The frame is symbolicated regardless of [`ProcessorOptions::symbolicate_modules`];
+/// use [`ModuleFilter::selects`] to skip the same modules processing does.
+pub async fn symbolicate_frame<P>(
+    frame: &mut StackFrame,
+    symbol_provider: &P,
+) -> Result<(), FillSymbolError>
+where
+    P: SymbolProvider + Sync,
+{
+    let module = frame.module.take().ok_or(FillSymbolError {})?;
+
+    // Don't duplicate the inlines of a previous symbolication.
+    frame.inlines.clear();
+    let result = symbol_provider.fill_symbol(&module, frame).await;
+    frame.module = Some(module);
+
+    // If we got any inlines, reverse them! The symbol format makes it simplest to
+    // emit inlines from the shallowest callee to the deepest one ("inner to outer"),
+    // but we want inlines to be in the same order as the stackwalk itself, which means
+    // we want the deepest frame first (the callee-est frame).
+    frame.inlines.reverse();
+
+    result
+}
+
+/// Symbolicate many frames concurrently with [`symbolicate_frame`].
+///
+/// All the lookups are in flight at once, so a [`SymbolProvider`] that does its
+/// work on other threads (or over the network) can make progress on every frame
+/// at the same time. The frames don't need to come from the same thread or be
+/// in any particular order.
+///
+/// This all runs on the current task, so CPU-bound work like parsing symbol files
+/// only uses one core. See [`symbolicate_frames_parallel`] (with the `parallel`
+/// feature) for that.
+///
+/// Only frames in modules that `symbolicate_modules` selects are looked up, the
+/// others get an error as if there were no symbols for them. Pass the
+/// [`ProcessorOptions::symbolicate_modules`] the frames were processed with to
+/// symbolicate the same modules as processing would have.
+///
+/// The result for each frame is returned in the same order as `frames`.
+pub async fn symbolicate_frames<P>(
+    frames: &mut [StackFrame],
+    symbol_provider: &P,
+    symbolicate_modules: &ModuleFilter,
+) -> Vec<Result<(), FillSymbolError>>
+where
+    P: SymbolProvider + Sync,
+{
+    futures_util::future::join_all(
+        frames
+            .iter_mut()
+            .map(|frame| symbolicate_selected_frame(frame, symbol_provider, symbolicate_modules)),
+    )
+    .await
+}
+
+/// Symbolicate many frames in parallel with [`symbolicate_frame`], on rayon's
+/// thread pool.
+///
+/// Unlike [`symbolicate_frames`], this spreads CPU-bound work like parsing symbol
+/// files over all cores. Each frame's lookup is driven to completion on a rayon
+/// thread, outside of any async runtime, so this only works with a [`SymbolProvider`]
+/// whose lookups don't need one. A [`Symbolizer`][crate::Symbolizer] reading local
+/// symbol files is fine, but one that downloads them over HTTP isn't.
+///
+/// Like [`symbolicate_frames`], only frames in modules that `symbolicate_modules`
+/// selects are looked up.
+///
+/// The result for each frame is returned in the same order as `frames`.
+#[cfg(feature = "parallel")]
+pub fn symbolicate_frames_parallel<P>(
+    frames: &mut [StackFrame],
+    symbol_provider: &P,
+    symbolicate_modules: &ModuleFilter,
+) -> Vec<Result<(), FillSymbolError>>
+where
+    P: SymbolProvider + Sync,
+{
+    use rayon::prelude::*;
 
-        // If we got any inlines, reverse them! The symbol format makes it simplest to
-        // emit inlines from the shallowest callee to the deepest one ("inner to outer"),
-        // but we want inlines to be in the same order as the stackwalk itself, which means
-        // we want the deepest frame first (the callee-est frame).
-        frame.inlines.reverse();
+    frames
+        .par_iter_mut()
+        .map(|frame| {
+            futures_executor::block_on(symbolicate_selected_frame(
+                frame,
+                symbol_provider,
+                symbolicate_modules,
+            ))
+        })
+        .collect()
+}
+
+/// [`symbolicate_frame`], if `symbolicate_modules` selects the frame's module.
+async fn symbolicate_selected_frame<P>(
+    frame: &mut StackFrame,
+    symbol_provider: &P,
+    symbolicate_modules: &ModuleFilter,
+) -> Result<(), FillSymbolError>
+where
+    P: SymbolProvider + Sync,
+{
+    match &frame.module {
+        Some(module) if symbolicate_modules.selects(module) => {
+            symbolicate_frame(frame, symbol_provider).await
+        }
+        _ => Err(FillSymbolError {}),
     }
 }
 
-#[tracing::instrument(name = "unwind_thread", level = "trace", skip_all, fields(idx = thread_idx, tid = stack.thread_id, tname = stack.thread_name.as_deref().unwrap_or("")))]
 pub async fn walk_stack<P>(
     thread_idx: usize,
     options: &ProcessorOptions<'_>,
@@ -209,12 +405,57 @@ pub async fn walk_stack<P>(
     symbol_provider: &P,
 ) where
     P: SymbolProvider + Sync,
+{
+    walk_stack_with_thread_stack(
+        thread_idx,
+        options,
+        stack,
+        stack_memory,
+        None,
+        None,
+        modules,
+        system_info,
+        symbol_provider,
+    )
+    .await
+}
+
+/// Like [`walk_stack`], but for a thread whose context may not be running on the
+/// thread's own stack.
+///
+/// This happens when the context comes from an exception that was handled on a
+/// signal alt-stack. Unwinding starts out in `stack_memory` (which should contain
+/// the context's stack pointer), and switches over to `thread_stack` as soon as a
+/// frame's stack pointer lands in it.
+///
+/// `memory_list` is all of the process's memory. If
+/// [`ProcessorOptions::use_pdata_unwinding`] is enabled, it is used to read
+/// the unwinding information of modules.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(name = "unwind_thread", level = "trace", skip_all, fields(idx = thread_idx, tid = stack.thread_id, tname = stack.thread_name.as_deref().unwrap_or("")))]
+pub(crate) async fn walk_stack_with_thread_stack<P>(
+    thread_idx: usize,
+    options: &ProcessorOptions<'_>,
+    stack: &mut CallStack,
+    mut stack_memory: Option<UnifiedMemory<'_, '_>>,
+    thread_stack: Option<UnifiedMemory<'_, '_>>,
+    memory_list: Option<&UnifiedMemoryList<'_>>,
+    modules: &MinidumpModuleList,
+    system_info: &SystemInfo,
+    symbol_provider: &P,
+) where
+    P: SymbolProvider + Sync,
 {
     trace!(
         "starting stack unwind of thread {} {}",
         stack.thread_id,
         stack.thread_name.as_deref().unwrap_or(""),
     );
+    let memory_list = memory_list.filter(|_| options.use_pdata_unwinding);
+    let scan = ScanOptions::new(options);
+    // Whether a signal frame already moved the walk to a lower stack.
+    let mut left_signal_stack = false;
+
     // Begin with the context frame, and keep getting callers until there are no more.
     let mut has_new_frame = !stack.frames.is_empty();
     while has_new_frame {
@@ -222,7 +463,8 @@ pub async fn walk_stack<P>(
         let frame_idx = stack.frames.len() - 1;
         let frame = stack.frames.last_mut().unwrap();
 
-        fill_source_line_info(frame, modules, symbol_provider).await;
+        let symbolicator = (!options.defer_symbolication).then_some(symbol_provider);
+        fill_source_line_info(frame, modules, symbolicator, &options.symbolicate_modules).await;
 
         // Report the frame as walked and symbolicated
         if let Some(reporter) = options.stat_reporter {
@@ -240,22 +482,91 @@ pub async fn walk_stack<P>(
             Some(name) => trace!("unwinding {}", name),
             None => trace!("unwinding 0x{:016x}", callee_frame.instruction),
         }
+        // If we've unwound off of the stack we started on, move over to the
+        // thread's own stack so that memory reads and scans stay in bounds.
+        let callee_sp = callee_frame.context.get_stack_pointer();
+        if !memory_contains(stack_memory, callee_sp) && memory_contains(thread_stack, callee_sp) {
+            trace!("switching to the thread's stack at 0x{:016x}", callee_sp);
+            stack_memory = thread_stack;
+        }
         let new_frame = get_caller_frame(
             frame_idx,
             callee_frame,
             grand_callee_frame,
             stack_memory,
+            memory_list,
             modules,
             system_info,
+            scan,
+            options.unwind_hints,
             symbol_provider,
         )
         .await;
 
-        // Check if we're done
-        if let Some(new_frame) = new_frame {
-            stack.frames.push(new_frame);
-        } else {
-            has_new_frame = false;
+        // Check if we're done. Whatever the unwinder found, a frame whose stack pointer
+        // isn't on the stack can't be unwound any further (and is likely garbage).
+        let new_frame = new_frame.and_then(|frame| {
+            let sp = frame.context.get_stack_pointer();
+            if stack_contains(stack_memory, sp) || stack_contains(thread_stack, sp) {
+                Ok(frame)
+            } else {
+                Err(StackWalkEnd::LeftStack)
+            }
+        });
+        // A signal handler may have run on an alt-stack anywhere in memory, so the
+        // frame it interrupted can have a lower stack pointer. That can only happen
+        // once per walk, or a chain of signal frames could cycle between stacks.
+        // (The unwinders check the stack pointers of all other frames themselves.)
+        let new_frame = new_frame.and_then(|frame| {
+            if frame.trust != FrameTrust::SignalContext
+                || frame.context.get_stack_pointer() > callee_sp
+            {
+                Ok(frame)
+            } else if !left_signal_stack {
+                trace!("signal frame moved to a lower stack");
+                left_signal_stack = true;
+                Ok(frame)
+            } else {
+                Err(StackWalkEnd::StackPointerNotIncreasing)
+            }
+        });
+        // A frame with the same registers as a recent one would just be unwound the
+        // same way again, forever.
+        let new_frame = new_frame.and_then(|frame| {
+            let key = |frame: &StackFrame| {
+                (
+                    frame.context.get_instruction_pointer(),
+                    frame.context.get_stack_pointer(),
+                )
+            };
+            let new_key = key(&frame);
+            let looped = stack
+                .frames
+                .iter()
+                .rev()
+                .take(LOOP_DETECTION_FRAMES)
+                .any(|recent| key(recent) == new_key);
+            if looped {
+                Err(StackWalkEnd::Loop)
+            } else {
+                Ok(frame)
+            }
+        });
+        // Whatever else went wrong, don't let a corrupt stack unwind forever.
+        let new_frame = new_frame.and_then(|frame| {
+            if stack.frames.len() < MAX_FRAMES_PER_THREAD {
+                Ok(frame)
+            } else {
+                Err(StackWalkEnd::FrameLimit)
+            }
+        });
+        match new_frame {
+            Ok(new_frame) => stack.frames.push(new_frame),
+            Err(end) => {
+                trace!("unwind complete: {:?}", end);
+                stack.walk_end = Some(end);
+                has_new_frame = false;
+            }
         }
     }
     trace!(
@@ -265,6 +576,57 @@ pub async fn walk_stack<P>(
     );
 }
 
+/// Whether `sp` is a stack pointer into `memory`.
+///
+/// This includes the address just past the end of `memory`, which is where the
+/// stack pointer of the outermost frame ends up if the whole stack was captured.
+fn stack_contains(memory: Option<UnifiedMemory<'_, '_>>, sp: u64) -> bool {
+    matches!(memory, Some(memory)
+        if sp >= memory.base_address() && sp - memory.base_address() <= memory.size())
+}
+
+fn memory_contains(memory: Option<UnifiedMemory<'_, '_>>, address: u64) -> bool {
+    memory
+        .and_then(|memory| memory.get_memory_at_address::<u8>(address))
+        .is_some()
+}
+
+/// Look up the name of the function containing `address` with the symbol provider.
+async fn function_name_at<P>(
+    address: u64,
+    modules: &MinidumpModuleList,
+    symbol_provider: &P,
+) -> Option<String>
+where
+    P: SymbolProvider + Sync,
+{
+    use crate::FrameSymbolizer;
+
+    struct NameFrame {
+        instruction: u64,
+        name: Option<String>,
+    }
+    impl FrameSymbolizer for NameFrame {
+        fn get_instruction(&self) -> u64 {
+            self.instruction
+        }
+        fn set_function(&mut self, name: &str, _base: u64, _parameter_size: u32) {
+            self.name = Some(name.to_owned());
+        }
+        fn set_source_file(&mut self, _file: &str, _line: u32, _base: u64) {
+            // Do nothing
+        }
+    }
+
+    let module = modules.module_at_address(address)?;
+    let mut frame = NameFrame {
+        instruction: address,
+        name: None,
+    };
+    symbol_provider.fill_symbol(module, &mut frame).await.ok()?;
+    frame.name
+}
+
 /// Checks if we can dismiss the validity of an instruction based on our symbols,
 /// to refine the quality of each unwinder's instruction_seems_valid implementation.
 async fn instruction_seems_valid_by_symbols<P>(
@@ -1,23 +1,40 @@
 // Copyright 2015 Ted Mielczarek. See the COPYRIGHT
 // file at the top-level directory of this distribution.
 
-use crate::process_state::StackFrame;
-use crate::{SymbolProvider, SystemInfo};
-use minidump::{MinidumpModuleList, UnifiedMemory};
+use super::ScanOptions;
+use crate::process_state::{StackFrame, StackWalkEnd};
+use crate::{SymbolProvider, SystemInfo, UnwindHints};
+use minidump::{MinidumpModuleList, UnifiedMemory, UnifiedMemoryList};
 
 /// A trait for things that can unwind to a caller.
 #[async_trait::async_trait]
 pub trait Unwind {
     /// Get the caller frame of this frame.
+    ///
+    /// If there is no caller, or the caller that was found doesn't make sense, this
+    /// returns why unwinding should stop instead.
+    ///
+    /// `memory_list` is all of the process's memory, which unwinders may use to
+    /// read unwinding information out of loaded images. It is only provided if
+    /// that kind of unwinding is enabled.
+    ///
+    /// `scan` restricts which stack slots are considered if the caller has to be
+    /// found by stack scanning.
+    ///
+    /// `unwind_hints` describe how to unwind modules that have no CFI.
+    #[allow(clippy::too_many_arguments)]
     async fn get_caller_frame<P>(
         &self,
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         system_info: &SystemInfo,
+        scan: ScanOptions,
+        unwind_hints: Option<&UnwindHints>,
         symbol_provider: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync;
 }
@@ -6,15 +6,18 @@
 // so that it can be copied between the two with minimal changes. It's not
 // worth the effort to *actually* unify the implementations.
 
-use crate::process_state::{FrameTrust, StackFrame};
+use crate::process_state::{FrameTrust, StackFrame, StackWalkEnd};
 use crate::stackwalker::unwind::Unwind;
 use crate::stackwalker::CfiStackWalker;
-use crate::{SymbolProvider, SystemInfo};
+use crate::stackwalker::ScanOptions;
+use crate::{SymbolProvider, SystemInfo, UnwindHint, UnwindHints};
 use minidump::format::CONTEXT_X86;
 use minidump::{
-    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, UnifiedMemory,
+    MinidumpContext, MinidumpContextValidity, MinidumpModuleList, MinidumpRawContext, Module,
+    UnifiedMemory, UnifiedMemoryList,
 };
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use tracing::trace;
 
 type Pointer = u32;
@@ -196,11 +199,84 @@ where
     Some(StackFrame::from_context(context, FrameTrust::FramePointer))
 }
 
+fn get_caller_by_hint(
+    ctx: &CONTEXT_X86,
+    callee: &StackFrame,
+    stack_memory: UnifiedMemory<'_, '_>,
+    modules: &MinidumpModuleList,
+    unwind_hints: Option<&UnwindHints>,
+) -> Option<StackFrame> {
+    let module = modules.module_at_address(callee.instruction)?;
+    let hint = unwind_hints?.get(module.debug_identifier()?)?;
+    trace!("trying unwind hint {:?}", hint);
+
+    let has_register = |reg| match callee.context.valid {
+        MinidumpContextValidity::All => true,
+        MinidumpContextValidity::Some(ref which) => which.contains(reg),
+    };
+    // The caller's ip is the return address, which is right below the arguments
+    // that the callee pops off the stack when it returns.
+    let (address_of_ip, caller_bp, parameter_size) = match hint {
+        UnwindHint::FramePointer { parameter_size } => {
+            if !has_register(FRAME_POINTER_REGISTER) {
+                return None;
+            }
+            let caller_bp: Pointer = stack_memory.get_memory_at_address(ctx.ebp as u64)?;
+            let address_of_ip = ctx.ebp.checked_add(POINTER_WIDTH)?;
+            (address_of_ip, Some(caller_bp), parameter_size)
+        }
+        UnwindHint::Frameless {
+            frame_size,
+            parameter_size,
+        } => {
+            if !has_register(STACK_POINTER_REGISTER) {
+                return None;
+            }
+            let address_of_ip = ctx.esp.checked_add(frame_size)?;
+            (address_of_ip, None, parameter_size)
+        }
+    };
+    let caller_ip: Pointer = stack_memory.get_memory_at_address(address_of_ip as u64)?;
+    let caller_sp = address_of_ip
+        .checked_add(POINTER_WIDTH)?
+        .checked_add(parameter_size)?;
+
+    trace!(
+        "unwind hint seems valid -- caller_ip: 0x{:08x}, caller_sp: 0x{:08x}",
+        caller_ip,
+        caller_sp,
+    );
+
+    let (mut caller_ctx, mut valid) = match caller_bp {
+        Some(caller_bp) => {
+            let caller_ctx = CONTEXT_X86 {
+                ebp: caller_bp,
+                ..CONTEXT_X86::default()
+            };
+            let mut valid = HashSet::new();
+            valid.insert(FRAME_POINTER_REGISTER);
+            (caller_ctx, valid)
+        }
+        // Without a frame, the callee has no reason to touch the callee-saved registers.
+        None => (ctx.clone(), callee_forwarded_regs(&callee.context.valid)),
+    };
+    caller_ctx.eip = caller_ip;
+    caller_ctx.esp = caller_sp;
+    valid.insert(INSTRUCTION_REGISTER);
+    valid.insert(STACK_POINTER_REGISTER);
+    let context = MinidumpContext {
+        raw: MinidumpRawContext::X86(caller_ctx),
+        valid: MinidumpContextValidity::Some(valid),
+    };
+    Some(StackFrame::from_context(context, FrameTrust::UnwindHint))
+}
+
 async fn get_caller_by_scan<P>(
     ctx: &CONTEXT_X86,
     callee: &StackFrame,
     stack_memory: UnifiedMemory<'_, '_>,
     modules: &MinidumpModuleList,
+    scan: ScanOptions,
     symbol_provider: &P,
 ) -> Option<StackFrame>
 where
@@ -241,8 +317,14 @@ where
         default_scan_range
     };
 
+    let first_address = scan.first_address(last_sp as u64, POINTER_WIDTH as u64)?;
+    let first_address = Pointer::try_from(first_address).ok()?;
+
     for i in 0..scan_range {
-        let address_of_ip = last_sp.checked_add(i * POINTER_WIDTH)?;
+        let address_of_ip = first_address.checked_add(i * POINTER_WIDTH)?;
+        if !scan.includes(address_of_ip as u64, stack_memory, POINTER_WIDTH as u64) {
+            return None;
+        }
         let caller_ip = stack_memory.get_memory_at_address(address_of_ip as u64)?;
         if instruction_seems_valid(caller_ip, modules, symbol_provider).await {
             // ip is pushed by CALL, so sp is just address_of_ip + ptr
@@ -392,44 +474,50 @@ impl Unwind for CONTEXT_X86 {
         callee: &StackFrame,
         grand_callee: Option<&StackFrame>,
         stack_memory: Option<UnifiedMemory<'_, '_>>,
+        _memory_list: Option<&UnifiedMemoryList<'_>>,
         modules: &MinidumpModuleList,
         _system_info: &SystemInfo,
+        scan: ScanOptions,
+        unwind_hints: Option<&UnwindHints>,
         syms: &P,
-    ) -> Option<StackFrame>
+    ) -> Result<StackFrame, StackWalkEnd>
     where
         P: SymbolProvider + Sync,
     {
-        let stack = stack_memory?;
+        let stack = stack_memory.ok_or(StackWalkEnd::MissingStackMemory)?;
 
         // .await doesn't like closures, so don't use Option chaining
         let mut frame = None;
         if frame.is_none() {
             frame = get_caller_by_cfi(self, callee, grand_callee, stack, modules, syms).await;
         }
+        if frame.is_none() {
+            frame = get_caller_by_hint(self, callee, stack, modules, unwind_hints);
+        }
         if frame.is_none() {
             frame = get_caller_by_frame_pointer(self, callee, stack, modules, syms);
         }
         if frame.is_none() {
-            frame = get_caller_by_scan(self, callee, stack, modules, syms).await;
+            frame = get_caller_by_scan(self, callee, stack, modules, scan, syms).await;
         }
-        let mut frame = frame?;
+        let mut frame = frame.ok_or(StackWalkEnd::NoCaller)?;
 
         // We now check the frame to see if it looks like unwinding is complete,
         // based on the frame we computed having a nonsense value. Returning
-        // None signals to the unwinder to stop unwinding.
+        // an error signals to the unwinder to stop unwinding.
 
         // if the instruction is within the first ~page of memory, it's basically
         // null, and we can assume unwinding is complete.
         if frame.context.get_instruction_pointer() < 4096 {
             trace!("instruction pointer was nullish, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::NullInstructionPointer);
         }
         // If the new stack pointer is at a lower address than the old,
         // then that's clearly incorrect. Treat this as end-of-stack to
         // enforce progress and avoid infinite loops.
         if frame.context.get_stack_pointer() <= self.esp as u64 {
             trace!("stack pointer went backwards, assuming unwind complete");
-            return None;
+            return Err(StackWalkEnd::StackPointerNotIncreasing);
         }
 
         // Ok, the frame now seems well and truly valid, do final cleanup.
@@ -442,6 +530,6 @@ impl Unwind for CONTEXT_X86 {
         let ip = frame.context.get_instruction_pointer();
         frame.instruction = ip - 1;
 
-        Some(frame)
+        Ok(frame)
     }
 }
@@ -53,6 +53,8 @@ impl TestFixture {
             cpu_info: None,
             cpu_microcode_version: None,
             cpu_count: 1,
+            os_flavor: None,
+            os_pretty_name: String::new(),
         };
         let symbolizer = Symbolizer::new(string_symbol_supplier(self.symbols.clone()));
         let options = ProcessorOptions::default();
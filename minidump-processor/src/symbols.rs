@@ -15,6 +15,11 @@
 //!       generically handle different symbol fetching strategies (which minidump-processor
 //!       selects and configures).
 //!
+//! * [SymbolSource][] - looks up the symbols at an address in a [Module][]
+//!     * Implemented by [BreakpadSymbolSource][], and by `PdbSymbolSource` with the `pdb` feature
+//!     * Several sources are tried in order by [SymbolSourceProvider][], which is a
+//!       [SymbolProvider][]
+//!
 //!
 //!
 //! While minidump-processor provides implementations of these traits:
@@ -89,15 +94,24 @@
 //! ```
 //!
 
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
+use debugid::DebugId;
+use futures_util::FutureExt;
 use minidump::Module;
+use minidump_common::utils::basename;
+
+use crate::InlineFrame;
 
 pub use breakpad_symbols::{
-    FileError, FileKind, FillSymbolError, FrameSymbolizer, FrameWalker, PendingSymbolStats,
-    SymbolError, SymbolFile, SymbolStats, SymbolSupplier, Symbolizer,
+    FileError, FileKind, FilePathMapper, FillSymbolError, FrameSymbolizer, FrameWalker,
+    PendingSymbolStats, SymbolError, SymbolFile, SymbolStats, SymbolStatus, SymbolSupplier,
+    Symbolizer,
 };
 
 /// The [`SymbolProvider`] is the main extension point for minidump processing.
@@ -266,6 +280,158 @@ impl SymbolProvider for MultiSymbolProvider {
     }
 }
 
+/// A [`SymbolProvider`] that keeps a misbehaving provider from taking down processing.
+///
+/// Panics in the wrapped provider are caught and recorded, along with symbol
+/// files that were found but couldn't be loaded, by the module that caused them
+/// (see [`IsolatedSymbolProvider::errors`]). Modules that simply have no symbols
+/// aren't errors. After a provider panics on a module it
+/// isn't asked about that module again, so frames in it are left unsymbolicated
+/// and unwinding through it falls back to the other strategies.
+///
+/// This is what [`process_minidump_isolated`][crate::process_minidump_isolated] uses.
+pub struct IsolatedSymbolProvider<'a, P> {
+    provider: &'a P,
+    errors: Mutex<HashMap<String, String>>,
+    panicked: Mutex<HashSet<String>>,
+}
+
+impl<'a, P> IsolatedSymbolProvider<'a, P> {
+    pub fn new(provider: &'a P) -> Self {
+        IsolatedSymbolProvider {
+            provider,
+            errors: Mutex::new(HashMap::new()),
+            panicked: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The first error the provider had with each module.
+    ///
+    /// Keys are the module's code file, followed by its debug id if it has one,
+    /// so that different modules with the same file name are kept apart.
+    pub fn errors(&self) -> HashMap<String, String> {
+        self.errors.lock().unwrap().clone()
+    }
+
+    fn module_key(module: &(dyn Module + Sync)) -> String {
+        match module.debug_identifier() {
+            Some(id) => format!("{} {}", module.code_file(), id.breakpad()),
+            None => module.code_file().to_string(),
+        }
+    }
+
+    fn has_panicked(&self, module: &(dyn Module + Sync)) -> bool {
+        self.panicked
+            .lock()
+            .unwrap()
+            .contains(&Self::module_key(module))
+    }
+
+    fn record_error(&self, module: &(dyn Module + Sync), error: String) {
+        self.errors
+            .lock()
+            .unwrap()
+            .entry(Self::module_key(module))
+            .or_insert(error);
+    }
+
+    fn record_panic(&self, module: &(dyn Module + Sync), payload: Box<dyn Any + Send>) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        self.record_error(module, format!("symbol provider panicked: {message}"));
+        self.panicked
+            .lock()
+            .unwrap()
+            .insert(Self::module_key(module));
+    }
+}
+
+#[async_trait]
+impl<'a, P: SymbolProvider + Sync> SymbolProvider for IsolatedSymbolProvider<'a, P> {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        if self.has_panicked(module) {
+            return Err(FillSymbolError {});
+        }
+        match AssertUnwindSafe(self.provider.fill_symbol(module, frame))
+            .catch_unwind()
+            .await
+        {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => {
+                // Most of the time this just means there are no symbols for the
+                // module, so only record it if the provider says loading them failed.
+                if let Some(SymbolStats {
+                    status: SymbolStatus::LoadError(error),
+                    ..
+                }) = self.provider.stats().remove(basename(&module.code_file()))
+                {
+                    self.record_error(module, error);
+                }
+                Err(e)
+            }
+            Err(payload) => {
+                self.record_panic(module, payload);
+                Err(FillSymbolError {})
+            }
+        }
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        if self.has_panicked(module) {
+            return None;
+        }
+        match AssertUnwindSafe(self.provider.walk_frame(module, walker))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result,
+            Err(payload) => {
+                self.record_panic(module, payload);
+                None
+            }
+        }
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        if self.has_panicked(module) {
+            return Err(FileError::NotFound);
+        }
+        match AssertUnwindSafe(self.provider.get_file_path(module, file_kind))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result,
+            Err(payload) => {
+                self.record_panic(module, payload);
+                Err(FileError::NotFound)
+            }
+        }
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        self.provider.stats()
+    }
+
+    fn pending_stats(&self) -> PendingSymbolStats {
+        self.provider.pending_stats()
+    }
+}
+
 #[async_trait]
 impl SymbolProvider for Symbolizer {
     async fn fill_symbol(
@@ -297,6 +463,324 @@ impl SymbolProvider for Symbolizer {
     }
 }
 
+/// A source of symbol information, such as breakpad .sym files or native PDBs.
+///
+/// This is a narrower interface than [`SymbolProvider`]: a source only has to
+/// look up what's at an address. Sources are tried in order by a
+/// [`SymbolSourceProvider`], which is what gets passed to the processor.
+#[async_trait]
+pub trait SymbolSource {
+    /// Look up the symbol information for the instruction `address` in `module`.
+    ///
+    /// `address` is an absolute address, like a frame's instruction, while the
+    /// addresses in the returned [`ResolvedSymbol`] are relative to the module.
+    ///
+    /// An Error indicates that this source has no symbols for the module. If it
+    /// has symbols but none cover `address`, the result has no `function_name`.
+    async fn symbolize(
+        &self,
+        module: &(dyn Module + Sync),
+        address: u64,
+    ) -> Result<ResolvedSymbol, FillSymbolError>;
+
+    /// Tries to use CFI to walk the stack frame of the [`FrameWalker`], see
+    /// [`SymbolProvider::walk_frame`].
+    ///
+    /// By default sources have no CFI.
+    async fn walk_frame(
+        &self,
+        _module: &(dyn Module + Sync),
+        _walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        None
+    }
+
+    /// Gets the path to a file for a given module, see [`SymbolProvider::get_file_path`].
+    async fn get_file_path(
+        &self,
+        _module: &(dyn Module + Sync),
+        _file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        Err(FileError::NotFound)
+    }
+
+    /// Collect various statistics on the symbols, see [`SymbolProvider::stats`].
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        HashMap::new()
+    }
+
+    /// Collect various pending statistics on the symbols, see
+    /// [`SymbolProvider::pending_stats`].
+    fn pending_stats(&self) -> PendingSymbolStats {
+        PendingSymbolStats::default()
+    }
+}
+
+/// A [`SymbolProvider`] that tries several [`SymbolSource`]s in order.
+///
+/// A frame is filled in from the first source that finds a function for its
+/// address, and CFI comes from the first source that can walk the frame.
+///
+/// ```rust
+/// use minidump_processor::{
+///     simple_symbol_supplier, BreakpadSymbolSource, SymbolSourceProvider, Symbolizer,
+/// };
+///
+/// let mut provider = SymbolSourceProvider::new();
+/// provider.add(Box::new(BreakpadSymbolSource::new(Symbolizer::new(
+///     simple_symbol_supplier(vec!["symbols".into()]),
+/// ))));
+/// ```
+#[derive(Default)]
+pub struct SymbolSourceProvider {
+    sources: Vec<Box<dyn SymbolSource + Send + Sync>>,
+}
+
+impl SymbolSourceProvider {
+    pub fn new() -> SymbolSourceProvider {
+        Default::default()
+    }
+
+    pub fn add(&mut self, source: Box<dyn SymbolSource + Send + Sync>) {
+        self.sources.push(source);
+    }
+}
+
+#[async_trait]
+impl SymbolProvider for SymbolSourceProvider {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        // Like MultiSymbolProvider, return Ok if *any* source had symbols for the module.
+        let mut best_result = Err(FillSymbolError {});
+        for source in self.sources.iter() {
+            if let Ok(symbol) = source.symbolize(module, frame.get_instruction()).await {
+                if symbol.function_name.is_some() {
+                    symbol.fill_frame(module.base_address(), frame);
+                    return Ok(());
+                }
+                best_result = Ok(());
+            }
+        }
+        best_result
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        for source in self.sources.iter() {
+            let result = source.walk_frame(module, walker).await;
+            if result.is_some() {
+                return result;
+            }
+        }
+        None
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        let mut best_result = Err(FileError::NotFound);
+        for source in self.sources.iter() {
+            let new_result = source.get_file_path(module, file_kind).await;
+            best_result = best_result.or(new_result);
+        }
+        best_result
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        let mut result = HashMap::new();
+        for source in self.sources.iter() {
+            result.extend(source.stats());
+        }
+        result
+    }
+
+    fn pending_stats(&self) -> PendingSymbolStats {
+        let mut result = PendingSymbolStats::default();
+        for source in self.sources.iter() {
+            // FIXME: merge these like MultiSymbolProvider would have to.
+            result = source.pending_stats();
+        }
+        result
+    }
+}
+
+/// A [`SymbolSource`] for breakpad .sym files, looked up by a [`Symbolizer`].
+pub struct BreakpadSymbolSource {
+    symbolizer: Symbolizer,
+}
+
+impl BreakpadSymbolSource {
+    pub fn new(symbolizer: Symbolizer) -> Self {
+        BreakpadSymbolSource { symbolizer }
+    }
+}
+
+#[async_trait]
+impl SymbolSource for BreakpadSymbolSource {
+    async fn symbolize(
+        &self,
+        module: &(dyn Module + Sync),
+        address: u64,
+    ) -> Result<ResolvedSymbol, FillSymbolError> {
+        let mut symbol = ResolvedSymbol {
+            offset: address,
+            ..ResolvedSymbol::default()
+        };
+        self.symbolizer.fill_symbol(module, &mut symbol).await?;
+        // The Symbolizer works with absolute addresses.
+        let base = module.base_address();
+        symbol.offset = address.wrapping_sub(base);
+        symbol.function_base = symbol.function_base.map(|a| a.wrapping_sub(base));
+        symbol.source_line_base = symbol.source_line_base.map(|a| a.wrapping_sub(base));
+        Ok(symbol)
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        self.symbolizer.walk_frame(module, walker).await
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        self.symbolizer.get_file_path(module, file_kind).await
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        self.symbolizer.stats()
+    }
+
+    fn pending_stats(&self) -> PendingSymbolStats {
+        self.symbolizer.pending_stats()
+    }
+}
+
+/// The symbol information found for an address by [`symbolize_addresses`].
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedSymbol {
+    /// The module-relative address that was looked up.
+    pub offset: u64,
+    /// The name of the function containing the address.
+    pub function_name: Option<String>,
+    /// The module-relative address of the start of `function_name`.
+    pub function_base: Option<u64>,
+    /// The size of the parameters of `function_name` on the stack, if known.
+    pub parameter_size: Option<u32>,
+    /// Whether `function_name` came from a PUBLIC symbol because no FUNC covered
+    /// the address.
+    pub function_is_public: bool,
+    /// The source file containing the address.
+    pub source_file_name: Option<String>,
+    /// The (1-based) source line of the address.
+    pub source_line: Option<u32>,
+    /// The module-relative address of the start of `source_line`.
+    pub source_line_base: Option<u64>,
+    /// Functions inlined at the address, from the innermost callee outwards,
+    /// like [`StackFrame::inlines`][crate::StackFrame::inlines].
+    pub inlines: Vec<InlineFrame>,
+}
+
+impl FrameSymbolizer for ResolvedSymbol {
+    fn get_instruction(&self) -> u64 {
+        self.offset
+    }
+    fn set_function(&mut self, name: &str, base: u64, parameter_size: u32) {
+        self.function_name = Some(String::from(name));
+        self.function_base = Some(base);
+        self.parameter_size = Some(parameter_size);
+    }
+    fn set_source_file(&mut self, file: &str, line: u32, base: u64) {
+        self.source_file_name = Some(String::from(file));
+        self.source_line = Some(line);
+        self.source_line_base = Some(base);
+    }
+    fn add_inline_frame(&mut self, name: &str, file: Option<&str>, line: Option<u32>) {
+        self.inlines.push(InlineFrame {
+            function_name: name.to_string(),
+            source_file_name: file.map(ToString::to_string),
+            source_line: line,
+        });
+    }
+    fn set_function_is_public(&mut self) {
+        self.function_is_public = true;
+    }
+}
+
+impl ResolvedSymbol {
+    /// Fill `frame` with this symbol, for a module loaded at `base`.
+    fn fill_frame(&self, base: u64, frame: &mut (dyn FrameSymbolizer + Send)) {
+        let function_base = self.function_base.unwrap_or(self.offset);
+        if let Some(name) = &self.function_name {
+            frame.set_function(
+                name,
+                base.wrapping_add(function_base),
+                self.parameter_size.unwrap_or(0),
+            );
+        }
+        if self.function_is_public {
+            frame.set_function_is_public();
+        }
+        // `inlines` is in the order they were added, from outside to inside.
+        for inline in &self.inlines {
+            frame.add_inline_frame(
+                &inline.function_name,
+                inline.source_file_name.as_deref(),
+                inline.source_line,
+            );
+        }
+        if let (Some(file), Some(line)) = (&self.source_file_name, self.source_line) {
+            let line_base = self.source_line_base.unwrap_or(function_base);
+            frame.set_source_file(file, line, base.wrapping_add(line_base));
+        }
+    }
+}
+
+/// Look up the symbols of addresses in a module, without a minidump.
+///
+/// This is for when all that's known about a crash is the module's debug file and
+/// id and some module-relative addresses, such as from a log. The module is
+/// identified the same way as in symbol servers (e.g. `"xul.pdb"` and its debug
+/// id), and the addresses are looked up concurrently.
+///
+/// The results are in the same order as `offsets`. If `symbol_provider` has no
+/// symbols for the module, the results only have their `offset` filled in.
+pub async fn symbolize_addresses<P>(
+    debug_file: &str,
+    debug_id: DebugId,
+    offsets: &[u64],
+    symbol_provider: &P,
+) -> Vec<ResolvedSymbol>
+where
+    P: SymbolProvider + Sync,
+{
+    let module = (debug_file, debug_id);
+    futures_util::future::join_all(offsets.iter().map(|&offset| async move {
+        let mut symbol = ResolvedSymbol {
+            offset,
+            ..ResolvedSymbol::default()
+        };
+        // Missing symbols just leave the address unresolved.
+        let _ = symbol_provider.fill_symbol(&module, &mut symbol).await;
+        // Inlines are reported outermost first, see `symbolicate_frame`.
+        symbol.inlines.reverse();
+        symbol
+    }))
+    .await
+}
+
 /// Gets a SymbolSupplier that looks up symbols by path or with urls.
 ///
 /// * `symbols_paths` is a list of paths to check for symbol files. Paths
@@ -351,11 +835,31 @@ pub fn string_symbol_supplier(modules: HashMap<String, String>) -> impl SymbolSu
     breakpad_symbols::StringSymbolSupplier::new(modules)
 }
 
+#[derive(Default)]
+struct Lazy<T>(futures_util::lock::Mutex<Option<T>>);
+
+impl<T> Lazy<T> {
+    pub async fn get<F: FnOnce() -> T>(&self, if_missing: F) -> &T {
+        let mut guard = self.0.lock().await;
+        if guard.is_none() {
+            *guard = Some(if_missing());
+        }
+        debug_assert!(guard.is_some());
+        // # Safety
+        // The inner value is guaranteed to be set, and it will never be changed again so we
+        // may return a &T tied to the lifetime of &self.
+        unsafe {
+            (guard.as_ref().unwrap_unchecked() as *const T)
+                .as_ref()
+                .unwrap_unchecked()
+        }
+    }
+}
+
 pub mod debuginfo {
     use super::*;
     use breakpad_symbols::SymbolFile;
     use cachemap2::CacheMap;
-    use futures_util::lock::Mutex;
     use memmap2::Mmap;
     use std::collections::HashMap;
     use std::fs::File;
@@ -373,27 +877,6 @@ pub mod debuginfo {
         loaded: CacheMap<PathBuf, Lazy<Option<DebugInfo>>>,
     }
 
-    #[derive(Default)]
-    struct Lazy<T>(Mutex<Option<T>>);
-
-    impl<T> Lazy<T> {
-        pub async fn get<F: FnOnce() -> T>(&self, if_missing: F) -> &T {
-            let mut guard = self.0.lock().await;
-            if guard.is_none() {
-                *guard = Some(if_missing());
-            }
-            debug_assert!(guard.is_some());
-            // # Safety
-            // The inner value is guaranteed to be set, and it will never be changed again so we
-            // may return a &T tied to the lifetime of &self.
-            unsafe {
-                (guard.as_ref().unwrap_unchecked() as *const T)
-                    .as_ref()
-                    .unwrap_unchecked()
-            }
-        }
-    }
-
     struct DebugInfo {
         functions: Vec<Function>,
         unwind_symbol_file: Option<SymbolFile>,
@@ -438,7 +921,7 @@ pub mod debuginfo {
         pub fn function_by_address(&self, addr: u64) -> Option<&Function> {
             // Get the index of the first function that starts after our search address.
             let functions = &self.functions;
-            let index_after_location = functions.partition_point(|f| addr < f.address);
+            let index_after_location = functions.partition_point(|f| f.address <= addr);
             if index_after_location == 0 {
                 return None;
             }
@@ -509,6 +992,12 @@ pub mod debuginfo {
                 .await
                 .as_ref()
         }
+
+        /// Load the debug information of `module`, from where the minidump says it is.
+        async fn module_debug_info(&self, module: &(dyn Module + Sync)) -> Option<&DebugInfo> {
+            let dbg = module.debug_file()?;
+            self.debug_info(dbg.as_ref().into()).await
+        }
     }
 
     #[async_trait]
@@ -528,19 +1017,23 @@ pub mod debuginfo {
                 }
             }
 
-            let dbg = module.debug_file().ok_or(FillSymbolError {})?;
             let info = self
-                .debug_info(dbg.as_ref().into())
+                .module_debug_info(module)
                 .await
                 .ok_or(FillSymbolError {})?;
 
             // From this point on, we consider that symbols were found for the module, so we no
             // longer return FillSymbolError.
-            let function = info.function_by_address(frame.get_instruction());
+            // The debug info's addresses are relative to the module.
+            let base = module.base_address();
+            let function = frame
+                .get_instruction()
+                .checked_sub(base)
+                .and_then(|addr| info.function_by_address(addr));
 
             if let Some(function) = function {
                 // XXX parameter size
-                frame.set_function(function.name.as_ref(), function.address, 0);
+                frame.set_function(function.name.as_ref(), function.address + base, 0);
                 for inlinee in &function.inlinees {
                     let (file, line) = inlinee
                         .lines
@@ -553,7 +1046,7 @@ pub mod debuginfo {
                     frame.set_source_file(
                         line.file.as_ref(),
                         saturating_cast(line.line),
-                        line.address,
+                        line.address + base,
                     );
                 }
             }
@@ -566,8 +1059,7 @@ pub mod debuginfo {
             module: &(dyn Module + Sync),
             walker: &mut (dyn FrameWalker + Send),
         ) -> Option<()> {
-            let dbg = module.debug_file()?;
-            let info = self.debug_info(dbg.as_ref().into()).await?;
+            let info = self.module_debug_info(module).await?;
             info.unwind_symbol_file
                 .as_ref()
                 .and_then(|sym_file| sym_file.walk_frame(module, walker))
@@ -598,3 +1090,237 @@ pub mod debuginfo {
         }
     }
 }
+
+#[cfg(feature = "pdb")]
+pub use pdb_source::PdbSymbolSource;
+
+#[cfg(feature = "pdb")]
+mod pdb_source {
+    use super::*;
+    use cachemap2::CacheMap;
+    use pdb::{FallibleIterator, SymbolData, PDB};
+    use std::convert::TryFrom;
+    use std::fs::File;
+    use std::path::Path;
+
+    /// The paths that the debug file of `module` may be found at, in order of preference.
+    ///
+    /// The directories in `symbol_paths` are laid out like a symbol server, and the
+    /// path recorded in the minidump is tried last.
+    fn debug_file_paths(symbol_paths: &[PathBuf], module: &(dyn Module + Sync)) -> Vec<PathBuf> {
+        let debug_file = match module.debug_file() {
+            Some(debug_file) => debug_file,
+            None => return Vec::new(),
+        };
+        let leaf = debug_file.rsplit(['/', '\\']).next().unwrap_or_default();
+        let mut paths = Vec::new();
+        if let Some(debug_id) = module.debug_identifier() {
+            let debug_id = debug_id.breakpad().to_string();
+            paths.extend(
+                symbol_paths
+                    .iter()
+                    .map(|path| path.join(leaf).join(&debug_id).join(leaf)),
+            );
+        }
+        paths.push(PathBuf::from(debug_file.as_ref()));
+        paths
+    }
+
+    /// A [`SymbolSource`] that reads native Windows PDB files.
+    ///
+    /// This lets Windows modules be symbolicated without converting their PDBs to
+    /// breakpad .sym files with dump_syms first. A module's PDB is looked up at
+    /// `<path>/<debug file>/<debug id>/<debug file>` in each of the symbol paths,
+    /// like a symbol server, and then at the path recorded in the minidump.
+    ///
+    /// Only function names and source lines are read from the PDB, so this can't
+    /// be used for CFI unwinding.
+    #[derive(Default)]
+    pub struct PdbSymbolSource {
+        symbol_paths: Vec<PathBuf>,
+        /// If a file fails to load for any reason, None is stored.
+        loaded: CacheMap<PathBuf, Lazy<Option<PdbSymbols>>>,
+    }
+
+    struct PdbLine {
+        rva: u32,
+        file: String,
+        line: u32,
+    }
+
+    struct PdbFunction {
+        rva: u32,
+        len: u32,
+        name: String,
+        /// Sorted by `rva`.
+        lines: Vec<PdbLine>,
+    }
+
+    struct PdbSymbols {
+        /// Procedures, sorted by `rva`.
+        functions: Vec<PdbFunction>,
+        /// Public function symbols as (rva, name), sorted by rva.
+        publics: Vec<(u32, String)>,
+    }
+
+    impl PdbSymbols {
+        fn load(path: &Path) -> pdb::Result<Self> {
+            let mut pdb = PDB::open(File::open(path)?)?;
+            let address_map = pdb.address_map()?;
+            let string_table = pdb.string_table()?;
+
+            let mut publics = Vec::new();
+            let globals = pdb.global_symbols()?;
+            let mut symbols = globals.iter();
+            while let Some(symbol) = symbols.next()? {
+                if let Ok(SymbolData::Public(public)) = symbol.parse() {
+                    if let (true, Some(rva)) = (public.function, public.offset.to_rva(&address_map))
+                    {
+                        publics.push((rva.0, public.name.to_string().into_owned()));
+                    }
+                }
+            }
+            publics.sort_unstable_by_key(|&(rva, _)| rva);
+
+            let mut functions = Vec::new();
+            let debug_info = pdb.debug_information()?;
+            let mut modules = debug_info.modules()?;
+            while let Some(module) = modules.next()? {
+                let info = match pdb.module_info(&module)? {
+                    Some(info) => info,
+                    None => continue,
+                };
+                let program = info.line_program()?;
+                let mut symbols = info.symbols()?;
+                while let Some(symbol) = symbols.next()? {
+                    let procedure = match symbol.parse() {
+                        Ok(SymbolData::Procedure(procedure)) => procedure,
+                        _ => continue,
+                    };
+                    let rva = match procedure.offset.to_rva(&address_map) {
+                        Some(rva) => rva.0,
+                        None => continue,
+                    };
+                    let mut lines = Vec::new();
+                    let mut line_infos = program.lines_for_symbol(procedure.offset);
+                    while let Some(line_info) = line_infos.next()? {
+                        if let Some(line_rva) = line_info.offset.to_rva(&address_map) {
+                            let file = program
+                                .get_file_info(line_info.file_index)?
+                                .name
+                                .to_string_lossy(&string_table)?
+                                .into_owned();
+                            lines.push(PdbLine {
+                                rva: line_rva.0,
+                                file,
+                                line: line_info.line_start,
+                            });
+                        }
+                    }
+                    lines.sort_unstable_by_key(|line| line.rva);
+                    functions.push(PdbFunction {
+                        rva,
+                        len: procedure.len,
+                        name: procedure.name.to_string().into_owned(),
+                        lines,
+                    });
+                }
+            }
+            functions.sort_unstable_by_key(|function| function.rva);
+
+            Ok(PdbSymbols { functions, publics })
+        }
+
+        /// Fill in the function and source line that contain `rva`.
+        fn resolve(&self, rva: u32, symbol: &mut ResolvedSymbol) {
+            let index = self.functions.partition_point(|f| f.rva <= rva);
+            if let Some(function) = index.checked_sub(1).map(|i| &self.functions[i]) {
+                if rva - function.rva < function.len {
+                    symbol.function_name = Some(function.name.clone());
+                    symbol.function_base = Some(function.rva as u64);
+                    let index = function.lines.partition_point(|l| l.rva <= rva);
+                    if let Some(line) = index.checked_sub(1).map(|i| &function.lines[i]) {
+                        symbol.source_file_name = Some(line.file.clone());
+                        symbol.source_line = Some(line.line);
+                        symbol.source_line_base = Some(line.rva as u64);
+                    }
+                    return;
+                }
+            }
+
+            // Like breakpad's PUBLIC records, public symbols have no size, so this is
+            // just the closest one before the address.
+            let index = self.publics.partition_point(|&(p, _)| p <= rva);
+            if let Some((base, name)) = index.checked_sub(1).map(|i| &self.publics[i]) {
+                symbol.function_name = Some(name.clone());
+                symbol.function_base = Some(*base as u64);
+                symbol.function_is_public = true;
+            }
+        }
+    }
+
+    impl PdbSymbolSource {
+        /// Create a source that looks for PDBs in `symbol_paths`.
+        pub fn new(symbol_paths: Vec<PathBuf>) -> Self {
+            PdbSymbolSource {
+                symbol_paths,
+                ..Default::default()
+            }
+        }
+
+        /// Find and load the PDB of `module`.
+        async fn module_symbols(&self, module: &(dyn Module + Sync)) -> Option<&PdbSymbols> {
+            for path in debug_file_paths(&self.symbol_paths, module) {
+                if !path.is_file() {
+                    continue;
+                }
+                let symbols = self
+                    .loaded
+                    .cache_default(path.clone())
+                    .get(|| PdbSymbols::load(&path).ok())
+                    .await;
+                if let Some(symbols) = symbols {
+                    return Some(symbols);
+                }
+            }
+            None
+        }
+    }
+
+    #[async_trait]
+    impl SymbolSource for PdbSymbolSource {
+        async fn symbolize(
+            &self,
+            module: &(dyn Module + Sync),
+            address: u64,
+        ) -> Result<ResolvedSymbol, FillSymbolError> {
+            let symbols = self
+                .module_symbols(module)
+                .await
+                .ok_or(FillSymbolError {})?;
+            let offset = address.wrapping_sub(module.base_address());
+            let mut symbol = ResolvedSymbol {
+                offset,
+                ..ResolvedSymbol::default()
+            };
+            if let Ok(rva) = u32::try_from(offset) {
+                symbols.resolve(rva, &mut symbol);
+            }
+            Ok(symbol)
+        }
+
+        async fn get_file_path(
+            &self,
+            module: &(dyn Module + Sync),
+            file_kind: FileKind,
+        ) -> Result<PathBuf, FileError> {
+            match file_kind {
+                FileKind::ExtraDebugInfo => debug_file_paths(&self.symbol_paths, module)
+                    .into_iter()
+                    .find(|path| path.is_file())
+                    .ok_or(FileError::NotFound),
+                _ => Err(FileError::NotFound),
+            }
+        }
+    }
+}
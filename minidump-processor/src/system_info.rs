@@ -29,6 +29,32 @@ pub struct SystemInfo {
     ///
     /// Will be greater than one for multi-core systems.
     pub cpu_count: usize,
+    /// A string identifying the flavor of the operating system.
+    ///
+    /// On Linux this is the distribution, for example "Ubuntu 22.04.3 LTS", taken
+    /// from the `LinuxLsbRelease` stream, if present.
+    pub os_flavor: Option<String>,
+    /// A single human-readable label for the operating system, for example
+    /// "Windows 10.0.19041.1 (WinBuild.160101.0800)" or "Ubuntu 22.04.3 LTS".
+    ///
+    /// The first available of these is used:
+    ///
+    /// * Windows: "Windows" and the build string from `MINIDUMP_MISC_INFO_4`.
+    /// * Linux: the distribution ([`SystemInfo::os_flavor`]).
+    /// * Everything else, and the above if they're missing: the OS and its version
+    ///   from the system info stream, see `MinidumpSystemInfo::os_version_string`.
+    pub os_pretty_name: String,
+}
+
+/// Replacement values for the platform a `Minidump` should be interpreted as.
+///
+/// See [`ProcessorOptions::override_system_info`][crate::ProcessorOptions::override_system_info].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemInfoOverride {
+    /// The operating system to interpret the minidump as
+    pub os: Os,
+    /// The CPU to interpret the minidump as
+    pub cpu: Cpu,
 }
 
 impl SystemInfo {
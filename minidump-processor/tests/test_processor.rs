@@ -3,11 +3,19 @@
 
 use minidump::system_info::{Cpu, Os};
 use minidump::{
-    Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpRawContext, Module,
+    Error, Minidump, MinidumpContext, MinidumpContextValidity, MinidumpModule, MinidumpRawContext,
+    Module,
 };
 use minidump_processor::{
-    simple_symbol_supplier, CallStackInfo, FrameTrust, LinuxStandardBase, ProcessState, Symbolizer,
+    diff_json, diff_process_states, simple_symbol_supplier, string_symbol_supplier,
+    symbolicate_frame, symbolicate_frames, symbolize_addresses, CallStackInfo, CrashClassification,
+    CustomStreamRegistry, FallbackResolver, FileError, FileKind, FillSymbolError, FrameField,
+    FrameName, FrameSymbolizer, FrameTrust, FrameWalker, InlineFrame, LinuxStandardBase,
+    ModuleFilter, ProcessState, ProcessorOptions, StackFrame, StackWalkEnd, StateDifference,
+    SymbolProvider, SymbolStats, SymbolicationStats, Symbolizer, SystemInfoOverride, UnwindHint,
+    UnwindHints, UnwindMethodCounts,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use minidump_synth::*;
@@ -133,6 +141,75 @@ async fn test_processor_symbols() {
     );
 }
 
+#[tokio::test]
+async fn test_diff_process_states() {
+    let dump = read_test_minidump().unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![testdata_symbol_path()])),
+    )
+    .await
+    .unwrap();
+    assert_eq!(diff_process_states(&state, &state), vec![]);
+
+    let mut modified = state.clone();
+    modified.threads[0].frames[1].function_name = Some(String::from("SomethingElse"));
+    modified.threads[0].frames.pop();
+    assert_eq!(
+        diff_process_states(&state, &modified),
+        vec![
+            StateDifference::FrameCount { left: 4, right: 3 },
+            StateDifference::Frame {
+                index: 1,
+                field: FrameField::Function,
+                left: state.threads[0].frames[1].function_name.clone(),
+                right: Some(String::from("SomethingElse")),
+            },
+        ]
+    );
+    modified.requesting_thread = Some(1);
+    let differences = diff_process_states(&state, &modified);
+    assert_eq!(
+        differences[0],
+        StateDifference::CrashingThread {
+            left: Some(0),
+            right: Some(1)
+        }
+    );
+
+    // The JSON output compares the same, regardless of how addresses are written.
+    let json: serde_json::Value = serde_json::from_str(&state.into_json().unwrap()).unwrap();
+    assert_eq!(diff_json(&json, &json), vec![]);
+    let mut reformatted = json.clone();
+    for frame in reformatted["crashing_thread"]["frames"]
+        .as_array_mut()
+        .unwrap()
+    {
+        let offset = frame["module_offset"].as_str().unwrap();
+        let offset = u64::from_str_radix(offset.trim_start_matches("0x"), 16).unwrap();
+        frame["module_offset"] = serde_json::json!(format!("0X{offset:016X}"));
+    }
+    assert_eq!(diff_json(&json, &reformatted), vec![]);
+
+    reformatted["crashing_thread"]["frames"][0]["module_offset"] = serde_json::json!(0x1234);
+    reformatted["crash_info"]["type"] = serde_json::json!("SIGSEGV");
+    let differences = diff_json(&json, &reformatted);
+    assert_eq!(differences.len(), 2);
+    assert_eq!(
+        differences[0],
+        StateDifference::CrashReason {
+            left: Some(String::from("EXCEPTION_ACCESS_VIOLATION_WRITE")),
+            right: Some(String::from("SIGSEGV")),
+        }
+    );
+    let f0 = &state.threads[0].frames[0];
+    let module_offset = f0.instruction - f0.module.as_ref().unwrap().raw.base_of_image;
+    assert_eq!(
+        differences[1].to_string(),
+        format!("frame 0 offset: {module_offset:#x} != 0x1234")
+    );
+}
+
 fn minimal_minidump() -> SynthMinidump {
     let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
     let stack = Memory::with_section(
@@ -155,6 +232,49 @@ async fn read_synth_dump(dump: SynthMinidump) -> ProcessState {
         .unwrap()
 }
 
+/// Like `read_synth_dump`, but with the given breakpad symbols for the module
+/// named `module`.
+async fn read_synth_dump_with_symbols(
+    dump: SynthMinidump,
+    module: &str,
+    symbols: &[&str],
+) -> ProcessState {
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(string_symbol_supplier(module_symbols(module, symbols)));
+    minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap()
+}
+
+/// Breakpad symbols for a single module, for `string_symbol_supplier`.
+fn module_symbols(module: &str, symbols: &[&str]) -> HashMap<String, String> {
+    std::iter::once((String::from(module), symbols.concat())).collect()
+}
+
+/// Adds a 0x10000 byte module called `name` without any debug info to `dump`.
+fn with_module(dump: SynthMinidump, name: &str, base: u64) -> SynthMinidump {
+    let name = DumpString::new(name, Endian::Little);
+    let module = minidump_synth::Module::new(Endian::Little, base, 0x10000, &name, 0, 0, None);
+    dump.add_module(module).add(name)
+}
+
+fn amd64_system_info() -> SystemInfo {
+    SystemInfo::new(Endian::Little).set_processor_architecture(
+        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
+    )
+}
+
+fn amd64_linux_system_info() -> SystemInfo {
+    amd64_system_info().set_platform_id(minidump_common::format::PlatformId::Linux as u32)
+}
+
+/// An exception on the thread `thread_id` that crashed in `context`.
+fn exception_in(thread_id: u32, context: &Section) -> Exception {
+    let mut exception = Exception::new(Endian::Little);
+    exception.thread_id = thread_id;
+    exception.context(context)
+}
+
 #[tokio::test]
 async fn test_linux_cpu_info() {
     // Whitespace intentionally wonky to test robustness
@@ -193,6 +313,10 @@ DISTRIB_DESCRIPTION= wow long string!!!
         assert_eq!(release, "there");
         assert_eq!(codename, "very long string");
         assert_eq!(description, "wow long string!!!");
+        assert_eq!(
+            state.system_info.os_flavor.as_deref(),
+            Some("wow long string!!!")
+        );
     }
 
     {
@@ -217,17 +341,56 @@ PRETTY_NAME= wow long string!!!
         assert_eq!(codename, "very long string");
         assert_eq!(description, "wow long string!!!");
     }
+
+    // Without a description, the os flavor is built from the id and release
+    {
+        let input = b"DISTRIB_ID=Debian\nDISTRIB_RELEASE=12\n";
+        let dump = minimal_minidump().set_linux_lsb_release(input);
+        let state = read_synth_dump(dump).await;
+
+        assert_eq!(state.system_info.os_flavor.as_deref(), Some("Debian 12"));
+    }
+
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.system_info.os_flavor, None);
 }
 
 #[tokio::test]
 async fn test_linux_environ() {
-    // Whitespace intentionally wonky to test robustness
-
-    // TODO: add tests for values we care about
-    let input = b"";
+    // No trailing NUL, an entry without `=`, and a value containing `=`
+    let input = b"HOME=/home/user\0NOT_A_VAR\0OPTS=a=b c\0EMPTY=";
 
     let dump = minimal_minidump().set_linux_environ(input);
-    let _state = read_synth_dump(dump).await;
+    let state = read_synth_dump(dump).await;
+
+    let environment = state.environment.unwrap();
+    assert_eq!(
+        environment,
+        vec![
+            ("HOME".to_owned(), "/home/user".to_owned()),
+            ("OPTS".to_owned(), "a=b c".to_owned()),
+            ("EMPTY".to_owned(), "".to_owned()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_linux_cmd_line() {
+    let input = b"/usr/bin/firefox\0--new-window\0\0https://example.com/a b\0";
+
+    let dump = minimal_minidump().set_linux_cmd_line(input);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(
+        state.command_line.unwrap(),
+        vec![
+            "/usr/bin/firefox",
+            "--new-window",
+            "",
+            "https://example.com/a b"
+        ]
+    );
+    assert!(state.environment.is_none());
 }
 
 #[tokio::test]
@@ -241,6 +404,213 @@ async fn test_linux_proc_status() {
     let _state = read_synth_dump(dump).await;
 }
 
+#[tokio::test]
+async fn test_process_id() {
+    // MiscInfo is preferred over /proc/self/status
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.process_id = Some(0x1234abcd);
+    misc.process_times = Some(MiscFieldsProcessTimes {
+        process_create_time: 0x5f000000,
+        ..Default::default()
+    });
+    let dump = minimal_minidump()
+        .add_stream(misc)
+        .set_linux_proc_status(b"Tgid:\t4321\n");
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.process_id(), Some(0x1234abcd));
+    assert_eq!(
+        state.process_create_time(),
+        Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(0x5f000000))
+    );
+
+    // Without MiscInfo, the Tgid (not the Pid) of /proc/self/status is used
+    let dump =
+        minimal_minidump().set_linux_proc_status(b"Name:\tfirefox\nTgid:   4321\nPid:\t4325\n");
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.process_id(), Some(4321));
+    assert_eq!(state.process_create_time(), None);
+
+    // Neither source is present
+    let state = read_synth_dump(minimal_minidump()).await;
+
+    assert_eq!(state.process_id(), None);
+    assert_eq!(state.process_create_time(), None);
+}
+
+#[tokio::test]
+async fn test_cpu_count_and_speed() {
+    let mut system_info = SystemInfo::new(Endian::Little);
+    system_info.number_of_processors = 8;
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.power_info = Some(MiscFieldsPowerInfo {
+        processor_max_mhz: 3200,
+        processor_current_mhz: 2100,
+        ..Default::default()
+    });
+    let dump = minimal_minidump()
+        .add_system_info(system_info)
+        .add_stream(misc);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.cpu_count(), Some(8));
+    assert_eq!(state.cpu_mhz(), Some(3200));
+
+    let json: serde_json::Value = serde_json::from_str(&state.into_json().unwrap()).unwrap();
+    assert_eq!(json["system_info"]["cpu_count"], 8);
+    assert_eq!(json["system_info"]["cpu_mhz"], 3200);
+
+    // The power info is only in MiscInfo2 and later.
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.process_id = Some(0x1234);
+    let dump = minimal_minidump().add_stream(misc);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.cpu_count(), Some(1));
+    assert_eq!(state.cpu_mhz(), None);
+
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.cpu_mhz(), None);
+    let json: serde_json::Value = serde_json::from_str(&state.into_json().unwrap()).unwrap();
+    assert_eq!(json["system_info"]["cpu_mhz"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_os_pretty_name() {
+    use minidump_common::format::PlatformId;
+
+    let os_dump = |platform_id: PlatformId, version: (u32, u32, u32), csd: Option<&str>| {
+        let mut system_info = SystemInfo::new(Endian::Little)
+            .set_platform_id(platform_id as u32)
+            .set_version(version.0, version.1, version.2);
+        let mut dump = minimal_minidump();
+        if let Some(csd) = csd {
+            let csd = DumpString::new(csd, Endian::Little);
+            system_info = system_info.set_csd_version(&csd);
+            dump = dump.add(csd);
+        }
+        dump.add_system_info(system_info)
+    };
+
+    // Windows prefers the MiscInfo4 build string
+    let mut build_string = [0; 260];
+    for (dst, src) in build_string
+        .iter_mut()
+        .zip("10.0.19041.1 (WinBuild.160101.0800)".encode_utf16())
+    {
+        *dst = src;
+    }
+    let mut misc = MiscStream::new(Endian::Little);
+    misc.build_strings = Some(MiscFieldsBuildString {
+        build_string,
+        dbg_bld_str: [0; 40],
+    });
+    let dump = os_dump(PlatformId::VER_PLATFORM_WIN32_NT, (10, 0, 19041), None).add_stream(misc);
+    let state = read_synth_dump(dump).await;
+    assert_eq!(
+        state.os_pretty_name(),
+        "Windows 10.0.19041.1 (WinBuild.160101.0800)"
+    );
+
+    // ...else the version from the system info
+    let dump = os_dump(
+        PlatformId::VER_PLATFORM_WIN32_NT,
+        (6, 1, 7601),
+        Some("Service Pack 1"),
+    );
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.os_pretty_name(), "Windows 6.1.7601 Service Pack 1");
+
+    // Linux prefers the distro
+    let dump = os_dump(
+        PlatformId::Linux,
+        (0, 0, 0),
+        Some("Linux 5.15.0-86-generic #96-Ubuntu SMP x86_64"),
+    )
+    .set_linux_lsb_release(b"DISTRIB_ID=Ubuntu\nDISTRIB_DESCRIPTION=\"Ubuntu 22.04.3 LTS\"\n");
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.os_pretty_name(), "Ubuntu 22.04.3 LTS");
+
+    // ...else the kernel version
+    let dump = os_dump(
+        PlatformId::Linux,
+        (0, 0, 0),
+        Some("Linux 5.15.0-86-generic #96-Ubuntu SMP x86_64"),
+    );
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.os_pretty_name(), "Linux 5.15.0-86-generic");
+
+    // macOS is the version and build
+    let dump = os_dump(PlatformId::MacOs, (13, 4, 1), Some("22F82"));
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.os_pretty_name(), "Mac OS X 13.4.1 (22F82)");
+}
+
+#[tokio::test]
+async fn test_crash_signature() {
+    let mut state = read_synth_dump(minimal_minidump()).await;
+    let context = state.threads[0].frames[0].context.clone();
+    let frame = |trust, instruction, function: Option<&str>| {
+        let mut frame = StackFrame::from_context(context.clone(), trust);
+        frame.instruction = instruction;
+        frame.function_name = function.map(String::from);
+        frame
+    };
+
+    let mut top = frame(
+        FrameTrust::Context,
+        0x1000,
+        Some("std::vector<int, std::allocator<int> >::at(unsigned long)"),
+    );
+    top.inlines.push(InlineFrame {
+        function_name: String::from("mozilla::Maybe<bool>::value()"),
+        source_file_name: None,
+        source_line: None,
+    });
+    let mut in_module = frame(FrameTrust::CallFrameInfo, 0x10001234, None);
+    in_module.module = Some(MinidumpModule::new(0x10000000, 0x10000, "C:\\foo\\bar.dll"));
+    state.threads[0].frames = vec![
+        top,
+        frame(FrameTrust::Scan, 0x2000, Some("junk")),
+        in_module,
+        frame(FrameTrust::CfiScan, 0x2100, Some("more_junk")),
+        frame(FrameTrust::FramePointer, 0xdeadbeef, None),
+        frame(
+            FrameTrust::CallFrameInfo,
+            0x3000,
+            Some("Foo::operator<(Foo<int> const&)"),
+        ),
+        frame(FrameTrust::CallFrameInfo, 0x4000, Some("main")),
+    ];
+    state.requesting_thread = Some(0);
+
+    assert_eq!(
+        state.crash_signature(10, false).unwrap(),
+        "mozilla::Maybe<bool>::value() \
+         | std::vector<int, std::allocator<int> >::at(unsigned long) \
+         | bar.dll@0x1234 \
+         | @0xdeadbeef \
+         | Foo::operator<(Foo<int> const&) \
+         | main"
+    );
+    assert_eq!(
+        state.crash_signature(5, true).unwrap(),
+        "mozilla::Maybe<T>::value() \
+         | std::vector<T>::at(unsigned long) \
+         | bar.dll@0x1234 \
+         | @0xdeadbeef \
+         | Foo::operator<(Foo<T> const&)"
+    );
+    assert_eq!(
+        state.crash_signature(2, true).unwrap(),
+        "mozilla::Maybe<T>::value() | std::vector<T>::at(unsigned long)"
+    );
+
+    state.requesting_thread = None;
+    assert_eq!(state.crash_signature(10, false), None);
+}
+
 #[tokio::test]
 async fn test_no_frames() {
     let context = minidump_synth::x86_context(Endian::Little, 0, 0);
@@ -269,6 +639,87 @@ async fn test_no_frames() {
     state.print_json(&mut std::io::sink(), true).unwrap();
 }
 
+#[tokio::test]
+async fn test_json_versions() {
+    let state = read_synth_dump(minimal_minidump()).await;
+
+    let compact = state.into_json().unwrap();
+    let pretty = state.into_json_pretty().unwrap();
+    assert!(!compact.contains('\n'));
+    assert!(pretty.contains('\n'));
+
+    let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+    let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(compact, pretty);
+
+    assert_eq!(
+        compact["processor_version"],
+        env!("CARGO_PKG_VERSION"),
+        "processor_version should be this crate's version"
+    );
+    assert_eq!(
+        compact["schema_version"],
+        minidump_processor::JSON_SCHEMA_VERSION
+    );
+}
+
+#[tokio::test]
+async fn test_json_frame_limit() {
+    // A deeply recursive stack of 20 frames.
+    let callers = vec![(0x40002010, 0x10); 19];
+    let (stack, context) =
+        minidump_synth::x86_frame_pointer_stack(Endian::Little, 0x40001000, 0x80000, &callers);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x1234;
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x40000000);
+    let state = read_synth_dump(dump).await;
+    // The full walk still happens.
+    assert_eq!(state.threads[0].frames.len(), 20);
+
+    let json_with_limit = |limit| {
+        let mut output = Vec::new();
+        state
+            .print_json_with_frame_limit(&mut output, false, limit)
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+
+    let json = json_with_limit(Some(5));
+    for thread in [&json["threads"][0], &json["crashing_thread"]] {
+        assert_eq!(thread["frame_count"], 20);
+        assert_eq!(thread["truncated"], true);
+        let frames = thread["frames"].as_array().unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0]["offset"], "0x40001000");
+        assert_eq!(frames[4]["frame"], 4);
+    }
+    assert!(json["crashing_thread"]["frames"][0]["registers"].is_object());
+
+    // Limits the stack already fits in don't truncate anything.
+    for limit in [None, Some(20)] {
+        let json = json_with_limit(limit);
+        let thread = &json["threads"][0];
+        assert_eq!(thread["frame_count"], 20);
+        assert!(thread["truncated"].is_null());
+        assert_eq!(thread["frames"].as_array().unwrap().len(), 20);
+    }
+
+    // Even a limit of zero frames produces valid output.
+    let json = json_with_limit(Some(0));
+    assert_eq!(json["crashing_thread"]["truncated"], true);
+    assert!(json["crashing_thread"]["frames"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
 #[tokio::test]
 async fn test_bit_flip() {
     let context = minidump_synth::amd64_context(Endian::Little, 0, 0);
@@ -277,10 +728,6 @@ async fn test_bit_flip() {
     let heap_info = MemoryInfo::new(Endian::Little, 0x80000, 0x80000, 0, 8, 0, 0, 0);
 
     let thread = Thread::new(Endian::Little, 1, &stack, &context);
-    let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
-        minidump_common::format::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16,
-    );
-
     let mut ex = Exception::new(Endian::Little);
     ex.thread_id = 1;
     ex.exception_record.exception_address = 0x80400;
@@ -288,7 +735,7 @@ async fn test_bit_flip() {
     let dump = SynthMinidump::with_endian(Endian::Little)
         .add_thread(thread)
         .add_exception(ex)
-        .add_system_info(system_info)
+        .add_system_info(amd64_system_info())
         .add(context)
         .add_memory(stack)
         .add_memory_info(heap_info);
@@ -336,3 +783,2376 @@ async fn test_no_bit_flip_32bit() {
         .possible_bit_flips
         .is_empty());
 }
+
+#[tokio::test]
+async fn test_exception_on_alt_stack() {
+    // A signal handler running on an alt-stack: the exception context points into the
+    // alt-stack, while the thread's stack holds the frames that were interrupted.
+    let thread_stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .append_repeated(0, 0x100)
+            // return address into `start`
+            .D64(0x70003010)
+            // end of stack
+            .D64(0)
+            .append_repeated(0, 0xf0),
+        0x80000,
+    );
+    let alt_stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D64(0)
+            // return address into `main_loop`
+            .D64(0x70002010)
+            // the interrupted stack pointer
+            .D64(0x80100)
+            .append_repeated(0, 0xe8),
+        0x40000,
+    );
+
+    let thread_context = minidump_synth::amd64_context(Endian::Little, 0x70002005, 0x80100);
+    let exception_context = minidump_synth::amd64_context(Endian::Little, 0x70001010, 0x40000);
+    let thread = Thread::new(Endian::Little, 1, &thread_stack, &thread_context);
+    let ex = exception_in(1, &exception_context);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(amd64_linux_system_info())
+        .add(thread_context)
+        .add(exception_context)
+        .add_memory(thread_stack)
+        .add_memory(alt_stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+
+    let symbols = [
+        "FUNC 1000 100 0 handler\n",
+        // The signal frame: the interrupted state is saved on the alt-stack.
+        "STACK CFI INIT 1000 100 .cfa: $rsp 16 + ^ .ra: $rsp 8 + ^\n",
+        "FUNC 2000 100 0 main_loop\n",
+        "STACK CFI INIT 2000 100 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+        "FUNC 3000 100 0 start\n",
+        "STACK CFI INIT 3000 100 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+    ];
+    let state = read_synth_dump_with_symbols(dump, "module1", &symbols).await;
+
+    assert_eq!(state.requesting_thread, Some(0));
+    let frames = &state.threads[0].frames;
+    let function_names = frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        function_names,
+        [Some("handler"), Some("main_loop"), Some("start")]
+    );
+    assert_eq!(frames[0].context.get_stack_pointer(), 0x40000);
+    assert_eq!(frames[1].context.get_stack_pointer(), 0x80100);
+    assert_eq!(frames[2].context.get_stack_pointer(), 0x80108);
+}
+
+#[tokio::test]
+async fn test_linux_exception_context_differs_from_thread() {
+    // Breakpad on Linux records the signal handler's context in the thread list, while
+    // the exception stream holds the context that actually crashed.
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x200),
+        0x80000,
+    );
+
+    let thread_context = minidump_synth::amd64_context(Endian::Little, 0x70002010, 0x80040);
+    let exception_context = minidump_synth::amd64_context(Endian::Little, 0x70001010, 0x80100);
+    let thread = Thread::new(Endian::Little, 1, &stack, &thread_context);
+    let ex = exception_in(1, &exception_context);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(amd64_linux_system_info())
+        .add(thread_context)
+        .add(exception_context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+
+    let symbols = [
+        "FUNC 1000 100 0 crash\n",
+        "FUNC 2000 100 0 signal_handler\n",
+    ];
+    let state = read_synth_dump_with_symbols(dump, "module1", &symbols).await;
+
+    assert_eq!(state.requesting_thread, Some(0));
+    let thread = &state.threads[0];
+    assert_eq!(thread.frames[0].instruction, 0x70001010);
+    assert_eq!(thread.frames[0].function_name.as_deref(), Some("crash"));
+    assert_eq!(thread.frames[0].context.get_stack_pointer(), 0x80100);
+    assert_eq!(
+        state
+            .exception_info
+            .as_ref()
+            .unwrap()
+            .crash_address_in_module,
+        Some(true)
+    );
+    assert_eq!(thread.stack_size, Some(0x200));
+    assert_eq!(thread.stack_used, Some(0x100));
+}
+
+#[tokio::test]
+async fn test_crash_address_not_in_module() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x200),
+        0x80000,
+    );
+
+    // The crash address is just past the end of the only module.
+    let context = minidump_synth::amd64_context(Endian::Little, 0x70010100, 0x80100);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let ex = exception_in(1, &context);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(amd64_linux_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+    let state = read_synth_dump(dump).await;
+
+    let info = state.exception_info.unwrap();
+    assert_eq!(info.crash_address_in_module, Some(false));
+    let nearest = info.nearest_module.unwrap();
+    assert_eq!(nearest.name, "module1");
+    assert_eq!(nearest.base_address, 0x70000000u64.into());
+    assert_eq!(nearest.gap, 0x100);
+
+    // Without the crashing thread, there's no telling where the crash was.
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x9999;
+    let dump = with_module(minimal_minidump().add_exception(ex), "module1", 0x70000000);
+    let state = read_synth_dump(dump).await;
+    let info = state.exception_info.unwrap();
+    assert_eq!(info.crash_address_in_module, None);
+    assert!(info.nearest_module.is_none());
+}
+
+#[tokio::test]
+async fn test_big_endian() {
+    let endian = Endian::Big;
+    let module_name = DumpString::new("module1", endian);
+    let module = minidump_synth::Module::new(endian, 0x40000000, 0x10000, &module_name, 0, 0, None);
+
+    // A frame pointer chain: ebp -> (saved ebp, return address).
+    let stack = Memory::with_section(
+        Section::with_endian(endian)
+            .append_repeated(0, 0x10)
+            .D32(0x80100) // saved ebp
+            .D32(0x40002010) // return address
+            .append_repeated(0, 0xf8)
+            .D32(0)
+            .D32(0)
+            .append_repeated(0, 0xf8),
+        0x80000,
+    );
+    let context =
+        minidump_synth::x86_context_with_frame_pointer(endian, 0x40001000, 0x80000, 0x80010);
+    let thread = Thread::new(endian, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(endian)
+        .set_platform_id(minidump_common::format::PlatformId::Linux as u32)
+        .set_version(0, 0, 0);
+
+    let mut ex = Exception::new(endian);
+    ex.thread_id = 0x1234;
+    ex.exception_record.exception_code = 11; // SIGSEGV
+    ex.exception_record.exception_address = 0xdeadbeef;
+    let ex = ex.context(&context);
+
+    let dump = SynthMinidump::with_endian(endian)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add_module(module)
+        .add(module_name)
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.system_info.os, Os::Linux);
+    assert_eq!(state.system_info.cpu, Cpu::X86);
+    assert_eq!(state.modules.iter().count(), 1);
+    let module = state.modules.iter().next().unwrap();
+    assert_eq!(module.code_file(), "module1");
+    assert_eq!(module.base_address(), 0x40000000);
+
+    let exception = state.exception_info.as_ref().unwrap();
+    assert_eq!(exception.reason.to_string(), "SIGSEGV");
+    assert_eq!(exception.address, 0xdeadbeef.into());
+
+    assert_eq!(state.requesting_thread, Some(0));
+    let thread = &state.threads[0];
+    assert_eq!(thread.thread_id, 0x1234);
+    assert_eq!(thread.frames.len(), 2);
+    assert_eq!(thread.frames[0].instruction, 0x40001000);
+    assert_eq!(thread.frames[0].trust, FrameTrust::Context);
+    assert_eq!(thread.frames[1].resume_address, 0x40002010);
+    assert_eq!(thread.frames[1].trust, FrameTrust::FramePointer);
+    assert_eq!(
+        thread.frames[1].module.as_ref().unwrap().code_file(),
+        "module1"
+    );
+}
+
+#[tokio::test]
+async fn test_x86_frame_pointer_stack() {
+    let (stack, context) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001000,
+        0x80000,
+        &[(0x40002010, 0x20), (0x40003020, 0x8), (0x40004030, 0x100)],
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x40000000);
+    let state = read_synth_dump(dump).await;
+
+    let frames = &state.threads[0].frames;
+    let instructions: Vec<u64> = frames.iter().map(|frame| frame.resume_address).collect();
+    assert_eq!(
+        instructions,
+        [0x40001000, 0x40002010, 0x40003020, 0x40004030]
+    );
+    assert!(frames[1..]
+        .iter()
+        .all(|frame| frame.trust == FrameTrust::FramePointer));
+    let stack_pointers: Vec<u64> = frames
+        .iter()
+        .map(|frame| frame.context.get_stack_pointer())
+        .collect();
+    assert_eq!(stack_pointers, [0x80000, 0x80020, 0x80028, 0x80128]);
+}
+
+#[tokio::test]
+async fn test_truncated_context() {
+    let (stack, context) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001000,
+        0x80000,
+        &[(0x40002010, 0x20), (0x40003020, 0x20)],
+    );
+    // Cut the context off just after esp, dropping ss and the extended registers.
+    let context = context.get_contents().unwrap();
+    let context = Section::with_endian(Endian::Little).append_bytes(&context[..200]);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x40000000);
+    let state = read_synth_dump(dump).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[0].context.get_register("eip"), Some(0x40001000));
+    assert_eq!(frames[0].context.get_register("esp"), Some(0x80000));
+    let instructions: Vec<u64> = frames.iter().map(|frame| frame.resume_address).collect();
+    assert_eq!(instructions, [0x40001000, 0x40002010, 0x40003020]);
+    assert_eq!(frames[1].trust, FrameTrust::FramePointer);
+}
+
+#[tokio::test]
+async fn test_amd64_frame_pointer_stack() {
+    let (stack, context) = minidump_synth::amd64_frame_pointer_stack(
+        Endian::Little,
+        0x7400c0001000,
+        0x80000,
+        &[
+            (0x7400c0002010, 0x30),
+            (0x7400c0003020, 0x10),
+            (0x7400c0004030, 0x100),
+        ],
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    // Windows doesn't allow frame pointer unwinding on amd64.
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(amd64_linux_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x7400c0000000);
+    let state = read_synth_dump(dump).await;
+
+    let frames = &state.threads[0].frames;
+    let instructions: Vec<u64> = frames.iter().map(|frame| frame.resume_address).collect();
+    assert_eq!(
+        instructions,
+        [
+            0x7400c0001000,
+            0x7400c0002010,
+            0x7400c0003020,
+            0x7400c0004030
+        ]
+    );
+    assert!(frames[1..]
+        .iter()
+        .all(|frame| frame.trust == FrameTrust::FramePointer));
+    let stack_pointers: Vec<u64> = frames
+        .iter()
+        .map(|frame| frame.context.get_stack_pointer())
+        .collect();
+    assert_eq!(stack_pointers, [0x80000, 0x80030, 0x80040, 0x80140]);
+}
+
+#[tokio::test]
+async fn test_crash_instruction_bytes() {
+    // mov rax, [rbx]; nop; ret
+    let code_bytes = [0x48, 0x8b, 0x03, 0x90, 0xc3];
+    let code = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(&code_bytes),
+        0x7400c0001000,
+    );
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, 0x7400c0001000, 0x80000);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let mut exception = exception_in(0x1234, &context);
+    exception.exception_record.exception_code = 0xc0000005; // EXCEPTION_ACCESS_VIOLATION
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(exception)
+        .add_system_info(amd64_system_info())
+        .add(context)
+        .add_memory(stack)
+        .add_memory(code);
+    let state = read_synth_dump(dump).await;
+
+    let exception_info = state.exception_info.unwrap();
+    assert_eq!(
+        exception_info.instruction_bytes.as_deref(),
+        Some(&code_bytes[..])
+    );
+    if cfg!(feature = "disasm_amd64") {
+        assert_eq!(
+            exception_info.disassembly,
+            ["mov rax, qword [rbx]", "nop", "ret"]
+        );
+        assert_eq!(
+            exception_info.instruction_str.as_deref(),
+            Some("mov rax, qword [rbx]")
+        );
+    }
+}
+
+#[cfg(feature = "disasm_amd64")]
+#[tokio::test]
+async fn test_crash_analysis_uses_exception_context_stack() {
+    // The crash is a `ret` on a signal alt-stack, so the return address that was
+    // jumped to is on the stack the exception context points at, not the thread's.
+    let code = Memory::with_section(
+        Section::with_endian(Endian::Little).append_bytes(&[0xc3]),
+        0x7400c0001000,
+    );
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let alt_stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D64(0xdead00001000)
+            .append_repeated(0, 0xf8),
+        0x90000,
+    );
+    let thread_context = minidump_synth::amd64_context(Endian::Little, 0x7400c0002000, 0x80040);
+    let exception_context = minidump_synth::amd64_context(Endian::Little, 0x7400c0001000, 0x90000);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &thread_context);
+    let exception = exception_in(0x1234, &exception_context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(exception)
+        .add_system_info(amd64_linux_system_info())
+        .add(thread_context)
+        .add(exception_context)
+        .add_memory(stack)
+        .add_memory(alt_stack)
+        .add_memory(code);
+    let state = read_synth_dump(dump).await;
+
+    let exception_info = state.exception_info.unwrap();
+    assert_eq!(exception_info.instruction_str.as_deref(), Some("ret"));
+    let accessed: Vec<u64> = exception_info
+        .memory_accesses
+        .unwrap()
+        .iter()
+        .map(|access| access.address)
+        .collect();
+    assert_eq!(accessed, [0xdead00001000]);
+}
+
+#[tokio::test]
+async fn test_crash_instruction_bytes_missing() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x80000);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let exception = exception_in(0x1234, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(exception)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    let exception_info = state.exception_info.unwrap();
+    assert_eq!(exception_info.instruction_bytes, None);
+    assert!(exception_info.disassembly.is_empty());
+}
+
+#[tokio::test]
+async fn test_deferred_symbolication() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, 0x70001010, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(amd64_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    // Unwind without any symbols.
+    let mut options = ProcessorOptions::default();
+    options.defer_symbolication = true;
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(string_symbol_supplier(Default::default())),
+        options,
+    )
+    .await
+    .unwrap();
+
+    let mut frame = state.threads[0].frames[0].clone();
+    assert_eq!(frame.instruction, 0x70001010);
+    assert_eq!(frame.module.as_ref().unwrap().code_file(), "module1");
+    assert_eq!(frame.function_name, None);
+
+    // Then symbolicate just the one frame.
+    let symbols = [
+        "FUNC 1000 100 0 crash\n",
+        "1000 100 12 0\n",
+        "FILE 0 foo.c\n",
+    ];
+    let symbolizer = Symbolizer::new(string_symbol_supplier(module_symbols("module1", &symbols)));
+    symbolicate_frame(&mut frame, &symbolizer).await.unwrap();
+    assert_eq!(frame.function_name.as_deref(), Some("crash"));
+    assert_eq!(frame.source_file_name.as_deref(), Some("foo.c"));
+    assert_eq!(frame.source_line, Some(12));
+}
+
+#[tokio::test]
+async fn test_symbolicate_modules() {
+    let module1_name = DumpString::new("module1", Endian::Little);
+    let module1_cv_record = Section::with_endian(Endian::Little)
+        .D32(minidump_common::format::CvSignature::Pdb70 as u32)
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1)
+        .append_bytes(b"module1.pdb\0");
+    let module1 = minidump_synth::Module::new(
+        Endian::Little,
+        0x40000000,
+        0x10000,
+        &module1_name,
+        0,
+        0,
+        None,
+    )
+    .cv_record(&module1_cv_record);
+    let module2_name = DumpString::new("c:\\windows\\system32\\Module2.dll", Endian::Little);
+    let module2 = minidump_synth::Module::new(
+        Endian::Little,
+        0x50000000,
+        0x10000,
+        &module2_name,
+        0,
+        0,
+        None,
+    );
+    let (stack, context) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001010,
+        0x80000,
+        &[(0x50001020, 0x10), (0x40002030, 0x10)],
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module1)
+        .add_module(module2)
+        .add(module1_name)
+        .add(module1_cv_record)
+        .add(module2_name)
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols: HashMap<_, _> = vec![
+        (
+            String::from("module1"),
+            String::from("FUNC 1000 100 0 one\nFUNC 2000 100 0 three\n"),
+        ),
+        (
+            String::from("c:\\windows\\system32\\Module2.dll"),
+            String::from("FUNC 1000 100 0 two\n"),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let function_names = |filter: ModuleFilter| {
+        let dump = &dump;
+        let symbols = symbols.clone();
+        async move {
+            let mut options = ProcessorOptions::default();
+            options.symbolicate_modules = filter;
+            let state = minidump_processor::process_minidump_with_options(
+                dump,
+                &Symbolizer::new(string_symbol_supplier(symbols)),
+                options,
+            )
+            .await
+            .unwrap();
+            state.threads[0]
+                .frames
+                .iter()
+                .map(|frame| {
+                    assert!(frame.module.is_some());
+                    frame.function_name.clone()
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+    let names = |names: &[Option<&str>]| {
+        names
+            .iter()
+            .map(|name| name.map(String::from))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        function_names(ModuleFilter::default()).await,
+        names(&[Some("one"), Some("two"), Some("three")])
+    );
+
+    // Names are compared without directories or case.
+    let mut denylist = ModuleFilter::denylist();
+    denylist.add_name("module2.DLL");
+    assert_eq!(
+        function_names(denylist).await,
+        names(&[Some("one"), None, Some("three")])
+    );
+
+    let mut allowlist = ModuleFilter::allowlist();
+    allowlist.add_name("module2.dll");
+    assert_eq!(
+        function_names(allowlist).await,
+        names(&[None, Some("two"), None])
+    );
+
+    // Modules can also be picked by debug id.
+    let module1_id = debugid::DebugId::from_breakpad("ABCD1234F00DBEEF01020304050607081").unwrap();
+    let mut denylist = ModuleFilter::denylist();
+    denylist.add_debug_id(module1_id);
+    assert_eq!(
+        function_names(denylist).await,
+        names(&[None, Some("two"), None])
+    );
+
+    let mut allowlist = ModuleFilter::allowlist();
+    allowlist.add_debug_id(module1_id);
+    assert_eq!(
+        function_names(allowlist).await,
+        names(&[Some("one"), None, Some("three")])
+    );
+
+    // An empty allowlist symbolicates nothing.
+    assert_eq!(
+        function_names(ModuleFilter::allowlist()).await,
+        names(&[None, None, None])
+    );
+}
+
+#[tokio::test]
+async fn test_symbolicate_frames() {
+    let modules = [
+        MinidumpModule::new(0x70000000, 0x10000, "module1"),
+        MinidumpModule::new(0x80000000, 0x10000, "module2"),
+    ];
+    let symbols = [
+        (
+            String::from("module1"),
+            String::from("FUNC 1000 100 0 one\n1000 100 12 0\nFUNC 2000 100 0 two\n2000 100 13 0\nFILE 0 one.c\n"),
+        ),
+        (
+            String::from("module2"),
+            String::from("FUNC 1000 100 0 three\n1000 100 14 0\nFILE 0 three.c\n"),
+        ),
+    ];
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols.iter().cloned().collect()));
+
+    // Lots of frames spread over both modules, plus some without symbols.
+    let frames: Vec<StackFrame> = (0..256u64)
+        .map(|i| {
+            let (module, address) = match i % 4 {
+                0 => (&modules[0], 0x70001000 + i % 0x100),
+                1 => (&modules[0], 0x70002000 + i % 0x100),
+                2 => (&modules[1], 0x80001000 + i % 0x100),
+                _ => (&modules[1], 0x80008000 + i % 0x100),
+            };
+            let mut frame = StackFrame::from_context(
+                MinidumpContext::from_raw(MinidumpRawContext::Amd64(Default::default())),
+                FrameTrust::Context,
+            );
+            frame.instruction = address;
+            frame.module = Some(module.clone());
+            frame
+        })
+        .collect();
+
+    let mut sequential = frames.clone();
+    let mut sequential_results = Vec::new();
+    for frame in &mut sequential {
+        sequential_results.push(symbolicate_frame(frame, &symbolizer).await.is_ok());
+    }
+
+    let mut concurrent = frames.clone();
+    let concurrent_results: Vec<bool> =
+        symbolicate_frames(&mut concurrent, &symbolizer, &ModuleFilter::default())
+            .await
+            .iter()
+            .map(Result::is_ok)
+            .collect();
+
+    assert_eq!(concurrent_results, sequential_results);
+    for (a, b) in concurrent.iter().zip(sequential.iter()) {
+        assert_eq!(a.function_name, b.function_name);
+        assert_eq!(a.source_file_name, b.source_file_name);
+        assert_eq!(a.source_line, b.source_line);
+    }
+    assert_eq!(concurrent[0].function_name.as_deref(), Some("one"));
+    assert_eq!(concurrent[1].function_name.as_deref(), Some("two"));
+    assert_eq!(concurrent[2].function_name.as_deref(), Some("three"));
+    assert_eq!(concurrent[2].source_line, Some(14));
+    assert_eq!(concurrent[3].function_name, None);
+
+    #[cfg(feature = "parallel")]
+    {
+        // A fresh symbolizer, so the symbol files are loaded in parallel too.
+        let symbolizer = Symbolizer::new(string_symbol_supplier(symbols.iter().cloned().collect()));
+        let mut parallel = frames.clone();
+        let parallel_results: Vec<bool> = minidump_processor::symbolicate_frames_parallel(
+            &mut parallel,
+            &symbolizer,
+            &ModuleFilter::default(),
+        )
+        .iter()
+        .map(Result::is_ok)
+        .collect();
+
+        assert_eq!(parallel_results, sequential_results);
+        for (a, b) in parallel.iter().zip(sequential.iter()) {
+            assert_eq!(a.function_name, b.function_name);
+            assert_eq!(a.source_file_name, b.source_file_name);
+            assert_eq!(a.source_line, b.source_line);
+        }
+    }
+
+    // Frames in modules the filter doesn't select are left alone.
+    let mut denylist = ModuleFilter::denylist();
+    denylist.add_name("module2");
+    let mut filtered = frames[..4].to_vec();
+    let filtered_results: Vec<bool> = symbolicate_frames(&mut filtered, &symbolizer, &denylist)
+        .await
+        .iter()
+        .map(Result::is_ok)
+        .collect();
+    assert_eq!(filtered_results, [true, true, false, false]);
+    assert_eq!(filtered[1].function_name.as_deref(), Some("two"));
+    assert_eq!(filtered[2].function_name, None);
+
+    #[cfg(feature = "parallel")]
+    {
+        let mut filtered = frames[..4].to_vec();
+        minidump_processor::symbolicate_frames_parallel(&mut filtered, &symbolizer, &denylist);
+        assert_eq!(filtered[1].function_name.as_deref(), Some("two"));
+        assert_eq!(filtered[2].function_name, None);
+    }
+}
+
+#[tokio::test]
+async fn test_thread_stack_usage() {
+    // A 0x1000 byte stack at 0x1000 with the stack pointer 0x300 bytes from the top.
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1d00);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+
+    let state = read_synth_dump(dump).await;
+    assert_eq!(state.threads[0].stack_size, Some(0x1000));
+    assert_eq!(state.threads[0].stack_used, Some(0x300));
+    assert!(state.threads[0].stack_memory_present);
+}
+
+#[tokio::test]
+async fn test_frame_registers() {
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1d00);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    // Off by default.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert_eq!(state.threads[0].frames[0].registers, None);
+
+    let mut options = ProcessorOptions::default();
+    options.frame_registers = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let frame = &state.threads[0].frames[0];
+    let registers = frame.registers.as_ref().unwrap();
+    // The context is CONTEXT_ALL, with everything but eip and esp zeroed.
+    let expected: BTreeMap<String, u64> = [
+        ("eax", 0),
+        ("ebp", 0),
+        ("ebx", 0),
+        ("ecx", 0),
+        ("edi", 0),
+        ("edx", 0),
+        ("eflags", 0),
+        ("eip", 0xabcd1234),
+        ("esi", 0),
+        ("esp", 0x1d00),
+    ]
+    .iter()
+    .map(|&(reg, val)| (reg.to_owned(), val))
+    .collect();
+    assert_eq!(registers, &expected);
+}
+
+#[tokio::test]
+async fn test_thread_priority_and_affinity() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x80000,
+    );
+    let context1 = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x80000);
+    let context2 = minidump_synth::x86_context(Endian::Little, 0x40002000, 0x80000);
+    // NORMAL_PRIORITY_CLASS, THREAD_PRIORITY_HIGHEST
+    let thread1 = Thread::new(Endian::Little, 0x1234, &stack, &context1).priority(0x20, 2);
+    // Only this thread has extra info.
+    let thread2 = Thread::new(Endian::Little, 0x5678, &stack, &context2);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_thread_info(ThreadInfo::new(Endian::Little, 0x5678, 0x40002000, 0xf0))
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context1)
+        .add(context2)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    assert_eq!(state.threads.len(), 2);
+    assert_eq!(state.threads[0].priority_class, Some(0x20));
+    assert_eq!(state.threads[0].priority, Some(2));
+    assert_eq!(state.threads[0].affinity, None);
+    assert_eq!(state.threads[1].priority_class, Some(0));
+    assert_eq!(state.threads[1].priority, Some(0));
+    assert_eq!(state.threads[1].affinity, Some(0xf0));
+}
+
+#[tokio::test]
+async fn test_thread_stack_missing() {
+    // The thread's stack descriptor doesn't refer to any captured memory.
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1d00);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x1000);
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add(stack);
+
+    let state = read_synth_dump(dump).await;
+    let thread = &state.threads[0];
+    assert_eq!(thread.frames.len(), 1);
+    assert_eq!(thread.stack_used, None);
+    assert!(!thread.stack_memory_present);
+    assert_eq!(thread.walk_end, Some(StackWalkEnd::MissingStackMemory));
+}
+
+#[tokio::test]
+async fn test_override_system_info() {
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+
+    // SIGSEGV on Linux, but not a meaningful exception code on Windows.
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x1234;
+    ex.exception_record.exception_code = 11;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .set_linux_lsb_release(b"DISTRIB_ID=Ubuntu\nDISTRIB_DESCRIPTION=\"Ubuntu 22.04.3 LTS\"\n");
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert_eq!(state.system_info.os, Os::Windows);
+    assert!(
+        state.os_pretty_name().starts_with("Windows"),
+        "{}",
+        state.os_pretty_name()
+    );
+    let windows_reason = state.exception_info.unwrap().reason.to_string();
+
+    let mut options = ProcessorOptions::default();
+    options.override_system_info = Some(SystemInfoOverride {
+        os: Os::Linux,
+        cpu: Cpu::X86,
+    });
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert_eq!(state.system_info.os, Os::Linux);
+    // Everything derived from the OS follows the override.
+    assert_eq!(state.os_pretty_name(), "Ubuntu 22.04.3 LTS");
+    let linux_reason = state.exception_info.unwrap().reason.to_string();
+
+    assert!(linux_reason.starts_with("SIGSEGV"), "{}", linux_reason);
+    assert_ne!(windows_reason, linux_reason);
+
+    // The parsed stream itself is untouched.
+    let raw_system_info = dump.get_stream::<minidump::MinidumpSystemInfo>().unwrap();
+    assert_eq!(raw_system_info.os, Os::Windows);
+}
+
+#[tokio::test]
+async fn test_custom_stream() {
+    #[derive(Debug, PartialEq, Eq)]
+    struct VendorInfo {
+        version: u32,
+        flags: u32,
+    }
+
+    const VENDOR_STREAM: u32 = 0x76650001;
+    const BROKEN_STREAM: u32 = 0x76650002;
+
+    let vendor_stream = SimpleStream {
+        stream_type: VENDOR_STREAM,
+        section: Section::with_endian(Endian::Little).D32(3).D32(0x10),
+    };
+    let broken_stream = SimpleStream {
+        stream_type: BROKEN_STREAM,
+        section: Section::with_endian(Endian::Little).D8(1),
+    };
+    let dump = minimal_minidump()
+        .add_stream(vendor_stream)
+        .add_stream(broken_stream);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let mut custom_streams = CustomStreamRegistry::new();
+    custom_streams.register(VENDOR_STREAM, |bytes, endian| {
+        use scroll::Pread;
+        let version = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        let flags = bytes
+            .pread_with(4, endian)
+            .or(Err(Error::StreamReadFailure))?;
+        Ok(Box::new(VendorInfo { version, flags }))
+    });
+    custom_streams.register(BROKEN_STREAM, |_, _| Err(Error::StreamReadFailure));
+
+    let mut options = ProcessorOptions::default();
+    options.custom_streams = Some(&custom_streams);
+    let state = minidump_processor::process_minidump_with_options(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+        options,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        state.custom_stream::<VendorInfo>(VENDOR_STREAM),
+        Some(&VendorInfo {
+            version: 3,
+            flags: 0x10
+        })
+    );
+    assert_eq!(state.custom_stream::<u32>(VENDOR_STREAM), None);
+    assert_eq!(state.custom_stream::<VendorInfo>(BROKEN_STREAM), None);
+
+    // Streams that were parsed are no longer unknown, but ones that failed to parse are.
+    let unknown = state
+        .unknown_streams
+        .iter()
+        .map(|stream| stream.stream_type)
+        .collect::<Vec<_>>();
+    assert_eq!(unknown, vec![BROKEN_STREAM]);
+}
+
+#[tokio::test]
+async fn test_sanitizer_report() {
+    let text = "==4102==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000014 at pc 0x55d4c8a4c1a3 bp 0x7ffd7d0d0a50 sp 0x7ffd7d0d0a48\n\
+                  READ of size 4 at 0x602000000014 thread T0\n\0";
+    let comment = SimpleStream {
+        stream_type: minidump_common::format::MINIDUMP_STREAM_TYPE::CommentStreamA as u32,
+        section: Section::with_endian(Endian::Little).append_bytes(text.as_bytes()),
+    };
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x1234;
+    ex.exception_record.exception_code = 6; // SIGABRT
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_platform_id(minidump_common::format::PlatformId::Linux as u32);
+    let dump = minimal_minidump()
+        .add_system_info(system_info)
+        .add_exception(ex)
+        .add_stream(comment);
+    let state = read_synth_dump(dump).await;
+
+    let report = state.sanitizer_report.clone().unwrap();
+    assert_eq!(report.sanitizer, "AddressSanitizer");
+    assert_eq!(report.bug_type, "heap-buffer-overflow");
+    assert_eq!(report.address, Some(0x602000000014));
+
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Crash reason:  SIGABRT (AddressSanitizer: heap-buffer-overflow)\n"));
+
+    let json: serde_json::Value = serde_json::from_str(&state.into_json().unwrap()).unwrap();
+    assert_eq!(
+        json["crash_info"]["type"],
+        "SIGABRT (AddressSanitizer: heap-buffer-overflow)"
+    );
+
+    // Streams of unknown formats aren't scanned for reports.
+    let vendor = SimpleStream {
+        stream_type: 0x4b6b0002,
+        section: Section::with_endian(Endian::Little).append_bytes(text.as_bytes()),
+    };
+    let state = read_synth_dump(minimal_minidump().add_stream(vendor)).await;
+    assert_eq!(state.sanitizer_report, None);
+}
+
+#[tokio::test]
+async fn test_thread_indices_match_thread_list() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let thread_ids = [0x30, 0x10, 0x20];
+    let mut dump = SynthMinidump::with_endian(Endian::Little);
+    for &id in &thread_ids {
+        dump = dump.add_thread(Thread::new(Endian::Little, id, &stack, &context));
+    }
+
+    // The last thread crashed, and the middle one wrote the dump.
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x20;
+    let breakpad_info = SimpleStream {
+        stream_type: minidump_common::format::MINIDUMP_STREAM_TYPE::BreakpadInfoStream as u32,
+        section: Section::with_endian(Endian::Little)
+            .D32(minidump_common::format::BreakpadInfoValid::DumpThreadId.bits())
+            .D32(0x10)
+            .D32(0),
+    };
+    let dump = dump
+        .add_exception(ex)
+        .add_stream(breakpad_info)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let state = minidump_processor::process_minidump(
+        &dump,
+        &Symbolizer::new(simple_symbol_supplier(vec![])),
+    )
+    .await
+    .unwrap();
+
+    let thread_list = dump.get_stream::<minidump::MinidumpThreadList>().unwrap();
+    assert_eq!(state.threads.len(), thread_list.threads.len());
+    for (i, (processed, raw)) in state.threads.iter().zip(&thread_list.threads).enumerate() {
+        assert_eq!(processed.thread_id, raw.raw.thread_id, "thread {}", i);
+        assert_eq!(processed.thread_id, thread_ids[i]);
+    }
+    assert_eq!(state.requesting_thread, Some(2));
+    assert_eq!(state.threads[1].info, CallStackInfo::DumpThreadSkipped);
+    assert_eq!(state.threads[0].info, CallStackInfo::Ok);
+}
+
+#[tokio::test]
+async fn test_requested_dump_without_exception() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let mut dump = SynthMinidump::with_endian(Endian::Little);
+    for &id in &[0x30, 0x10, 0x20] {
+        dump = dump.add_thread(Thread::new(Endian::Little, id, &stack, &context));
+    }
+
+    // No exception stream: the second thread asked the last one to write the dump.
+    let breakpad_info = SimpleStream {
+        stream_type: minidump_common::format::MINIDUMP_STREAM_TYPE::BreakpadInfoStream as u32,
+        section: Section::with_endian(Endian::Little)
+            .D32(minidump_common::format::BreakpadInfoValid::all().bits())
+            .D32(0x20)
+            .D32(0x10),
+    };
+    let dump = dump
+        .add_stream(breakpad_info)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let state = read_synth_dump(dump).await;
+
+    assert!(!state.crashed());
+    assert!(state.requested_dump);
+    assert_eq!(state.requesting_thread, Some(1));
+    assert_eq!(state.threads[1].info, CallStackInfo::Ok);
+    assert_eq!(state.threads[2].info, CallStackInfo::DumpThreadSkipped);
+
+    let json: serde_json::Value = serde_json::from_str(&state.into_json().unwrap()).unwrap();
+    assert_eq!(json["crash_info"]["requested_dump"], true);
+    assert_eq!(json["crash_info"]["type"], serde_json::Value::Null);
+    assert_eq!(json["crash_info"]["crashing_thread"], 1);
+
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("No crash (requested dump)"));
+    assert!(output.contains("Thread 1  (requested dump, did not crash)"));
+
+    // Without BreakpadInfo there's no way to know who asked.
+    let state = read_synth_dump(minimal_minidump()).await;
+    assert_eq!(state.requesting_thread, None);
+    assert!(!state.requested_dump);
+}
+
+#[tokio::test]
+async fn test_symbolicate_handle_operations() {
+    let operation = HandleOperation::new(
+        Endian::Little,
+        0xabcd,
+        0x1234,
+        0x5678,
+        minidump_common::format::HandleTraceOperation::OperationDbClose as u32,
+        &[0x70001010, 0x70002000, 0x90000000],
+    );
+    let dump =
+        with_module(minimal_minidump(), "module1", 0x70000000).add_handle_operation(operation);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols = ["FUNC 1000 100 0 close_handle\n", "FUNC 1f00 100 0 caller\n"];
+    let symbolizer = Symbolizer::new(string_symbol_supplier(module_symbols("module1", &symbols)));
+
+    // Without the option, frames are only attributed to modules.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert_eq!(state.handle_operations.len(), 1);
+    let operation = &state.handle_operations[0];
+    assert_eq!(operation.handle, 0xabcd);
+    assert_eq!(operation.thread_id, 0x5678);
+    assert!(operation.frames[0].module.is_some());
+    assert_eq!(operation.frames[0].function_name, None);
+
+    let mut options = ProcessorOptions::default();
+    options.symbolicate_handle_operations = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let frames = &state.handle_operations[0].frames;
+    let names = frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref())
+        .collect::<Vec<_>>();
+    // The second return address is right after a call at the end of `caller`.
+    assert_eq!(names, vec![Some("close_handle"), Some("caller"), None]);
+    assert_eq!(frames[0].function_base, Some(0x70001000));
+    assert_eq!(frames[2].return_address, 0x90000000);
+    assert!(frames[2].module.is_none());
+}
+
+#[tokio::test]
+async fn test_typical_minidump() {
+    use minidump_common::format::ProcessorArchitecture;
+
+    const STACK_DEPTH: u32 = 10;
+    let symbols = (0..STACK_DEPTH)
+        .map(|i| format!("FUNC {:x} 100 0 function{}\n", 0x1000 + i * 0x100, i))
+        .collect::<String>();
+    let symbols = format!("MODULE windows x86 {TYPICAL_DEBUG_ID} typical.pdb\n{symbols}");
+    let symbols = module_symbols("typical.exe", &[&symbols]);
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+
+    for arch in [
+        ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL,
+        ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64,
+    ] {
+        let dump = SynthMinidump::typical(arch, 3, STACK_DEPTH);
+        let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+        let state = minidump_processor::process_minidump(&dump, &symbolizer)
+            .await
+            .unwrap();
+
+        assert!(state.crashed());
+        assert_eq!(state.requesting_thread, Some(0));
+        let module = state.modules.iter().next().unwrap();
+        assert_eq!(
+            module.debug_identifier().unwrap().breakpad().to_string(),
+            TYPICAL_DEBUG_ID
+        );
+        assert_eq!(state.threads.len(), 3);
+        for thread in &state.threads {
+            let names = thread
+                .frames
+                .iter()
+                .map(|frame| frame.function_name.as_deref().unwrap())
+                .collect::<Vec<_>>();
+            let expected = (0..STACK_DEPTH)
+                .map(|i| format!("function{i}"))
+                .collect::<Vec<_>>();
+            assert_eq!(names, expected, "{:?}", arch);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_modules_json() {
+    let main_name = DumpString::new("c:\\app\\main.exe", Endian::Little);
+    let main_cv_record = Section::with_endian(Endian::Little)
+        .D32(minidump_common::format::CvSignature::Pdb70 as u32)
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1)
+        .append_bytes(b"c:\\app\\main.pdb\0");
+    let main =
+        minidump_synth::Module::new(Endian::Little, 0x70000000, 0x10000, &main_name, 0, 0, None)
+            .cv_record(&main_cv_record);
+    let helper_name = DumpString::new("c:\\app\\helper.dll", Endian::Little);
+    let helper =
+        minidump_synth::Module::new(Endian::Little, 0x71000000, 0x2000, &helper_name, 0, 0, None);
+
+    // One thread in each module, so that we look for symbols for both.
+    let stack1 = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let stack2 = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x2000,
+    );
+    let context1 = minidump_synth::x86_context(Endian::Little, 0x70001010, 0x1000);
+    let context2 = minidump_synth::x86_context(Endian::Little, 0x71000010, 0x2000);
+    let thread1 = Thread::new(Endian::Little, 1, &stack1, &context1);
+    let thread2 = Thread::new(Endian::Little, 2, &stack2, &context2);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(main)
+        .add_module(helper)
+        .add(main_name)
+        .add(main_cv_record)
+        .add(helper_name)
+        .add(context1)
+        .add(context2)
+        .add_memory(stack1)
+        .add_memory(stack2);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols = vec![(
+        String::from("c:\\app\\main.exe"),
+        String::from("FUNC 1000 100 0 main\n"),
+    )];
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols.into_iter().collect()));
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+
+    // This is a stable interface, so changes here should be deliberate (and be
+    // reflected in json-schema.md).
+    assert_eq!(
+        state.modules_json(),
+        serde_json::json!([
+            {
+                "base_addr": "0x70000000",
+                "cert_subject": null,
+                "code_id": "0000000010000",
+                "corrupt_symbols": false,
+                "debug_file": "main.pdb",
+                "debug_id": "ABCD1234F00DBEEF01020304050607081",
+                "end_addr": "0x70010000",
+                "filename": "main.exe",
+                "loaded_symbols": true,
+                "missing_symbols": false,
+                "symbol_load_error": null,
+                "symbol_status": "loaded",
+                "symbol_url": null,
+                "version": "4369.4369.8738.8738"
+            },
+            {
+                "base_addr": "0x71000000",
+                "cert_subject": null,
+                "code_id": "",
+                "corrupt_symbols": false,
+                "debug_file": "",
+                "debug_id": "000000000000000000000000000000000",
+                "end_addr": "0x71002000",
+                "filename": "helper.dll",
+                "loaded_symbols": false,
+                "missing_symbols": true,
+                "symbol_load_error": null,
+                "symbol_status": "not_found",
+                "symbol_url": null,
+                "version": "4369.4369.8738.8738"
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_debuginfo_addresses() {
+    use breakpad_symbols::{SimpleFrame, SimpleModule};
+    use minidump_processor::debuginfo::DebugInfoSymbolProvider;
+    use minidump_processor::SymbolProvider;
+
+    // An ELF binary with DWARF debug info, see testdata/README.md.
+    let path = locate_testdata()
+        .join("symbols/debuginfo-test/63ACF583815DEA7CCFB7EEF47A4B9A110/debuginfo-test");
+    let path = path.to_str().unwrap();
+    let module = SimpleModule {
+        base_address: Some(0x7f0000000000),
+        size: Some(0x4000),
+        code_file: Some(String::from(path)),
+        debug_file: Some(String::from(path)),
+        ..SimpleModule::default()
+    };
+    let provider = DebugInfoSymbolProvider::default();
+
+    // The debug info's addresses are relative to the module's base address.
+    let mut frame = SimpleFrame::with_instruction(0x7f0000001004);
+    provider.fill_symbol(&module, &mut frame).await.unwrap();
+    assert_eq!(frame.function.as_deref(), Some("crash_here"));
+    assert_eq!(frame.function_base, Some(0x7f0000001000));
+
+    let mut frame = SimpleFrame::with_instruction(0x7f0000001010);
+    provider.fill_symbol(&module, &mut frame).await.unwrap();
+    assert_eq!(frame.function.as_deref(), Some("main"));
+    assert_eq!(frame.function_base, Some(0x7f000000100e));
+}
+
+/// The module of the small PDB in testdata/symbols, see testdata/README.md.
+#[cfg(feature = "pdb")]
+fn mini_pdb_module() -> breakpad_symbols::SimpleModule {
+    breakpad_symbols::SimpleModule {
+        base_address: Some(0x140000000),
+        size: Some(0x3000),
+        code_file: Some(String::from(r"C:\src\mini.exe")),
+        debug_file: Some(String::from(r"C:\src\mini.pdb")),
+        debug_id: Some(
+            debugid::DebugId::from_breakpad("04A5D348A839BBD54C4C44205044422E1").unwrap(),
+        ),
+        ..breakpad_symbols::SimpleModule::default()
+    }
+}
+
+#[cfg(feature = "pdb")]
+#[tokio::test]
+async fn test_pdb_symbol_source() {
+    use breakpad_symbols::{SimpleFrame, SimpleModule};
+    use minidump_processor::{
+        BreakpadSymbolSource, PdbSymbolSource, SymbolSource, SymbolSourceProvider,
+    };
+
+    let module = mini_pdb_module();
+    let source = PdbSymbolSource::new(vec![locate_testdata().join("symbols")]);
+
+    // The return address of the call to `crash`.
+    let symbol = source.symbolize(&module, 0x14000101e).await.unwrap();
+    assert_eq!(symbol.offset, 0x101e);
+    assert_eq!(symbol.function_name.as_deref(), Some("main"));
+    assert_eq!(symbol.function_base, Some(0x1010));
+    assert!(!symbol.function_is_public);
+    assert_eq!(symbol.source_file_name.as_deref(), Some(r"C:\src\mini.c"));
+    assert_eq!(symbol.source_line, Some(7));
+    assert_eq!(symbol.source_line_base, Some(0x101e));
+
+    // The padding between the two functions is only covered by the public symbol.
+    let symbol = source.symbolize(&module, 0x140001008).await.unwrap();
+    assert_eq!(symbol.function_name.as_deref(), Some("crash"));
+    assert!(symbol.function_is_public);
+    assert_eq!(symbol.source_line, None);
+
+    // Sources are tried in order, and the first one with a function for the address wins.
+    let mut provider = SymbolSourceProvider::new();
+    provider.add(Box::new(BreakpadSymbolSource::new(Symbolizer::new(
+        string_symbol_supplier(HashMap::new()),
+    ))));
+    provider.add(Box::new(source));
+    let mut frame = SimpleFrame::with_instruction(0x140001003);
+    provider.fill_symbol(&module, &mut frame).await.unwrap();
+    assert_eq!(frame.function.as_deref(), Some("crash"));
+    assert_eq!(frame.function_base, Some(0x140001000));
+    assert_eq!(frame.source_line, Some(3));
+    assert_eq!(frame.source_line_base, Some(0x140001003));
+
+    // No source has symbols for other modules.
+    let other = SimpleModule::new(
+        "other.pdb",
+        debugid::DebugId::from_breakpad("04A5D348A839BBD54C4C44205044422E1").unwrap(),
+    );
+    let mut frame = SimpleFrame::with_instruction(0x1000);
+    assert!(provider.fill_symbol(&other, &mut frame).await.is_err());
+}
+
+#[tokio::test]
+async fn test_additional_modules() {
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1d00);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let name = DumpString::new("main.exe", Endian::Little);
+    let module = minidump_synth::Module::new(Endian::Little, 0x200000, 0x1000, &name, 0, 0, None);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module)
+        .add(name)
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    // Without additional modules, the crashing address isn't in any module.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert!(state.threads[0].frames[0].module.is_none());
+
+    let mut options = ProcessorOptions::default();
+    options.additional_modules = Some(minidump::MinidumpModuleList::from_modules(vec![
+        MinidumpModule::new(0x200000, 0x1000, "other.exe"),
+        MinidumpModule::new(0xabcd0000, 0x10000, "extra.dll"),
+    ]));
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let module = state.threads[0].frames[0].module.as_ref().unwrap();
+    assert_eq!(module.code_file(), "extra.dll");
+    assert_eq!(module.base_address(), 0xabcd0000);
+
+    // The minidump's own module wins over an additional one at the same base.
+    let main = state.modules.module_at_address(0x200000).unwrap();
+    assert_eq!(main.code_file(), "main.exe");
+    assert_eq!(state.modules.iter().count(), 2);
+}
+
+#[tokio::test]
+async fn test_pie_module_symbols() {
+    // A position-independent executable loaded at a randomized address: its symbols are
+    // relative to the start of the image, wherever it was loaded.
+    let base = 0x55d4_1234_5000;
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            // return address into `main`
+            .D64(base + 0x2020)
+            .append_repeated(0, 0xf8),
+        0x80000,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, base + 0x1010, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(amd64_linux_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "pie_app", base);
+
+    let symbols = [
+        "FILE 0 src/main.c\n",
+        "FUNC 1000 100 0 crash\n",
+        "1000 100 12 0\n",
+        "STACK CFI INIT 1000 100 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n",
+        "FUNC 2000 100 0 main\n",
+        "2000 100 34 0\n",
+    ];
+    let state = read_synth_dump_with_symbols(dump, "pie_app", &symbols).await;
+
+    let frames = &state.threads[0].frames;
+    assert_eq!(frames[0].function_name.as_deref(), Some("crash"));
+    assert_eq!(frames[0].function_base, Some(base + 0x1000));
+    assert_eq!(frames[0].source_file_name.as_deref(), Some("src/main.c"));
+    assert_eq!(frames[0].source_line, Some(12));
+    assert_eq!(frames[0].source_line_base, Some(base + 0x1000));
+    assert_eq!(frames[1].trust, FrameTrust::CallFrameInfo);
+    assert_eq!(frames[1].function_name.as_deref(), Some("main"));
+    assert_eq!(frames[1].function_base, Some(base + 0x2000));
+    assert_eq!(frames[1].source_line, Some(34));
+}
+
+#[tokio::test]
+async fn test_fallback_resolver() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+        0x1000,
+    );
+    // One thread in the module, one in JIT code that no symbols cover.
+    let context1 = minidump_synth::x86_context(Endian::Little, 0x70001010, 0x1d00);
+    let context2 = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1d00);
+    let thread1 = Thread::new(Endian::Little, 1, &stack, &context1);
+    let thread2 = Thread::new(Endian::Little, 2, &stack, &context2);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context1)
+        .add(context2)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols = module_symbols("module1", &["FUNC 1000 100 0 crash\n"]);
+    let symbolizer = Symbolizer::new(string_symbol_supplier(symbols));
+
+    let addresses = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let resolver_addresses = addresses.clone();
+    let resolver = FallbackResolver::new(move |address, state| {
+        resolver_addresses.lock().unwrap().push(address);
+        // The state is fully processed when the resolver runs.
+        assert_eq!(state.threads.len(), 2);
+        (0xabcd0000..0xabce0000)
+            .contains(&address)
+            .then(|| FrameName {
+                function_base: Some(0xabcd1200),
+                ..FrameName::new("jit_code")
+            })
+    });
+    let mut options = ProcessorOptions::default();
+    options.fallback_resolver = Some(&resolver);
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        state.threads[0].frames[0].function_name.as_deref(),
+        Some("crash")
+    );
+    let frame = &state.threads[1].frames[0];
+    assert_eq!(frame.function_name.as_deref(), Some("jit_code"));
+    assert_eq!(frame.function_base, Some(0xabcd1200));
+    assert!(frame.module.is_none());
+
+    // Frames that symbols could name are never given to the resolver.
+    let addresses = addresses.lock().unwrap();
+    assert!(addresses.contains(&0xabcd1234));
+    assert!(!addresses.contains(&0x70001010));
+}
+
+#[tokio::test]
+async fn test_duplicate_threads() {
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+    let mut dump = SynthMinidump::with_endian(Endian::Little);
+    for &id in &[0x20, 0x10, 0x20, 0x10] {
+        dump = dump.add_thread(Thread::new(Endian::Little, id, &stack, &context));
+    }
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 0x10;
+    let dump = dump
+        .add_exception(ex)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    // By default the duplicates are kept, but the first one is the crashing thread.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert_eq!(state.threads.len(), 4);
+    assert_eq!(state.requesting_thread, Some(1));
+
+    let mut options = ProcessorOptions::default();
+    options.deduplicate_threads = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let ids = state
+        .threads
+        .iter()
+        .map(|thread| thread.thread_id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![0x20, 0x10]);
+    assert_eq!(state.requesting_thread, Some(1));
+}
+
+#[tokio::test]
+async fn test_bit_flip_execute_region() {
+    use minidump_common::format::{MemoryProtection, MemoryState, MemoryType};
+
+    let context = minidump_synth::amd64_context(Endian::Little, 0x110800, 0);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let region = |base, state: MemoryState, protection: MemoryProtection, ty: MemoryType| {
+        MemoryInfo::new(
+            Endian::Little,
+            base,
+            base,
+            protection.bits(),
+            0x1000,
+            state.bits(),
+            protection.bits(),
+            ty.bits(),
+        )
+    };
+    // The faulting address is in data, one flipped bit away from committed code
+    // and from code that was only reserved.
+    let data = region(
+        0x110000,
+        MemoryState::MEM_COMMIT,
+        MemoryProtection::PAGE_READWRITE,
+        MemoryType::MEM_PRIVATE,
+    );
+    let code = region(
+        0x010000,
+        MemoryState::MEM_COMMIT,
+        MemoryProtection::PAGE_EXECUTE_READ,
+        MemoryType::MEM_IMAGE,
+    );
+    let reserved = region(
+        0x190000,
+        MemoryState::MEM_RESERVE,
+        MemoryProtection::PAGE_EXECUTE_READ,
+        MemoryType::MEM_IMAGE,
+    );
+
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = amd64_system_info()
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_code =
+        minidump_common::errors::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32;
+    ex.exception_record.exception_address = 0x110800;
+    ex.exception_record.number_parameters = 2;
+    ex.exception_record.exception_information[0] =
+        minidump_common::errors::ExceptionCodeWindowsAccessType::EXEC as u64;
+    ex.exception_record.exception_information[1] = 0x110800;
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_memory_info(data)
+        .add_memory_info(code)
+        .add_memory_info(reserved);
+
+    let state = read_synth_dump(dump).await;
+
+    let bit_flips = state
+        .exception_info
+        .expect("missing exception info")
+        .possible_bit_flips;
+    let addresses = bit_flips.iter().map(|bf| bf.address.0).collect::<Vec<_>>();
+    assert_eq!(addresses, vec![0x010800]);
+}
+
+/// Process a Windows access violation of kind `access` at `address`, with the
+/// instruction pointer at `pc` and the given memory info.
+async fn windows_access_violation(
+    access: minidump_common::errors::ExceptionCodeWindowsAccessType,
+    pc: u64,
+    address: u64,
+    memory_info: Vec<MemoryInfo>,
+) -> ProcessState {
+    let context = minidump_synth::amd64_context(Endian::Little, pc, 0);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let system_info = amd64_system_info()
+        .set_platform_id(minidump_common::format::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+
+    let mut ex = Exception::new(Endian::Little);
+    ex.thread_id = 1;
+    ex.exception_record.exception_code =
+        minidump_common::errors::ExceptionCodeWindows::EXCEPTION_ACCESS_VIOLATION as u32;
+    ex.exception_record.exception_address = pc;
+    ex.exception_record.number_parameters = 2;
+    ex.exception_record.exception_information[0] = access as u64;
+    ex.exception_record.exception_information[1] = address;
+
+    let mut dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_exception(ex.context(&context))
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack);
+    for info in memory_info {
+        dump = dump.add_memory_info(info);
+    }
+    read_synth_dump(dump).await
+}
+
+#[tokio::test]
+async fn test_crash_classification() {
+    use minidump_common::errors::ExceptionCodeWindowsAccessType as Access;
+    use minidump_common::format::{MemoryProtection, MemoryState, MemoryType};
+
+    let region = |base, protection: MemoryProtection| {
+        MemoryInfo::new(
+            Endian::Little,
+            base,
+            base,
+            protection.bits(),
+            0x1000,
+            MemoryState::MEM_COMMIT.bits(),
+            protection.bits(),
+            MemoryType::MEM_PRIVATE.bits(),
+        )
+    };
+    let classification = |state: ProcessState| state.exception_info.unwrap().crash_classification;
+
+    // Jumping into the heap.
+    let state = windows_access_violation(
+        Access::EXEC,
+        0x110800,
+        0x110800,
+        vec![region(0x110000, MemoryProtection::PAGE_READWRITE)],
+    )
+    .await;
+    assert_eq!(
+        classification(state),
+        Some(CrashClassification::ExecuteNonExecutable)
+    );
+
+    // Writing to constant data from code.
+    let state = windows_access_violation(
+        Access::WRITE,
+        0x10800,
+        0x20800,
+        vec![
+            region(0x10000, MemoryProtection::PAGE_EXECUTE_READ),
+            region(0x20000, MemoryProtection::PAGE_READONLY),
+        ],
+    )
+    .await;
+    assert_eq!(
+        classification(state),
+        Some(CrashClassification::WriteReadOnly)
+    );
+
+    // Reading through a null pointer.
+    let state = windows_access_violation(
+        Access::READ,
+        0x10800,
+        0x18,
+        vec![region(0x10000, MemoryProtection::PAGE_EXECUTE_READ)],
+    )
+    .await;
+    assert_eq!(classification(state), Some(CrashClassification::NullPage));
+
+    // Reading unmapped memory isn't notable.
+    let state = windows_access_violation(
+        Access::READ,
+        0x10800,
+        0x12345678,
+        vec![region(0x10000, MemoryProtection::PAGE_EXECUTE_READ)],
+    )
+    .await;
+    assert_eq!(classification(state), None);
+}
+
+#[tokio::test]
+async fn test_frame_in_unloaded_module() {
+    // A crash in a DLL that was just unloaded: no loaded module covers the address,
+    // but the unloaded module list still knows what used to be there.
+    let name = DumpString::new("gone.dll", Endian::Little);
+    let unloaded = UnloadedModule::new(
+        Endian::Little,
+        0x40000000,
+        0x10000,
+        &name,
+        0xb1054d2a,
+        0x34571371,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0x40001234, 0x1010);
+    let stack = Memory::with_section(Section::with_endian(Endian::Little), 0x1000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add(context)
+        .add_memory(stack)
+        .add_unloaded_module(unloaded)
+        .add(name)
+        .add_system_info(SystemInfo::new(Endian::Little));
+
+    let state = read_synth_dump(dump).await;
+    let frame = &state.threads[0].frames[0];
+    assert!(frame.module.is_none());
+    let offsets = frame.unloaded_modules.get("gone.dll").unwrap();
+    assert_eq!(offsets.iter().copied().collect::<Vec<_>>(), vec![0x1234]);
+
+    let mut output = Vec::new();
+    state.print(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("0x40001234 (unloaded gone.dll@0x1234)"));
+}
+
+/// A symbol provider that fails on some modules.
+struct FlakySymbolProvider(Symbolizer);
+
+#[async_trait::async_trait]
+impl SymbolProvider for FlakySymbolProvider {
+    async fn fill_symbol(
+        &self,
+        module: &(dyn Module + Sync),
+        frame: &mut (dyn FrameSymbolizer + Send),
+    ) -> Result<(), FillSymbolError> {
+        match &*module.code_file() {
+            "panics.so" => panic!("lost connection to the symbol server"),
+            _ => self.0.fill_symbol(module, frame).await,
+        }
+    }
+
+    async fn walk_frame(
+        &self,
+        module: &(dyn Module + Sync),
+        walker: &mut (dyn FrameWalker + Send),
+    ) -> Option<()> {
+        self.0.walk_frame(module, walker).await
+    }
+
+    async fn get_file_path(
+        &self,
+        module: &(dyn Module + Sync),
+        file_kind: FileKind,
+    ) -> Result<PathBuf, FileError> {
+        self.0.get_file_path(module, file_kind).await
+    }
+
+    fn stats(&self) -> HashMap<String, SymbolStats> {
+        self.0.stats()
+    }
+}
+
+#[tokio::test]
+async fn test_process_minidump_isolated() {
+    let mut dump = SynthMinidump::with_endian(Endian::Little);
+    let names = ["good.so", "panics.so", "corrupt.so", "missing.so"];
+    for (i, name) in names.iter().enumerate() {
+        let dump_name = DumpString::new(name, Endian::Little);
+        let module = minidump_synth::Module::new(
+            Endian::Little,
+            0x7400c0000000 + i as u64 * 0x10000,
+            0x10000,
+            &dump_name,
+            0,
+            0,
+            None,
+        );
+        dump = if *name == "panics.so" {
+            // Errors are keyed by debug id as well as file name.
+            let cv_record = Section::with_endian(Endian::Little)
+                .D32(minidump_common::format::CvSignature::Pdb70 as u32)
+                .D32(0xabcd1234)
+                .D16(0xf00d)
+                .D16(0xbeef)
+                .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+                .D32(1)
+                .append_bytes(b"panics.so\0");
+            dump.add_module(module.cv_record(&cv_record)).add(cv_record)
+        } else {
+            dump.add_module(module)
+        };
+        dump = dump.add(dump_name);
+    }
+    // good.so -> panics.so -> corrupt.so -> missing.so -> good.so
+    let (stack, context) = minidump_synth::amd64_frame_pointer_stack(
+        Endian::Little,
+        0x7400c0001000,
+        0x80000,
+        &[
+            (0x7400c0011000, 0x30),
+            (0x7400c0021000, 0x10),
+            (0x7400c0031000, 0x20),
+            (0x7400c0002000, 0x100),
+        ],
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let dump = dump
+        .add_thread(thread)
+        .add_system_info(amd64_linux_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+
+    let symbols = [
+        ("good.so", "FUNC 0 10000 0 good.so_func\n"),
+        ("panics.so", "FUNC 0 10000 0 panics.so_func\n"),
+        ("corrupt.so", "this is not a symbol file\n"),
+    ]
+    .iter()
+    .map(|(name, symbols)| (name.to_string(), symbols.to_string()))
+    .collect();
+    let provider = FlakySymbolProvider(Symbolizer::new(string_symbol_supplier(symbols)));
+    let state = minidump_processor::process_minidump_isolated(
+        &dump,
+        &provider,
+        ProcessorOptions::default(),
+    )
+    .await
+    .unwrap();
+
+    // Only the frames in the failing modules are left unsymbolicated.
+    let functions: Vec<_> = state.threads[0]
+        .frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref())
+        .collect();
+    assert_eq!(
+        functions,
+        [Some("good.so_func"), None, None, None, Some("good.so_func")]
+    );
+    let mut errors: Vec<_> = state.symbol_provider_errors.into_iter().collect();
+    errors.sort();
+    assert_eq!(
+        errors,
+        [
+            (
+                String::from("corrupt.so"),
+                String::from("parse error: failed to parse file at line 0")
+            ),
+            (
+                String::from("panics.so ABCD1234F00DBEEF01020304050607081"),
+                String::from("symbol provider panicked: lost connection to the symbol server")
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_unwind_method_counts() {
+    let module1_name = DumpString::new("module1.dll", Endian::Little);
+    let module1 = minidump_synth::Module::new(
+        Endian::Little,
+        0x40000000,
+        0x10000,
+        &module1_name,
+        0,
+        0,
+        None,
+    );
+    let module2_name = DumpString::new("module2.dll", Endian::Little);
+    let module2 = minidump_synth::Module::new(
+        Endian::Little,
+        0x50000000,
+        0x10000,
+        &module2_name,
+        0,
+        0,
+        None,
+    );
+
+    // module1 -> module2 -> module2, linked by frame pointers.
+    let (stack1, context1) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001000,
+        0x80000,
+        &[(0x50002010, 0x20), (0x50003020, 0x10)],
+    );
+    let thread1 = Thread::new(Endian::Little, 1, &stack1, &context1);
+
+    // module2 -> module1, without a frame pointer, so the caller is found by scanning.
+    let context2 = minidump_synth::x86_context(Endian::Little, 0x50001000, 0x90000);
+    let stack2 = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0)
+            .D32(0x40001234)
+            .append_repeated(0, 0x10),
+        0x90000,
+    );
+    let thread2 = Thread::new(Endian::Little, 2, &stack2, &context2);
+
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread1)
+        .add_thread(thread2)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module1)
+        .add_module(module2)
+        .add(module1_name)
+        .add(module2_name)
+        .add(context1)
+        .add(context2)
+        .add_memory(stack1)
+        .add_memory(stack2);
+    let state = read_synth_dump(dump).await;
+
+    let counts = state.unwind_method_counts();
+    assert_eq!(
+        counts.keys().map(String::as_str).collect::<Vec<_>>(),
+        ["module1.dll", "module2.dll"]
+    );
+    assert_eq!(
+        counts["module1.dll"],
+        UnwindMethodCounts {
+            context: 1,
+            scan: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        counts["module2.dll"],
+        UnwindMethodCounts {
+            context: 1,
+            frame_pointer: 2,
+            ..Default::default()
+        }
+    );
+    assert_eq!(counts["module2.dll"].total(), 3);
+}
+
+#[tokio::test]
+async fn test_symbolize_addresses() {
+    let symbols = tempfile::tempdir().unwrap();
+    let debug_id = "abcd1234-abcd-1234-abcd-abcd12345678-a".parse().unwrap();
+    let sym_dir = symbols
+        .path()
+        .join("foo.pdb/ABCD1234ABCD1234ABCDABCD12345678a");
+    std::fs::create_dir_all(&sym_dir).unwrap();
+    std::fs::write(
+        sym_dir.join("foo.sym"),
+        "MODULE windows x86 ABCD1234ABCD1234ABCDABCD12345678a foo.pdb
+FILE 1 outer.c
+FILE 2 mid.c
+FILE 3 inner.c
+INLINE_ORIGIN 1 mid_func()
+INLINE_ORIGIN 2 inner_func()
+FUNC 1000 30 10 outer_func()
+INLINE 0 60 1 1 1000 20
+INLINE 1 12 2 2 1000 10
+1000 10 42 3
+1010 10 17 2
+1020 10 62 1
+PUBLIC 2000 0 public_func
+",
+    )
+    .unwrap();
+    let provider = Symbolizer::new(simple_symbol_supplier(vec![symbols.path().to_path_buf()]));
+
+    let offsets = [0x1004, 0x1024, 0x2010, 0x800];
+    let resolved = symbolize_addresses("foo.pdb", debug_id, &offsets, &provider).await;
+    assert_eq!(
+        resolved
+            .iter()
+            .map(|symbol| symbol.offset)
+            .collect::<Vec<_>>(),
+        offsets
+    );
+
+    // Two levels of inlining, innermost first.
+    assert_eq!(resolved[0].function_name.as_deref(), Some("outer_func()"));
+    assert_eq!(resolved[0].function_base, Some(0x1000));
+    assert_eq!(resolved[0].source_file_name.as_deref(), Some("outer.c"));
+    assert_eq!(resolved[0].source_line, Some(60));
+    let inlines: Vec<_> = resolved[0]
+        .inlines
+        .iter()
+        .map(|inline| {
+            (
+                inline.function_name.as_str(),
+                inline.source_file_name.as_deref(),
+                inline.source_line,
+            )
+        })
+        .collect();
+    assert_eq!(
+        inlines,
+        [
+            ("inner_func()", Some("inner.c"), Some(42)),
+            ("mid_func()", Some("mid.c"), Some(12)),
+        ]
+    );
+
+    assert_eq!(resolved[1].function_name.as_deref(), Some("outer_func()"));
+    assert_eq!(resolved[1].source_line, Some(62));
+    assert!(resolved[1].inlines.is_empty());
+
+    assert_eq!(resolved[2].function_name.as_deref(), Some("public_func"));
+    assert!(resolved[2].function_is_public);
+
+    assert_eq!(resolved[3].function_name, None);
+
+    // A module without symbols leaves everything unresolved.
+    let resolved = symbolize_addresses("bar.pdb", debug_id, &offsets, &provider).await;
+    assert_eq!(resolved.len(), 4);
+    assert!(resolved.iter().all(|symbol| symbol.function_name.is_none()));
+}
+
+#[tokio::test]
+async fn test_scan_skip_bytes_and_alignment() {
+    // The stack pointer is misaligned and points at a value that looks like a return
+    // address, and so does something at the base of the stack, past the real caller.
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0)
+            .D64(0x70001000)
+            .D32(0)
+            // the real return address
+            .D64(0x70002000)
+            .append_repeated(0, 0x10)
+            // junk at the base of the stack
+            .D64(0x70004000)
+            .append_repeated(0, 0x8),
+        0x80000,
+    );
+    let context = minidump_synth::amd64_context(Endian::Little, 0x70003000, 0x80004);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(amd64_system_info())
+        .add(context)
+        .add_memory(stack);
+    let dump = with_module(dump, "module1", 0x70000000);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    let caller_addresses = |state: &ProcessState| {
+        state.threads[0].frames[1..]
+            .iter()
+            .map(|frame| {
+                assert_eq!(frame.trust, FrameTrust::Scan);
+                frame.context.get_instruction_pointer()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // By default, scanning picks up the junk.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    assert_eq!(caller_addresses(&state)[0], 0x70001000);
+
+    let mut options = ProcessorOptions::default();
+    options.scan_align_to_pointer = true;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert_eq!(caller_addresses(&state), [0x70002000, 0x70004000]);
+
+    // Skipping only ignores the base of the stack, not the stack pointer's end.
+    let mut options = ProcessorOptions::default();
+    options.scan_skip_bytes = 0x10;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert_eq!(caller_addresses(&state)[0], 0x70001000);
+
+    let mut options = ProcessorOptions::default();
+    options.scan_align_to_pointer = true;
+    options.scan_skip_bytes = 0x10;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert_eq!(caller_addresses(&state), [0x70002000]);
+
+    let mut options = ProcessorOptions::default();
+    options.scan_align_to_pointer = true;
+    options.scan_skip_bytes = 0x28;
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    assert!(caller_addresses(&state).is_empty());
+}
+
+#[tokio::test]
+async fn test_module_paths_from_linux_maps() {
+    let name1 = DumpString::new("libfoo.so", Endian::Little);
+    let module1 =
+        minidump_synth::Module::new(Endian::Little, 0x70000000, 0x10000, &name1, 0, 0, None);
+    let name2 = DumpString::new("/opt/app/libbar.so", Endian::Little);
+    let module2 =
+        minidump_synth::Module::new(Endian::Little, 0x70010000, 0x10000, &name2, 0, 0, None);
+    let name3 = DumpString::new("libunmapped.so", Endian::Little);
+    let module3 =
+        minidump_synth::Module::new(Endian::Little, 0x70020000, 0x10000, &name3, 0, 0, None);
+    let maps = b"\
+70000000-70008000 r-xp 00000000 08:12 1234 /usr/lib/libfoo.so
+70008000-70010000 rw-p 00008000 08:12 1234 /usr/lib/libfoo.so
+70010000-70020000 r-xp 00000000 08:12 5678 /usr/lib/libbar.so
+70020000-70030000 rw-p 00000000 00:00 0
+";
+    let context = minidump_synth::x86_context(Endian::Little, 0x70001000, 0x1010);
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+        0x1000,
+    );
+    let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+    let system_info = SystemInfo::new(Endian::Little)
+        .set_platform_id(minidump_common::format::PlatformId::Linux as u32);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(system_info)
+        .add(context)
+        .add_memory(stack)
+        .add_module(module1)
+        .add_module(module2)
+        .add_module(module3)
+        .add(name1)
+        .add(name2)
+        .add(name3)
+        .set_linux_maps(maps);
+    let state = read_synth_dump(dump).await;
+
+    let names = state
+        .modules
+        .iter()
+        .map(|module| module.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["/usr/lib/libfoo.so", "/opt/app/libbar.so", "libunmapped.so"]
+    );
+}
+
+#[tokio::test]
+async fn test_unwind_hints() {
+    let legacy_name = DumpString::new("legacy.dll", Endian::Little);
+    let legacy_cv_record = Section::with_endian(Endian::Little)
+        .D32(minidump_common::format::CvSignature::Pdb70 as u32)
+        .D32(0xabcd1234)
+        .D16(0xf00d)
+        .D16(0xbeef)
+        .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+        .D32(1)
+        .append_bytes(b"legacy.pdb\0");
+    let legacy = minidump_synth::Module::new(
+        Endian::Little,
+        0x40000000,
+        0x10000,
+        &legacy_name,
+        0,
+        0,
+        None,
+    )
+    .cv_record(&legacy_cv_record);
+    let main_name = DumpString::new("main.exe", Endian::Little);
+    let main =
+        minidump_synth::Module::new(Endian::Little, 0x50000000, 0x10000, &main_name, 0, 0, None);
+
+    // A frameless stdcall function in legacy.dll, which has a pointer into its own
+    // module in its locals, and was called by main.exe with two arguments.
+    let stack = Memory::with_section(
+        Section::with_endian(Endian::Little)
+            .D32(0x40002000)
+            .append_repeated(0, 0xc)
+            .D32(0x50001234)
+            .D32(1)
+            .D32(2)
+            .append_repeated(0, 0x20),
+        0x80000,
+    );
+    let context = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x80000);
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    // Another thread in legacy.dll, in a function that does have a frame pointer.
+    let (fp_stack, fp_context) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001000,
+        0x90000,
+        &[(0x50001234, 0x10)],
+    );
+    let fp_thread = Thread::new(Endian::Little, 2, &fp_stack, &fp_context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_thread(fp_thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(legacy)
+        .add_module(main)
+        .add(legacy_name)
+        .add(legacy_cv_record)
+        .add(main_name)
+        .add(context)
+        .add(fp_context)
+        .add_memory(stack)
+        .add_memory(fp_stack);
+    let dump = Minidump::read(dump.finish().unwrap()).unwrap();
+    let symbolizer = Symbolizer::new(simple_symbol_supplier(vec![]));
+
+    // Scanning mistakes the local for the return address.
+    let state = minidump_processor::process_minidump(&dump, &symbolizer)
+        .await
+        .unwrap();
+    let frame = &state.threads[0].frames[1];
+    assert_eq!(frame.trust, FrameTrust::Scan);
+    assert_eq!(frame.context.get_instruction_pointer(), 0x40002000);
+
+    let mut unwind_hints = UnwindHints::new();
+    unwind_hints.register(
+        "abcd1234-f00d-beef-0102-030405060708-1".parse().unwrap(),
+        UnwindHint::Frameless {
+            frame_size: 0x10,
+            parameter_size: 8,
+        },
+    );
+    let mut options = ProcessorOptions::default();
+    options.unwind_hints = Some(&unwind_hints);
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let frame = &state.threads[0].frames[1];
+    assert_eq!(frame.trust, FrameTrust::UnwindHint);
+    assert_eq!(frame.context.get_instruction_pointer(), 0x50001234);
+    assert_eq!(frame.context.get_stack_pointer(), 0x8001c);
+    assert_eq!(frame.module.as_ref().unwrap().name, "main.exe");
+
+    // Frames found with a frame pointer hint are only as good as the hint, too.
+    let mut unwind_hints = UnwindHints::new();
+    unwind_hints.register(
+        "abcd1234-f00d-beef-0102-030405060708-1".parse().unwrap(),
+        UnwindHint::FramePointer { parameter_size: 0 },
+    );
+    let mut options = ProcessorOptions::default();
+    options.unwind_hints = Some(&unwind_hints);
+    let state = minidump_processor::process_minidump_with_options(&dump, &symbolizer, options)
+        .await
+        .unwrap();
+    let frame = &state.threads[1].frames[1];
+    assert_eq!(frame.trust, FrameTrust::UnwindHint);
+    assert_eq!(frame.context.get_instruction_pointer(), 0x50001234);
+}
+
+#[tokio::test]
+async fn test_symbolication_stats() {
+    let module1_name = DumpString::new("module1", Endian::Little);
+    let module1 = minidump_synth::Module::new(
+        Endian::Little,
+        0x40000000,
+        0x10000,
+        &module1_name,
+        0,
+        0,
+        None,
+    );
+    let module2_name = DumpString::new("module2", Endian::Little);
+    let module2 = minidump_synth::Module::new(
+        Endian::Little,
+        0x50000000,
+        0x10000,
+        &module2_name,
+        0,
+        0,
+        None,
+    );
+
+    // crash (FUNC with lines) -> exported (PUBLIC) -> module2 (no symbols)
+    let (stack, context) = minidump_synth::x86_frame_pointer_stack(
+        Endian::Little,
+        0x40001010,
+        0x80000,
+        &[(0x40002010, 0x20), (0x50003020, 0x10)],
+    );
+    let thread = Thread::new(Endian::Little, 1, &stack, &context);
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_thread(thread)
+        .add_system_info(SystemInfo::new(Endian::Little))
+        .add_module(module1)
+        .add_module(module2)
+        .add(module1_name)
+        .add(module2_name)
+        .add(context)
+        .add_memory(stack);
+
+    let symbols = [
+        "FILE 0 crash.c\n",
+        "FUNC 1000 100 0 crash\n",
+        "1000 100 42 0\n",
+        "PUBLIC 2000 0 exported\n",
+    ];
+    let state = read_synth_dump_with_symbols(dump, "module1", &symbols).await;
+
+    let stats = state.symbolication_stats();
+    assert_eq!(
+        stats,
+        SymbolicationStats {
+            total_frames: 3,
+            symbolized_frames: 2,
+            frames_with_line_info: 1,
+            func_frames: 1,
+            public_frames: 1,
+            unresolved_frames: 1,
+        }
+    );
+    assert_eq!(stats.hit_rate(), Some(2.0 / 3.0));
+    assert_eq!(SymbolicationStats::default().hit_rate(), None);
+}
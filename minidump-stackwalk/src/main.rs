@@ -17,6 +17,8 @@ use minidump_processor::{
     MultiSymbolProvider, PendingProcessorStatSubscriptions, PendingProcessorStats,
     ProcessorOptions, SymbolProvider, Symbolizer,
 };
+#[cfg(feature = "pdb")]
+use minidump_processor::{PdbSymbolSource, SymbolSourceProvider};
 
 use clap::{
     builder::{PossibleValuesParser, TypedValueParser},
@@ -118,7 +120,7 @@ struct Cli {
     ///  
     /// stable-all enables: nothing (currently identical to stable-basic)
     ///  
-    /// unstable-all enables: `--recover-function-args`
+    /// unstable-all enables: `--recover-function-args`, `--use-pdata-unwinding`
     ///  
     /// minidump-stackwalk wants to be a reliable and stable tool, but we also want to be able
     /// to introduce new features which may be experimental or expensive. To balance these two
@@ -166,6 +168,14 @@ struct Cli {
     #[arg(long)]
     pretty: bool,
 
+    /// Output at most this many frames per thread in --json output
+    ///
+    /// Threads with more frames are marked with `"truncated": true`, and their
+    /// `frame_count` is still the number of frames that were walked. This keeps
+    /// the output of deeply recursive stacks bounded.
+    #[arg(long, value_name = "FRAMES")]
+    json_frame_limit: Option<usize>,
+
     /// Provide a briefer --human or --dump report
     ///
     /// For human: Only provides the top-level summary and a backtrace of the crashing thread.
@@ -195,7 +205,30 @@ struct Cli {
     #[arg(long)]
     recover_function_args: bool,
 
+    /// **UNSTABLE** Unwind Windows x64 frames with the unwinding info of their modules
+    ///
+    /// If the minidump's memory contains the `.pdata` and `.xdata` sections of a module,
+    /// they are used to unwind its frames when its symbols have no CFI.
+    #[arg(long)]
+    use_pdata_unwinding: bool,
+
+    /// Ignore the last BYTES at the base of each thread's stack when scanning for return addresses
+    ///
+    /// Stack scanning is the last resort for finding a frame's caller. Some platforms keep
+    /// data that can look like return addresses at the base of the stack (its highest
+    /// addresses), which this avoids.
+    #[arg(long, value_name = "BYTES", default_value_t = 0)]
+    scan_skip_bytes: u64,
+
+    /// Only consider pointer-aligned stack slots when scanning for return addresses
+    #[arg(long)]
+    scan_align_to_pointer: bool,
+
     /// Use debug information from local files referred to by the minidump, if present.
+    ///
+    /// Windows PDBs in the --symbols-path directories are also used, without converting them
+    /// to .sym files, if they're laid out like a symbol server:
+    /// `<path>/<debug file>/<debug id>/<debug file>`.
     #[arg(long)]
     use_local_debuginfo: bool,
 
@@ -388,6 +421,9 @@ async fn main_result() -> std::io::Result<()> {
     // Now overload the defaults
     options.evil_json = cli.evil_json.as_deref();
     options.recover_function_args = cli.recover_function_args;
+    options.use_pdata_unwinding |= cli.use_pdata_unwinding;
+    options.scan_skip_bytes = cli.scan_skip_bytes;
+    options.scan_align_to_pointer = cli.scan_align_to_pointer;
 
     // Register for instractive updates, if we want them
     let interactive_enabled = !json && !cli.no_interactive && cli.output_file.is_none();
@@ -425,6 +461,13 @@ async fn main_result() -> std::io::Result<()> {
 
             if cli.use_local_debuginfo {
                 provider.add(Box::<DebugInfoSymbolProvider>::default());
+
+                #[cfg(feature = "pdb")]
+                {
+                    let mut sources = SymbolSourceProvider::new();
+                    sources.add(Box::new(PdbSymbolSource::new(symbols_paths.clone())));
+                    provider.add(Box::new(sources));
+                }
             }
 
             if !cli.symbols_url.is_empty() {
@@ -489,9 +532,17 @@ async fn main_result() -> std::io::Result<()> {
                     // Print the json output if requested (using "cyborg" output if available).
                     if json {
                         if let Some(mut cyborg_output_f) = cyborg_output_f {
-                            state.print_json(&mut cyborg_output_f, cli.pretty)?;
+                            state.print_json_with_frame_limit(
+                                &mut cyborg_output_f,
+                                cli.pretty,
+                                cli.json_frame_limit,
+                            )?;
                         } else {
-                            state.print_json(&mut output, cli.pretty)?;
+                            state.print_json_with_frame_limit(
+                                &mut output,
+                                cli.pretty,
+                                cli.json_frame_limit,
+                            )?;
                         }
                     }
                     Ok(())
@@ -698,9 +749,21 @@ where
     if let Ok(thread_names) = dump.get_stream::<MinidumpThreadNames>() {
         thread_names.print(output)?;
     }
+    if let Ok(thread_infos) = dump.get_stream::<MinidumpThreadInfoList>() {
+        thread_infos.print(output)?;
+    }
     if let Ok(breakpad_info) = dump.get_stream::<MinidumpBreakpadInfo>() {
         breakpad_info.print(output)?;
     }
+    if let Ok(function_tables) = dump.get_stream::<MinidumpFunctionTableList>() {
+        function_tables.print(output)?;
+    }
+    if let Ok(handle_operations) = dump.get_stream::<MinidumpHandleOperationList>() {
+        handle_operations.print(output)?;
+    }
+    if let Ok(tokens) = dump.get_stream::<MinidumpTokenList>() {
+        tokens.print(output)?;
+    }
     match dump.get_stream::<MinidumpCrashpadInfo>() {
         Ok(crashpad_info) => crashpad_info.print(output)?,
         Err(Error::StreamNotFound) => (),
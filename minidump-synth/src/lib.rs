@@ -27,6 +27,10 @@ use test_assembler::*;
 pub struct SynthMinidump {
     /// The `Section` containing the minidump contents.
     section: Section,
+    /// The signature, for the header.
+    signature: Label,
+    /// The version, for the header.
+    version: Label,
     /// The minidump flags, for the header.
     flags: Label,
     /// The number of streams.
@@ -35,8 +39,8 @@ pub struct SynthMinidump {
     stream_count_label: Label,
     /// The directory's file offset, for the header.
     stream_directory_rva: Label,
-    /// The contents of the stream directory.
-    stream_directory: Section,
+    /// The entries of the stream directory, in the order the streams were added.
+    stream_directory: Vec<Section>,
     /// System info (cpu arch, os, etc.)
     system_info: Option<SystemInfo>,
     /// High level crash info (error code, crash address, assertion, ...)
@@ -55,6 +59,10 @@ pub struct SynthMinidump {
     memory64_list: Option<Memory64ListStream>,
     /// List of extra info about memory regions in this minidump.
     memory_info_list: Option<ExListStream<MemoryInfo>>,
+    /// List of handle operations in this minidump.
+    handle_operation_list: Option<ExListStream<HandleOperation>>,
+    /// List of extra info about threads in this minidump.
+    thread_info_list: Option<ExListStream<ThreadInfo>>,
     /// Crashpad extension containing annotations.
     crashpad_info: Option<CrashpadInfo>,
     /// /proc/self/maps string
@@ -65,10 +73,73 @@ pub struct SynthMinidump {
     linux_cpu_info: Option<SimpleStream>,
     /// /proc/self/environ string
     linux_environ: Option<SimpleStream>,
+    /// /proc/self/cmdline string
+    linux_cmd_line: Option<SimpleStream>,
     /// /proc/self/status string
     linux_proc_status: Option<SimpleStream>,
     /// Continuous memory used by `Memory64List` stream
     memory64_section: Option<Section>,
+    /// An explicit file offset for the stream directory.
+    directory_rva: Option<u32>,
+    /// The type, file offset and size of each stream, in the order they were added.
+    stream_labels: Vec<(u32, Label, Label)>,
+    /// Whether to shuffle the stream directory, see
+    /// [`SynthMinidump::with_out_of_order_directory`].
+    out_of_order_directory: bool,
+    /// Streams to write after the stream directory, with their file offsets.
+    streams_after_directory: Vec<(Label, Section)>,
+}
+
+/// The final layout of a finished [`SynthMinidump`].
+///
+/// This locates the fields of the header and stream directory in the minidump's
+/// contents, so tests (or fuzzing corpus generators) can deterministically mutate them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SynthLayout {
+    /// The file offset of the stream directory.
+    pub directory_rva: u64,
+    /// Every stream, in the order they appear in the stream directory.
+    pub streams: Vec<StreamLayout>,
+}
+
+/// Where a single stream of a [`SynthLayout`] ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamLayout {
+    /// The stream type.
+    pub stream_type: u32,
+    /// The file offset of the stream's `MINIDUMP_DIRECTORY` entry.
+    pub entry_offset: u64,
+    /// The file offset of the stream's contents.
+    pub rva: u64,
+    /// The size of the stream's contents.
+    pub size: u64,
+}
+
+impl SynthLayout {
+    /// The file offset of the header's `stream_count` field.
+    pub const STREAM_COUNT_OFFSET: u64 = 8;
+    /// The file offset of the header's `stream_directory_rva` field.
+    pub const DIRECTORY_RVA_OFFSET: u64 = 12;
+
+    /// Get the first stream of type `stream_type`.
+    pub fn stream<S: Into<u32>>(&self, stream_type: S) -> Option<&StreamLayout> {
+        let stream_type = stream_type.into();
+        self.streams
+            .iter()
+            .find(|stream| stream.stream_type == stream_type)
+    }
+}
+
+impl StreamLayout {
+    /// The file offset of the `data_size` field of the stream's directory entry.
+    pub fn size_field_offset(&self) -> u64 {
+        self.entry_offset + 4
+    }
+
+    /// The file offset of the `rva` field of the stream's directory entry.
+    pub fn rva_field_offset(&self) -> u64 {
+        self.entry_offset + 8
+    }
 }
 
 /// A block of data contained in a minidump.
@@ -164,12 +235,14 @@ impl SynthMinidump {
 
     /// Create a `SynthMinidump` with `endian` endianness.
     pub fn with_endian(endian: Endian) -> SynthMinidump {
+        let signature = Label::new();
+        let version = Label::new();
         let flags = Label::new();
         let stream_count_label = Label::new();
         let stream_directory_rva = Label::new();
         let section = Section::with_endian(endian)
-            .D32(md::MINIDUMP_SIGNATURE)
-            .D32(md::MINIDUMP_VERSION)
+            .D32(&signature)
+            .D32(&version)
             .D32(&stream_count_label)
             .D32(&stream_directory_rva)
             .D32(0)
@@ -181,11 +254,13 @@ impl SynthMinidump {
 
         SynthMinidump {
             section,
+            signature,
+            version,
             flags,
             stream_count: 0,
             stream_count_label,
             stream_directory_rva,
-            stream_directory: Section::with_endian(endian),
+            stream_directory: Vec::new(),
             system_info: None,
             exception: None,
             module_list: Some(ListStream::new(
@@ -218,22 +293,199 @@ impl SynthMinidump {
                 mem::size_of::<md::MINIDUMP_MEMORY_INFO>(),
                 endian,
             )),
+            handle_operation_list: Some(ExListStream::new(
+                md::MINIDUMP_STREAM_TYPE::HandleOperationListStream,
+                md::AVRF_HANDLE_OPERATION::size_with(&LE),
+                endian,
+            )),
+            thread_info_list: Some(ExListStream::new(
+                md::MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
+                md::MINIDUMP_THREAD_INFO::size_with(&LE),
+                endian,
+            )),
             linux_maps: None,
             linux_lsb_release: None,
             linux_environ: None,
+            linux_cmd_line: None,
             linux_cpu_info: None,
             linux_proc_status: None,
             crashpad_info: None,
             memory64_section: Some(memory64_section),
+            directory_rva: None,
+            stream_labels: Vec::new(),
+            out_of_order_directory: false,
+            streams_after_directory: Vec::new(),
         }
     }
 
+    /// Create a `SynthMinidump` of a typical crashed process, e.g. for benchmarks.
+    ///
+    /// The minidump is little-endian, comes from Windows, and contains:
+    ///
+    /// * system info for `arch`, which must be x86 or amd64
+    /// * a module named `typical.exe` at [`TYPICAL_MODULE_BASE`], whose CodeView record
+    ///   refers to `typical.pdb` with the debug identifier [`TYPICAL_DEBUG_ID`]
+    /// * `num_threads` threads with ids starting at 1, each with its own stack memory
+    /// * an access violation on the first thread
+    ///
+    /// Each stack holds a chain of frames, linked by frame pointers, that unwinds to
+    /// `stack_depth` frames. Frame `i` executes in the 0x100 byte "function" at offset
+    /// `0x1000 + i * 0x100` of the module, so a symbol file with matching `FUNC`
+    /// records can symbolicate all of them.
+    ///
+    /// # Panics
+    ///
+    /// If `arch` isn't x86 or amd64, or `stack_depth` is 0.
+    pub fn typical(
+        arch: md::ProcessorArchitecture,
+        num_threads: u32,
+        stack_depth: u32,
+    ) -> SynthMinidump {
+        use md::ProcessorArchitecture::*;
+
+        assert!(stack_depth > 0, "a stack needs at least one frame");
+        let word_size = match arch {
+            PROCESSOR_ARCHITECTURE_INTEL => 4,
+            PROCESSOR_ARCHITECTURE_AMD64 => 8,
+            _ => panic!(
+                "unsupported architecture for a typical minidump: {:?}",
+                arch
+            ),
+        };
+        let endian = Endian::Little;
+        let function_address = |i: u32| TYPICAL_MODULE_BASE + 0x1000 + u64::from(i) * 0x100;
+
+        let system_info = SystemInfo::new(endian)
+            .set_processor_architecture(arch as u16)
+            .set_platform_id(md::PlatformId::VER_PLATFORM_WIN32_NT as u32);
+
+        let module_name = DumpString::new("typical.exe", endian);
+        let cv_record = Section::with_endian(endian)
+            .D32(md::CvSignature::Pdb70 as u32)
+            .D32(0xabcd1234) // signature, a GUID
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1) // age
+            .append_bytes(b"typical.pdb\0");
+        let module = Module::new(
+            endian,
+            TYPICAL_MODULE_BASE,
+            0x1000 + stack_depth * 0x100,
+            &module_name,
+            0x5f5e100,
+            0,
+            None,
+        )
+        .cv_record(&cv_record);
+
+        let mut dump = SynthMinidump::with_endian(endian)
+            .add_system_info(system_info)
+            .add_module(module)
+            .add(module_name)
+            .add(cv_record);
+
+        for thread_idx in 0..num_threads {
+            let thread_id = thread_idx + 1;
+            let stack_base = 0x100000 * u64::from(thread_id);
+
+            // Each frame is a saved frame pointer, a return address, and a couple of
+            // words of locals. The outermost frame returns to 0, which ends the stack.
+            let frame_size = 4 * word_size;
+            let mut stack = Section::with_endian(endian);
+            for frame in 0..stack_depth {
+                let (caller_fp, return_address) = if frame + 1 < stack_depth {
+                    (
+                        stack_base + u64::from(frame + 1) * frame_size,
+                        function_address(frame + 1) + 0x80,
+                    )
+                } else {
+                    (0, 0)
+                };
+                for value in [caller_fp, return_address, 0, 0] {
+                    stack = match word_size {
+                        4 => stack.D32(value as u32),
+                        _ => stack.D64(value),
+                    };
+                }
+            }
+            let stack = stack.append_repeated(0, 0x100);
+            let stack = Memory::with_section(stack, stack_base);
+
+            let ip = function_address(0) + 0x10;
+            let context = match arch {
+                PROCESSOR_ARCHITECTURE_INTEL => x86_context_with_frame_pointer(
+                    endian,
+                    ip as u32,
+                    stack_base as u32,
+                    stack_base as u32,
+                ),
+                _ => amd64_context_with_frame_pointer(endian, ip, stack_base, stack_base),
+            };
+            let thread = Thread::new(endian, thread_id, &stack, &context);
+
+            if thread_idx == 0 {
+                let mut exception = Exception::new(endian);
+                exception.thread_id = thread_id;
+                exception.exception_record.exception_code = 0xc0000005; // EXCEPTION_ACCESS_VIOLATION
+                exception.exception_record.exception_address = ip;
+                exception.exception_record.number_parameters = 2;
+                exception.exception_record.exception_information[1] = 0x8; // the address read
+                dump = dump.add_exception(exception.context(&context));
+            }
+
+            dump = dump.add_thread(thread).add(context).add_memory(stack);
+        }
+
+        dump
+    }
+
+    /// Write `signature` in the header instead of [`md::MINIDUMP_SIGNATURE`].
+    pub fn with_signature(self, signature: u32) -> SynthMinidump {
+        self.signature.set_const(signature as u64);
+        self
+    }
+
+    /// Write `version` in the header instead of [`md::MINIDUMP_VERSION`].
+    ///
+    /// The low 16 bits are the format version, and the high 16 bits are
+    /// implementation-specific.
+    pub fn with_version(self, version: u32) -> SynthMinidump {
+        self.version.set_const(version as u64);
+        self
+    }
+
     /// Set the minidump flags to `flags`.
     pub fn flags(self, flags: u64) -> SynthMinidump {
         self.flags.set_const(flags);
         self
     }
 
+    /// Place the stream directory at file offset `rva`.
+    ///
+    /// By default the directory is written right after the last stream. With this the
+    /// dump is padded with zeros up to `rva` first. If `rva` is before the end of the
+    /// streams, [`SynthMinidump::finish`] returns `None`.
+    pub fn with_directory_rva(mut self, rva: u32) -> SynthMinidump {
+        self.directory_rva = Some(rva);
+        self
+    }
+
+    /// Write a stream directory that doesn't follow the layout of the streams.
+    ///
+    /// The directory entries are written in the reverse of the order the streams were
+    /// added, and the contents of every other stream are written after the directory
+    /// instead of before it. The directory itself still goes at the offset given to
+    /// [`SynthMinidump::with_directory_rva`], if any.
+    ///
+    /// Only streams added after this is called can be moved after the directory. The
+    /// streams built from the `add_*` methods (like the thread list) are always added
+    /// when the minidump is finished, so this applies to all of them.
+    pub fn with_out_of_order_directory(mut self) -> SynthMinidump {
+        self.out_of_order_directory = true;
+        self
+    }
+
     /// Append `section` to `self`, setting its location appropriately.
     // Perhaps should have been called .add_section().
     #[allow(clippy::should_implement_trait)]
@@ -299,6 +551,24 @@ impl SynthMinidump {
         self
     }
 
+    /// Add `operation` to `self`, adding it to the handle operation list stream as well.
+    pub fn add_handle_operation(mut self, operation: HandleOperation) -> SynthMinidump {
+        self.handle_operation_list = self
+            .handle_operation_list
+            .take()
+            .map(|operation_list| operation_list.add(operation));
+        self
+    }
+
+    /// Add `info` to `self`, adding it to the thread info list stream as well.
+    pub fn add_thread_info(mut self, info: ThreadInfo) -> SynthMinidump {
+        self.thread_info_list = self
+            .thread_info_list
+            .take()
+            .map(|info_list| info_list.add(info));
+        self
+    }
+
     /// Add `thread` to `self`, adding it to the thread list stream as well.
     pub fn add_thread(mut self, thread: Thread) -> SynthMinidump {
         self.thread_list = self
@@ -371,6 +641,15 @@ impl SynthMinidump {
         self
     }
 
+    /// Set the contents of the `LinuxCmdLine` stream.
+    pub fn set_linux_cmd_line(mut self, cmd_line: &[u8]) -> SynthMinidump {
+        self.linux_cmd_line = Some(SimpleStream {
+            stream_type: md::MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+            section: Section::new().append_bytes(cmd_line),
+        });
+        self
+    }
+
     /// Set the contents of the `LinuxEnviron` stream.
     pub fn set_linux_environ(mut self, environ: &[u8]) -> SynthMinidump {
         self.linux_environ = Some(SimpleStream {
@@ -382,9 +661,24 @@ impl SynthMinidump {
 
     /// Append `stream` to `self`, setting its location appropriately and adding it to the stream directory.
     pub fn add_stream<T: Stream>(mut self, stream: T) -> SynthMinidump {
-        self.stream_directory = stream.cite_stream_in(self.stream_directory);
+        self.stream_labels.push((
+            stream.stream_type(),
+            stream.file_offset(),
+            stream.file_size(),
+        ));
+        self.stream_directory
+            .push(stream.cite_stream_in(Section::with_endian(self.section.endian)));
         self.stream_count += 1;
-        self.add(stream)
+        // Move every other stream after the directory.
+        if self.out_of_order_directory
+            && self.streams_after_directory.len() < self.stream_count as usize / 2
+        {
+            self.streams_after_directory
+                .push((stream.file_offset(), stream.into()));
+            self
+        } else {
+            self.add(stream)
+        }
     }
 
     fn finish_list<T: ListItem>(self, list: Option<ListStream<T>>) -> SynthMinidump {
@@ -414,7 +708,13 @@ impl SynthMinidump {
     }
 
     /// Finish generating the minidump and return the contents.
-    pub fn finish(mut self) -> Option<Vec<u8>> {
+    pub fn finish(self) -> Option<Vec<u8>> {
+        self.finish_with_layout().map(|(contents, _)| contents)
+    }
+
+    /// Finish generating the minidump and return the contents, along with
+    /// where the stream directory and streams ended up.
+    pub fn finish_with_layout(mut self) -> Option<(Vec<u8>, SynthLayout)> {
         // Add module list stream if any modules were added.
         let modules = self.module_list.take();
         self = self.finish_list(modules);
@@ -433,6 +733,12 @@ impl SynthMinidump {
         // Add memory info list stream if any memory infos were added.
         let memory_infos = self.memory_info_list.take();
         self = self.finish_ex_list(memory_infos);
+        // Add handle operation list stream if any handle operations were added.
+        let handle_operations = self.handle_operation_list.take();
+        self = self.finish_ex_list(handle_operations);
+        // Add thread info list stream if any thread infos were added.
+        let thread_infos = self.thread_info_list.take();
+        self = self.finish_ex_list(thread_infos);
         // Add thread list stream if any threads were added.
         let threads = self.thread_list.take();
         self = self.finish_list(threads);
@@ -464,28 +770,78 @@ impl SynthMinidump {
         if let Some(stream) = self.linux_environ.take() {
             self = self.add_stream(stream);
         }
+        if let Some(stream) = self.linux_cmd_line.take() {
+            self = self.add_stream(stream);
+        }
         if let Some(memory64_section) = self.memory64_section.take() {
             self = self.add(memory64_section);
         }
 
         let SynthMinidump {
             section,
+            signature,
+            version,
             flags,
             stream_count,
             stream_count_label,
             stream_directory_rva,
-            stream_directory,
+            mut stream_directory,
+            directory_rva,
+            mut stream_labels,
+            out_of_order_directory,
+            streams_after_directory,
             ..
         } = self;
+        if signature.value().is_none() {
+            signature.set_const(md::MINIDUMP_SIGNATURE as u64);
+        }
+        if version.value().is_none() {
+            version.set_const(md::MINIDUMP_VERSION as u64);
+        }
         if flags.value().is_none() {
             flags.set_const(0);
         }
+        let section = match directory_rva {
+            Some(rva) => {
+                let padding = (rva as u64).checked_sub(section.size())?;
+                section.append_repeated(0, padding as usize)
+            }
+            None => section,
+        };
         // Create the stream directory.
         stream_count_label.set_const(stream_count as u64);
-        section
-            .mark(&stream_directory_rva)
-            .append_section(stream_directory)
-            .get_contents()
+        let mut section = section.mark(&stream_directory_rva);
+        if out_of_order_directory {
+            stream_directory.reverse();
+            stream_labels.reverse();
+        }
+        for entry in stream_directory {
+            section = section.append_section(entry);
+        }
+        for (offset, stream) in streams_after_directory {
+            section = section.mark(&offset).append_section(stream);
+        }
+        let contents = section.get_contents()?;
+
+        let directory_rva = stream_directory_rva.value()?;
+        let entry_size = mem::size_of::<md::MINIDUMP_DIRECTORY>() as u64;
+        let streams = stream_labels
+            .into_iter()
+            .enumerate()
+            .map(|(i, (stream_type, rva, size))| {
+                Some(StreamLayout {
+                    stream_type,
+                    entry_offset: directory_rva + i as u64 * entry_size,
+                    rva: rva.value()?,
+                    size: size.value()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let layout = SynthLayout {
+            directory_rva,
+            streams,
+        };
+        Some((contents, layout))
     }
 }
 
@@ -859,21 +1215,26 @@ pub struct DumpString {
 impl DumpString {
     /// Create a new `DumpString` with `s` as its contents, using `endian` endianness.
     pub fn new(s: &str, endian: Endian) -> DumpString {
-        let u16_s = s
-            .encode_utf16()
-            .fold(Vec::with_capacity(s.len() * 2), |mut v, s| {
-                match endian {
-                    Endian::Little => {
-                        v.push((s & 0xff) as u8);
-                        v.push((s >> 8) as u8);
-                    }
-                    Endian::Big => {
-                        v.push((s >> 8) as u8);
-                        v.push((s & 0xff) as u8);
-                    }
+        DumpString::from_utf16(s.encode_utf16(), endian)
+    }
+
+    /// Create a new `DumpString` from raw UTF-16 code units, using `endian` endianness.
+    ///
+    /// Unlike [`DumpString::new`], this can produce malformed UTF-16 (like lone surrogates).
+    pub fn from_utf16(units: impl IntoIterator<Item = u16>, endian: Endian) -> DumpString {
+        let u16_s = units.into_iter().fold(Vec::new(), |mut v, s| {
+            match endian {
+                Endian::Little => {
+                    v.push((s & 0xff) as u8);
+                    v.push((s >> 8) as u8);
+                }
+                Endian::Big => {
+                    v.push((s >> 8) as u8);
+                    v.push((s & 0xff) as u8);
                 }
-                v
-            });
+            }
+            v
+        });
 
         let section = Section::with_endian(endian)
             .D32(u16_s.len() as u32)
@@ -913,6 +1274,12 @@ impl From<DumpUtf8String> for Section {
 
 impl_dumpsection!(DumpUtf8String);
 
+/// The base address of the module in [`SynthMinidump::typical`].
+pub const TYPICAL_MODULE_BASE: u64 = 0x400000;
+
+/// The breakpad debug identifier of the module in [`SynthMinidump::typical`].
+pub const TYPICAL_DEBUG_ID: &str = "ABCD1234F00DBEEF01020304050607081";
+
 /// A fixed set of version info to use for tests.
 pub const STOCK_VERSION_INFO: md::VS_FIXEDFILEINFO = md::VS_FIXEDFILEINFO {
     signature: md::VS_FFI_SIGNATURE,
@@ -1041,6 +1408,9 @@ impl From<UnloadedModule> for Section {
 /// A minidump thread.
 pub struct Thread {
     section: Section,
+    priority_class: Label,
+    priority: Label,
+    teb: Label,
 }
 
 impl Thread {
@@ -1048,15 +1418,36 @@ impl Thread {
     where
         T: DumpSection,
     {
+        let priority_class = Label::new();
+        let priority = Label::new();
+        let teb = Label::new();
         let section = Section::with_endian(endian)
             .D32(id)
             .D32(0) // suspend_count
-            .D32(0) // priority_class
-            .D32(0) // priority
-            .D64(0) // teb
+            .D32(&priority_class)
+            .D32(&priority)
+            .D64(&teb)
             .cite_memory(stack)
             .cite_location(context);
-        Thread { section }
+        Thread {
+            section,
+            priority_class,
+            priority,
+            teb,
+        }
+    }
+
+    /// Set the thread's priority class and priority level, which are 0 otherwise.
+    pub fn priority(self, priority_class: u32, priority: u32) -> Thread {
+        self.priority_class.set_const(priority_class as u64);
+        self.priority.set_const(priority as u64);
+        self
+    }
+
+    /// Set the address of the thread's TEB, which is 0 otherwise.
+    pub fn teb(self, teb: u64) -> Thread {
+        self.teb.set_const(teb);
+        self
     }
 }
 
@@ -1064,10 +1455,49 @@ impl_dumpsection!(Thread);
 
 impl From<Thread> for Section {
     fn from(thread: Thread) -> Self {
+        if thread.priority_class.value().is_none() {
+            thread.priority_class.set_const(0);
+        }
+        if thread.priority.value().is_none() {
+            thread.priority.set_const(0);
+        }
+        if thread.teb.value().is_none() {
+            thread.teb.set_const(0);
+        }
         thread.section
     }
 }
 
+/// Extra information about a minidump thread.
+pub struct ThreadInfo {
+    section: Section,
+}
+
+impl ThreadInfo {
+    pub fn new(endian: Endian, thread_id: u32, start_address: u64, affinity: u64) -> ThreadInfo {
+        let section = Section::with_endian(endian)
+            .D32(thread_id)
+            .D32(0) // dump_flags
+            .D32(0) // dump_error
+            .D32(0) // exit_status
+            .D64(0) // create_time
+            .D64(0) // exit_time
+            .D64(0) // kernel_time
+            .D64(0) // user_time
+            .D64(start_address)
+            .D64(affinity);
+        ThreadInfo { section }
+    }
+}
+
+impl_dumpsection!(ThreadInfo);
+
+impl From<ThreadInfo> for Section {
+    fn from(info: ThreadInfo) -> Self {
+        info.section
+    }
+}
+
 /// A minidump thread name.
 pub struct ThreadName {
     section: Section,
@@ -1160,6 +1590,44 @@ impl From<MemoryInfo> for Section {
     }
 }
 
+/// A handle operation recorded by Application Verifier.
+pub struct HandleOperation {
+    section: Section,
+}
+
+impl HandleOperation {
+    pub fn new(
+        endian: Endian,
+        handle: u64,
+        process_id: u32,
+        thread_id: u32,
+        operation_type: u32,
+        backtrace: &[u64],
+    ) -> HandleOperation {
+        assert!(backtrace.len() <= md::AVRF_MAX_TRACES);
+        let mut section = Section::with_endian(endian)
+            .D64(handle)
+            .D32(process_id)
+            .D32(thread_id)
+            .D32(operation_type)
+            .D32(0) // spare0
+            .D32(backtrace.len() as u32)
+            .D32(0); // index
+        for i in 0..md::AVRF_MAX_TRACES {
+            section = section.D64(backtrace.get(i).copied().unwrap_or(0));
+        }
+        HandleOperation { section }
+    }
+}
+
+impl_dumpsection!(HandleOperation);
+
+impl From<HandleOperation> for Section {
+    fn from(operation: HandleOperation) -> Self {
+        operation.section
+    }
+}
+
 /// MINIDUMP_MISC_INFO stream.
 ///
 /// Fields that must be initialized together (i.e. because they are guarded
@@ -1431,43 +1899,262 @@ impl Stream for MiscStream {
     }
 }
 
+/// The x87 FPU and SSE registers of a CPU context.
+///
+/// See [`fxsave_area`] for how these are laid out.
+#[derive(Clone, Debug, Default)]
+pub struct FloatRegisters {
+    pub control_word: u16,
+    pub status_word: u16,
+    /// The abridged tag word of FXSAVE, with a bit set for each valid ST register.
+    pub tag_word: u8,
+    pub mx_csr: u32,
+    /// ST0-ST7 (aliased by MM0-MM7). Only the low 80 bits of each are used.
+    pub st_registers: [u128; 8],
+    /// XMM0-XMM15. x86 contexts only have XMM0-XMM7.
+    pub xmm_registers: [u128; 16],
+}
+
+/// Append `value` to `section` as a 128-bit integer in `endian` byte order.
+fn append_u128(section: Section, endian: Endian, value: u128) -> Section {
+    let (low, high) = (value as u64, (value >> 64) as u64);
+    match endian {
+        Endian::Little => section.D64(low).D64(high),
+        Endian::Big => section.D64(high).D64(low),
+    }
+}
+
+/// Lay out `float` as the legacy FXSAVE area (an `XMM_SAVE_AREA32`).
+///
+/// This is the `float_save` of a `CONTEXT_AMD64`, and the `extended_registers` of
+/// a `CONTEXT_X86`.
+pub fn fxsave_area(endian: Endian, float: &FloatRegisters) -> Section {
+    let mut section = Section::with_endian(endian)
+        .D16(float.control_word)
+        .D16(float.status_word)
+        .D8(float.tag_word)
+        .D8(0) // reserved1
+        .D16(0) // error_opcode
+        .D32(0) // error_offset
+        .D16(0) // error_selector
+        .D16(0) // reserved2
+        .D32(0) // data_offset
+        .D16(0) // data_selector
+        .D16(0) // reserved3
+        .D32(float.mx_csr)
+        .D32(0); // mx_csr_mask
+    for &register in float.st_registers.iter().chain(&float.xmm_registers) {
+        section = append_u128(section, endian, register);
+    }
+    let section = section.append_repeated(0, 96); // reserved4
+    assert_eq!(section.size(), md::XMM_SAVE_AREA32::size_with(&LE) as u64);
+    section
+}
+
+/// Lay out `float` as the legacy FNSAVE area of a `CONTEXT_X86` (a `FLOATING_SAVE_AREA_X86`).
+fn fnsave_area(endian: Endian, float: &FloatRegisters) -> Section {
+    // Unlike FXSAVE, FNSAVE has two bits per register, with 0b11 meaning empty.
+    let tag_word = (0..8)
+        .filter(|i| float.tag_word & (1 << i) == 0)
+        .fold(0, |tag_word, i| tag_word | (0b11 << (2 * i)));
+    let mut section = Section::with_endian(endian)
+        .D32(float.control_word as u32)
+        .D32(float.status_word as u32)
+        .D32(tag_word)
+        .append_repeated(0, 4 * 4); // error_offset, error_selector, data_offset, data_selector
+    for &register in &float.st_registers {
+        // The registers are 80 bits, packed together.
+        let bytes = match endian {
+            Endian::Little => register.to_le_bytes()[..10].to_vec(),
+            Endian::Big => register.to_be_bytes()[6..].to_vec(),
+        };
+        section = section.append_bytes(&bytes);
+    }
+    let section = section.D32(0); // cr0_npx_state
+    assert_eq!(
+        section.size(),
+        md::FLOATING_SAVE_AREA_X86::size_with(&LE) as u64
+    );
+    section
+}
+
 /// Populate a `CONTEXT_X86` struct with the given `endian`, `eip`, and `esp`.
 pub fn x86_context(endian: Endian, eip: u32, esp: u32) -> Section {
+    x86_context_with_frame_pointer(endian, eip, esp, 0)
+}
+
+/// Populate a `CONTEXT_X86` struct with the given `endian`, `eip`, `esp`, and `ebp`.
+pub fn x86_context_with_frame_pointer(endian: Endian, eip: u32, esp: u32, ebp: u32) -> Section {
+    x86_context_full(endian, eip, esp, ebp, None)
+}
+
+/// Populate a `CONTEXT_X86` struct with the given `endian`, `eip`, `esp`, and
+/// floating point registers.
+///
+/// The registers are saved in both the FNSAVE (`float_save`) and FXSAVE
+/// (`extended_registers`) formats.
+pub fn x86_context_with_float_registers(
+    endian: Endian,
+    eip: u32,
+    esp: u32,
+    float: &FloatRegisters,
+) -> Section {
+    x86_context_full(endian, eip, esp, 0, Some(float))
+}
+
+fn x86_context_full(
+    endian: Endian,
+    eip: u32,
+    esp: u32,
+    ebp: u32,
+    float: Option<&FloatRegisters>,
+) -> Section {
+    let (float_save, extended_registers) = match float {
+        Some(float) => (fnsave_area(endian, float), fxsave_area(endian, float)),
+        None => (
+            Section::new().append_repeated(0, md::FLOATING_SAVE_AREA_X86::size_with(&LE)),
+            Section::new().append_repeated(0, 512),
+        ),
+    };
     let section = Section::with_endian(endian)
         .D32(0x1007f) // context_flags: CONTEXT_ALL
         .append_repeated(0, 4 * 6) // dr0,1,2,3,6,7, 4 bytes each
-        .append_repeated(0, md::FLOATING_SAVE_AREA_X86::size_with(&LE)) // float_save
-        .append_repeated(0, 4 * 11) // gs-ebp, 4 bytes each
+        .append_section(float_save)
+        .append_repeated(0, 4 * 10) // gs-eax, 4 bytes each
+        .D32(ebp)
         .D32(eip)
         .D32(0) // cs
         .D32(0) // eflags
         .D32(esp)
         .D32(0) // ss
-        .append_repeated(0, 512); // extended_registers
+        .append_section(extended_registers);
     assert_eq!(section.size(), md::CONTEXT_X86::size_with(&LE) as u64);
     section
 }
 
 /// Populate a `CONTEXT_AMD64` struct with the given `endian`, `rip`, and `rsp`.
 pub fn amd64_context(endian: Endian, rip: u64, rsp: u64) -> Section {
+    amd64_context_with_frame_pointer(endian, rip, rsp, 0)
+}
+
+/// Populate a `CONTEXT_AMD64` struct with the given `endian`, `rip`, `rsp`, and `rbp`.
+pub fn amd64_context_with_frame_pointer(endian: Endian, rip: u64, rsp: u64, rbp: u64) -> Section {
+    amd64_context_full(endian, rip, rsp, rbp, &FloatRegisters::default())
+}
+
+/// Populate a `CONTEXT_AMD64` struct with the given `endian`, `rip`, `rsp`, and
+/// floating point registers.
+pub fn amd64_context_with_float_registers(
+    endian: Endian,
+    rip: u64,
+    rsp: u64,
+    float: &FloatRegisters,
+) -> Section {
+    amd64_context_full(endian, rip, rsp, 0, float)
+}
+
+fn amd64_context_full(
+    endian: Endian,
+    rip: u64,
+    rsp: u64,
+    rbp: u64,
+    float: &FloatRegisters,
+) -> Section {
     let section = Section::with_endian(endian)
         .append_repeated(0, mem::size_of::<u64>() * 6) // p[1-6]_home
         .D32(0x10001f) // context_flags: CONTEXT_ALL
-        .D32(0) // mx_csr
+        .D32(float.mx_csr)
         .append_repeated(0, mem::size_of::<u16>() * 6) // cs,ds,es,fs,gs,ss
         .D32(0) // eflags
         .append_repeated(0, mem::size_of::<u64>() * 6) // dr0,1,2,3,6,7
         .append_repeated(0, mem::size_of::<u64>() * 4) // rax,rcx,rdx,rbx
         .D64(rsp)
-        .append_repeated(0, mem::size_of::<u64>() * 11) // rbp-r15
+        .D64(rbp)
+        .append_repeated(0, mem::size_of::<u64>() * 10) // rsi-r15
         .D64(rip)
-        .append_repeated(0, 512) // float_save
+        .append_section(fxsave_area(endian, float)) // float_save
         .append_repeated(0, mem::size_of::<u128>() * 26) // vector_register
         .append_repeated(0, mem::size_of::<u64>() * 6); // trailing stuff
     assert_eq!(section.size(), md::CONTEXT_AMD64::size_with(&LE) as u64);
     section
 }
 
+/// Build an x86 stack of frame-pointer-linked frames, along with a context for the
+/// innermost frame.
+///
+/// See [`amd64_frame_pointer_stack`] for the layout of the frames.
+pub fn x86_frame_pointer_stack(
+    endian: Endian,
+    eip: u32,
+    esp: u32,
+    frames: &[(u32, u32)],
+) -> (Memory, Section) {
+    let frames: Vec<(u64, u64)> = frames
+        .iter()
+        .map(|&(return_address, frame_size)| (return_address as u64, frame_size as u64))
+        .collect();
+    let (memory, ebp) = frame_pointer_stack(endian, 4, esp as u64, &frames);
+    let context = x86_context_with_frame_pointer(endian, eip, esp, ebp as u32);
+    (memory, context)
+}
+
+/// Build an amd64 stack of frame-pointer-linked frames, along with a context for the
+/// innermost frame.
+///
+/// `frames` is a list of `(return_address, frame_size)` pairs, innermost first.
+/// Each frame is `frame_size` bytes long, and ends with the caller's saved frame
+/// pointer followed by `return_address`, so the first frame starts at `rsp` and
+/// each caller starts where its callee ends. The outermost frame's saved frame
+/// pointer points at a null frame pointer and return address just past the last
+/// frame, which ends the chain. The returned context has `rbp` pointing at the
+/// innermost saved frame pointer.
+pub fn amd64_frame_pointer_stack(
+    endian: Endian,
+    rip: u64,
+    rsp: u64,
+    frames: &[(u64, u64)],
+) -> (Memory, Section) {
+    let (memory, rbp) = frame_pointer_stack(endian, 8, rsp, frames);
+    let context = amd64_context_with_frame_pointer(endian, rip, rsp, rbp);
+    (memory, context)
+}
+
+/// Lay out `frames` for [`x86_frame_pointer_stack`] and [`amd64_frame_pointer_stack`],
+/// returning the memory and the innermost frame pointer.
+fn frame_pointer_stack(
+    endian: Endian,
+    word_size: u64,
+    sp: u64,
+    frames: &[(u64, u64)],
+) -> (Memory, u64) {
+    let mut section = Section::with_endian(endian);
+    let mut frame_start = sp;
+    let mut frame_pointers = Vec::with_capacity(frames.len());
+    for &(_, frame_size) in frames {
+        assert!(
+            frame_size >= word_size * 2,
+            "frames need room for a frame pointer and return address"
+        );
+        frame_start += frame_size;
+        frame_pointers.push(frame_start - word_size * 2);
+    }
+    // The terminating frame pointer and return address.
+    frame_pointers.push(frame_start);
+    for (i, &(return_address, frame_size)) in frames.iter().chain(&[(0, word_size * 2)]).enumerate()
+    {
+        let caller_frame_pointer = frame_pointers.get(i + 1).copied().unwrap_or(0);
+        section = section.append_repeated(0, (frame_size - word_size * 2) as usize);
+        section = if word_size == 4 {
+            section
+                .D32(caller_frame_pointer as u32)
+                .D32(return_address as u32)
+        } else {
+            section.D64(caller_frame_pointer).D64(return_address)
+        };
+    }
+    (Memory::with_section(section, sp), frame_pointers[0])
+}
+
 pub struct SectionRef {
     section: Section,
     data_section: Section,
@@ -1771,6 +2458,8 @@ pub struct SystemInfo {
     pub suite_mask: u16,
     pub reserved2: u16,
     pub cpu: CpuInfo,
+    /// The CSD version string, overriding `csd_version_rva` if set.
+    csd_version: Option<Label>,
 }
 
 pub enum CpuInfo {
@@ -1805,6 +2494,7 @@ impl SystemInfo {
                 feature_information: 0,
                 amd_extended_cpu_features: 0,
             },
+            csd_version: None,
         }
     }
 
@@ -1817,6 +2507,19 @@ impl SystemInfo {
         self.platform_id = platform_id;
         self
     }
+
+    pub fn set_version(mut self, major: u32, minor: u32, build: u32) -> Self {
+        self.major_version = major;
+        self.minor_version = minor;
+        self.build_number = build;
+        self
+    }
+
+    /// Set the CSD version to `csd_version`, which must also be added to the minidump.
+    pub fn set_csd_version(mut self, csd_version: &DumpString) -> Self {
+        self.csd_version = Some(csd_version.file_offset());
+        self
+    }
 }
 
 impl_dumpsection!(SystemInfo);
@@ -1833,10 +2536,12 @@ impl From<SystemInfo> for Section {
             .D32(info.major_version)
             .D32(info.minor_version)
             .D32(info.build_number)
-            .D32(info.platform_id)
-            .D32(info.csd_version_rva)
-            .D16(info.suite_mask)
-            .D16(info.reserved2);
+            .D32(info.platform_id);
+        let section = match info.csd_version {
+            Some(csd_version) => section.D32(&csd_version),
+            None => section.D32(info.csd_version_rva),
+        };
+        let section = section.D16(info.suite_mask).D16(info.reserved2);
 
         match info.cpu {
             CpuInfo::X86CpuInfo {
@@ -1868,6 +2573,7 @@ pub struct Exception {
     pub exception_record: ExceptionRecord,
     // TODO: implement this LOCATION_DESCRIPTOR properly
     pub thread_context: (u32, u32),
+    context: Option<(Label, Label)>,
 }
 
 pub struct ExceptionRecord {
@@ -1894,8 +2600,15 @@ impl Exception {
                 exception_information: [0; 15],
             },
             thread_context: (0, 0),
+            context: None,
         }
     }
+
+    /// Point the exception's thread context at `context`, overriding `thread_context`.
+    pub fn context<T: DumpSection>(mut self, context: &T) -> Self {
+        self.context = Some((context.file_size(), context.file_offset()));
+        self
+    }
 }
 
 impl_dumpsection!(Exception);
@@ -1917,9 +2630,12 @@ impl From<Exception> for Section {
             section = section.D64(chunk);
         }
 
-        section = section
-            .D32(info.thread_context.0)
-            .D32(info.thread_context.1);
+        section = match info.context {
+            Some(context) => section.cite_location(&context),
+            None => section
+                .D32(info.thread_context.0)
+                .D32(info.thread_context.1),
+        };
 
         section
     }
@@ -1967,6 +2683,67 @@ fn test_dump_header_bigendian() {
     );
 }
 
+#[test]
+fn test_finish_with_layout() {
+    let stream = SimpleStream {
+        stream_type: 0x47670001,
+        section: Section::with_endian(Endian::Little).append_repeated(0xaa, 0x10),
+    };
+    let dump = SynthMinidump::with_endian(Endian::Little)
+        .add_stream(stream)
+        .add_system_info(SystemInfo::new(Endian::Little));
+    let (mut contents, layout) = dump.finish_with_layout().unwrap();
+
+    assert_eq!(layout.streams.len(), 2);
+    assert_eq!(
+        layout.directory_rva,
+        contents.len() as u64 - 2 * mem::size_of::<md::MINIDUMP_DIRECTORY>() as u64
+    );
+    let directory_rva_offset = SynthLayout::DIRECTORY_RVA_OFFSET as usize;
+    assert_eq!(
+        contents[directory_rva_offset..directory_rva_offset + 4],
+        (layout.directory_rva as u32).to_le_bytes()
+    );
+
+    let stream = layout.stream(0x47670001u32).unwrap();
+    assert_eq!(stream.size, 0x10);
+    assert_eq!(stream.entry_offset, layout.directory_rva);
+    let rva = stream.rva as usize;
+    assert_eq!(contents[rva..rva + 0x10], [0xaa; 0x10]);
+    let system_info = layout
+        .stream(md::MINIDUMP_STREAM_TYPE::SystemInfoStream)
+        .unwrap();
+    assert_eq!(
+        system_info.size,
+        mem::size_of::<md::MINIDUMP_SYSTEM_INFO>() as u64
+    );
+
+    // Mutate the size of the first stream through its directory entry.
+    let size_offset = stream.size_field_offset() as usize;
+    assert_eq!(
+        contents[size_offset..size_offset + 4],
+        0x10u32.to_le_bytes()
+    );
+    contents[size_offset..size_offset + 4].copy_from_slice(&0x20u32.to_le_bytes());
+    let rva_offset = stream.rva_field_offset() as usize;
+    assert_eq!(
+        contents[rva_offset..rva_offset + 4],
+        (stream.rva as u32).to_le_bytes()
+    );
+
+    // The layout of `finish` is the same.
+    let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+        stream_type: 0x47670001,
+        section: Section::with_endian(Endian::Little).append_repeated(0xaa, 0x10),
+    });
+    let (contents, _) = dump.finish_with_layout().unwrap();
+    let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+        stream_type: 0x47670001,
+        section: Section::with_endian(Endian::Little).append_repeated(0xaa, 0x10),
+    });
+    assert_eq!(dump.finish().unwrap(), contents);
+}
+
 #[test]
 fn test_section_cite() {
     let s1 = Section::with_endian(Endian::Little).append_repeated(0, 0x0a);
@@ -3,10 +3,11 @@ use libfuzzer_sys::fuzz_target;
 
 use minidump::{
     MinidumpAssertion, MinidumpBreakpadInfo, MinidumpCrashpadInfo, MinidumpException,
+    MinidumpFunctionTableList, MinidumpHandleOperationList, MinidumpLinuxCmdLine,
     MinidumpLinuxCpuInfo, MinidumpLinuxEnviron, MinidumpLinuxLsbRelease, MinidumpLinuxMaps,
     MinidumpLinuxProcStatus, MinidumpMacCrashInfo, MinidumpMemory64List, MinidumpMemoryInfoList,
     MinidumpMemoryList, MinidumpMiscInfo, MinidumpModuleList, MinidumpSystemInfo,
-    MinidumpThreadList, MinidumpThreadNames, MinidumpUnloadedModuleList,
+    MinidumpThreadList, MinidumpThreadNames, MinidumpTokenList, MinidumpUnloadedModuleList,
 };
 
 fuzz_target!(|data: &[u8]| {
@@ -15,6 +16,9 @@ fuzz_target!(|data: &[u8]| {
         let _ = dump.get_stream::<MinidumpBreakpadInfo>();
         let _ = dump.get_stream::<MinidumpCrashpadInfo>();
         let _ = dump.get_stream::<MinidumpException>();
+        let _ = dump.get_stream::<MinidumpFunctionTableList>();
+        let _ = dump.get_stream::<MinidumpHandleOperationList>();
+        let _ = dump.get_stream::<MinidumpLinuxCmdLine>();
         let _ = dump.get_stream::<MinidumpLinuxCpuInfo>();
         let _ = dump.get_stream::<MinidumpLinuxEnviron>();
         let _ = dump.get_stream::<MinidumpLinuxLsbRelease>();
@@ -29,6 +33,7 @@ fuzz_target!(|data: &[u8]| {
         let _ = dump.get_stream::<MinidumpSystemInfo>();
         let _ = dump.get_stream::<MinidumpThreadNames>();
         let _ = dump.get_stream::<MinidumpThreadList>();
+        let _ = dump.get_stream::<MinidumpTokenList>();
         let _ = dump.get_stream::<MinidumpUnloadedModuleList>();
     }
 });
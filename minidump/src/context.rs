@@ -4,6 +4,7 @@
 //! CPU contexts.
 
 use num_traits::FromPrimitive;
+use scroll::ctx::SizeWith;
 use scroll::{self, Pread};
 use std::collections::HashSet;
 use std::fmt;
@@ -13,6 +14,7 @@ use std::mem;
 use tracing::warn;
 
 use crate::iostuff::*;
+use crate::system_info::Cpu;
 use crate::{MinidumpMiscInfo, MinidumpSystemInfo};
 use minidump_common::format as md;
 use minidump_common::format::ContextFlagsCpu;
@@ -1029,6 +1031,33 @@ pub enum ContextError {
 //======================================================
 // Implementations
 
+/// Get the CPU architecture named by the cpu bits of some `context_flags`.
+fn cpu_from_context_flags(flags: u32) -> Option<Cpu> {
+    let flags = ContextFlagsCpu::from_flags(flags);
+    if flags == ContextFlagsCpu::CONTEXT_X86 {
+        Some(Cpu::X86)
+    } else if flags == ContextFlagsCpu::CONTEXT_AMD64 {
+        Some(Cpu::X86_64)
+    } else if flags == ContextFlagsCpu::CONTEXT_PPC {
+        Some(Cpu::Ppc)
+    } else if flags == ContextFlagsCpu::CONTEXT_PPC64 {
+        Some(Cpu::Ppc64)
+    } else if flags == ContextFlagsCpu::CONTEXT_SPARC {
+        Some(Cpu::Sparc)
+    } else if flags == ContextFlagsCpu::CONTEXT_ARM {
+        Some(Cpu::Arm)
+    } else if flags == ContextFlagsCpu::CONTEXT_ARM64 || flags == ContextFlagsCpu::CONTEXT_ARM64_OLD
+    {
+        Some(Cpu::Arm64)
+    } else if flags == ContextFlagsCpu::CONTEXT_MIPS {
+        Some(Cpu::Mips)
+    } else if flags == ContextFlagsCpu::CONTEXT_MIPS64 {
+        Some(Cpu::Mips64)
+    } else {
+        None
+    }
+}
+
 impl MinidumpContext {
     /// Return a MinidumpContext given a `MinidumpRawContext`.
     pub fn from_raw(raw: MinidumpRawContext) -> MinidumpContext {
@@ -1039,7 +1068,75 @@ impl MinidumpContext {
     }
 
     /// Read a `MinidumpContext` from `bytes`.
+    ///
+    /// If `bytes` is shorter than the context structure (some producers truncate it),
+    /// as much as is present is read. Only the registers that were entirely present
+    /// are marked as valid, and the rest are zero. The `context_flags` must still be
+    /// present.
     pub fn read(
+        bytes: &[u8],
+        endian: scroll::Endian,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Result<MinidumpContext, ContextError> {
+        let size = match Self::context_size(system_info, endian) {
+            Some(size) if bytes.len() < size => size,
+            _ => return Self::read_full(bytes, endian, system_info, misc),
+        };
+
+        // Parse the context padded out with two different fill bytes. A register
+        // comes out the same in both exactly when none of its bytes were padding.
+        let padded = |fill: u8| {
+            let mut padded = vec![fill; size];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            padded
+        };
+        let zeroed = Self::read_full(&padded(0x00), endian, system_info, misc)?;
+        let filled = Self::read_full(&padded(0xff), endian, system_info, misc)?;
+        let valid = zeroed
+            .registers()
+            .zip(filled.registers())
+            .filter(|((_, zeroed), (_, filled))| zeroed == filled)
+            .map(|((reg, _), _)| reg)
+            .collect();
+        warn!(
+            "Cpu context is truncated ({} of {} bytes), only some registers are valid",
+            bytes.len(),
+            size
+        );
+        Ok(MinidumpContext {
+            valid: MinidumpContextValidity::Some(valid),
+            ..zeroed
+        })
+    }
+
+    /// The size of the context structure for the CPU architecture in `system_info`.
+    pub(crate) fn context_size(
+        system_info: &MinidumpSystemInfo,
+        endian: scroll::Endian,
+    ) -> Option<usize> {
+        use md::ProcessorArchitecture::*;
+
+        let size =
+            match md::ProcessorArchitecture::from_u16(system_info.raw.processor_architecture)? {
+                PROCESSOR_ARCHITECTURE_INTEL | PROCESSOR_ARCHITECTURE_IA32_ON_WIN64 => {
+                    md::CONTEXT_X86::size_with(&endian)
+                }
+                PROCESSOR_ARCHITECTURE_AMD64 => md::CONTEXT_AMD64::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_PPC => md::CONTEXT_PPC::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_PPC64 => md::CONTEXT_PPC64::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_SPARC => md::CONTEXT_SPARC::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_ARM => md::CONTEXT_ARM::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_ARM64 => md::CONTEXT_ARM64::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_ARM64_OLD => md::CONTEXT_ARM64_OLD::size_with(&endian),
+                PROCESSOR_ARCHITECTURE_MIPS => md::CONTEXT_MIPS::size_with(&endian),
+                _ => return None,
+            };
+        Some(size)
+    }
+
+    /// Read a complete `MinidumpContext` from `bytes`.
+    fn read_full(
         bytes: &[u8],
         endian: scroll::Endian,
         system_info: &MinidumpSystemInfo,
@@ -1080,10 +1177,7 @@ impl MinidumpContext {
 
                 let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
                 if flags == ContextFlagsCpu::CONTEXT_AMD64 {
-                    if ctx.context_flags & md::CONTEXT_HAS_XSTATE != 0 {
-                        // FIXME: uses MISC_INFO_5 to parse out extra sections here
-                        warn!("Cpu context has extra XSTATE that is being ignored");
-                    }
+                    // Any extra XSTATE is read separately, see `MinidumpXState`.
                     Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
                 } else {
                     Err(ContextError::ReadFailure)
@@ -1144,7 +1238,19 @@ impl MinidumpContext {
 
                 let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
                 if flags == ContextFlagsCpu::CONTEXT_ARM64 {
-                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)))
+                    return Ok(MinidumpContext::from_raw(MinidumpRawContext::Arm64(ctx)));
+                }
+
+                // Windows on ARM emulates x64 code (ARM64EC), and the threads running
+                // it have an x64 context even though the system is ARM64. See
+                // `MinidumpContext::arm64ec_view` for the native registers.
+                let ctx: md::CONTEXT_AMD64 = bytes
+                    .pread_with(0, endian)
+                    .or(Err(ContextError::ReadFailure))?;
+
+                let flags = ContextFlagsCpu::from_flags(ctx.context_flags);
+                if flags == ContextFlagsCpu::CONTEXT_AMD64 {
+                    Ok(MinidumpContext::from_raw(MinidumpRawContext::Amd64(ctx)))
                 } else {
                     Err(ContextError::ReadFailure)
                 }
@@ -1321,6 +1427,177 @@ impl MinidumpContext {
         }
     }
 
+    /// Get the raw `context_flags` value of this context.
+    ///
+    /// Some contexts store their flags as a `u64`, but only the low 32 bits
+    /// are meaningful so they are truncated here.
+    pub fn raw_context_flags(&self) -> u32 {
+        match &self.raw {
+            MinidumpRawContext::X86(ctx) => ctx.context_flags,
+            MinidumpRawContext::Ppc(ctx) => ctx.context_flags,
+            MinidumpRawContext::Ppc64(ctx) => ctx.context_flags as u32,
+            MinidumpRawContext::Amd64(ctx) => ctx.context_flags,
+            MinidumpRawContext::Sparc(ctx) => ctx.context_flags,
+            MinidumpRawContext::Arm(ctx) => ctx.context_flags,
+            MinidumpRawContext::Arm64(ctx) => ctx.context_flags,
+            MinidumpRawContext::OldArm64(ctx) => ctx.context_flags as u32,
+            MinidumpRawContext::Mips(ctx) => ctx.context_flags,
+        }
+    }
+
+    /// Get the CPU architecture this context belongs to.
+    ///
+    /// This is derived from the cpu bits of `context_flags`. Contexts that were
+    /// produced by [`MinidumpContext::read`] have already had those bits checked
+    /// against the architecture in [`MinidumpSystemInfo`]. If the flags don't
+    /// name an architecture (e.g. a context synthesized during unwinding), or
+    /// name one that can't be stored in this kind of context, the architecture
+    /// implied by the context's layout is used instead.
+    pub fn architecture(&self) -> Cpu {
+        let layout_cpu = match self.raw {
+            MinidumpRawContext::X86(_) => Cpu::X86,
+            MinidumpRawContext::Ppc(_) => Cpu::Ppc,
+            MinidumpRawContext::Ppc64(_) => Cpu::Ppc64,
+            MinidumpRawContext::Amd64(_) => Cpu::X86_64,
+            MinidumpRawContext::Sparc(_) => Cpu::Sparc,
+            MinidumpRawContext::Arm(_) => Cpu::Arm,
+            MinidumpRawContext::Arm64(_) | MinidumpRawContext::OldArm64(_) => Cpu::Arm64,
+            MinidumpRawContext::Mips(_) => Cpu::Mips,
+        };
+
+        let flags_cpu = match cpu_from_context_flags(self.raw_context_flags()) {
+            Some(cpu) => cpu,
+            None => return layout_cpu,
+        };
+
+        match (layout_cpu, flags_cpu) {
+            // CONTEXT_MIPS is used for both 32-bit and 64-bit MIPS.
+            (Cpu::Mips, Cpu::Mips64) => Cpu::Mips64,
+            (layout, flags) if layout == flags => flags,
+            (layout, flags) => {
+                warn!(
+                    "context_flags claim {} but the context is laid out for {}",
+                    flags, layout
+                );
+                layout
+            }
+        }
+    }
+
+    /// Guess the CPU architecture of a raw context that hasn't been parsed yet.
+    ///
+    /// [`MinidumpContext::read`] needs [`MinidumpSystemInfo`] to know how to parse a
+    /// context, so this is useful for dumps that lack it. Like breakpad, this
+    /// recognizes amd64 contexts by their size (their `context_flags` aren't at
+    /// the start of the context), and every other context by the cpu bits of the
+    /// `context_flags` they start with.
+    pub fn guess_architecture(bytes: &[u8], endian: scroll::Endian) -> Option<Cpu> {
+        if bytes.len() == md::CONTEXT_AMD64::size_with(&endian) {
+            let ctx: md::CONTEXT_AMD64 = bytes.pread_with(0, endian).ok()?;
+            if cpu_from_context_flags(ctx.context_flags) == Some(Cpu::X86_64) {
+                return Some(Cpu::X86_64);
+            }
+        }
+        let flags: u32 = bytes.pread_with(0, endian).ok()?;
+        cpu_from_context_flags(flags)
+    }
+
+    /// Get the native ARM64 registers of an emulated x64 (ARM64EC) context.
+    ///
+    /// On Windows on ARM, threads running x64 code have an amd64 context, but each
+    /// x64 register is really an ARM64 register under the ARM64EC ABI. The ARM64
+    /// registers without an x64 counterpart live in the x87/MMX registers of the
+    /// `float_save` area, and `v0`-`v15` are the xmm registers.
+    ///
+    /// Returns `None` if this isn't an amd64 context. `x13`, `x14`, `x18`, `x23`,
+    /// `x24` and `x28` aren't available to ARM64EC code, so they are never valid.
+    pub fn arm64ec_view(&self) -> Option<MinidumpContext> {
+        // (ARM64 register, amd64 register)
+        const GPRS: &[(&str, &str)] = &[
+            ("x8", "rax"),
+            ("x0", "rcx"),
+            ("x1", "rdx"),
+            ("x27", "rbx"),
+            ("sp", "rsp"),
+            ("fp", "rbp"),
+            ("x25", "rsi"),
+            ("x26", "rdi"),
+            ("x2", "r8"),
+            ("x3", "r9"),
+            ("x4", "r10"),
+            ("x5", "r11"),
+            ("x19", "r12"),
+            ("x20", "r13"),
+            ("x21", "r14"),
+            ("x22", "r15"),
+            ("pc", "rip"),
+        ];
+        // (ARM64 register, offset into `float_save` of the low 64 bits of mm0-mm7)
+        const MMX: &[(&str, usize)] = &[
+            ("lr", 32),
+            ("x6", 48),
+            ("x7", 64),
+            ("x9", 80),
+            ("x10", 96),
+            ("x11", 112),
+            ("x12", 128),
+            ("x15", 144),
+        ];
+        // x16 and x17 are split into 16-bit pieces across the upper bits of mm0-mm3
+        // and mm4-mm7 respectively.
+        const SPLIT: &[(&str, [usize; 4])] =
+            &[("x16", [40, 56, 72, 88]), ("x17", [104, 120, 136, 152])];
+        const XMM_OFFSET: usize = 160;
+
+        let ctx = match &self.raw {
+            MinidumpRawContext::Amd64(ctx) => ctx,
+            _ => return None,
+        };
+
+        // `float_save` is raw FXSAVE data, which is always little-endian.
+        let float_save = &ctx.float_save[..];
+        let read_u64 = |offset: usize| float_save.pread_with::<u64>(offset, scroll::LE).unwrap();
+        let read_u16 = |offset: usize| float_save.pread_with::<u16>(offset, scroll::LE).unwrap();
+
+        let mut arm64 = md::CONTEXT_ARM64 {
+            context_flags: ContextFlagsCpu::CONTEXT_ARM64.bits(),
+            ..Default::default()
+        };
+        let mut valid = HashSet::new();
+
+        for &(arm64_reg, amd64_reg) in GPRS {
+            if let Some(val) = ctx.get_register(amd64_reg, &self.valid) {
+                arm64.set_register(arm64_reg, val);
+                valid.insert(arm64_reg);
+            }
+        }
+
+        // The float_save area is only known to be present in a complete context.
+        if let MinidumpContextValidity::All = self.valid {
+            for &(reg, offset) in MMX {
+                arm64.set_register(reg, read_u64(offset));
+                valid.insert(reg);
+            }
+            for &(reg, offsets) in SPLIT {
+                let val = offsets.iter().enumerate().fold(0, |val, (i, &offset)| {
+                    val | u64::from(read_u16(offset)) << (16 * i)
+                });
+                arm64.set_register(reg, val);
+                valid.insert(reg);
+            }
+            for (i, v) in arm64.float_regs[..16].iter_mut().enumerate() {
+                *v = float_save
+                    .pread_with(XMM_OFFSET + 16 * i, scroll::LE)
+                    .unwrap();
+            }
+        }
+
+        Some(MinidumpContext {
+            raw: MinidumpRawContext::Arm64(arm64),
+            valid: MinidumpContextValidity::Some(valid),
+        })
+    }
+
     /// Write a human-readable description of this `MinidumpContext` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -1617,6 +1894,150 @@ impl MinidumpContext {
     }
 }
 
+/// The extended processor state (XSTATE) saved along with an amd64 CPU context.
+///
+/// When a context's `context_flags` have [`md::CONTEXT_HAS_XSTATE`] set, the
+/// context is followed by an XSAVE area holding state that doesn't fit in
+/// [`md::CONTEXT_AMD64`], such as the upper halves of the AVX (YMM) and
+/// AVX-512 (ZMM) registers. Where each part ("feature") of it lives is described
+/// by the [`md::XSTATE_CONFIG_FEATURE_MSC_INFO`] in the MiscInfo stream, so that
+/// is required to read it. See its docs for the details of the format.
+///
+/// Registers are returned as their raw bytes, in the order they were in memory
+/// (little-endian, lowest lane first).
+#[derive(Debug, Clone)]
+pub struct MinidumpXState<'a> {
+    /// The bytes of the CPU context, XSAVE area included.
+    bytes: &'a [u8],
+    /// The features that were saved, as described by MiscInfo.
+    config: md::XSTATE_CONFIG_FEATURE_MSC_INFO,
+    /// The features that aren't in their initial (all zero) state.
+    xstate_bv: u64,
+}
+
+impl<'a> MinidumpXState<'a> {
+    /// The offset of the legacy XSAVE area (which is also `float_save`) in the
+    /// context. All feature offsets are relative to this.
+    const XSAVE_OFFSET: usize = 256;
+    /// The offset of the XSAVE header in the legacy XSAVE area.
+    const XSAVE_HEADER_OFFSET: usize = 512;
+    /// The offset of the XMM registers in the legacy XSAVE area.
+    const XMM_OFFSET: usize = 160;
+
+    /// Read the XSTATE of the CPU context in `bytes`.
+    ///
+    /// Returns `None` if the context isn't an amd64 context with
+    /// [`md::CONTEXT_HAS_XSTATE`] set, or if `misc` has no XSTATE information.
+    pub fn read(
+        bytes: &'a [u8],
+        endian: scroll::Endian,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<MinidumpXState<'a>> {
+        if system_info.raw.processor_architecture
+            != md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16
+        {
+            return None;
+        }
+        let ctx: md::CONTEXT_AMD64 = bytes.pread_with(0, endian).ok()?;
+        if ctx.context_flags & md::CONTEXT_HAS_XSTATE == 0 {
+            return None;
+        }
+        let config = misc?.raw.xstate_data()?.clone();
+        let xstate_bv = bytes
+            .pread_with(Self::XSAVE_OFFSET + Self::XSAVE_HEADER_OFFSET, endian)
+            .ok()?;
+
+        Some(MinidumpXState {
+            bytes,
+            config,
+            xstate_bv,
+        })
+    }
+
+    /// The XSTATE configuration this was read with.
+    pub fn config(&self) -> &md::XSTATE_CONFIG_FEATURE_MSC_INFO {
+        &self.config
+    }
+
+    /// A bitmask of the features that were saved, indexed by [`md::XstateFeatureIndex`].
+    pub fn enabled_features(&self) -> u64 {
+        self.config.enabled_features
+    }
+
+    /// The saved bytes of `feature`.
+    ///
+    /// Returns `None` if the feature wasn't saved, or lies outside of the context.
+    /// A feature that is still in its initial state may not have been written by
+    /// the processor, so check [`MinidumpXState::is_initial`] before trusting these.
+    pub fn feature(&self, feature: md::XstateFeatureIndex) -> Option<&'a [u8]> {
+        let idx = feature as usize;
+        if self.config.enabled_features & (1 << idx) == 0 {
+            return None;
+        }
+        let entry = self.config.features[idx];
+        let start = Self::XSAVE_OFFSET + entry.offset as usize;
+        self.bytes
+            .get(start..start.checked_add(entry.size as usize)?)
+    }
+
+    /// Whether `feature` is in its initial state, meaning all of its registers are zero.
+    pub fn is_initial(&self, feature: md::XstateFeatureIndex) -> bool {
+        self.xstate_bv & (1 << feature as usize) == 0
+    }
+
+    /// The value of the `n`th YMM register (`ymm0` through `ymm15`).
+    ///
+    /// The low half is the corresponding XMM register, the high half comes from
+    /// the AVX feature. Returns `None` if AVX state wasn't saved.
+    pub fn ymm(&self, n: usize) -> Option<[u8; 32]> {
+        if n >= 16 {
+            return None;
+        }
+        // The XMM registers are always saved in the legacy area.
+        let xmm = Self::XSAVE_OFFSET + Self::XMM_OFFSET + n * 16;
+        let mut ymm = [0; 32];
+        ymm[..16].copy_from_slice(self.bytes.get(xmm..xmm + 16)?);
+        ymm[16..].copy_from_slice(self.register(md::XstateFeatureIndex::GSSE_AND_AVX, n, 16)?);
+        Some(ymm)
+    }
+
+    /// The value of the `n`th ZMM register (`zmm0` through `zmm31`).
+    ///
+    /// For the first 16 registers, the low half is the corresponding YMM register
+    /// and the high half comes from the AVX-512 `ZMM_Hi256` feature. The other 16
+    /// come from the `Hi16_ZMM` feature. Returns `None` if the needed state wasn't
+    /// saved.
+    pub fn zmm(&self, n: usize) -> Option<[u8; 64]> {
+        let mut zmm = [0; 64];
+        if n < 16 {
+            zmm[..32].copy_from_slice(&self.ymm(n)?);
+            zmm[32..].copy_from_slice(self.register(
+                md::XstateFeatureIndex::AVX512_ZMM_H,
+                n,
+                32,
+            )?);
+        } else if n < 32 {
+            zmm.copy_from_slice(self.register(md::XstateFeatureIndex::ACK512_ZMM, n - 16, 64)?);
+        } else {
+            return None;
+        }
+        Some(zmm)
+    }
+
+    /// Get the `n`th `size`-byte register (or part of one) in `feature`.
+    ///
+    /// This is all zero if the feature is in its initial state.
+    fn register(&self, feature: md::XstateFeatureIndex, n: usize, size: usize) -> Option<&'a [u8]> {
+        const ZEROES: [u8; 64] = [0; 64];
+        let bytes = self.feature(feature)?;
+        if self.is_initial(feature) {
+            return Some(&ZEROES[..size]);
+        }
+        bytes.get(n * size..(n + 1) * size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1646,4 +2067,119 @@ mod tests {
         assert_eq!(context.memoize_register("fp"), Some("fp"));
         assert_eq!(context.memoize_register("foo"), None);
     }
+
+    #[test]
+    fn test_architecture() {
+        fn check(raw: MinidumpRawContext, flags: u32, expected: Cpu) {
+            let context = MinidumpContext::from_raw(raw);
+            assert_eq!(context.raw_context_flags(), flags);
+            assert_eq!(context.architecture(), expected);
+        }
+
+        let cpu = |flags: ContextFlagsCpu| flags.bits() | 0x3;
+
+        // Not all raw contexts implement `Default`, so parse them from zeroes.
+        fn zeroed<T: scroll::ctx::TryFromCtx<'static, scroll::Endian, Error = scroll::Error>>() -> T
+        {
+            const ZEROES: [u8; 4096] = [0; 4096];
+            ZEROES.pread_with(0, scroll::LE).unwrap()
+        }
+
+        check(
+            MinidumpRawContext::X86(md::CONTEXT_X86 {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_X86),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_X86),
+            Cpu::X86,
+        );
+        check(
+            MinidumpRawContext::Amd64(md::CONTEXT_AMD64 {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_AMD64),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_AMD64),
+            Cpu::X86_64,
+        );
+        check(
+            MinidumpRawContext::Ppc(md::CONTEXT_PPC {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_PPC),
+                ..zeroed()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_PPC),
+            Cpu::Ppc,
+        );
+        check(
+            MinidumpRawContext::Ppc64(md::CONTEXT_PPC64 {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_PPC64) as u64,
+                ..zeroed()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_PPC64),
+            Cpu::Ppc64,
+        );
+        check(
+            MinidumpRawContext::Sparc(md::CONTEXT_SPARC {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_SPARC),
+                ..zeroed()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_SPARC),
+            Cpu::Sparc,
+        );
+        check(
+            MinidumpRawContext::Arm(md::CONTEXT_ARM {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_ARM),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_ARM),
+            Cpu::Arm,
+        );
+        check(
+            MinidumpRawContext::Arm64(md::CONTEXT_ARM64 {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_ARM64),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_ARM64),
+            Cpu::Arm64,
+        );
+        check(
+            MinidumpRawContext::OldArm64(md::CONTEXT_ARM64_OLD {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_ARM64_OLD) as u64,
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_ARM64_OLD),
+            Cpu::Arm64,
+        );
+        check(
+            MinidumpRawContext::Mips(md::CONTEXT_MIPS {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_MIPS),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_MIPS),
+            Cpu::Mips,
+        );
+        check(
+            MinidumpRawContext::Mips(md::CONTEXT_MIPS {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_MIPS64),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_MIPS64),
+            Cpu::Mips64,
+        );
+
+        // Contexts without cpu bits fall back to their layout.
+        check(
+            MinidumpRawContext::Amd64(md::CONTEXT_AMD64::default()),
+            0,
+            Cpu::X86_64,
+        );
+        // Mismatched cpu bits also defer to the layout.
+        check(
+            MinidumpRawContext::Arm(md::CONTEXT_ARM {
+                context_flags: cpu(ContextFlagsCpu::CONTEXT_X86),
+                ..Default::default()
+            }),
+            cpu(ContextFlagsCpu::CONTEXT_X86),
+            Cpu::Arm,
+        );
+    }
 }
@@ -72,6 +72,9 @@
 //! * [`MinidumpBreakpadInfo`][]
 //! * [`MinidumpCrashpadInfo`][]
 //! * [`MinidumpException`][]
+//! * [`MinidumpFunctionTableList`][]
+//! * [`MinidumpHandleOperationList`][]
+//! * [`MinidumpLinuxCmdLine`][]
 //! * [`MinidumpLinuxCpuInfo`][]
 //! * [`MinidumpLinuxEnviron`][]
 //! * [`MinidumpLinuxLsbRelease`][]
@@ -83,8 +86,10 @@
 //! * [`MinidumpMiscInfo`][]
 //! * [`MinidumpModuleList`][]
 //! * [`MinidumpSystemInfo`][]
+//! * [`MinidumpThreadInfoList`][]
 //! * [`MinidumpThreadList`][]
 //! * [`MinidumpThreadNames`][]
+//! * [`MinidumpTokenList`][]
 //! * [`MinidumpUnloadedModuleList`][]
 //!
 //!
@@ -235,7 +240,8 @@
 //! `MiniDumpWithUnloadedModules`, you can just check the directory for the
 //! [`MinidumpUnloadedModuleList`][] stream.)
 //!
-//! At `stream_directory_rva` (typically immediately after the header) you will find
+//! At `stream_directory_rva` (anywhere in the file; Breakpad puts it right after the
+//! header, most other producers put it after the streams) you will find
 //! an array of `stream_count` [`MINIDUMP_DIRECTORY`][format::MINIDUMP_DIRECTORY] entries,
 //! with the following layout:
 //!
@@ -353,7 +359,9 @@
 //!
 //! Known members of this family:
 //!
+//! * [`MinidumpHandleOperationList`][] (entries are [`AVRF_HANDLE_OPERATION`][format::AVRF_HANDLE_OPERATION])
 //! * [`MinidumpMemoryInfoList`][] (entries are [`MINIDUMP_MEMORY_INFO`][format::MINIDUMP_MEMORY_INFO])
+//! * [`MinidumpThreadInfoList`][] (entries are [`MINIDUMP_THREAD_INFO`][format::MINIDUMP_THREAD_INFO])
 //! * [`MinidumpUnloadedModuleList`][] (entries are [`MINIDUMP_UNLOADED_MODULE`][format::MINIDUMP_UNLOADED_MODULE])
 //!
 //!
@@ -377,8 +385,9 @@
 //!
 //! Known members of this family:
 //!
+//! * [`MinidumpLinuxCmdLine`][] (no separator, arguments are NUL-delimited)
 //! * [`MinidumpLinuxCpuInfo`][] (separator is `:`)
-//! * [`MinidumpLinuxEnviron`][] (separator is `=`)
+//! * [`MinidumpLinuxEnviron`][] (separator is `=`, entries are NUL-delimited)
 //! * [`MinidumpLinuxLsbRelease`][] (separator is `=`)
 //! * [`MinidumpLinuxProcStatus`][] (separator is `:`)
 //!
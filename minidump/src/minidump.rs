@@ -7,8 +7,10 @@ use num_traits::FromPrimitive;
 use scroll::ctx::{SizeWith, TryFromCtx};
 use scroll::{self, Pread, BE, LE};
 use std::borrow::Cow;
-use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt;
 use std::fs::File;
@@ -98,6 +100,8 @@ pub enum Error {
     DataError,
     #[error("Error reading CodeView data")]
     CodeViewReadFailure,
+    #[error("Truncated minidump: expected at least {expected} bytes, found {actual} bytes")]
+    Truncated { expected: usize, actual: usize },
 }
 
 impl Error {
@@ -118,10 +122,30 @@ impl Error {
             Error::MemoryReadFailure => "MemoryReadFailure",
             Error::DataError => "DataError",
             Error::CodeViewReadFailure => "CodeViewReadFailure",
+            Error::Truncated { .. } => "Truncated",
         }
     }
 }
 
+/// A structural problem with a minidump, found by [`Minidump::validate`].
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ValidationProblem {
+    /// The header or the stream directory can't be read, so nothing else was checked.
+    #[error("{0}")]
+    Unreadable(Error),
+    /// The data of a stream directory entry isn't entirely within the file.
+    #[error(
+        "Stream {index} (type {stream_type:#x}) at {rva:#x} ({data_size} bytes) is out of bounds"
+    )]
+    StreamOutOfBounds {
+        /// The index of the entry in the stream directory.
+        index: u32,
+        stream_type: u32,
+        rva: md::RVA,
+        data_size: u32,
+    },
+}
+
 /// The fundamental unit of data in a `Minidump`.
 pub trait MinidumpStream<'a>: Sized {
     /// The stream type constant used in the `md::MDRawDirectory` entry.
@@ -182,6 +206,8 @@ pub struct MinidumpLinuxMaps<'a> {
     /// Map from address range to index in regions. Use
     /// [`MinidumpLinuxMaps::memory_info_at_address`].
     regions_by_addr: RangeMap<u64, usize>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
 }
 
 /// A memory mapping entry for the process we are analyzing.
@@ -242,6 +268,19 @@ pub struct MinidumpMemoryInfoList<'a> {
     /// Map from address range to index in regions. Use
     /// [`MinidumpMemoryInfoList::memory_info_at_address`].
     regions_by_addr: RangeMap<u64, usize>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
+}
+
+/// The kind of memory backing a region, see [`MinidumpMemoryInfo::region_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegionType {
+    /// The region is mapped into the view of an image section (an executable or library).
+    Image,
+    /// The region is mapped into the view of a section (e.g. a memory-mapped file).
+    Mapped,
+    /// The region is private to the process.
+    Private,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -289,6 +328,21 @@ pub struct MinidumpModule {
     debug_id: Option<DebugId>,
 }
 
+/// The decoded `file_subtype` of a module's version info, see [`MinidumpModule::file_subtype`].
+///
+/// What the subtype means depends on the module's [`md::VersionFileType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionFileSubtype {
+    /// The subtype of a driver ([`md::VersionFileType::VFT_DRV`]).
+    Driver(md::VersionFileSubtypeDriver),
+    /// The subtype of a font ([`md::VersionFileType::VFT_FONT`]).
+    Font(md::VersionFileSubtypeFont),
+    /// The virtual device identifier of a virtual device ([`md::VersionFileType::VFT_VXD`]).
+    VirtualDevice(u32),
+    /// A subtype this library doesn't know how to interpret.
+    Unknown(u32),
+}
+
 /// A list of `MinidumpModule`s contained in a `Minidump`.
 #[derive(Debug, Clone)]
 pub struct MinidumpModuleList {
@@ -304,6 +358,22 @@ pub struct MinidumpThreadNames {
     names: BTreeMap<u32, String>,
 }
 
+/// Extra information about the threads of the process, such as their processor affinity.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpThreadInfoList {
+    /// The thread infos, in the order they were stored in the minidump.
+    thread_infos: Vec<MinidumpThreadInfo>,
+    /// A map of thread id to index in `thread_infos`.
+    thread_ids: HashMap<u32, usize>,
+}
+
+/// Extra information about a single thread.
+#[derive(Debug, Clone)]
+pub struct MinidumpThreadInfo {
+    /// The `MINIDUMP_THREAD_INFO` direct from the minidump file.
+    pub raw: md::MINIDUMP_THREAD_INFO,
+}
+
 /// An executable or shared library that was once loaded into the process, but was unloaded
 /// by the time the `Minidump` was written.
 #[derive(Debug, Clone)]
@@ -344,6 +414,8 @@ pub struct MinidumpThreadList<'a> {
     pub threads: Vec<MinidumpThread<'a>>,
     /// A map of thread id to index in `threads`.
     thread_ids: HashMap<u32, usize>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
 }
 
 /// Information about the system that generated the minidump.
@@ -458,6 +530,12 @@ pub struct MinidumpLinuxEnviron<'a> {
     data: &'a [u8],
 }
 
+/// The command line of the process, from /proc/self/cmdline
+#[derive(Default, Debug)]
+pub struct MinidumpLinuxCmdLine<'a> {
+    data: &'a [u8],
+}
+
 /// Interesting values extracted from /proc/cpuinfo
 #[derive(Default, Debug)]
 pub struct MinidumpLinuxCpuInfo<'a> {
@@ -546,6 +624,8 @@ pub struct MinidumpException<'a> {
     context: Option<&'a [u8]>,
     /// Saved endianess for lazy parsing.
     endian: scroll::Endian,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
 }
 
 /// A list of memory regions included in a minidump.
@@ -556,6 +636,28 @@ pub struct MinidumpMemoryListBase<'a, Descriptor> {
     regions: Vec<MinidumpMemoryBase<'a, Descriptor>>,
     /// Map from address range to index in regions. Use `MinidumpMemoryList::memory_at_address`.
     regions_by_addr: RangeMap<u64, usize>,
+    /// Pairs of indices in regions whose address ranges overlap.
+    overlaps: Vec<(usize, usize)>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
+}
+
+/// How to pick between memory regions whose address ranges overlap.
+///
+/// Well-formed minidumps never contain overlapping memory regions, but some
+/// producers have been observed to emit them. See
+/// [`MinidumpMemoryListBase::resolve_overlaps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryOverlapPolicy {
+    /// Keep the region with the lowest base address and ignore the rest of
+    /// any region that overlaps it. This is the default.
+    #[default]
+    LowestAddress,
+    /// Prefer the region that was written later in the minidump.
+    PreferLater,
+    /// Prefer the larger region, falling back to the later one if they are
+    /// the same size.
+    PreferLarger,
 }
 
 /// A list of memory regions included in a minidump.
@@ -582,6 +684,69 @@ pub struct MinidumpAssertion {
     pub raw: md::MINIDUMP_ASSERTION_INFO,
 }
 
+/// A list of handle operations recorded by Application Verifier.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpHandleOperationList {
+    /// The operations, in the order they were stored in the minidump.
+    operations: Vec<MinidumpHandleOperation>,
+}
+
+/// A list of the dynamic function tables of the process.
+///
+/// Function tables describe how to unwind code that was generated at runtime
+/// (e.g. by a JIT), and so doesn't have unwinding information in an image.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpFunctionTableList<'a> {
+    /// The function tables, in the order they were stored in the minidump.
+    tables: Vec<MinidumpFunctionTable<'a>>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
+}
+
+/// A dynamic function table of the process.
+#[derive(Debug, Clone)]
+pub struct MinidumpFunctionTable<'a> {
+    /// The `MINIDUMP_FUNCTION_TABLE_DESCRIPTOR` direct from the minidump file.
+    pub raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR,
+    /// The OS-specific descriptor of the table (`DYNAMIC_FUNCTION_TABLE` on Windows).
+    pub native_descriptor: &'a [u8],
+    /// The raw function entries of the table.
+    pub entries: &'a [u8],
+    /// The size of each function entry.
+    entry_size: usize,
+    /// The endianness of the minidump which is used for reading the entries.
+    endian: scroll::Endian,
+}
+
+/// A list of the access tokens of the process.
+#[derive(Debug, Clone, Default)]
+pub struct MinidumpTokenList<'a> {
+    /// The tokens, in the order they were stored in the minidump.
+    tokens: Vec<MinidumpToken<'a>>,
+    /// The bytes of the stream this was read from, see `raw_bytes`.
+    stream_bytes: &'a [u8],
+}
+
+/// An access token of the process.
+///
+/// The token's contents are an opaque structure (containing e.g. SIDs and privileges),
+/// so they are only exposed as raw bytes.
+#[derive(Debug, Clone)]
+pub struct MinidumpToken<'a> {
+    /// The `MINIDUMP_TOKEN_INFO_HEADER` direct from the minidump file.
+    pub raw: md::MINIDUMP_TOKEN_INFO_HEADER,
+    /// The token's data.
+    pub data: &'a [u8],
+}
+
+/// An operation on a handle (such as opening or closing it), along with a backtrace
+/// of the thread that performed it.
+#[derive(Debug, Clone)]
+pub struct MinidumpHandleOperation {
+    /// The `AVRF_HANDLE_OPERATION` direct from the minidump file.
+    pub raw: md::AVRF_HANDLE_OPERATION,
+}
+
 /// A typed annotation object.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -669,22 +834,72 @@ fn location_slice<'a>(
         .ok_or(Error::StreamReadFailure)
 }
 
+/// The largest string, in bytes, that will be read from a minidump.
+///
+/// Strings are length-prefixed, so a corrupt or malicious minidump can claim an
+/// enormous one. Longer strings are truncated to this size (real strings, like
+/// module paths, are nowhere near it). Strings that don't fit in the minidump
+/// aren't read at all.
+pub const MAX_STRING_SIZE: usize = 1 << 20;
+
 /// Read a u32 length-prefixed UTF-16 string from `bytes` at `offset`.
+///
+/// Malformed UTF-16 (like lone surrogates) is replaced with U+FFFD, since some
+/// producers write such strings and they shouldn't make the whole stream unreadable.
 fn read_string_utf16(offset: &mut usize, bytes: &[u8], endian: scroll::Endian) -> Option<String> {
+    let (encoding, data) = utf16_string_slice(offset, bytes, endian)?;
+    let (s, had_errors) = encoding.decode_without_bom_handling(data);
+    if had_errors {
+        warn!("replaced malformed UTF-16 in string: {}", s);
+    }
+    Some(s.into())
+}
+
+/// Read a u32 length-prefixed UTF-16 string from `bytes` at `offset`, failing on
+/// malformed UTF-16.
+fn read_string_utf16_strict(
+    offset: &mut usize,
+    bytes: &[u8],
+    endian: scroll::Endian,
+) -> Option<String> {
+    let (encoding, data) = utf16_string_slice(offset, bytes, endian)?;
+    let s = encoding.decode_without_bom_handling_and_without_replacement(data)?;
+    Some(s.into())
+}
+
+/// Get the bytes of a u32 length-prefixed UTF-16 string in `bytes` at `offset`,
+/// along with the encoding to decode them with.
+fn utf16_string_slice<'a>(
+    offset: &mut usize,
+    bytes: &'a [u8],
+    endian: scroll::Endian,
+) -> Option<(&'static encoding_rs::Encoding, &'a [u8])> {
     let u: u32 = bytes.gread_with(offset, endian).ok()?;
     let size = u as usize;
-    if size % 2 != 0 || (*offset + size) > bytes.len() {
+    let end = offset.checked_add(size)?;
+    if !size.is_multiple_of(2) || end > bytes.len() {
         return None;
     }
     let encoding = match endian {
         scroll::Endian::Little => encoding_rs::UTF_16LE,
         scroll::Endian::Big => encoding_rs::UTF_16BE,
     };
-
-    let s = encoding
-        .decode_without_bom_handling_and_without_replacement(&bytes[*offset..*offset + size])?;
-    *offset += size;
-    Some(s.into())
+    let mut data = &bytes[*offset..end];
+    *offset = end;
+    if data.len() > MAX_STRING_SIZE {
+        warn!(
+            "truncating a {} byte string to {} bytes",
+            data.len(),
+            MAX_STRING_SIZE
+        );
+        data = &data[..MAX_STRING_SIZE];
+        // Don't split a surrogate pair.
+        let last: u16 = data.pread_with(data.len() - 2, endian).ok()?;
+        if (0xd800..0xdc00).contains(&last) {
+            data = &data[..data.len() - 2];
+        }
+    }
+    Some((encoding, data))
 }
 
 #[inline]
@@ -694,7 +909,21 @@ fn read_string_utf8_unterminated<'a>(
     endian: scroll::Endian,
 ) -> Option<&'a str> {
     let length: u32 = bytes.gread_with(offset, endian).ok()?;
-    let slice = bytes.gread_with(offset, length as usize).ok()?;
+    let mut slice: &[u8] = bytes.gread_with(offset, length as usize).ok()?;
+    if slice.len() > MAX_STRING_SIZE {
+        warn!(
+            "truncating a {} byte string to {} bytes",
+            slice.len(),
+            MAX_STRING_SIZE
+        );
+        slice = &slice[..MAX_STRING_SIZE];
+        // Don't split a character.
+        if let Err(e) = std::str::from_utf8(slice) {
+            if e.error_len().is_none() {
+                slice = &slice[..e.valid_up_to()];
+            }
+        }
+    }
     std::str::from_utf8(slice).ok()
 }
 
@@ -755,13 +984,20 @@ fn read_codeview(
     })
 }
 
-fn read_debug_id(codeview_info: &CodeView, endian: scroll::Endian) -> Option<DebugId> {
+fn read_debug_id(codeview_info: &CodeView) -> Option<DebugId> {
     match codeview_info {
         CodeView::Pdb70(ref raw) => {
             // For macOS, this should be its code ID with the age (0)
             // appended to the end of it. This makes it identical to debug
             // IDs for Windows, and is why it doesn't have a special case
             // here.
+            //
+            // Note that Mach-O's LC_UUID is a plain array of bytes, but the
+            // dumper stores it here as a GUID whose first three fields were
+            // read big-endian from that array. Reading those fields back with
+            // the minidump's endianness and laying them out big-endian (as
+            // `Uuid::from_fields` does) therefore recovers the original bytes,
+            // which is what `dump_syms` uses for the symbol file's id.
             let uuid = Uuid::from_fields(
                 raw.signature.data1,
                 raw.signature.data2,
@@ -781,7 +1017,12 @@ fn read_debug_id(codeview_info: &CodeView, endian: scroll::Endian) -> Option<Deb
             // For backwards-compat (Linux minidumps have historically
             // been written using PDB70 CodeView info), treat build_id
             // as if the first 16 bytes were a GUID.
-            let guid_size = <md::GUID>::size_with(&endian);
+            //
+            // The build id is a plain array of bytes, so unlike a real GUID its
+            // fields are always read little-endian, regardless of the minidump's
+            // endianness. This matches how Breakpad's `dump_syms` derives the
+            // id of the symbol file.
+            let guid_size = <md::GUID>::size_with(&LE);
             let guid = if raw.build_id.len() < guid_size {
                 // Pad with zeros.
                 let v: Vec<u8> = raw
@@ -791,9 +1032,9 @@ fn read_debug_id(codeview_info: &CodeView, endian: scroll::Endian) -> Option<Deb
                     .chain(iter::repeat(0))
                     .take(guid_size)
                     .collect();
-                v.pread_with::<md::GUID>(0, endian).ok()
+                v.pread_with::<md::GUID>(0, LE).ok()
             } else {
-                raw.build_id.pread_with::<md::GUID>(0, endian).ok()
+                raw.build_id.pread_with::<md::GUID>(0, LE).ok()
             };
             guid.map(|g| Uuid::from_fields(g.data1, g.data2, g.data3, &g.data4))
                 .map(DebugId::from_uuid)
@@ -844,6 +1085,66 @@ impl MinidumpModule {
         }
     }
 
+    /// The module's `VS_FIXEDFILEINFO`, if it has a valid one.
+    pub fn version_info(&self) -> Option<&md::VS_FIXEDFILEINFO> {
+        let info = &self.raw.version_info;
+        if info.signature == md::VS_FFI_SIGNATURE && info.struct_version == md::VS_FFI_STRUCVERSION
+        {
+            Some(info)
+        } else {
+            None
+        }
+    }
+
+    /// The module's file flags (debug, prerelease, patched...), if it has version info.
+    ///
+    /// Only the flags that are set in `file_flags_mask` are reported.
+    pub fn file_flags(&self) -> Option<md::VersionFileFlags> {
+        self.version_info().map(|info| {
+            md::VersionFileFlags::from_bits_truncate(info.file_flags & info.file_flags_mask)
+        })
+    }
+
+    /// Whether the module's version info says it is a debug build.
+    pub fn is_debug_build(&self) -> bool {
+        self.file_flags()
+            .map(|flags| flags.contains(md::VersionFileFlags::VS_FF_DEBUG))
+            .unwrap_or(false)
+    }
+
+    /// The operating system the module was designed for, if it has version info.
+    ///
+    /// Returns `None` for unknown values.
+    pub fn file_os(&self) -> Option<md::VersionFileOs> {
+        md::VersionFileOs::from_u32(self.version_info()?.file_os)
+    }
+
+    /// The general type of the module (application, DLL, driver...), if it has version info.
+    ///
+    /// Returns `None` for unknown values.
+    pub fn file_type(&self) -> Option<md::VersionFileType> {
+        md::VersionFileType::from_u32(self.version_info()?.file_type)
+    }
+
+    /// The function of the module, if it has version info.
+    ///
+    /// This is only meaningful for drivers, fonts and virtual devices, any other
+    /// nonzero value is reported as [`VersionFileSubtype::Unknown`].
+    pub fn file_subtype(&self) -> Option<VersionFileSubtype> {
+        let subtype = self.version_info()?.file_subtype;
+        let decoded = match self.file_type() {
+            Some(md::VersionFileType::VFT_DRV) => {
+                md::VersionFileSubtypeDriver::from_u32(subtype).map(VersionFileSubtype::Driver)
+            }
+            Some(md::VersionFileType::VFT_FONT) => {
+                md::VersionFileSubtypeFont::from_u32(subtype).map(VersionFileSubtype::Font)
+            }
+            Some(md::VersionFileType::VFT_VXD) => Some(VersionFileSubtype::VirtualDevice(subtype)),
+            _ => None,
+        };
+        Some(decoded.unwrap_or(VersionFileSubtype::Unknown(subtype)))
+    }
+
     /// Read additional data to construct a `MinidumpModule` from `bytes` using the information
     /// from the module list in `raw`.
     pub fn read(
@@ -863,9 +1164,7 @@ impl MinidumpModule {
 
         let os = system_info.map(|info| info.os).unwrap_or(Os::Unknown(0));
 
-        let debug_id = codeview_info
-            .as_ref()
-            .and_then(|cv| read_debug_id(cv, endian));
+        let debug_id = codeview_info.as_ref().and_then(read_debug_id);
 
         Ok(MinidumpModule {
             raw,
@@ -1045,6 +1344,17 @@ impl Module for MinidumpModule {
         Cow::Borrowed(&self.name)
     }
 
+    /// The module's code id, in the form symbol servers use to look up the binary itself:
+    ///
+    /// * PE (Windows): `TTTTTTTTSSSSS`, the `time_date_stamp` as 8 hex digits
+    ///   followed by the `size_of_image` in hex (the Microsoft symbol server layout).
+    /// * ELF (Linux/Android): the build id, in hex.
+    /// * Mach-O (macOS/iOS): the UUID, in hex without dashes.
+    ///
+    /// Like all [`CodeId`]s, these are normalized to lowercase.
+    ///
+    /// Returns `None` for modules without a meaningful id, like ELF files with an
+    /// empty build id.
     fn code_identifier(&self) -> Option<CodeId> {
         match self.codeview_info {
             Some(CodeView::Pdb70(ref raw)) if matches!(self.os, Os::MacOs | Os::Ios) => {
@@ -1102,20 +1412,15 @@ impl Module for MinidumpModule {
         self.debug_id
     }
     fn version(&self) -> Option<Cow<'_, str>> {
-        if self.raw.version_info.signature == md::VS_FFI_SIGNATURE
-            && self.raw.version_info.struct_version == md::VS_FFI_STRUCVERSION
-        {
-            let ver = format!(
-                "{}.{}.{}.{}",
-                self.raw.version_info.file_version_hi >> 16,
-                self.raw.version_info.file_version_hi & 0xffff,
-                self.raw.version_info.file_version_lo >> 16,
-                self.raw.version_info.file_version_lo & 0xffff
-            );
-            Some(Cow::Owned(ver))
-        } else {
-            None
-        }
+        let info = self.version_info()?;
+        let ver = format!(
+            "{}.{}.{}.{}",
+            info.file_version_hi >> 16,
+            info.file_version_hi & 0xffff,
+            info.file_version_lo >> 16,
+            info.file_version_lo & 0xffff
+        );
+        Some(Cow::Owned(ver))
     }
 }
 
@@ -1412,6 +1717,121 @@ MINIDUMP_THREAD_NAME
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpThreadInfoList {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::ThreadInfoListStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        let mut offset = 0;
+        let raw_thread_infos: Vec<md::MINIDUMP_THREAD_INFO> =
+            read_ex_stream_list(&mut offset, bytes, endian)?;
+        let mut thread_ids = HashMap::with_capacity(raw_thread_infos.len());
+        for (index, raw) in raw_thread_infos.iter().enumerate() {
+            // Like the thread list, the first info for a thread wins.
+            thread_ids.entry(raw.thread_id).or_insert(index);
+        }
+        let thread_infos = raw_thread_infos
+            .into_iter()
+            .map(|raw| MinidumpThreadInfo { raw })
+            .collect();
+        Ok(MinidumpThreadInfoList {
+            thread_infos,
+            thread_ids,
+        })
+    }
+}
+
+impl MinidumpThreadInfoList {
+    /// Return an empty `MinidumpThreadInfoList`.
+    pub fn new() -> MinidumpThreadInfoList {
+        MinidumpThreadInfoList {
+            thread_infos: vec![],
+            thread_ids: HashMap::new(),
+        }
+    }
+
+    /// Iterate over the thread infos in the order they were stored in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpThreadInfo> {
+        self.thread_infos.iter()
+    }
+
+    /// Get the info for the thread with id `thread_id`, if there is any.
+    pub fn get_thread_info(&self, thread_id: u32) -> Option<&MinidumpThreadInfo> {
+        self.thread_ids
+            .get(&thread_id)
+            .map(|&index| &self.thread_infos[index])
+    }
+
+    /// Write a human-readable description of this `MinidumpThreadInfoList` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpThreadInfoList
+  thread_info_count = {}
+
+",
+            self.thread_infos.len()
+        )?;
+        for (i, info) in self.thread_infos.iter().enumerate() {
+            writeln!(f, "thread_info[{i}]")?;
+            info.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MinidumpThreadInfo {
+    /// The id of the thread.
+    pub fn thread_id(&self) -> u32 {
+        self.raw.thread_id
+    }
+
+    /// The processor affinity mask of the thread.
+    pub fn affinity(&self) -> u64 {
+        self.raw.affinity
+    }
+
+    /// The address the thread started executing at.
+    pub fn start_address(&self) -> u64 {
+        self.raw.start_address
+    }
+
+    /// Write a human-readable description of this `MinidumpThreadInfo` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        writeln!(
+            f,
+            "MINIDUMP_THREAD_INFO
+  thread_id     = {:#x}
+  dump_flags    = {:#x}
+  dump_error    = {:#x}
+  exit_status   = {:#x}
+  create_time   = {:#x}
+  exit_time     = {:#x}
+  kernel_time   = {:#x}
+  user_time     = {:#x}
+  start_address = {:#x}
+  affinity      = {:#x}
+",
+            self.raw.thread_id,
+            self.raw.dump_flags,
+            self.raw.dump_error,
+            self.raw.exit_status,
+            self.raw.create_time,
+            self.raw.exit_time,
+            self.raw.kernel_time,
+            self.raw.user_time,
+            self.raw.start_address,
+            self.raw.affinity,
+        )
+    }
+}
+
 impl MinidumpModuleList {
     /// Return an empty `MinidumpModuleList`.
     pub fn new() -> MinidumpModuleList {
@@ -1463,6 +1883,16 @@ impl MinidumpModuleList {
             .map(move |&(_, index)| &self.modules[index])
     }
 
+    /// Pairs of indices (in minidump order, see [`Self::iter`]) of modules whose
+    /// address ranges overlap.
+    ///
+    /// This is empty for well-formed minidumps. Otherwise [`Self::module_at_address`]
+    /// can only return one of the overlapping modules, so frames in the others are
+    /// attributed to the wrong module.
+    pub fn overlaps(&self) -> Vec<(usize, usize)> {
+        find_overlaps(self.modules.iter().map(|module| module.memory_range()))
+    }
+
     /// Write a human-readable description of this `MinidumpModuleList` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -1705,6 +2135,78 @@ impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
         self.bytes.pread_with::<T>(start, self.endian).ok()
     }
 
+    /// Get a pointer-sized value at `addr` from this region.
+    ///
+    /// `pointer_width` is usually [`Minidump::pointer_width`]. 32-bit pointers
+    /// are zero-extended. Return `None` if the pointer width is unknown or the
+    /// requested address range falls out of the bounds of this memory region.
+    pub fn read_pointer(&self, addr: u64, pointer_width: PointerWidth) -> Option<u64> {
+        match pointer_width {
+            PointerWidth::Bits32 => self.get_memory_at_address::<u32>(addr).map(u64::from),
+            PointerWidth::Bits64 => self.get_memory_at_address::<u64>(addr),
+            PointerWidth::Unknown => None,
+        }
+    }
+
+    /// Read up to `count` consecutive values of type `T` at `addr` from this region,
+    /// in the minidump's endianness.
+    ///
+    /// `T` can be anything scroll can read, including structs that derive `Pread`,
+    /// which makes this handy for arrays of simple C structs. Reading stops at the end
+    /// of this region, so fewer than `count` values may be returned. Return `None` if
+    /// `addr` is not in this region.
+    pub fn read_array<T>(&self, addr: u64, count: usize) -> Option<Vec<T>>
+    where
+        T: TryFromCtx<'a, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        let bytes = self.bytes_from(addr)?;
+        let mut offset = 0;
+        let values = std::iter::from_fn(|| bytes.gread_with::<T>(&mut offset, self.endian).ok())
+            .take(count)
+            .collect();
+        Some(values)
+    }
+
+    /// Read a NUL-terminated string of bytes at `addr` from this region.
+    ///
+    /// Reading stops at the NUL, at the end of this region, or after `max_len` bytes,
+    /// whichever comes first, and everything before that is returned (without the
+    /// NUL). Return `None` if `addr` is not in this region.
+    pub fn read_cstring(&self, addr: u64, max_len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes_from(addr)?;
+        let bytes = &bytes[..bytes.len().min(max_len)];
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(&bytes[..len])
+    }
+
+    /// Read a NUL-terminated UTF-16 string at `addr` from this region.
+    ///
+    /// Like [`MinidumpMemoryBase::read_cstring`], but `max_len` is in UTF-16 code
+    /// units, which are read in the minidump's endianness. Invalid UTF-16 is replaced
+    /// with U+FFFD.
+    pub fn read_utf16z(&self, addr: u64, max_len: usize) -> Option<String> {
+        let bytes = self.bytes_from(addr)?;
+        let len = bytes
+            .chunks_exact(2)
+            .take(max_len)
+            .take_while(|unit| unit != &[0, 0])
+            .count();
+        let encoding = match self.endian {
+            scroll::Endian::Little => encoding_rs::UTF_16LE,
+            scroll::Endian::Big => encoding_rs::UTF_16BE,
+        };
+        let (s, _) = encoding.decode_without_bom_handling(&bytes[..len * 2]);
+        Some(s.into_owned())
+    }
+
+    /// The bytes of this region from `addr` on.
+    fn bytes_from(&self, addr: u64) -> Option<&'a [u8]> {
+        if !self.contains(addr) {
+            return None;
+        }
+        self.bytes.get((addr - self.base_address) as usize..)
+    }
+
     /// Write the contents of this `MinidumpMemory` to `f` as a hex string.
     pub fn print_contents<T: Write>(&self, f: &mut T) -> io::Result<()> {
         const PARAGRAPH_SIZE: usize = 16;
@@ -1744,6 +2246,18 @@ impl<'a, Descriptor> MinidumpMemoryBase<'a, Descriptor> {
             self.base_address.checked_add(self.size)? - 1,
         ))
     }
+
+    /// Whether `addr` is within this region.
+    pub fn contains(&self, addr: u64) -> bool {
+        addr >= self.base_address && addr - self.base_address < self.size
+    }
+
+    /// Whether any part of `start..start + len` is within this region.
+    fn intersects(&self, start: u64, len: u64) -> bool {
+        let end = start.saturating_add(len);
+        let region_end = self.base_address.saturating_add(self.size);
+        len != 0 && self.base_address < end && start < region_end
+    }
 }
 
 impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
@@ -1757,6 +2271,37 @@ impl<'a, 'mdmp> UnifiedMemory<'a, 'mdmp> {
         }
     }
 
+    pub fn read_pointer(&self, addr: u64, pointer_width: PointerWidth) -> Option<u64> {
+        match self {
+            UnifiedMemory::Memory(this) => this.read_pointer(addr, pointer_width),
+            UnifiedMemory::Memory64(this) => this.read_pointer(addr, pointer_width),
+        }
+    }
+
+    pub fn read_array<T>(&self, addr: u64, count: usize) -> Option<Vec<T>>
+    where
+        T: TryFromCtx<'mdmp, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        match self {
+            UnifiedMemory::Memory(this) => this.read_array(addr, count),
+            UnifiedMemory::Memory64(this) => this.read_array(addr, count),
+        }
+    }
+
+    pub fn read_cstring(&self, addr: u64, max_len: usize) -> Option<&'mdmp [u8]> {
+        match self {
+            UnifiedMemory::Memory(this) => this.read_cstring(addr, max_len),
+            UnifiedMemory::Memory64(this) => this.read_cstring(addr, max_len),
+        }
+    }
+
+    pub fn read_utf16z(&self, addr: u64, max_len: usize) -> Option<String> {
+        match self {
+            UnifiedMemory::Memory(this) => this.read_utf16z(addr, max_len),
+            UnifiedMemory::Memory64(this) => this.read_utf16z(addr, max_len),
+        }
+    }
+
     pub fn memory_range(&self) -> Option<Range<u64>> {
         match self {
             UnifiedMemory::Memory(this) => this.memory_range(),
@@ -1806,10 +2351,20 @@ impl<'mdmp, Descriptor> MinidumpMemoryListBase<'mdmp, Descriptor> {
         MinidumpMemoryListBase {
             regions: vec![],
             regions_by_addr: RangeMap::new(),
+            overlaps: vec![],
+            stream_bytes: &[],
         }
     }
 
+    /// The bytes of the stream this memory list was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'mdmp [u8] {
+        self.stream_bytes
+    }
+
     /// Create a `MinidumpMemoryListBase` from a list of `MinidumpMemoryBase`s.
+    ///
+    /// Overlapping regions are resolved with [`MemoryOverlapPolicy::LowestAddress`].
     pub fn from_regions(
         regions: Vec<MinidumpMemoryBase<'mdmp, Descriptor>>,
     ) -> MinidumpMemoryListBase<'mdmp, Descriptor> {
@@ -1818,12 +2373,58 @@ impl<'mdmp, Descriptor> MinidumpMemoryListBase<'mdmp, Descriptor> {
             .enumerate()
             .map(|(i, region)| (region.memory_range(), i))
             .into_rangemap_safe();
+        let overlaps = find_overlaps(regions.iter().map(|region| region.memory_range()));
+        for &(first, second) in &overlaps {
+            warn!(
+                "memory regions {} ({:#x}+{:#x}) and {} ({:#x}+{:#x}) overlap",
+                first,
+                regions[first].base_address,
+                regions[first].size,
+                second,
+                regions[second].base_address,
+                regions[second].size,
+            );
+        }
         MinidumpMemoryListBase {
             regions,
             regions_by_addr,
+            overlaps,
+            stream_bytes: &[],
         }
     }
 
+    /// Pairs of indices (in minidump order, see [`Self::iter`]) of regions whose
+    /// address ranges overlap.
+    ///
+    /// This is empty for well-formed minidumps.
+    pub fn overlapping_regions(&self) -> &[(usize, usize)] {
+        &self.overlaps
+    }
+
+    /// Choose which region [`Self::memory_at_address`] returns for addresses
+    /// covered by more than one region.
+    ///
+    /// This does nothing if no regions overlap.
+    pub fn resolve_overlaps(&mut self, policy: MemoryOverlapPolicy) {
+        if self.overlaps.is_empty() {
+            return;
+        }
+        let ranges = self.regions.iter().enumerate().map(|(i, region)| {
+            let priority = match policy {
+                MemoryOverlapPolicy::LowestAddress => (0, 0),
+                MemoryOverlapPolicy::PreferLater => (0, i),
+                MemoryOverlapPolicy::PreferLarger => (region.size, i),
+            };
+            (region.memory_range(), priority, i)
+        });
+        self.regions_by_addr = match policy {
+            MemoryOverlapPolicy::LowestAddress => {
+                ranges.map(|(range, _, i)| (range, i)).into_rangemap_safe()
+            }
+            _ => paint_ranges(ranges),
+        };
+    }
+
     /// Return a `MinidumpMemoryBase` containing memory at `address`, if one exists.
     pub fn memory_at_address(
         &self,
@@ -1850,10 +2451,93 @@ impl<'mdmp, Descriptor> MinidumpMemoryListBase<'mdmp, Descriptor> {
     pub fn by_addr<'slf>(
         &'slf self,
     ) -> impl Iterator<Item = &'slf MinidumpMemoryBase<'mdmp, Descriptor>> {
+        // A region may be split in several pieces if overlaps were resolved.
+        let mut seen = HashSet::new();
         self.regions_by_addr
             .ranges_values()
+            .filter(move |&&(_, index)| seen.insert(index))
             .map(move |&(_, index)| &self.regions[index])
     }
+
+    /// Iterate, in order by memory address, over the memory regions that contain any
+    /// part of the `len` bytes starting at `start`.
+    ///
+    /// This is useful for reading a value that spans several regions. Note that
+    /// there may be gaps between the regions, which this does not report.
+    pub fn regions_in_range<'slf>(
+        &'slf self,
+        start: u64,
+        len: u64,
+    ) -> impl Iterator<Item = &'slf MinidumpMemoryBase<'mdmp, Descriptor>> {
+        self.by_addr()
+            .filter(move |region| region.intersects(start, len))
+    }
+}
+
+/// Find the pairs of regions whose address ranges overlap.
+fn find_overlaps(ranges: impl Iterator<Item = Option<Range<u64>>>) -> Vec<(usize, usize)> {
+    let mut sorted: Vec<_> = ranges
+        .enumerate()
+        .filter_map(|(i, range)| Some((range?, i)))
+        .collect();
+    sorted.sort_by_key(|&(range, i)| (range.start, i));
+
+    // Sweep by start address, keeping the ranges that may still overlap later ones.
+    let mut overlaps = vec![];
+    let mut active: Vec<(Range<u64>, usize)> = vec![];
+    for (range, i) in sorted {
+        active.retain(|(last, _)| range.start <= last.end);
+        for &(_, last_i) in &active {
+            overlaps.push((cmp::min(last_i, i), cmp::max(last_i, i)));
+        }
+        active.push((range, i));
+    }
+    overlaps.sort_unstable();
+    overlaps
+}
+
+/// Build a `RangeMap` from possibly-overlapping ranges, where every address
+/// maps to the covering range with the highest priority.
+fn paint_ranges<P: Ord + Copy>(
+    ranges: impl Iterator<Item = (Option<Range<u64>>, P, usize)>,
+) -> RangeMap<u64, usize> {
+    // Ranges are inclusive, so a range stops covering addresses at `end + 1`.
+    // This can't overflow since `memory_range` checks that `base + size` fits.
+    let mut events = vec![];
+    for (range, priority, i) in ranges {
+        if let Some(range) = range {
+            events.push((range.start, true, priority, i));
+            events.push((range.end + 1, false, priority, i));
+        }
+    }
+    events.sort_by_key(|&(addr, ..)| addr);
+
+    let mut active = BTreeSet::new();
+    let mut pieces = vec![];
+    let mut events = events.into_iter().peekable();
+    while let Some(&(addr, ..)) = events.peek() {
+        while let Some(&(event_addr, is_start, priority, i)) = events.peek() {
+            if event_addr != addr {
+                break;
+            }
+            if is_start {
+                active.insert((priority, i));
+            } else {
+                active.remove(&(priority, i));
+            }
+            events.next();
+        }
+        let next_addr = match events.peek() {
+            Some(&(next_addr, ..)) => next_addr,
+            None => break,
+        };
+        if let Some(&(_, i)) = active.iter().next_back() {
+            pieces.push((Range::new(addr, next_addr - 1), i));
+        }
+    }
+    // The pieces are disjoint by construction, and adjacent pieces of the same
+    // region get merged.
+    RangeMap::try_from_iter(pieces).unwrap()
 }
 
 impl<'mdmp> MinidumpMemoryList<'mdmp> {
@@ -1910,6 +2594,68 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
         }
     }
 
+    /// Get a pointer-sized value at `address` from whichever region contains it.
+    ///
+    /// See [`MinidumpMemoryBase::read_pointer`].
+    pub fn read_pointer(&self, address: u64, pointer_width: PointerWidth) -> Option<u64> {
+        self.memory_at_address(address)?
+            .read_pointer(address, pointer_width)
+    }
+
+    /// Get `mem::size_of::<T>()` bytes of memory at `address` from whichever region
+    /// contains it, in the minidump's endianness.
+    ///
+    /// Return `None` if no region contains all of the requested bytes.
+    pub fn get_memory_at_address<T>(&self, address: u64) -> Option<T>
+    where
+        T: TryFromCtx<'mdmp, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        self.memory_at_address(address)?
+            .get_memory_at_address(address)
+    }
+
+    /// Read up to `count` consecutive values of type `T` at `address` from whichever
+    /// region contains it.
+    ///
+    /// See [`MinidumpMemoryBase::read_array`]. Values are never read across regions.
+    pub fn read_array<T>(&self, address: u64, count: usize) -> Option<Vec<T>>
+    where
+        T: TryFromCtx<'mdmp, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        self.memory_at_address(address)?.read_array(address, count)
+    }
+
+    /// Read a NUL-terminated string of bytes at `address` from whichever region
+    /// contains it.
+    ///
+    /// See [`MinidumpMemoryBase::read_cstring`].
+    pub fn read_cstring(&self, address: u64, max_len: usize) -> Option<&'mdmp [u8]> {
+        self.memory_at_address(address)?
+            .read_cstring(address, max_len)
+    }
+
+    /// Read a NUL-terminated UTF-16 string at `address` from whichever region
+    /// contains it.
+    ///
+    /// See [`MinidumpMemoryBase::read_utf16z`].
+    pub fn read_utf16z(&self, address: u64, max_len: usize) -> Option<String> {
+        self.memory_at_address(address)?
+            .read_utf16z(address, max_len)
+    }
+
+    /// Read a pointer at `address`, and then a `T` at the address it points to.
+    ///
+    /// This is the building block for walking data structures, such as linked lists,
+    /// by hand. Return `None` if either read is out of bounds (or the pointer width
+    /// is unknown).
+    pub fn follow_pointer<T>(&self, address: u64, pointer_width: PointerWidth) -> Option<T>
+    where
+        T: TryFromCtx<'mdmp, scroll::Endian, [u8], Error = scroll::Error>,
+    {
+        let target = self.read_pointer(address, pointer_width)?;
+        self.get_memory_at_address(target)
+    }
+
     pub fn iter<'slf>(&'slf self) -> impl Iterator<Item = UnifiedMemory<'slf, 'mdmp>> {
         let iter1 = if let UnifiedMemoryList::Memory(this) = self {
             Some(this.iter().map(UnifiedMemory::Memory))
@@ -1945,6 +2691,22 @@ impl<'mdmp> UnifiedMemoryList<'mdmp> {
             .chain(iter2.into_iter().flatten())
     }
 
+    /// Pairs of indices of regions whose address ranges overlap.
+    pub fn overlapping_regions(&self) -> &[(usize, usize)] {
+        match self {
+            UnifiedMemoryList::Memory(this) => this.overlapping_regions(),
+            UnifiedMemoryList::Memory64(this) => this.overlapping_regions(),
+        }
+    }
+
+    /// Choose which region is returned for addresses covered by more than one region.
+    pub fn resolve_overlaps(&mut self, policy: MemoryOverlapPolicy) {
+        match self {
+            UnifiedMemoryList::Memory(this) => this.resolve_overlaps(policy),
+            UnifiedMemoryList::Memory64(this) => this.resolve_overlaps(policy),
+        }
+    }
+
     pub fn print<T: Write>(&self, f: &mut T, brief: bool) -> io::Result<()> {
         match self {
             UnifiedMemoryList::Memory(this) => this.print(f, brief),
@@ -1981,7 +2743,9 @@ impl<'a> MinidumpStream<'a> for MinidumpMemoryList<'a> {
                 continue;
             }
         }
-        Ok(MinidumpMemoryList::from_regions(regions))
+        let mut list = MinidumpMemoryList::from_regions(regions);
+        list.stream_bytes = bytes;
+        Ok(list)
     }
 }
 
@@ -2031,7 +2795,7 @@ impl<'a> MinidumpStream<'a> for MinidumpMemory64List<'a> {
             let end = rva
                 .checked_add(raw.data_size)
                 .ok_or(Error::StreamReadFailure)?;
-            let bytes = all
+            let region_bytes = all
                 .get(start as usize..end as usize)
                 .ok_or(Error::StreamReadFailure)?;
 
@@ -2039,13 +2803,15 @@ impl<'a> MinidumpStream<'a> for MinidumpMemory64List<'a> {
                 desc: raw,
                 base_address: raw.start_of_memory_range,
                 size: raw.data_size,
-                bytes,
+                bytes: region_bytes,
                 endian,
             });
 
             rva = end;
         }
-        Ok(MinidumpMemory64List::from_regions(regions))
+        let mut list = MinidumpMemory64List::from_regions(regions);
+        list.stream_bytes = bytes;
+        Ok(list)
     }
 }
 
@@ -2074,7 +2840,9 @@ impl<'a> MinidumpStream<'a> for MinidumpMemoryInfoList<'a> {
                 _phantom: PhantomData,
             })
             .collect();
-        Ok(MinidumpMemoryInfoList::from_regions(regions))
+        let mut list = MinidumpMemoryInfoList::from_regions(regions);
+        list.stream_bytes = bytes;
+        Ok(list)
     }
 }
 
@@ -2090,9 +2858,16 @@ impl<'mdmp> MinidumpMemoryInfoList<'mdmp> {
         MinidumpMemoryInfoList {
             regions: vec![],
             regions_by_addr: RangeMap::new(),
+            stream_bytes: &[],
         }
     }
 
+    /// The bytes of the stream this memory info list was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'mdmp [u8] {
+        self.stream_bytes
+    }
+
     /// Create a `MinidumpMemoryList` from a list of `MinidumpMemory`s.
     pub fn from_regions(regions: Vec<MinidumpMemoryInfo<'mdmp>>) -> MinidumpMemoryInfoList<'mdmp> {
         let regions_by_addr = regions
@@ -2103,6 +2878,7 @@ impl<'mdmp> MinidumpMemoryInfoList<'mdmp> {
         MinidumpMemoryInfoList {
             regions,
             regions_by_addr,
+            stream_bytes: &[],
         }
     }
 
@@ -2212,6 +2988,33 @@ impl<'a> MinidumpMemoryInfo<'a> {
                 | md::MemoryProtection::PAGE_EXECUTE_WRITECOPY,
         )
     }
+
+    /// Whether this memory range was a guard page.
+    ///
+    /// Accessing a guard page raises an exception, whatever its other protections.
+    pub fn is_guard_page(&self) -> bool {
+        self.protection.contains(md::MemoryProtection::PAGE_GUARD)
+    }
+
+    /// Whether this memory range was committed, as opposed to reserved or free.
+    ///
+    /// The protection of a range is only meaningful if it was committed.
+    pub fn is_committed(&self) -> bool {
+        self.state.contains(md::MemoryState::MEM_COMMIT)
+    }
+
+    /// The kind of memory backing this range, if known.
+    pub fn region_type(&self) -> Option<MemoryRegionType> {
+        if self.ty.contains(md::MemoryType::MEM_IMAGE) {
+            Some(MemoryRegionType::Image)
+        } else if self.ty.contains(md::MemoryType::MEM_MAPPED) {
+            Some(MemoryRegionType::Mapped)
+        } else if self.ty.contains(md::MemoryType::MEM_PRIVATE) {
+            Some(MemoryRegionType::Private)
+        } else {
+            None
+        }
+    }
 }
 
 impl<'a> MinidumpStream<'a> for MinidumpLinuxMaps<'a> {
@@ -2229,7 +3032,9 @@ impl<'a> MinidumpStream<'a> for MinidumpLinuxMaps<'a> {
             .filter_map(|x| x.ok())
             .collect::<Vec<_>>();
 
-        Ok(MinidumpLinuxMaps::from_regions(regions))
+        let mut maps = MinidumpLinuxMaps::from_regions(regions);
+        maps.stream_bytes = bytes;
+        Ok(maps)
     }
 }
 
@@ -2245,9 +3050,16 @@ impl<'mdmp> MinidumpLinuxMaps<'mdmp> {
         Self {
             regions: vec![],
             regions_by_addr: RangeMap::new(),
+            stream_bytes: &[],
         }
     }
 
+    /// The bytes of the stream this maps was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'mdmp [u8] {
+        self.stream_bytes
+    }
+
     /// Create a `MinidumpMemoryList` from a list of `MinidumpMemory`s.
     pub fn from_regions(regions: Vec<MinidumpLinuxMapInfo<'mdmp>>) -> Self {
         let regions_by_addr = regions
@@ -2258,6 +3070,7 @@ impl<'mdmp> MinidumpLinuxMaps<'mdmp> {
         Self {
             regions,
             regions_by_addr,
+            stream_bytes: &[],
         }
     }
 
@@ -2516,6 +3329,20 @@ impl<'a> MinidumpLinuxMapInfo<'a> {
     pub fn is_executable(&self) -> bool {
         self.is_exec
     }
+
+    /// Whether this memory range was a guard page.
+    ///
+    /// /proc/self/maps doesn't distinguish guard pages, so this is always false.
+    pub fn is_guard_page(&self) -> bool {
+        false
+    }
+
+    /// Whether this memory range was committed.
+    ///
+    /// Everything in /proc/self/maps is mapped, so this is always true.
+    pub fn is_committed(&self) -> bool {
+        true
+    }
 }
 
 impl<'a> Default for UnifiedMemoryInfoList<'a> {
@@ -2653,6 +3480,12 @@ impl<'a> UnifiedMemoryInfo<'a> {
 
         /// Whether this memory range was executable.
         pub fn is_executable(&self) -> bool;
+
+        /// Whether this memory range was a guard page.
+        pub fn is_guard_page(&self) -> bool;
+
+        /// Whether this memory range was committed.
+        pub fn is_committed(&self) -> bool;
     }
 }
 
@@ -2667,6 +3500,41 @@ impl<'a> MinidumpThread<'a> {
             .map(Cow::Owned)
     }
 
+    /// Get the extended processor state (such as AVX registers) saved with this
+    /// thread's CPU context, if there is any.
+    ///
+    /// This requires the XSTATE information from MiscInfo, see [`MinidumpXState`].
+    pub fn xstate(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<MinidumpXState<'a>> {
+        MinidumpXState::read(self.context?, self.endian, system_info, misc)
+    }
+
+    /// The raw bytes of this thread's CPU context, without parsing them.
+    ///
+    /// This is useful for handing the context to an external unwinder. The layout of
+    /// the bytes depends on the CPU architecture, see [`MinidumpThread::raw_context_cpu`].
+    /// This is `None` if the thread has no context, or its location is outside of the
+    /// minidump.
+    pub fn raw_context_bytes(&self) -> Option<&'a [u8]> {
+        self.context
+    }
+
+    /// The CPU architecture of [`MinidumpThread::raw_context_bytes`], which determines its layout.
+    ///
+    /// This is the CPU from `system_info` if it's known, and is otherwise guessed from
+    /// the context itself (see [`MinidumpContext::guess_architecture`]).
+    pub fn raw_context_cpu(&self, system_info: Option<&MinidumpSystemInfo>) -> Option<Cpu> {
+        match system_info {
+            Some(system_info) if !matches!(system_info.cpu, Cpu::Unknown(_)) => {
+                Some(system_info.cpu)
+            }
+            _ => MinidumpContext::guess_architecture(self.context?, self.endian),
+        }
+    }
+
     pub fn stack_memory<'mem>(
         &'mem self,
         memory_list: &'mem UnifiedMemoryList<'a>,
@@ -2777,24 +3645,48 @@ impl<'a> MinidumpThread<'a> {
         Ok(())
     }
 
+    /// The address of the thread's Thread Environment Block (TEB) on Windows.
+    ///
+    /// On Linux, this is instead the address of the thread's `pthread` structure.
+    pub fn teb(&self) -> u64 {
+        self.raw.teb
+    }
+
+    /// Gets the address of the Process Environment Block (PEB) from the thread's TEB.
+    ///
+    /// This is `None` if the TEB isn't in `memory`.
+    pub fn peb(&self, cpu: Cpu, memory: &UnifiedMemoryList) -> Option<u64> {
+        // The PEB pointer is 12 pointers into the TEB.
+        let addr = self.teb_field_address(cpu, 12)?;
+        memory.read_pointer(addr, cpu.pointer_width())
+    }
+
+    /// Gets the raw last error code the thread recorded, just like win32's GetLastError.
+    ///
+    /// This is `None` if the TEB isn't in `memory`. See also [`MinidumpThread::last_error`].
+    pub fn last_error_value(&self, cpu: Cpu, memory: &UnifiedMemoryList) -> Option<u32> {
+        // Early hacky implementation: rather than implementing all the TEB layouts,
+        // just use the fact that we know the value we want is a 13-pointers offset
+        // from the start of the TEB.
+        let addr = self.teb_field_address(cpu, 13)?;
+        memory.memory_at_address(addr)?.get_memory_at_address(addr)
+    }
+
     /// Gets the last error code the thread recorded, just like win32's GetLastError.
     ///
     /// The value is heuristically converted into a CrashReason because that's our
     /// general error code handling machinery, even though this may not actually be
     /// the reason for the crash!
     pub fn last_error(&self, cpu: Cpu, memory: &UnifiedMemoryList) -> Option<CrashReason> {
-        // Early hacky implementation: rather than implementing all the TEB layouts,
-        // just use the fact that we know the value we want is a 13-pointers offset
-        // from the start of the TEB.
-        let teb = self.raw.teb;
-        let pointer_width = cpu.pointer_width().size_in_bytes()? as u64;
-        let offset = pointer_width.checked_mul(13)?;
-        let addr = teb.checked_add(offset)?;
-        let val: u32 = memory
-            .memory_at_address(addr)?
-            .get_memory_at_address(addr)?;
+        self.last_error_value(cpu, memory)
+            .map(CrashReason::from_windows_error)
+    }
 
-        Some(CrashReason::from_windows_error(val))
+    /// The address of the field that is `pointers` pointers into the TEB.
+    fn teb_field_address(&self, cpu: Cpu, pointers: u64) -> Option<u64> {
+        let pointer_width = cpu.pointer_width().size_in_bytes()? as u64;
+        let offset = pointer_width.checked_mul(pointers)?;
+        self.raw.teb.checked_add(offset)
     }
 }
 
@@ -2812,7 +3704,15 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadList<'a> {
         let mut threads = Vec::with_capacity(raw_threads.len());
         let mut thread_ids = HashMap::with_capacity(raw_threads.len());
         for raw in raw_threads.into_iter() {
-            thread_ids.insert(raw.thread_id, threads.len());
+            // Some producers write the same thread more than once, the first one wins.
+            match thread_ids.entry(raw.thread_id) {
+                Entry::Occupied(_) => {
+                    warn!("duplicate thread id {:#x} in thread list", raw.thread_id)
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(threads.len());
+                }
+            }
 
             // Defer parsing of this to the `context` method, where we will have access
             // to other streams that are required to parse a context properly.
@@ -2831,6 +3731,7 @@ impl<'a> MinidumpStream<'a> for MinidumpThreadList<'a> {
         Ok(MinidumpThreadList {
             threads,
             thread_ids,
+            stream_bytes: bytes,
         })
     }
 }
@@ -2841,6 +3742,47 @@ impl<'a> MinidumpThreadList<'a> {
         self.thread_ids.get(&id).map(|&index| &self.threads[index])
     }
 
+    /// The bytes of the stream this thread list was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.stream_bytes
+    }
+
+    /// The ids of threads that appear more than once in this thread list, in ascending order.
+    ///
+    /// Well-formed minidumps never have duplicate threads, but some buggy producers
+    /// write the same thread several times. [`get_thread`][Self::get_thread] always
+    /// returns the first of them.
+    pub fn duplicate_thread_ids(&self) -> Vec<u32> {
+        let mut ids = self
+            .threads
+            .iter()
+            .enumerate()
+            .filter(|&(i, thread)| self.thread_ids[&thread.raw.thread_id] != i)
+            .map(|(_, thread)| thread.raw.thread_id)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Remove threads whose id already appeared earlier in this thread list.
+    pub fn deduplicate(&mut self) {
+        let mut i = 0;
+        let thread_ids = &self.thread_ids;
+        self.threads.retain(|thread| {
+            let first = thread_ids[&thread.raw.thread_id] == i;
+            i += 1;
+            first
+        });
+        self.thread_ids = self
+            .threads
+            .iter()
+            .enumerate()
+            .map(|(i, thread)| (thread.raw.thread_id, i))
+            .collect();
+    }
+
     /// Write a human-readable description of this `MinidumpThreadList` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -3139,6 +4081,25 @@ impl MinidumpSystemInfo {
             (version.into(), Some(build))
         }
     }
+
+    /// A human-readable label for the operating system and its version, such as
+    /// "Windows 10.0.19045" or "Linux 5.15.0".
+    ///
+    /// This combines the platform, version numbers and CSD version: on Windows the CSD
+    /// version is the installed service pack, and on macOS and iOS it's the build. On
+    /// Linux and Android it's the rest of the `uname` output, which is left out.
+    pub fn os_version_string(&self) -> String {
+        let (version, build) = self.os_parts();
+        let name = match self.os {
+            Os::Windows => Cow::Borrowed("Windows"),
+            ref os => os.long_name(),
+        };
+        match (self.os, build) {
+            (Os::Windows, Some(build)) => format!("{name} {version} {build}"),
+            (Os::MacOs | Os::Ios, Some(build)) => format!("{name} {version} ({build})"),
+            _ => format!("{name} {version}"),
+        }
+    }
 }
 
 // Generates an accessor for a MISC_INFO field with two possible syntaxes:
@@ -3560,6 +4521,19 @@ impl<'a> MinidumpStream<'a> for MinidumpLinuxLsbRelease<'a> {
     }
 }
 
+impl<'a> MinidumpStream<'a> for MinidumpLinuxCmdLine<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        _endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<MinidumpLinuxCmdLine<'a>, Error> {
+        Ok(Self { data: bytes })
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpLinuxEnviron<'a> {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::LinuxEnviron as u32;
 
@@ -3613,16 +4587,73 @@ impl<'a> MinidumpLinuxCpuInfo<'a> {
     pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
         Cow::Borrowed(self.data)
     }
+
+    /// Get the CPU's model name (e.g. "Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz").
+    ///
+    /// `/proc/cpuinfo` lists every core separately, this returns the value
+    /// for the first one that has a `model name` entry.
+    pub fn model_name(&self) -> Option<&'a LinuxOsStr> {
+        self.iter()
+            .find(|(key, _)| key.as_bytes() == b"model name")
+            .map(|(_, val)| val)
+    }
+
+    /// Get an iterator over the CPU's feature flags (e.g. "sse4_2", "avx2").
+    ///
+    /// These come from the first `flags` entry (x86) or `Features` entry (ARM),
+    /// split on whitespace. The iterator is empty if neither is present.
+    pub fn flags(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
+        self.iter()
+            .find(|(key, _)| matches!(key.as_bytes(), b"flags" | b"Features"))
+            .map(|(_, val)| val)
+            .unwrap_or_else(|| LinuxOsStr::new())
+            .split_ascii_whitespace()
+    }
+
+    /// Check whether the CPU reports the given feature flag.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags().any(|f| f.as_bytes() == flag.as_bytes())
+    }
+
+    /// Get the number of cores listed, i.e. the number of `processor` entries.
+    pub fn core_count(&self) -> usize {
+        self.iter()
+            .filter(|(key, _)| key.as_bytes() == b"processor")
+            .count()
+    }
+}
+
+/// Splits a NUL-separated list, tolerating a missing trailing NUL.
+fn linux_nul_list_iter(bytes: &[u8]) -> impl Iterator<Item = &LinuxOsStr> {
+    let bytes = bytes.strip_suffix(b"\0").unwrap_or(bytes);
+    let empty = bytes.is_empty();
+    LinuxOsStr::from_bytes(bytes)
+        .split(b'\0')
+        .filter(move |_| !empty)
+}
+
+impl<'a> MinidumpLinuxCmdLine<'a> {
+    /// Get an iterator over the arguments stored in the `/proc/self/cmdline` dump.
+    ///
+    /// The first argument is usually the path of the executable. Arguments
+    /// are returned verbatim, including empty ones.
+    pub fn args(&self) -> impl Iterator<Item = &'a LinuxOsStr> {
+        linux_nul_list_iter(self.data)
+    }
+
+    /// Get the raw bytes of the `/proc/self/cmdline` dump.
+    pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.data)
+    }
 }
 
 impl<'a> MinidumpLinuxEnviron<'a> {
     /// Get an iterator over the key-value pairs stored in the `/proc/self/environ` dump.
     ///
-    /// Keys and values are `trim`ed of leading/trailing spaces, and if a key
-    /// or value was surrounded by quotes ("like this"), the quotes will be
-    /// stripped.
+    /// Entries are separated by NULs and split on their first `=`. Keys and
+    /// values are returned verbatim, and entries without an `=` are skipped.
     pub fn iter(&self) -> impl Iterator<Item = (&'a LinuxOsStr, &'a LinuxOsStr)> {
-        linux_list_iter(self.data, b'=')
+        linux_nul_list_iter(self.data).filter_map(|entry| entry.split_once(b'='))
     }
 
     /// Get the raw bytes of the `/proc/self/environ` dump.
@@ -3661,6 +4692,49 @@ impl<'a> MinidumpLinuxLsbRelease<'a> {
     pub fn raw_bytes(&self) -> Cow<'a, [u8]> {
         Cow::Borrowed(self.data)
     }
+
+    /// Get the value of the first of `keys` that is present.
+    ///
+    /// Keys are tried in order, so that lsb-release keys can be preferred over
+    /// their os-release equivalents.
+    fn value(&self, keys: &[&[u8]]) -> Option<&'a LinuxOsStr> {
+        keys.iter().find_map(|&wanted| {
+            self.iter()
+                .find(|(key, _)| key.as_bytes() == wanted)
+                .map(|(_, val)| val)
+        })
+    }
+
+    /// Get the distribution's id (e.g. "Ubuntu" or "fedora").
+    ///
+    /// This is `DISTRIB_ID` in lsb-release format, or `ID` in os-release format.
+    pub fn id(&self) -> Option<&'a LinuxOsStr> {
+        self.value(&[b"DISTRIB_ID", b"ID"])
+    }
+
+    /// Get the distribution's release (e.g. "22.04").
+    ///
+    /// This is `DISTRIB_RELEASE` in lsb-release format, or `VERSION_ID` in
+    /// os-release format.
+    pub fn release(&self) -> Option<&'a LinuxOsStr> {
+        self.value(&[b"DISTRIB_RELEASE", b"VERSION_ID"])
+    }
+
+    /// Get the distribution's release codename (e.g. "jammy").
+    ///
+    /// This is `DISTRIB_CODENAME` in lsb-release format, or `VERSION_CODENAME`
+    /// in os-release format.
+    pub fn codename(&self) -> Option<&'a LinuxOsStr> {
+        self.value(&[b"DISTRIB_CODENAME", b"VERSION_CODENAME"])
+    }
+
+    /// Get the distribution's human-readable description (e.g. "Ubuntu 22.04.3 LTS").
+    ///
+    /// This is `DISTRIB_DESCRIPTION` in lsb-release format, or `PRETTY_NAME`
+    /// in os-release format.
+    pub fn description(&self) -> Option<&'a LinuxOsStr> {
+        self.value(&[b"DISTRIB_DESCRIPTION", b"PRETTY_NAME"])
+    }
 }
 
 fn systemtime_from_timestamp(timestamp: u64) -> Option<SystemTime> {
@@ -3674,6 +4748,18 @@ impl MinidumpMiscInfo {
             .and_then(|t| systemtime_from_timestamp(*t as u64))
     }
 
+    /// Get the OS build string (e.g. "10.0.19041.1 (WinBuild.160101.0800)").
+    ///
+    /// This is only present in `MINIDUMP_MISC_INFO_4` and later, and `None` is
+    /// returned if it is missing or empty.
+    pub fn build_string(&self) -> Option<String> {
+        self.raw
+            .build_string()
+            .and_then(|string| utf16_to_string(&string[..]))
+            .map(|string| string.trim().to_owned())
+            .filter(|string| !string.is_empty())
+    }
+
     /// Write a human-readable description of this `MinidumpMiscInfo` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -4470,6 +5556,7 @@ impl<'a> MinidumpStream<'a> for MinidumpException<'a> {
             thread_id,
             context,
             endian,
+            stream_bytes: bytes,
         })
     }
 }
@@ -4495,6 +5582,69 @@ impl<'a> MinidumpException<'a> {
             .map(Cow::Owned)
     }
 
+    /// Get the cpu context of the crashing thread, looking for it in `memory` if needed.
+    ///
+    /// Some producers don't cite the context in the exception stream at all, and
+    /// instead store its address as one of the exception's parameters, with the
+    /// context itself in the dumped memory. If the stream's context location is
+    /// empty, each parameter is tried as the address of a context in `memory`, and
+    /// the first one that parses as a context for this system is returned.
+    ///
+    /// This is a best-effort fallback: a parameter could happen to point at memory
+    /// that merely looks like a context. Otherwise this is the same as
+    /// [`MinidumpException::context`].
+    pub fn context_with_memory(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+        memory: &UnifiedMemoryList,
+    ) -> Option<Cow<'a, MinidumpContext>> {
+        let location = &self.raw.thread_context;
+        if location.data_size != 0 && location.rva != 0 {
+            return self.context(system_info, misc);
+        }
+
+        let size = MinidumpContext::context_size(system_info, self.endian)?;
+        let record = &self.raw.exception_record;
+        let count = (record.number_parameters as usize).min(record.exception_information.len());
+        let context = record.exception_information[..count]
+            .iter()
+            .map(|&address| match system_info.cpu.pointer_width() {
+                PointerWidth::Bits32 => address as u32 as u64,
+                _ => address,
+            })
+            .filter(|&address| address != 0)
+            .find_map(|address| {
+                let region = memory.memory_at_address(address)?;
+                let offset = (address - region.base_address()).try_into().ok()?;
+                let bytes = region.bytes().get(offset..)?.get(..size)?;
+                MinidumpContext::read(bytes, self.endian, system_info, misc).ok()
+            })?;
+        warn!(
+            "exception stream has no context, using the one its parameters point at (ip {:#x})",
+            context.get_instruction_pointer()
+        );
+        Some(Cow::Owned(context))
+    }
+
+    /// Get the extended processor state (such as AVX registers) saved with the
+    /// exception's CPU context, if there is any.
+    ///
+    /// This requires the XSTATE information from MiscInfo, see [`MinidumpXState`].
+    pub fn xstate(
+        &self,
+        system_info: &MinidumpSystemInfo,
+        misc: Option<&MinidumpMiscInfo>,
+    ) -> Option<MinidumpXState<'a>> {
+        MinidumpXState::read(self.context?, self.endian, system_info, misc)
+    }
+
+    /// The bytes of the stream this exception was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.stream_bytes
+    }
+
     /// Get the address that "caused" the crash.
     ///
     /// The meaning of this value depends on the kind of crash this was.
@@ -4548,6 +5698,50 @@ impl<'a> MinidumpException<'a> {
         self.thread_id
     }
 
+    /// Follow the chain of nested exceptions linked to this exception.
+    ///
+    /// On Windows, an exception raised while another one is being handled (such as a
+    /// C++ rethrow) links to the other exception's `EXCEPTION_RECORD` in the process's
+    /// memory with [`exception_record`][md::MINIDUMP_EXCEPTION::exception_record]. This
+    /// reads the linked records out of `memory`, in chain order, stopping at a null
+    /// link, a record that wasn't captured, a cycle, or after [`MAX_CHAINED_EXCEPTIONS`]
+    /// records. `pointer_width` is usually [`Minidump::pointer_width`], and determines
+    /// the layout of the records.
+    ///
+    /// The returned exceptions are for the same thread as this one, but have no context.
+    pub fn chained_exceptions(
+        &self,
+        memory: &UnifiedMemoryList<'_>,
+        pointer_width: PointerWidth,
+    ) -> Vec<MinidumpException<'a>> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut address = self.raw.exception_record.exception_record;
+        while address != 0 && chain.len() < MAX_CHAINED_EXCEPTIONS && seen.insert(address) {
+            let record = match read_exception_record(memory, address, pointer_width) {
+                Some(record) => record,
+                None => break,
+            };
+            address = record.exception_record;
+            chain.push(MinidumpException {
+                raw: md::MINIDUMP_EXCEPTION_STREAM {
+                    thread_id: self.thread_id,
+                    __align: 0,
+                    exception_record: record,
+                    thread_context: md::MINIDUMP_LOCATION_DESCRIPTOR {
+                        data_size: 0,
+                        rva: 0,
+                    },
+                },
+                thread_id: self.thread_id,
+                context: None,
+                endian: self.endian,
+                stream_bytes: &[],
+            });
+        }
+        chain
+    }
+
     /// Write a human-readable description of this `MinidumpException` to `f`.
     ///
     /// This is very verbose, it is the format used by `minidump_dump`.
@@ -4612,6 +5806,42 @@ impl<'a> MinidumpException<'a> {
     }
 }
 
+/// The most exceptions [`MinidumpException::chained_exceptions`] will follow.
+pub const MAX_CHAINED_EXCEPTIONS: usize = 16;
+
+/// Read an `EXCEPTION_RECORD` at `address` in the process's memory.
+///
+/// 64-bit processes use the same layout as [`md::MINIDUMP_EXCEPTION`], while 32-bit
+/// ones have 32-bit pointers and parameters (`EXCEPTION_RECORD32`).
+fn read_exception_record(
+    memory: &UnifiedMemoryList<'_>,
+    address: u64,
+    pointer_width: PointerWidth,
+) -> Option<md::MINIDUMP_EXCEPTION> {
+    let region = memory.memory_at_address(address)?;
+    match pointer_width {
+        PointerWidth::Bits64 => region.get_memory_at_address(address),
+        PointerWidth::Bits32 => {
+            let word =
+                |index: u64| region.get_memory_at_address::<u32>(address.checked_add(4 * index)?);
+            let mut exception_information = [0u64; 15];
+            for (index, info) in exception_information.iter_mut().enumerate() {
+                *info = word(5 + index as u64)?.into();
+            }
+            Some(md::MINIDUMP_EXCEPTION {
+                exception_code: word(0)?,
+                exception_flags: word(1)?,
+                exception_record: word(2)?.into(),
+                exception_address: word(3)?.into(),
+                number_parameters: word(4)?,
+                __align: 0,
+                exception_information,
+            })
+        }
+        PointerWidth::Unknown => None,
+    }
+}
+
 impl<'a> MinidumpStream<'a> for MinidumpAssertion {
     const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::AssertionInfoStream as u32;
 
@@ -4628,15 +5858,13 @@ impl<'a> MinidumpStream<'a> for MinidumpAssertion {
     }
 }
 
+/// Convert a 0-terminated UTF-16 string to a `String`.
+///
+/// The code units have already been read with the minidump's endianness, so they
+/// must not be reinterpreted as bytes (which would assume the host's byte order).
 fn utf16_to_string(data: &[u16]) -> Option<String> {
-    use std::slice;
-
     let len = data.iter().take_while(|c| **c != 0).count();
-    let s16 = &data[..len];
-    let bytes = unsafe { slice::from_raw_parts(s16.as_ptr() as *const u8, s16.len() * 2) };
-    encoding_rs::UTF_16LE
-        .decode_without_bom_handling_and_without_replacement(bytes)
-        .map(String::from)
+    String::from_utf16(&data[..len]).ok()
 }
 
 impl MinidumpAssertion {
@@ -4677,72 +5905,483 @@ impl MinidumpAssertion {
     }
 }
 
-fn read_string_list(
-    all: &[u8],
-    location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
-    endian: scroll::Endian,
-) -> Result<Vec<String>, Error> {
-    let data = location_slice(all, location).or(Err(Error::StreamReadFailure))?;
-    if data.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let mut offset = 0;
+impl<'a> MinidumpStream<'a> for MinidumpHandleOperationList {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::HandleOperationListStream as u32;
 
-    let count: u32 = data
-        .gread_with(&mut offset, endian)
-        .or(Err(Error::StreamReadFailure))?;
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        let mut offset = 0;
+        let raw_operations: Vec<md::AVRF_HANDLE_OPERATION> =
+            read_ex_stream_list(&mut offset, bytes, endian)?;
+        let operations = raw_operations
+            .into_iter()
+            .map(|raw| MinidumpHandleOperation { raw })
+            .collect();
+        Ok(MinidumpHandleOperationList { operations })
+    }
+}
 
-    let (count, _) = ensure_count_in_bound(all, count as usize, <md::RVA>::size_with(&endian), 0)?;
+impl MinidumpHandleOperationList {
+    /// Return an empty `MinidumpHandleOperationList`.
+    pub fn new() -> MinidumpHandleOperationList {
+        MinidumpHandleOperationList { operations: vec![] }
+    }
 
-    let mut strings = Vec::with_capacity(count);
-    for _ in 0..count {
-        let rva: md::RVA = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::StreamReadFailure))?;
+    /// Iterate over the handle operations in the order they were stored in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpHandleOperation> {
+        self.operations.iter()
+    }
 
-        let string = read_string_utf8(&mut (rva as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?
-            .to_owned();
+    /// Write a human-readable description of this `MinidumpHandleOperationList` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpHandleOperationList
+  operation_count = {}
 
-        strings.push(string);
+",
+            self.operations.len()
+        )?;
+        for (i, operation) in self.operations.iter().enumerate() {
+            writeln!(f, "operation[{i}]")?;
+            operation.print(f)?;
+        }
+        Ok(())
     }
-
-    Ok(strings)
 }
 
-fn read_simple_string_dictionary(
-    all: &[u8],
-    location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
-    endian: scroll::Endian,
-) -> Result<BTreeMap<String, String>, Error> {
-    let mut dictionary = BTreeMap::new();
-
-    let data = location_slice(all, location).or(Err(Error::StreamReadFailure))?;
-    if data.is_empty() {
-        return Ok(dictionary);
+impl MinidumpHandleOperation {
+    /// The handle that was operated on.
+    pub fn handle(&self) -> u64 {
+        self.raw.handle
     }
 
-    let mut offset = 0;
-
-    let count: u32 = data
-        .gread_with(&mut offset, endian)
-        .or(Err(Error::StreamReadFailure))?;
+    /// The id of the process that performed the operation.
+    pub fn process_id(&self) -> u32 {
+        self.raw.process_id
+    }
 
-    for _ in 0..count {
-        let entry: md::MINIDUMP_SIMPLE_STRING_DICTIONARY_ENTRY = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::StreamReadFailure))?;
+    /// The id of the thread that performed the operation.
+    pub fn thread_id(&self) -> u32 {
+        self.raw.thread_id
+    }
 
-        let key = read_string_utf8(&mut (entry.key as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?;
-        let value = read_string_utf8(&mut (entry.value as usize), all, endian)
-            .ok_or(Error::StreamReadFailure)?;
+    /// The kind of operation, if it is a known one.
+    pub fn operation_type(&self) -> Option<md::HandleTraceOperation> {
+        md::HandleTraceOperation::from_u32(self.raw.operation_type)
+    }
 
-        dictionary.insert(key.to_owned(), value.to_owned());
+    /// The return addresses of the backtrace captured for this operation, innermost first.
+    ///
+    /// A corrupt depth is clamped to the number of addresses actually stored.
+    pub fn backtrace(&self) -> &[u64] {
+        let trace = &self.raw.back_trace_information;
+        let depth = cmp::min(trace.depth as usize, trace.return_addresses.len());
+        &trace.return_addresses[..depth]
     }
 
-    Ok(dictionary)
+    /// The return addresses of the backtrace paired with the module containing each
+    /// of them, if any.
+    pub fn backtrace_modules<'slf, 'm: 'slf>(
+        &'slf self,
+        modules: &'m MinidumpModuleList,
+    ) -> impl Iterator<Item = (u64, Option<&'m MinidumpModule>)> + 'slf {
+        self.backtrace()
+            .iter()
+            .map(move |&address| (address, modules.module_at_address(address)))
+    }
+
+    /// Write a human-readable description of this `MinidumpHandleOperation` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "AVRF_HANDLE_OPERATION
+  handle          = {:#x}
+  process_id      = {:#x}
+  thread_id       = {:#x}
+  operation_type  = {:#x}
+  backtrace_depth = {}
+",
+            self.raw.handle,
+            self.raw.process_id,
+            self.raw.thread_id,
+            self.raw.operation_type,
+            self.raw.back_trace_information.depth,
+        )?;
+        for (i, address) in self.backtrace().iter().enumerate() {
+            writeln!(f, "  backtrace[{i}] = {address:#x}")?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpFunctionTableList<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::FunctionTable as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        let header: md::MINIDUMP_FUNCTION_TABLE_STREAM = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+
+        let descriptor_size = header.size_of_descriptor as usize;
+        if descriptor_size < md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+        let native_descriptor_size = header.size_of_native_descriptor as usize;
+        let entry_size = header.size_of_function_entry as usize;
+
+        let mut offset = (header.size_of_header as usize)
+            .checked_add(header.size_of_align_pad as usize)
+            .ok_or(Error::StreamReadFailure)?;
+
+        // Every table is at least as large as its descriptors.
+        let (count, _) = ensure_count_in_bound(
+            bytes,
+            header.number_of_descriptors as usize,
+            descriptor_size.saturating_add(native_descriptor_size),
+            offset,
+        )?;
+
+        let mut tables = Vec::with_capacity(count);
+        for _ in 0..count {
+            let raw: md::MINIDUMP_FUNCTION_TABLE_DESCRIPTOR = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            offset += descriptor_size;
+
+            let native_descriptor = bytes
+                .get(offset..offset + native_descriptor_size)
+                .ok_or(Error::StreamReadFailure)?;
+            offset += native_descriptor_size;
+
+            let entries_size = (raw.entry_count as usize)
+                .checked_mul(entry_size)
+                .ok_or(Error::StreamReadFailure)?;
+            let entries_end = offset
+                .checked_add(entries_size)
+                .ok_or(Error::StreamReadFailure)?;
+            let entries = bytes
+                .get(offset..entries_end)
+                .ok_or(Error::StreamReadFailure)?;
+            offset = entries_end
+                .checked_add(raw.size_of_align_pad as usize)
+                .ok_or(Error::StreamReadFailure)?;
+
+            tables.push(MinidumpFunctionTable {
+                raw,
+                native_descriptor,
+                entries,
+                entry_size,
+                endian,
+            });
+        }
+
+        Ok(MinidumpFunctionTableList {
+            tables,
+            stream_bytes: bytes,
+        })
+    }
+}
+
+impl<'a> MinidumpFunctionTableList<'a> {
+    /// Return an empty `MinidumpFunctionTableList`.
+    pub fn new() -> MinidumpFunctionTableList<'a> {
+        MinidumpFunctionTableList {
+            tables: vec![],
+            stream_bytes: &[],
+        }
+    }
+
+    /// The bytes of the stream this function table list was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.stream_bytes
+    }
+
+    /// Iterate over the function tables in the order they were stored in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpFunctionTable<'a>> {
+        self.tables.iter()
+    }
+
+    /// Get the function table whose functions cover `address`, if any.
+    pub fn table_at_address(&self, address: u64) -> Option<&MinidumpFunctionTable<'a>> {
+        self.tables.iter().find(|table| {
+            table.raw.minimum_address <= address && address < table.raw.maximum_address
+        })
+    }
+
+    /// Write a human-readable description of this `MinidumpFunctionTableList` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpFunctionTableList
+  table_count = {}
+
+",
+            self.tables.len()
+        )?;
+        for (i, table) in self.tables.iter().enumerate() {
+            writeln!(f, "table[{i}]")?;
+            table.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpFunctionTable<'a> {
+    /// Iterate over the raw function entries of this table.
+    ///
+    /// The format of the entries depends on the CPU architecture of the process.
+    pub fn raw_entries(&self) -> impl Iterator<Item = &'a [u8]> {
+        // chunks_exact panics on a zero size, but then there are no entries anyway.
+        self.entries.chunks_exact(self.entry_size.max(1))
+    }
+
+    /// Iterate over the function entries of this table as x86-64 `RUNTIME_FUNCTION`s.
+    ///
+    /// The addresses in the entries are relative to `raw.base_address`. This yields
+    /// nothing if the entries are too small to be `RUNTIME_FUNCTION`s.
+    pub fn runtime_functions(&self) -> impl Iterator<Item = md::IMAGE_RUNTIME_FUNCTION_ENTRY> + '_ {
+        self.raw_entries()
+            .filter_map(move |entry| entry.pread_with(0, self.endian).ok())
+    }
+
+    /// Get the x86-64 `RUNTIME_FUNCTION` of the function containing `address`, if any.
+    pub fn runtime_function_at_address(
+        &self,
+        address: u64,
+    ) -> Option<md::IMAGE_RUNTIME_FUNCTION_ENTRY> {
+        let rva: u32 = address
+            .checked_sub(self.raw.base_address)?
+            .try_into()
+            .ok()?;
+        self.runtime_functions()
+            .find(|function| function.begin_address <= rva && rva < function.end_address)
+    }
+
+    /// Write a human-readable description of this `MinidumpFunctionTable` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_FUNCTION_TABLE_DESCRIPTOR
+  minimum_address   = {:#x}
+  maximum_address   = {:#x}
+  base_address      = {:#x}
+  entry_count       = {}
+  size_of_align_pad = {}
+  (native_descriptor) = {}
+
+",
+            self.raw.minimum_address,
+            self.raw.maximum_address,
+            self.raw.base_address,
+            self.raw.entry_count,
+            self.raw.size_of_align_pad,
+            bytes_to_hex(self.native_descriptor),
+        )?;
+        for (i, function) in self.runtime_functions().enumerate() {
+            writeln!(
+                f,
+                "  function[{i}] = [{:#x}, {:#x}) unwind_info = {:#x}",
+                function.begin_address, function.end_address, function.unwind_info_address
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<'a> MinidumpStream<'a> for MinidumpTokenList<'a> {
+    const STREAM_TYPE: u32 = MINIDUMP_STREAM_TYPE::TokenStream as u32;
+
+    fn read(
+        bytes: &'a [u8],
+        _all: &'a [u8],
+        endian: scroll::Endian,
+        _system_info: Option<&MinidumpSystemInfo>,
+    ) -> Result<Self, Error> {
+        let header: md::MINIDUMP_TOKEN_INFO_LIST = bytes
+            .pread_with(0, endian)
+            .or(Err(Error::StreamReadFailure))?;
+
+        let element_header_size = header.element_header_size as usize;
+        if element_header_size < md::MINIDUMP_TOKEN_INFO_HEADER::size_with(&endian) {
+            return Err(Error::StreamReadFailure);
+        }
+
+        // Every entry is at least as large as its header.
+        let (count, _) = ensure_count_in_bound(
+            bytes,
+            header.token_list_entries as usize,
+            element_header_size,
+            header.list_header_size as usize,
+        )?;
+
+        let mut offset = header.list_header_size as usize;
+        let mut tokens = Vec::with_capacity(count);
+        for _ in 0..count {
+            let raw: md::MINIDUMP_TOKEN_INFO_HEADER = bytes
+                .pread_with(offset, endian)
+                .or(Err(Error::StreamReadFailure))?;
+            let token_size = raw.token_size as usize;
+            if token_size < element_header_size {
+                return Err(Error::StreamReadFailure);
+            }
+            let end = offset
+                .checked_add(token_size)
+                .ok_or(Error::StreamReadFailure)?;
+            let data = bytes
+                .get(offset + element_header_size..end)
+                .ok_or(Error::StreamReadFailure)?;
+            tokens.push(MinidumpToken { raw, data });
+            offset = end;
+        }
+
+        Ok(MinidumpTokenList {
+            tokens,
+            stream_bytes: bytes,
+        })
+    }
+}
+
+impl<'a> MinidumpTokenList<'a> {
+    /// Return an empty `MinidumpTokenList`.
+    pub fn new() -> MinidumpTokenList<'a> {
+        MinidumpTokenList {
+            tokens: vec![],
+            stream_bytes: &[],
+        }
+    }
+
+    /// The bytes of the stream this token list was read from, or nothing if it wasn't
+    /// read from a minidump. See [`Minidump::get_raw_stream`].
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.stream_bytes
+    }
+
+    /// Iterate over the tokens in the order they were stored in the minidump.
+    pub fn iter(&self) -> impl Iterator<Item = &MinidumpToken<'a>> {
+        self.tokens.iter()
+    }
+
+    /// Write a human-readable description of this `MinidumpTokenList` to `f`.
+    ///
+    /// This is very verbose, it is the format used by `minidump_dump`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MinidumpTokenList
+  token_count = {}
+
+",
+            self.tokens.len()
+        )?;
+        for (i, token) in self.tokens.iter().enumerate() {
+            writeln!(f, "token[{i}]")?;
+            token.print(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MinidumpToken<'a> {
+    /// Write a human-readable description of this `MinidumpToken` to `f`.
+    pub fn print<T: Write>(&self, f: &mut T) -> io::Result<()> {
+        write!(
+            f,
+            "MINIDUMP_TOKEN_INFO_HEADER
+  token_size   = {:#x}
+  token_id     = {:#x}
+  token_handle = {:#x}
+  (data)       = {}
+
+",
+            self.raw.token_size,
+            self.raw.token_id,
+            self.raw.token_handle,
+            bytes_to_hex(self.data),
+        )
+    }
+}
+
+fn read_string_list(
+    all: &[u8],
+    location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    endian: scroll::Endian,
+) -> Result<Vec<String>, Error> {
+    let data = location_slice(all, location).or(Err(Error::StreamReadFailure))?;
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut offset = 0;
+
+    let count: u32 = data
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
+
+    let (count, _) = ensure_count_in_bound(all, count as usize, <md::RVA>::size_with(&endian), 0)?;
+
+    let mut strings = Vec::with_capacity(count);
+    for _ in 0..count {
+        let rva: md::RVA = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+
+        let string = read_string_utf8(&mut (rva as usize), all, endian)
+            .ok_or(Error::StreamReadFailure)?
+            .to_owned();
+
+        strings.push(string);
+    }
+
+    Ok(strings)
+}
+
+fn read_simple_string_dictionary(
+    all: &[u8],
+    location: &md::MINIDUMP_LOCATION_DESCRIPTOR,
+    endian: scroll::Endian,
+) -> Result<BTreeMap<String, String>, Error> {
+    let mut dictionary = BTreeMap::new();
+
+    let data = location_slice(all, location).or(Err(Error::StreamReadFailure))?;
+    if data.is_empty() {
+        return Ok(dictionary);
+    }
+
+    let mut offset = 0;
+
+    let count: u32 = data
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::StreamReadFailure))?;
+
+    for _ in 0..count {
+        let entry: md::MINIDUMP_SIMPLE_STRING_DICTIONARY_ENTRY = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::StreamReadFailure))?;
+
+        let key = read_string_utf8(&mut (entry.key as usize), all, endian)
+            .ok_or(Error::StreamReadFailure)?;
+        let value = read_string_utf8(&mut (entry.value as usize), all, endian)
+            .ok_or(Error::StreamReadFailure)?;
+
+        dictionary.insert(key.to_owned(), value.to_owned());
+    }
+
+    Ok(dictionary)
 }
 
 fn read_annotation_objects(
@@ -4950,6 +6589,131 @@ impl MinidumpCrashpadInfo {
     }
 }
 
+/// Read the header of a minidump, and check that its stream directory is in bounds.
+fn read_header(data: &[u8]) -> Result<(md::MINIDUMP_HEADER, scroll::Endian), Error> {
+    let mut offset = 0;
+    let mut endian = LE;
+    let mut header: md::MINIDUMP_HEADER = data
+        .gread_with(&mut offset, endian)
+        .or(Err(Error::MissingHeader))?;
+    if header.signature != md::MINIDUMP_SIGNATURE {
+        if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+        // Try again with big-endian.
+        endian = BE;
+        offset = 0;
+        header = data
+            .gread_with(&mut offset, endian)
+            .or(Err(Error::MissingHeader))?;
+        if header.signature != md::MINIDUMP_SIGNATURE {
+            return Err(Error::HeaderMismatch);
+        }
+    }
+    if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+
+    // Make sure the whole directory is actually in the file before trusting
+    // `stream_count`, so a bogus count can't make us allocate a huge map.
+    ensure_count_in_bound(
+        data,
+        header.stream_count as usize,
+        <md::MINIDUMP_DIRECTORY>::size_with(&endian),
+        header.stream_directory_rva as usize,
+    )
+    .map_err(|err| match err {
+        Error::StreamSizeMismatch { expected, actual } => Error::Truncated { expected, actual },
+        _ => Error::Truncated {
+            expected: usize::MAX,
+            actual: data.len(),
+        },
+    })?;
+    Ok((header, endian))
+}
+
+/// How many plausible headers [`find_minidump`] checks the streams of.
+///
+/// Checking the streams is linear in the stream count, so without a limit data full
+/// of signatures followed by plausible headers would take quadratic time to scan.
+const MAX_MINIDUMP_CANDIDATES: usize = 16;
+
+/// Find a minidump embedded in `bytes`, e.g. after other data in an upload.
+///
+/// This scans for the minidump signature, and returns the offset of the first one
+/// that's followed by a plausible header: the version must be right, and the stream
+/// directory and all of the streams in it must be within `bytes`. The minidump can
+/// then be read with [`Minidump::read_from_slice_at`].
+///
+/// The header's own fields are checked first, which is cheap. Only the first
+/// 16 signatures that pass those checks get their streams checked, so a minidump
+/// after more bogus headers than that isn't found.
+pub fn find_minidump(bytes: &[u8]) -> Option<usize> {
+    let le_signature = md::MINIDUMP_SIGNATURE.to_le_bytes();
+    let be_signature = md::MINIDUMP_SIGNATURE.to_be_bytes();
+    let header_size = <md::MINIDUMP_HEADER>::size_with(&LE);
+    bytes
+        .windows(le_signature.len())
+        .enumerate()
+        .filter(|(_, window)| *window == le_signature || *window == be_signature)
+        .map(|(offset, _)| offset)
+        .filter(|&offset| match read_header(&bytes[offset..]) {
+            // This checks the version and that the directory is within `bytes`.
+            Ok((header, _)) => {
+                header.stream_count > 0 && header.stream_directory_rva as usize >= header_size
+            }
+            Err(_) => false,
+        })
+        .take(MAX_MINIDUMP_CANDIDATES)
+        .find(|&offset| Minidump::validate(&bytes[offset..]).is_empty())
+}
+
+impl<'a> Minidump<'a, &'a [u8]> {
+    /// Read a `Minidump` that starts at `offset` in `bytes`.
+    ///
+    /// This is for minidumps embedded in other data, see [`find_minidump`].
+    /// Everything in `bytes` after the minidump is ignored.
+    pub fn read_from_slice_at(bytes: &'a [u8], offset: usize) -> Result<Self, Error> {
+        let data = bytes.get(offset..).ok_or(Error::MissingHeader)?;
+        Minidump::read(data)
+    }
+
+    /// Check the structure of the minidump in `data`, without reading any streams.
+    ///
+    /// This checks the header, that the stream directory is within `data`, and that
+    /// the data of every stream in the directory is too. It's much cheaper than
+    /// processing a minidump, so it can be used to reject garbage early. An empty list
+    /// means no problems were found, which doesn't mean the streams themselves are
+    /// well-formed.
+    pub fn validate(data: &[u8]) -> Vec<ValidationProblem> {
+        let (header, endian) = match read_header(data) {
+            Ok(header) => header,
+            Err(e) => return vec![ValidationProblem::Unreadable(e)],
+        };
+
+        let mut problems = Vec::new();
+        let mut offset = header.stream_directory_rva as usize;
+        for index in 0..header.stream_count {
+            let dir: md::MINIDUMP_DIRECTORY = match data.gread_with(&mut offset, endian) {
+                Ok(dir) => dir,
+                Err(_) => {
+                    problems.push(ValidationProblem::Unreadable(Error::MissingDirectory));
+                    break;
+                }
+            };
+            if location_slice(data, &dir.location).is_err() {
+                problems.push(ValidationProblem::StreamOutOfBounds {
+                    index,
+                    stream_type: dir.stream_type,
+                    rva: dir.location.rva,
+                    data_size: dir.location.data_size,
+                });
+            }
+        }
+        problems
+    }
+}
+
 impl<'a> Minidump<'a, Mmap> {
     /// Read a `Minidump` from a `Path` to a file on disk.
     ///
@@ -4996,41 +6760,21 @@ where
     /// Read a `Minidump` from the provided `data`.
     ///
     /// Typically this will be a `Vec<u8>` or `&[u8]` with the full contents of the minidump,
-    /// but you can also use something like `memmap::Mmap`.
+    /// but you can also use something like `memmap::Mmap`. Streams refer to data anywhere
+    /// in the file (like module names or thread stacks), so the whole minidump is needed
+    /// up front: to parse one from an `io::Read`, read it into a `Vec<u8>` first.
+    ///
+    /// The stream directory is checked to fit in `data` before anything is allocated
+    /// for it ([`Error::Truncated`] otherwise), so a bogus stream count can't make this
+    /// allocate more than `data` could hold. All of the directory's entries are then
+    /// read eagerly, as is the system info stream (other streams need it to be parsed).
+    /// The rest of the streams are only parsed when requested with
+    /// [`Minidump::get_stream`] and friends.
     pub fn read(data: T) -> Result<Minidump<'a, T>, Error> {
-        let mut offset = 0;
-        let mut endian = LE;
-        let mut header: md::MINIDUMP_HEADER = data
-            .gread_with(&mut offset, endian)
-            .or(Err(Error::MissingHeader))?;
-        if header.signature != md::MINIDUMP_SIGNATURE {
-            if header.signature.swap_bytes() != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-            // Try again with big-endian.
-            endian = BE;
-            offset = 0;
-            header = data
-                .gread_with(&mut offset, endian)
-                .or(Err(Error::MissingHeader))?;
-            if header.signature != md::MINIDUMP_SIGNATURE {
-                return Err(Error::HeaderMismatch);
-            }
-        }
-        if (header.version & 0x0000ffff) != md::MINIDUMP_VERSION {
-            return Err(Error::VersionMismatch);
-        }
-
-        offset = header.stream_directory_rva as usize;
+        let (header, endian) = read_header(&data)?;
+        let mut offset = header.stream_directory_rva as usize;
 
-        let (count, _) = ensure_count_in_bound(
-            &data,
-            header.stream_count as usize,
-            <md::MINIDUMP_DIRECTORY>::size_with(&endian),
-            offset,
-        )?;
-
-        let mut streams = HashMap::with_capacity(count);
+        let mut streams = HashMap::with_capacity(header.stream_count as usize);
         for i in 0..header.stream_count {
             let dir: md::MINIDUMP_DIRECTORY = data
                 .gread_with(&mut offset, endian)
@@ -5139,6 +6883,7 @@ where
     /// * [`MinidumpBreakpadInfo`][]
     /// * [`MinidumpCrashpadInfo`][]
     /// * [`MinidumpException`][]
+    /// * [`MinidumpLinuxCmdLine`][]
     /// * [`MinidumpLinuxCpuInfo`][]
     /// * [`MinidumpLinuxEnviron`][]
     /// * [`MinidumpLinuxLsbRelease`][]
@@ -5151,6 +6896,7 @@ where
     /// * [`MinidumpMiscInfo`][]
     /// * [`MinidumpModuleList`][]
     /// * [`MinidumpSystemInfo`][]
+    /// * [`MinidumpThreadInfoList`][]
     /// * [`MinidumpThreadList`][]
     /// * [`MinidumpThreadNames`][]
     /// * [`MinidumpUnloadedModuleList`][]
@@ -5174,6 +6920,13 @@ where
     /// For streams of known types you almost certainly want to use
     /// [`Minidump::get_stream`][] instead.
     ///
+    /// The bytes are borrowed from the minidump's data, never copied, so this is also
+    /// how to reparse a stream differently. [`Minidump::get_raw_stream_of`][] does the
+    /// same for a [`MinidumpStream`][] type. Parsed streams that borrow from the
+    /// minidump, like [`MinidumpThreadList`][] or [`MinidumpMemoryList`][], also keep
+    /// these bytes and return them from their `raw_bytes` method, while the ones that
+    /// copy everything they need (like [`MinidumpSystemInfo`][]) don't.
+    ///
     /// Note that the lifetime of the returned stream is bound to the lifetime of the this
     /// `Minidump` struct itself and not to the lifetime of the data backing this minidump.
     /// This is a consequence of how this struct relies on [Deref] to access the data.
@@ -5187,6 +6940,61 @@ where
         }
     }
 
+    /// Get the raw data of the [`MinidumpStream`][] `S` from the minidump, without
+    /// parsing it.
+    ///
+    /// This is [`Minidump::get_raw_stream`][] with `S::STREAM_TYPE`, and works for every
+    /// stream type.
+    pub fn get_raw_stream_of<S>(&'a self) -> Result<&'a [u8], Error>
+    where
+        S: MinidumpStream<'a>,
+    {
+        self.get_raw_stream(S::STREAM_TYPE)
+    }
+
+    /// Read the `MINIDUMP_STRING` at `rva` in the minidump's data.
+    ///
+    /// Malformed UTF-16 (like lone surrogates) is replaced with U+FFFD, which is
+    /// how the strings in the minidump's streams (like module names) are read. Returns
+    /// `None` if the string isn't fully contained in the minidump.
+    pub fn read_string(&self, rva: md::RVA) -> Option<String> {
+        read_string_utf16(&mut (rva as usize), self.data.deref(), self.endian)
+    }
+
+    /// Read the `MINIDUMP_STRING` at `rva` in the minidump's data, failing on
+    /// malformed UTF-16.
+    ///
+    /// See [`Minidump::read_string`][] for a lossy version.
+    pub fn read_string_strict(&self, rva: md::RVA) -> Option<String> {
+        read_string_utf16_strict(&mut (rva as usize), self.data.deref(), self.endian)
+    }
+
+    /// Get the location of a stream of known type in the minidump's data.
+    ///
+    /// This is the `(rva, size)` of the bytes that [`Minidump::get_stream`][] would
+    /// parse `S` from, as recorded in the stream directory. Useful for relating a
+    /// parsed stream back to the file, e.g. in a hex dump.
+    pub fn get_stream_location<S>(&self) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error>
+    where
+        S: MinidumpStream<'a>,
+    {
+        self.get_raw_stream_location(S::STREAM_TYPE)
+    }
+
+    /// Get the location of a stream of any type in the minidump's data.
+    ///
+    /// This is the `(rva, size)` of the bytes that [`Minidump::get_raw_stream`][]
+    /// returns, as recorded in the stream directory.
+    pub fn get_raw_stream_location(
+        &self,
+        stream_type: u32,
+    ) -> Result<md::MINIDUMP_LOCATION_DESCRIPTOR, Error> {
+        match self.streams.get(&stream_type) {
+            None => Err(Error::StreamNotFound),
+            Some((_, dir)) => Ok(dir.location),
+        }
+    }
+
     /// Get whichever of the two MemoryLists are available in the minidump,
     /// preferring [`MinidumpMemory64List`][].
     pub fn get_memory(&'a self) -> Option<UnifiedMemoryList<'a>> {
@@ -5199,13 +7007,71 @@ where
             .ok()
     }
 
+    /// What the minidump was asked to capture, decoded from the header's flags.
+    ///
+    /// For example, a minidump with [`MiniDumpWithFullMemory`][md::MinidumpType::MiniDumpWithFullMemory]
+    /// should contain all of the process's readable memory. Unknown flags are dropped,
+    /// [`header.flags`][md::MINIDUMP_HEADER::flags] has the raw value.
+    pub fn flags(&self) -> md::MinidumpType {
+        md::MinidumpType::from_bits_truncate(self.header.flags)
+    }
+
+    /// The width of pointers in the process that wrote this minidump.
+    ///
+    /// This comes from the processor architecture in [`MinidumpSystemInfo`]. Dumps
+    /// lacking that (or naming an unknown architecture) fall back to the architecture
+    /// of the exception's CPU context, or that of the first thread.
+    pub fn pointer_width(&'a self) -> PointerWidth {
+        if let Some(system_info) = &self.system_info {
+            let pointer_width = system_info.cpu.pointer_width();
+            if pointer_width != PointerWidth::Unknown {
+                return pointer_width;
+            }
+        }
+
+        let exception_context = self
+            .get_stream::<MinidumpException>()
+            .ok()
+            .and_then(|exception| exception.context);
+        let thread_context = || {
+            self.get_stream::<MinidumpThreadList>()
+                .ok()
+                .and_then(|threads| threads.threads.first().and_then(|thread| thread.context))
+        };
+        exception_context
+            .or_else(thread_context)
+            .and_then(|context| MinidumpContext::guess_architecture(context, self.endian))
+            .map_or(PointerWidth::Unknown, |cpu| cpu.pointer_width())
+    }
+
+    /// The set of symbol files (debug_file, debug_identifier) that the modules of
+    /// this Minidump refer to.
+    ///
+    /// This only parses the module list, so it can be used to prefetch symbols
+    /// before any actual processing happens. Modules that are missing either half
+    /// of the key are skipped, and duplicate keys are only listed once.
+    pub fn referenced_symbols(&'a self) -> BTreeSet<(String, DebugId)> {
+        let modules = match self.get_stream::<MinidumpModuleList>() {
+            Ok(modules) => modules,
+            Err(_) => return BTreeSet::new(),
+        };
+        modules
+            .iter()
+            .filter_map(|module| {
+                let debug_file = module.debug_file()?;
+                let debug_id = module.debug_identifier()?;
+                Some((debug_file.into_owned(), debug_id))
+            })
+            .collect()
+    }
+
     /// A listing of all the streams in the Minidump that this library is *aware* of,
     /// but has no further analysis for.
     ///
     /// If there are multiple copies of the same stream type (which should not happen for
     /// well-formed Minidumps), then only one of them will be yielded, arbitrarily.
     pub fn unimplemented_streams(&self) -> impl Iterator<Item = MinidumpUnimplementedStream> + '_ {
-        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 32] = [
+        static UNIMPLEMENTED_STREAMS: [MINIDUMP_STREAM_TYPE; 27] = [
             // Presumably will never have an implementation:
             MINIDUMP_STREAM_TYPE::UnusedStream,
             MINIDUMP_STREAM_TYPE::ReservedStream0,
@@ -5216,10 +7082,6 @@ where
             MINIDUMP_STREAM_TYPE::CommentStreamA,
             MINIDUMP_STREAM_TYPE::CommentStreamW,
             MINIDUMP_STREAM_TYPE::HandleDataStream,
-            MINIDUMP_STREAM_TYPE::FunctionTable,
-            MINIDUMP_STREAM_TYPE::ThreadInfoListStream,
-            MINIDUMP_STREAM_TYPE::HandleOperationListStream,
-            MINIDUMP_STREAM_TYPE::TokenStream,
             MINIDUMP_STREAM_TYPE::JavaScriptDataStream,
             MINIDUMP_STREAM_TYPE::SystemMemoryInfoStream,
             MINIDUMP_STREAM_TYPE::ProcessVmCountersStream,
@@ -5240,7 +7102,6 @@ where
             MINIDUMP_STREAM_TYPE::ceStreamProcessModuleMap,
             MINIDUMP_STREAM_TYPE::ceStreamDiagnosisList,
             // non-standard streams (should also be implemented):
-            MINIDUMP_STREAM_TYPE::LinuxCmdLine,
             MINIDUMP_STREAM_TYPE::LinuxAuxv,
             MINIDUMP_STREAM_TYPE::LinuxDsoDebug,
         ];
@@ -5283,6 +7144,33 @@ where
         self.streams.iter().map(|(_, (_, stream))| stream)
     }
 
+    /// The types of all the streams in the Minidump.
+    ///
+    /// This only looks at the stream directory, so it's cheap: no stream is decoded.
+    /// Stream types this library doesn't know about are included.
+    pub fn streams_present(&self) -> HashSet<u32> {
+        self.streams.keys().copied().collect()
+    }
+
+    /// The CPU architecture of the process the minidump was taken of.
+    ///
+    /// This is the CPU in [`MinidumpSystemInfo`] if it's known. Some minimal
+    /// minidumps don't have that stream, so otherwise it's guessed from the first
+    /// thread context that can be recognized (see
+    /// [`MinidumpContext::guess_architecture`]).
+    pub fn architecture(&self) -> Option<Cpu> {
+        if let Ok(system_info) = self.get_stream::<MinidumpSystemInfo>() {
+            if !matches!(system_info.cpu, Cpu::Unknown(_)) {
+                return Some(system_info.cpu);
+            }
+        }
+        let thread_list = self.get_stream::<MinidumpThreadList>().ok()?;
+        thread_list
+            .threads
+            .iter()
+            .find_map(|thread| thread.raw_context_cpu(None))
+    }
+
     /// Write a verbose description of the `Minidump` to `f`.
     pub fn print<W: Write>(&self, f: &mut W) -> io::Result<()> {
         fn get_stream_name(stream_type: u32) -> Cow<'static, str> {
@@ -5367,11 +7255,11 @@ mod test {
     use md::GUID;
     use minidump_common::format::{PlatformId, ProcessorArchitecture};
     use minidump_synth::{
-        self, AnnotationValue, CrashpadInfo, DumpString, Exception, Memory,
-        MemoryInfo as SynthMemoryInfo, MiscFieldsBuildString, MiscFieldsPowerInfo,
+        self, AnnotationValue, CrashpadInfo, DumpSection, DumpString, Exception, HandleOperation,
+        Memory, MemoryInfo as SynthMemoryInfo, MiscFieldsBuildString, MiscFieldsPowerInfo,
         MiscFieldsProcessTimes, MiscFieldsTimeZone, MiscInfo5Fields, MiscStream,
         Module as SynthModule, ModuleCrashpadInfo, SimpleStream, SynthMinidump, SystemInfo, Thread,
-        ThreadName, UnloadedModule as SynthUnloadedModule, STOCK_VERSION_INFO,
+        ThreadInfo, ThreadName, UnloadedModule as SynthUnloadedModule, STOCK_VERSION_INFO,
     };
     use std::mem;
     use test_assembler::*;
@@ -5420,6 +7308,439 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_flags() {
+        let dump = read_synth_dump(SynthMinidump::with_endian(Endian::Little)).unwrap();
+        assert_eq!(dump.flags(), md::MinidumpType::empty());
+
+        // Unknown flags are ignored.
+        let dump = SynthMinidump::with_endian(Endian::Little).flags(0x8000_0000_0000_1006);
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(
+            dump.flags(),
+            md::MinidumpType::MiniDumpWithFullMemory
+                | md::MinidumpType::MiniDumpWithHandleData
+                | md::MinidumpType::MiniDumpWithThreadInfo
+        );
+        assert!(dump
+            .flags()
+            .contains(md::MinidumpType::MiniDumpWithFullMemory));
+        assert!(!dump
+            .flags()
+            .contains(md::MinidumpType::MiniDumpWithDataSegs));
+    }
+
+    #[test]
+    fn test_os_version_string() {
+        fn os_version_string(
+            platform_id: PlatformId,
+            version: (u32, u32, u32),
+            csd_version: Option<&str>,
+        ) -> String {
+            let mut system_info = SystemInfo::new(Endian::Little)
+                .set_platform_id(platform_id as u32)
+                .set_version(version.0, version.1, version.2);
+            let mut dump = SynthMinidump::with_endian(Endian::Little);
+            if let Some(csd_version) = csd_version {
+                let csd_version = DumpString::new(csd_version, Endian::Little);
+                system_info = system_info.set_csd_version(&csd_version);
+                dump = dump.add(csd_version);
+            }
+            let dump = read_synth_dump(dump.add_system_info(system_info)).unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            system_info.os_version_string()
+        }
+
+        assert_eq!(
+            os_version_string(PlatformId::VER_PLATFORM_WIN32_NT, (10, 0, 19045), None),
+            "Windows 10.0.19045"
+        );
+        assert_eq!(
+            os_version_string(
+                PlatformId::VER_PLATFORM_WIN32_NT,
+                (6, 1, 7601),
+                Some("Service Pack 1")
+            ),
+            "Windows 6.1.7601 Service Pack 1"
+        );
+        assert_eq!(
+            os_version_string(
+                PlatformId::Linux,
+                (0, 0, 0),
+                Some("Linux 5.15.0-76-generic #83-Ubuntu SMP Thu Jun 15 19:16:32 UTC 2023 x86_64")
+            ),
+            "Linux 5.15.0-76-generic"
+        );
+        assert_eq!(
+            os_version_string(PlatformId::MacOs, (10, 15, 7), Some("19H2")),
+            "Mac OS X 10.15.7 (19H2)"
+        );
+        assert_eq!(
+            os_version_string(PlatformId::Android, (0, 0, 0), None),
+            "Android 0.0.0"
+        );
+    }
+
+    #[test]
+    fn test_assertion_big_endian() {
+        fn utf16_field(section: Section, string: &str) -> Section {
+            let units: Vec<u16> = string.encode_utf16().collect();
+            let section = units
+                .iter()
+                .fold(section, |section, &unit| section.D16(unit));
+            section.append_repeated(0, (128 - units.len()) * 2)
+        }
+
+        let section = Section::with_endian(Endian::Big);
+        let section = utf16_field(section, "x != 0");
+        let section = utf16_field(section, "frobnicate");
+        let section = utf16_field(section, "frob.c");
+        let section = section.D32(42).D32(1);
+        let dump = SynthMinidump::with_endian(Endian::Big).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::AssertionInfoStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let assertion = dump.get_stream::<MinidumpAssertion>().unwrap();
+        assert_eq!(assertion.expression().unwrap(), "x != 0");
+        assert_eq!(assertion.function().unwrap(), "frobnicate");
+        assert_eq!(assertion.file().unwrap(), "frob.c");
+        assert_eq!(assertion.raw.line, 42);
+    }
+
+    #[test]
+    fn test_stream_location() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(SystemInfo::new(Endian::Little))
+            .add_stream(SimpleStream {
+                stream_type: STREAM_TYPE,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            });
+        let dump = read_synth_dump(dump).unwrap();
+
+        let directory = |stream_type: u32| {
+            dump.all_streams()
+                .find(|stream| stream.stream_type == stream_type)
+                .unwrap()
+                .location
+        };
+
+        let location = dump.get_stream_location::<MinidumpSystemInfo>().unwrap();
+        let expected = directory(MINIDUMP_STREAM_TYPE::SystemInfoStream as u32);
+        assert_eq!(location.rva, expected.rva);
+        assert_eq!(location.data_size, expected.data_size);
+        assert_eq!(
+            location.data_size as usize,
+            <md::MINIDUMP_SYSTEM_INFO>::size_with(&LE)
+        );
+
+        let location = dump.get_raw_stream_location(STREAM_TYPE).unwrap();
+        let expected = directory(STREAM_TYPE);
+        assert_eq!(location.rva, expected.rva);
+        assert_eq!(location.data_size, 4);
+        let start = location.rva as usize;
+        assert_eq!(
+            &dump.data[start..start + 4],
+            dump.get_raw_stream(STREAM_TYPE).unwrap()
+        );
+
+        assert_eq!(
+            dump.get_stream_location::<MinidumpThreadList>().err(),
+            Some(Error::StreamNotFound)
+        );
+    }
+
+    #[test]
+    fn test_directory_after_streams() {
+        // Most producers other than Breakpad write the directory after the streams,
+        // possibly with a gap in between.
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x11223344,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x22334455,
+                section: Section::with_endian(Endian::Little).D32(0x66778899),
+            })
+            .with_directory_rva(0x400);
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.header.stream_directory_rva, 0x400);
+        assert_eq!(dump.header.stream_count, 2);
+        assert_eq!(
+            dump.get_raw_stream(0x11223344).unwrap(),
+            &[0x88, 0x77, 0x66, 0x55]
+        );
+        assert_eq!(
+            dump.get_raw_stream(0x22334455).unwrap(),
+            &[0x99, 0x88, 0x77, 0x66]
+        );
+        assert!(dump
+            .all_streams()
+            .all(|stream| stream.location.rva < dump.header.stream_directory_rva));
+
+        // The directory can't overlap the streams.
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x11223344,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            })
+            .with_directory_rva(0x10);
+        assert!(dump.finish().is_none());
+    }
+
+    #[test]
+    fn test_out_of_order_directory() {
+        fn synth_dump(out_of_order: bool) -> SynthMinidump {
+            let name = DumpString::new("module.dll", Endian::Little);
+            let module = SynthModule::new(
+                Endian::Little,
+                0x40000000,
+                0x10000,
+                &name,
+                0xb1054d2a,
+                0x34571371,
+                Some(&STOCK_VERSION_INFO),
+            );
+            let context = minidump_synth::x86_context(Endian::Little, 0x40001000, 0x1010);
+            let stack = Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0xab, 0x100),
+                0x1000,
+            );
+            let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+            let mut exception = Exception::new(Endian::Little);
+            exception.thread_id = 0x1234;
+            exception.exception_record.exception_address = 0x40001000;
+            let mut dump = SynthMinidump::with_endian(Endian::Little);
+            if out_of_order {
+                dump = dump.with_out_of_order_directory().with_directory_rva(0x800);
+            }
+            dump.add_stream(SimpleStream {
+                stream_type: 0x11223344,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            })
+            .add_stream(SimpleStream {
+                stream_type: 0x22334455,
+                section: Section::with_endian(Endian::Little).D32(0x66778899),
+            })
+            .add_module(module)
+            .add(name)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_exception(exception)
+            .add_system_info(SystemInfo::new(Endian::Little))
+        }
+
+        let ordered = read_synth_dump(synth_dump(false)).unwrap();
+        let shuffled = read_synth_dump(synth_dump(true)).unwrap();
+
+        // The directory is neither in the order of the streams, nor after all of them.
+        let directory_rva = shuffled.header.stream_directory_rva;
+        assert_eq!(directory_rva, 0x800);
+        let rvas = shuffled
+            .all_streams()
+            .map(|stream| stream.location.rva)
+            .collect::<Vec<_>>();
+        assert!(rvas.iter().any(|&rva| rva > directory_rva));
+        assert!(rvas.iter().any(|&rva| rva < directory_rva));
+        let types = |dump: &Minidump<Vec<u8>>| {
+            let mut types = Vec::new();
+            for i in 0..dump.header.stream_count as usize {
+                let offset = dump.header.stream_directory_rva as usize
+                    + i * mem::size_of::<md::MINIDUMP_DIRECTORY>();
+                types.push(u32::from_le_bytes(
+                    dump.data[offset..offset + 4].try_into().unwrap(),
+                ));
+            }
+            types
+        };
+        let mut reversed = types(&ordered);
+        reversed.reverse();
+        assert_eq!(types(&shuffled), reversed);
+
+        // But both parse the same.
+        assert_eq!(ordered.streams_present(), shuffled.streams_present());
+        for stream_type in [0x11223344, 0x22334455] {
+            assert_eq!(
+                ordered.get_raw_stream(stream_type).unwrap(),
+                shuffled.get_raw_stream(stream_type).unwrap()
+            );
+        }
+        for dump in [&ordered, &shuffled] {
+            let modules = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let module = modules.module_at_address(0x40001000).unwrap();
+            assert_eq!(module.code_file(), "module.dll");
+
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            let misc_info = dump.get_stream::<MinidumpMiscInfo>().ok();
+            let threads = dump.get_stream::<MinidumpThreadList>().unwrap();
+            assert_eq!(threads.threads.len(), 1);
+            let thread = threads.get_thread(0x1234).unwrap();
+            let context = thread.context(&system_info, misc_info.as_ref()).unwrap();
+            assert_eq!(context.get_instruction_pointer(), 0x40001000);
+
+            let memory = dump.get_stream::<MinidumpMemoryList>().unwrap();
+            let stack = memory.memory_at_address(0x1000).unwrap();
+            assert_eq!(stack.bytes, &[0xab; 0x100][..]);
+
+            let exception = dump.get_stream::<MinidumpException>().unwrap();
+            assert_eq!(exception.get_crashing_thread_id(), 0x1234);
+            assert_eq!(exception.raw.exception_record.exception_address, 0x40001000);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x11223344,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            })
+            .add_system_info(SystemInfo::new(Endian::Little));
+        let mut data = dump.finish().unwrap();
+        assert_eq!(Minidump::validate(&data), vec![]);
+
+        // Point the first directory entry's data past the end of the file.
+        let rva_offset = {
+            let dump = Minidump::read(&data[..]).unwrap();
+            dump.header.stream_directory_rva as usize + 8
+        };
+        let bad_rva = data.len() as u32 - 2;
+        data[rva_offset..rva_offset + 4].copy_from_slice(&bad_rva.to_le_bytes());
+        assert_eq!(
+            Minidump::validate(&data),
+            vec![ValidationProblem::StreamOutOfBounds {
+                index: 0,
+                stream_type: 0x11223344,
+                rva: bad_rva,
+                data_size: 4,
+            }]
+        );
+
+        assert_eq!(
+            Minidump::validate(&data[..20]),
+            vec![ValidationProblem::Unreadable(Error::MissingHeader)]
+        );
+        assert_eq!(
+            Minidump::validate(b"not a minidump, but long enough to have a header"),
+            vec![ValidationProblem::Unreadable(Error::HeaderMismatch)]
+        );
+    }
+
+    #[test]
+    fn test_header_signature_and_version() {
+        let bytes = SynthMinidump::with_endian(Endian::Little)
+            .with_signature(0x12345678)
+            .finish()
+            .unwrap();
+        assert_eq!(
+            Minidump::read(&bytes[..]).err(),
+            Some(Error::HeaderMismatch)
+        );
+
+        let bytes = SynthMinidump::with_endian(Endian::Little)
+            .with_version(0x1234)
+            .finish()
+            .unwrap();
+        assert_eq!(
+            Minidump::read(&bytes[..]).err(),
+            Some(Error::VersionMismatch)
+        );
+
+        // The high 16 bits of the version are implementation-specific.
+        let bytes = SynthMinidump::with_endian(Endian::Big)
+            .with_version(0x1234_0000 | md::MINIDUMP_VERSION)
+            .finish()
+            .unwrap();
+        let dump = Minidump::read(&bytes[..]).unwrap();
+        assert_eq!(dump.header.version, 0x1234_0000 | md::MINIDUMP_VERSION);
+    }
+
+    #[test]
+    fn test_find_minidump() {
+        const STREAM_TYPE: u32 = 0x11223344;
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: STREAM_TYPE,
+            section: Section::with_endian(Endian::Little).D32(0x55667788),
+        });
+        let dump = dump.finish().unwrap();
+
+        // Junk (including a bogus signature) before the minidump, and more after it.
+        let mut data = b"--boundary\r\nContent-Type: MDMP junk\r\n\r\n".to_vec();
+        let offset = data.len();
+        data.extend_from_slice(&dump);
+        data.extend_from_slice(b"\r\n--boundary--\r\n");
+
+        assert_eq!(find_minidump(&data), Some(offset));
+        let dump = Minidump::read_from_slice_at(&data, offset).unwrap();
+        assert_eq!(
+            dump.get_raw_stream(STREAM_TYPE).unwrap(),
+            &[0x88, 0x77, 0x66, 0x55]
+        );
+
+        assert_eq!(find_minidump(&data[..offset]), None);
+        assert_eq!(find_minidump(&data[..offset + 40]), None);
+        assert_eq!(
+            Minidump::read_from_slice_at(&data, data.len() + 1).err(),
+            Some(Error::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn test_find_minidump_candidate_limit() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_stream(SimpleStream {
+                stream_type: 0x11223344,
+                section: Section::with_endian(Endian::Little).D32(0x55667788),
+            })
+            .finish()
+            .unwrap();
+        // A header that looks fine on its own, but its only stream is out of bounds.
+        let bogus = Section::with_endian(Endian::Little)
+            .D32(md::MINIDUMP_SIGNATURE)
+            .D32(md::MINIDUMP_VERSION)
+            .D32(1) // stream_count
+            .D32(32) // stream_directory_rva
+            .D32(0) // checksum
+            .D32(0) // time_date_stamp
+            .D64(0) // flags
+            .D32(0x11223344) // stream_type
+            .D32(0x1000_0000) // data_size
+            .D32(0) // rva
+            .get_contents()
+            .unwrap();
+
+        let embed = |bogus_count: usize| {
+            let mut data = bogus.repeat(bogus_count);
+            data.extend_from_slice(&dump);
+            (data, bogus_count * bogus.len())
+        };
+        let (data, offset) = embed(MAX_MINIDUMP_CANDIDATES - 1);
+        assert_eq!(find_minidump(&data), Some(offset));
+        let (data, _) = embed(MAX_MINIDUMP_CANDIDATES);
+        assert_eq!(find_minidump(&data), None);
+    }
+
+    #[test]
+    fn test_huge_stream_count() {
+        let header = Section::with_endian(Endian::Little)
+            .D32(md::MINIDUMP_SIGNATURE)
+            .D32(md::MINIDUMP_VERSION)
+            .D32(u32::MAX) // stream_count
+            .D32(32) // stream_directory_rva
+            .D32(0) // checksum
+            .D32(0) // time_date_stamp
+            .D64(0); // flags
+        let data = header.get_contents().unwrap();
+        assert_eq!(data.len(), 32);
+        match Minidump::read(data) {
+            Err(Error::Truncated { actual, .. }) => assert_eq!(actual, 32),
+            other => panic!("expected a truncation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn test_thread_names() {
         let good_thread_id = 17;
@@ -5447,6 +7768,87 @@ mod test {
         assert_eq!(thread_names.get_name(corrupt_thread_id), None);
     }
 
+    #[test]
+    fn test_module_list_malformed_name() {
+        // "c:\a<lone high surrogate>b.dll"
+        let units = "c:\\a"
+            .encode_utf16()
+            .chain(Some(0xd800))
+            .chain("b.dll".encode_utf16())
+            .collect::<Vec<_>>();
+        let name = DumpString::from_utf16(units, Endian::Little);
+        let name_rva = name.file_offset();
+        let module = SynthModule::new(
+            Endian::Little,
+            0x100000,
+            0x1000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            None,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name);
+        let dump = read_synth_dump(dump).unwrap();
+
+        // The bad surrogate doesn't prevent reading the module
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].code_file(), "c:\\a\u{fffd}b.dll");
+
+        let name_rva = name_rva.value().unwrap() as u32;
+        assert_eq!(
+            dump.read_string(name_rva).as_deref(),
+            Some("c:\\a\u{fffd}b.dll")
+        );
+        assert_eq!(dump.read_string_strict(name_rva), None);
+    }
+
+    #[test]
+    fn test_string_length_limits() {
+        // A string claiming to be far larger than the whole minidump.
+        let absurd = Section::with_endian(Endian::Little)
+            .D32(0xffff_fff0)
+            .D16(u16::from(b'a'))
+            .D16(0);
+        let absurd_rva = absurd.file_offset();
+        // A string that is really there, but longer than the limit, with a surrogate
+        // pair straddling the limit.
+        let units = vec![u16::from(b'a'); MAX_STRING_SIZE / 2 - 1]
+            .into_iter()
+            .chain([0xd83d, 0xde00])
+            .chain("tail".encode_utf16());
+        let huge = DumpString::from_utf16(units, Endian::Little);
+        let huge_rva = huge.file_offset();
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add(absurd)
+            .add(huge);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let absurd_rva = absurd_rva.value().unwrap() as u32;
+        assert_eq!(dump.read_string(absurd_rva), None);
+        assert_eq!(dump.read_string_strict(absurd_rva), None);
+
+        let huge_rva = huge_rva.value().unwrap() as u32;
+        let expected = "a".repeat(MAX_STRING_SIZE / 2 - 1);
+        assert_eq!(dump.read_string(huge_rva), Some(expected.clone()));
+        assert_eq!(dump.read_string_strict(huge_rva), Some(expected));
+
+        // UTF-8 strings are limited the same way, without splitting characters.
+        let mut bytes = vec![0; 4];
+        bytes.extend("a".repeat(MAX_STRING_SIZE - 1).bytes());
+        bytes.extend("\u{e9}tail".bytes());
+        let length = (bytes.len() - 4) as u32;
+        bytes[..4].copy_from_slice(&length.to_le_bytes());
+        let string = read_string_utf8_unterminated(&mut 0, &bytes, LE).unwrap();
+        assert_eq!(string.len(), MAX_STRING_SIZE - 1);
+
+        let absurd = [0xf0, 0xff, 0xff, 0xff, b'a'];
+        assert_eq!(read_string_utf8_unterminated(&mut 0, &absurd, LE), None);
+    }
+
     #[test]
     fn test_module_list() {
         let name = DumpString::new("single module", Endian::Little);
@@ -5490,6 +7892,143 @@ mod test {
             modules[0].debug_identifier().unwrap(),
             DebugId::from_breakpad("ABCD1234F00DBEEF01020304050607081").unwrap()
         );
+
+        // The names borrow from the module, so getting them doesn't allocate.
+        assert!(matches!(modules[0].code_file(), Cow::Borrowed(_)));
+        assert!(matches!(modules[0].debug_file(), Some(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn test_module_version_file_info() {
+        let version_info = md::VS_FIXEDFILEINFO {
+            // Prerelease is set but masked out
+            file_flags_mask: (md::VersionFileFlags::VS_FF_DEBUG
+                | md::VersionFileFlags::VS_FF_PATCHED)
+                .bits(),
+            file_flags: (md::VersionFileFlags::VS_FF_DEBUG
+                | md::VersionFileFlags::VS_FF_PATCHED
+                | md::VersionFileFlags::VS_FF_PRERELEASE)
+                .bits(),
+            file_os: md::VersionFileOs::VOS_NT_WINDOWS32 as u32,
+            file_type: md::VersionFileType::VFT_DRV as u32,
+            file_subtype: md::VersionFileSubtypeDriver::VFT2_DRV_DISPLAY as u32,
+            ..STOCK_VERSION_INFO
+        };
+        let name1 = DumpString::new("driver.sys", Endian::Little);
+        let name2 = DumpString::new("no_version.dll", Endian::Little);
+        let module1 = SynthModule::new(
+            Endian::Little,
+            0x100000,
+            0x1000,
+            &name1,
+            0xb1054d2a,
+            0x34571371,
+            Some(&version_info),
+        );
+        let module2 = SynthModule::new(
+            Endian::Little,
+            0x200000,
+            0x1000,
+            &name2,
+            0xb1054d2a,
+            0x34571371,
+            Some(&md::VS_FIXEDFILEINFO::default()),
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module1)
+            .add_module(module2)
+            .add(name1)
+            .add(name2);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            modules[0].file_flags(),
+            Some(md::VersionFileFlags::VS_FF_DEBUG | md::VersionFileFlags::VS_FF_PATCHED)
+        );
+        assert!(modules[0].is_debug_build());
+        assert_eq!(
+            modules[0].file_os(),
+            Some(md::VersionFileOs::VOS_NT_WINDOWS32)
+        );
+        assert_eq!(modules[0].file_type(), Some(md::VersionFileType::VFT_DRV));
+        assert_eq!(
+            modules[0].file_subtype(),
+            Some(VersionFileSubtype::Driver(
+                md::VersionFileSubtypeDriver::VFT2_DRV_DISPLAY
+            ))
+        );
+
+        assert!(modules[1].version_info().is_none());
+        assert_eq!(modules[1].file_flags(), None);
+        assert!(!modules[1].is_debug_build());
+        assert_eq!(modules[1].file_os(), None);
+        assert_eq!(modules[1].file_type(), None);
+        assert_eq!(modules[1].file_subtype(), None);
+    }
+
+    #[test]
+    fn test_referenced_symbols() {
+        let name1 = DumpString::new("main.exe", Endian::Little);
+        let cv_record1 = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32) // signature
+            // signature, a GUID
+            .D32(0xabcd1234)
+            .D16(0xf00d)
+            .D16(0xbeef)
+            .append_bytes(b"\x01\x02\x03\x04\x05\x06\x07\x08")
+            .D32(1) // age
+            .append_bytes(b"main.pdb\0"); // pdb_file_name
+        let module1 = SynthModule::new(
+            Endian::Little,
+            0x100000,
+            0x4000,
+            &name1,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record1);
+        let name2 = DumpString::new("helper.dll", Endian::Little);
+        let cv_record2 = Section::with_endian(Endian::Little)
+            .D32(md::CvSignature::Pdb70 as u32) // signature
+            // signature, a GUID
+            .D32(0x11223344)
+            .D16(0x5566)
+            .D16(0x7788)
+            .append_bytes(b"\x09\x0a\x0b\x0c\x0d\x0e\x0f\x10")
+            .D32(2) // age
+            .append_bytes(b"helper.pdb\0"); // pdb_file_name
+        let module2 = SynthModule::new(
+            Endian::Little,
+            0x200000,
+            0x4000,
+            &name2,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        )
+        .cv_record(&cv_record2);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module1)
+            .add_module(module2)
+            .add(name1)
+            .add(cv_record1)
+            .add(name2)
+            .add(cv_record2);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let symbols = dump.referenced_symbols();
+        assert_eq!(symbols.len(), 2);
+        assert!(symbols.contains(&(
+            String::from("main.pdb"),
+            DebugId::from_breakpad("ABCD1234F00DBEEF01020304050607081").unwrap()
+        )));
+        assert!(symbols.contains(&(
+            String::from("helper.pdb"),
+            DebugId::from_breakpad("1122334455667788090A0B0C0D0E0F102").unwrap()
+        )));
     }
 
     #[test]
@@ -5514,51 +8053,255 @@ mod test {
         let dump = SynthMinidump::with_endian(Endian::Little)
             .add_module(module)
             .add(name)
-            .add(cv_record);
+            .add(cv_record);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
+        assert_eq!(modules[0].size(), 0xada542bd);
+        assert_eq!(modules[0].code_file(), "single module");
+        // time_date_stamp and size_of_image concatenated
+        assert_eq!(
+            modules[0].code_identifier().unwrap(),
+            CodeId::new("B1054D2Aada542bd".to_string())
+        );
+        assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
+        assert_eq!(
+            modules[0].debug_identifier().unwrap(),
+            DebugId::from_pdb20(0xabcd1234, 1)
+        );
+    }
+
+    #[test]
+    fn test_unloaded_module_list() {
+        let name = DumpString::new("single module", Endian::Little);
+        let module = SynthUnloadedModule::new(
+            Endian::Little,
+            0xa90206ca83eb2852,
+            0xada542bd,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_unloaded_module(module)
+            .add(name);
+        let dump = read_synth_dump(dump).unwrap();
+        let module_list = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
+        let modules = module_list.iter().collect::<Vec<_>>();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
+        assert_eq!(modules[0].size(), 0xada542bd);
+        assert_eq!(modules[0].code_file(), "single module");
+        // time_date_stamp and size_of_image concatenated
+        assert_eq!(
+            modules[0].code_identifier().unwrap(),
+            CodeId::new("B1054D2Aada542bd".to_string())
+        );
+    }
+
+    #[test]
+    fn test_thread_info_list() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread_info(ThreadInfo::new(Endian::Little, 0x1234, 0x401000, 0b11))
+            .add_thread_info(ThreadInfo::new(Endian::Little, 0x5678, 0x402000, 0b100));
+        let dump = read_synth_dump(dump).unwrap();
+        // The stream is implemented, so it isn't listed as unimplemented.
+        assert_eq!(dump.unimplemented_streams().count(), 0);
+        let thread_infos = dump.get_stream::<MinidumpThreadInfoList>().unwrap();
+        assert_eq!(thread_infos.iter().count(), 2);
+
+        let info = thread_infos.get_thread_info(0x5678).unwrap();
+        assert_eq!(info.thread_id(), 0x5678);
+        assert_eq!(info.start_address(), 0x402000);
+        assert_eq!(info.affinity(), 0b100);
+        assert_eq!(
+            thread_infos.get_thread_info(0x1234).unwrap().affinity(),
+            0b11
+        );
+        assert!(thread_infos.get_thread_info(0x9abc).is_none());
+    }
+
+    #[test]
+    fn test_handle_operation_list() {
+        let name = DumpString::new("module 1", Endian::Little);
+        let module = SynthModule::new(
+            Endian::Little,
+            0x100000,
+            0x4000,
+            &name,
+            0xb1054d2a,
+            0x34571371,
+            Some(&STOCK_VERSION_INFO),
+        );
+        let operation = HandleOperation::new(
+            Endian::Little,
+            0xabcd,
+            0x1234,
+            0x5678,
+            md::HandleTraceOperation::OperationDbClose as u32,
+            &[0x101000, 0x102000, 0x900000],
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_module(module)
+            .add(name)
+            .add_handle_operation(operation);
         let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 1);
-        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
-        assert_eq!(modules[0].size(), 0xada542bd);
-        assert_eq!(modules[0].code_file(), "single module");
-        // time_date_stamp and size_of_image concatenated
+        let operation_list = dump.get_stream::<MinidumpHandleOperationList>().unwrap();
+        let operations = operation_list.iter().collect::<Vec<_>>();
+        assert_eq!(operations.len(), 1);
+
+        let operation = operations[0];
+        assert_eq!(operation.handle(), 0xabcd);
+        assert_eq!(operation.process_id(), 0x1234);
+        assert_eq!(operation.thread_id(), 0x5678);
         assert_eq!(
-            modules[0].code_identifier().unwrap(),
-            CodeId::new("B1054D2Aada542bd".to_string())
+            operation.operation_type(),
+            Some(md::HandleTraceOperation::OperationDbClose)
         );
-        assert_eq!(modules[0].debug_file().unwrap(), "c:\\foo\\file.pdb");
+        assert_eq!(operation.backtrace(), &[0x101000, 0x102000, 0x900000]);
+
+        let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+        let frames = operation
+            .backtrace_modules(&module_list)
+            .map(|(address, module)| (address, module.map(|m| m.code_file())))
+            .collect::<Vec<_>>();
         assert_eq!(
-            modules[0].debug_identifier().unwrap(),
-            DebugId::from_pdb20(0xabcd1234, 1)
+            frames,
+            vec![
+                (0x101000, Some(Cow::Borrowed("module 1"))),
+                (0x102000, Some(Cow::Borrowed("module 1"))),
+                (0x900000, None),
+            ]
         );
     }
 
     #[test]
-    fn test_unloaded_module_list() {
-        let name = DumpString::new("single module", Endian::Little);
-        let module = SynthUnloadedModule::new(
-            Endian::Little,
-            0xa90206ca83eb2852,
-            0xada542bd,
-            &name,
-            0xb1054d2a,
-            0x34571371,
+    fn test_function_table_list() {
+        let section = Section::with_endian(Endian::Little)
+            // MINIDUMP_FUNCTION_TABLE_STREAM
+            .D32(24)
+            .D32(32)
+            .D32(4)
+            .D32(12)
+            .D32(2)
+            .D32(4)
+            .append_repeated(0, 4)
+            // first table, with two functions
+            .D64(0x10000)
+            .D64(0x10200)
+            .D64(0x10000)
+            .D32(2)
+            .D32(4)
+            .D32(0xabcdef01)
+            .D32(0x0)
+            .D32(0x100)
+            .D32(0x1000)
+            .D32(0x100)
+            .D32(0x200)
+            .D32(0x1010)
+            .append_repeated(0, 4)
+            // second table, with no functions
+            .D64(0x20000)
+            .D64(0x20100)
+            .D64(0x20000)
+            .D32(0)
+            .D32(0)
+            .D32(0x12345678);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::FunctionTable as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let table_list = dump.get_stream::<MinidumpFunctionTableList>().unwrap();
+        let tables = table_list.iter().collect::<Vec<_>>();
+        assert_eq!(tables.len(), 2);
+
+        assert_eq!(tables[0].raw.minimum_address, 0x10000);
+        assert_eq!(tables[0].raw.maximum_address, 0x10200);
+        assert_eq!(tables[0].raw.base_address, 0x10000);
+        assert_eq!(tables[0].native_descriptor, &[0x01, 0xef, 0xcd, 0xab]);
+        let functions = tables[0]
+            .runtime_functions()
+            .map(|f| (f.begin_address, f.end_address, f.unwind_info_address))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            functions,
+            vec![(0x0, 0x100, 0x1000), (0x100, 0x200, 0x1010)]
         );
-        let dump = SynthMinidump::with_endian(Endian::Little)
-            .add_unloaded_module(module)
-            .add(name);
+        let function = tables[0].runtime_function_at_address(0x10180).unwrap();
+        assert_eq!(function.unwind_info_address, 0x1010);
+        assert!(tables[0].runtime_function_at_address(0x10200).is_none());
+
+        assert_eq!(tables[1].native_descriptor, &[0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(tables[1].runtime_functions().count(), 0);
+
+        assert_eq!(
+            table_list
+                .table_at_address(0x20080)
+                .unwrap()
+                .raw
+                .base_address,
+            0x20000
+        );
+        assert!(table_list.table_at_address(0x30000).is_none());
+    }
+
+    #[test]
+    fn test_token_list() {
+        let section = Section::with_endian(Endian::Little)
+            // MINIDUMP_TOKEN_INFO_LIST
+            .D32(16 + 16 + 4 + 16 + 2)
+            .D32(2)
+            .D32(16)
+            .D32(16)
+            // first token
+            .D32(16 + 4)
+            .D32(1)
+            .D64(0xabcd)
+            .append_bytes(&[1, 2, 3, 4])
+            // second token
+            .D32(16 + 2)
+            .D32(2)
+            .D64(0x1234)
+            .append_bytes(&[5, 6]);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::TokenStream as u32,
+            section,
+        });
+        let dump = read_synth_dump(dump).unwrap();
+        let token_list = dump.get_stream::<MinidumpTokenList>().unwrap();
+        let tokens = token_list.iter().collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].raw.token_id, 1);
+        assert_eq!(tokens[0].raw.token_handle, 0xabcd);
+        assert_eq!(tokens[0].data, &[1, 2, 3, 4]);
+        assert_eq!(tokens[1].raw.token_id, 2);
+        assert_eq!(tokens[1].raw.token_handle, 0x1234);
+        assert_eq!(tokens[1].data, &[5, 6]);
+    }
+
+    #[test]
+    fn test_token_list_truncated() {
+        let section = Section::with_endian(Endian::Little)
+            .D32(16 + 16 + 8)
+            .D32(1)
+            .D32(16)
+            .D32(16)
+            // token claims more data than the stream holds
+            .D32(16 + 8)
+            .D32(1)
+            .D64(0xabcd)
+            .append_bytes(&[1, 2, 3, 4]);
+        let dump = SynthMinidump::with_endian(Endian::Little).add_stream(SimpleStream {
+            stream_type: MINIDUMP_STREAM_TYPE::TokenStream as u32,
+            section,
+        });
         let dump = read_synth_dump(dump).unwrap();
-        let module_list = dump.get_stream::<MinidumpUnloadedModuleList>().unwrap();
-        let modules = module_list.iter().collect::<Vec<_>>();
-        assert_eq!(modules.len(), 1);
-        assert_eq!(modules[0].base_address(), 0xa90206ca83eb2852);
-        assert_eq!(modules[0].size(), 0xada542bd);
-        assert_eq!(modules[0].code_file(), "single module");
-        // time_date_stamp and size_of_image concatenated
         assert_eq!(
-            modules[0].code_identifier().unwrap(),
-            CodeId::new("B1054D2Aada542bd".to_string())
+            dump.get_stream::<MinidumpTokenList>().unwrap_err(),
+            Error::StreamReadFailure
         );
     }
 
@@ -5638,6 +8381,9 @@ mod test {
         assert_eq!(infos[0].state, info1_state);
         assert_eq!(infos[0].ty, info1_ty);
         assert!(infos[0].is_executable());
+        assert!(!infos[0].is_committed());
+        assert!(!infos[0].is_guard_page());
+        assert_eq!(infos[0].region_type(), Some(MemoryRegionType::Mapped));
 
         assert_eq!(infos[1].raw.base_address, 0xd70206ca83eb2852);
         assert_eq!(infos[1].raw.allocation_base, 0xb802064383eb2752);
@@ -5655,6 +8401,186 @@ mod test {
         assert_eq!(infos[1].state, info2_state);
         assert_eq!(infos[1].ty, info2_ty);
         assert!(!infos[1].is_executable());
+        assert!(infos[1].is_committed());
+        assert_eq!(infos[1].region_type(), Some(MemoryRegionType::Private));
+    }
+
+    #[test]
+    fn test_memory_info_protection() {
+        // Committed code, and a guard page below a stack.
+        let code = SynthMemoryInfo::new(
+            Endian::Little,
+            0x10000,
+            0x10000,
+            md::MemoryProtection::PAGE_READONLY.bits(),
+            0x1000,
+            md::MemoryState::MEM_COMMIT.bits(),
+            md::MemoryProtection::PAGE_EXECUTE_READ.bits(),
+            md::MemoryType::MEM_IMAGE.bits(),
+        );
+        let guard = SynthMemoryInfo::new(
+            Endian::Little,
+            0x20000,
+            0x20000,
+            md::MemoryProtection::PAGE_READWRITE.bits(),
+            0x1000,
+            md::MemoryState::MEM_COMMIT.bits(),
+            (md::MemoryProtection::PAGE_READWRITE | md::MemoryProtection::PAGE_GUARD).bits(),
+            md::MemoryType::MEM_PRIVATE.bits(),
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory_info(code)
+            .add_memory_info(guard);
+        let dump = read_synth_dump(dump).unwrap();
+        let info_list = dump.get_stream::<MinidumpMemoryInfoList>().unwrap();
+
+        let code = info_list.memory_info_at_address(0x10800).unwrap();
+        assert!(code.is_readable());
+        assert!(!code.is_writable());
+        assert!(code.is_executable());
+        assert!(!code.is_guard_page());
+        assert!(code.is_committed());
+        assert_eq!(code.region_type(), Some(MemoryRegionType::Image));
+
+        let guard = info_list.memory_info_at_address(0x20800).unwrap();
+        assert!(guard.is_writable());
+        assert!(!guard.is_executable());
+        assert!(guard.is_guard_page());
+        assert!(guard.is_committed());
+        assert_eq!(guard.region_type(), Some(MemoryRegionType::Private));
+    }
+
+    #[test]
+    fn test_linux_cpu_info() {
+        // Two processors, a line without a separator, and doubled spaces in the flags
+        let input = b"processor\t: 0
+vendor_id\t: GenuineIntel
+model name\t: Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz
+flags\t\t: fpu vme  de pse sse4_2 avx2
+this line has no separator
+
+processor\t: 1
+model name\t: Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz
+flags\t\t: fpu vme de pse sse4_2 avx2
+";
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cpu_info(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let cpu_info = dump.get_stream::<MinidumpLinuxCpuInfo>().unwrap();
+
+        assert_eq!(
+            cpu_info.model_name().unwrap().as_bytes(),
+            b"Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz"
+        );
+        let flags = cpu_info.flags().map(|f| f.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(
+            flags,
+            vec![&b"fpu"[..], b"vme", b"de", b"pse", b"sse4_2", b"avx2"]
+        );
+        assert!(cpu_info.has_flag("avx2"));
+        assert!(!cpu_info.has_flag("avx512f"));
+        assert_eq!(cpu_info.core_count(), 2);
+        assert_eq!(&cpu_info.raw_bytes()[..], &input[..]);
+
+        // ARM calls them Features, and nothing is required to be present.
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .set_linux_cpu_info(b"Features\t: fp asimd crc32\n");
+        let dump = read_synth_dump(dump).unwrap();
+        let cpu_info = dump.get_stream::<MinidumpLinuxCpuInfo>().unwrap();
+        assert!(cpu_info.model_name().is_none());
+        assert!(cpu_info.has_flag("asimd"));
+        assert_eq!(cpu_info.core_count(), 0);
+    }
+
+    #[test]
+    fn test_linux_cmd_line() {
+        let args = |input: &[u8]| {
+            let dump = SynthMinidump::with_endian(Endian::Little).set_linux_cmd_line(input);
+            let dump = read_synth_dump(dump).unwrap();
+            let cmd_line = dump.get_stream::<MinidumpLinuxCmdLine>().unwrap();
+            cmd_line
+                .args()
+                .map(|arg| arg.as_bytes().to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        let expected = vec![
+            b"/usr/bin/app".to_vec(),
+            b"--flag".to_vec(),
+            b"two words".to_vec(),
+        ];
+        assert_eq!(args(b"/usr/bin/app\0--flag\0two words\0"), expected);
+        // A missing trailing NUL is tolerated
+        assert_eq!(args(b"/usr/bin/app\0--flag\0two words"), expected);
+        assert!(args(b"").is_empty());
+    }
+
+    #[test]
+    fn test_linux_environ() {
+        let input = b"A=1\0B=x=y\0garbage\0\0C=";
+
+        let dump = SynthMinidump::with_endian(Endian::Little).set_linux_environ(input);
+        let dump = read_synth_dump(dump).unwrap();
+        let environ = dump.get_stream::<MinidumpLinuxEnviron>().unwrap();
+
+        let vars = environ
+            .iter()
+            .map(|(key, val)| (key.as_bytes(), val.as_bytes()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vars,
+            vec![(&b"A"[..], &b"1"[..]), (b"B", b"x=y"), (b"C", b"")]
+        );
+    }
+
+    #[test]
+    fn test_linux_lsb_release() {
+        let lsb_release = |input: &'static [u8]| {
+            let dump = SynthMinidump::with_endian(Endian::Little).set_linux_lsb_release(input);
+            let dump = read_synth_dump(dump).unwrap();
+            let lsb = dump.get_stream::<MinidumpLinuxLsbRelease>().unwrap();
+            [lsb.id(), lsb.release(), lsb.codename(), lsb.description()]
+                .iter()
+                .map(|val| val.map(|val| val.to_string_lossy().into_owned()))
+                .collect::<Vec<_>>()
+        };
+        let owned = |vals: &[&str]| {
+            vals.iter()
+                .map(|val| Some(val.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        // lsb-release format
+        let input = b"DISTRIB_ID=Ubuntu
+DISTRIB_RELEASE=22.04
+DISTRIB_CODENAME=jammy
+DISTRIB_DESCRIPTION=\"Ubuntu 22.04.3 LTS\"
+";
+        assert_eq!(
+            lsb_release(input),
+            owned(&["Ubuntu", "22.04", "jammy", "Ubuntu 22.04.3 LTS"])
+        );
+
+        // os-release format, with some noise
+        let input = b"NAME=\"Fedora Linux\"
+VERSION_ID=39
+ID=fedora
+# a comment
+PRETTY_NAME=\"Fedora Linux 39 (Workstation Edition)\"
+";
+        assert_eq!(
+            lsb_release(input),
+            vec![
+                Some("fedora".to_owned()),
+                Some("39".to_owned()),
+                None,
+                Some("Fedora Linux 39 (Workstation Edition)".to_owned()),
+            ]
+        );
+
+        // lsb-release keys win when both are present
+        let input = b"ID=debian\nDISTRIB_ID=Debian\n";
+        assert_eq!(lsb_release(input)[0].as_deref(), Some("Debian"));
     }
 
     #[test]
@@ -6087,6 +9013,16 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
                 .code_file(),
             "module 5"
         );
+        // But all the overlaps are listed. Module 3 ends on the first byte of module 5.
+        assert_eq!(
+            module_list.overlaps(),
+            vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (2, 4)]
+        );
+        assert!(
+            MinidumpModuleList::from_modules(vec![modules[0].clone(), modules[4].clone()])
+                .overlaps()
+                .is_empty()
+        );
     }
 
     #[test]
@@ -6106,6 +9042,354 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(&regions[0].bytes, &CONTENTS);
     }
 
+    #[test]
+    fn test_read_pointer_32bit() {
+        let memory = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .D32(0x12345678)
+                .D32(0x9abcdef0),
+            0x1000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let pointer_width = dump.pointer_width();
+        assert_eq!(pointer_width, PointerWidth::Bits32);
+
+        let memory = dump.get_memory().unwrap();
+        assert_eq!(memory.read_pointer(0x1000, pointer_width), Some(0x12345678));
+        assert_eq!(memory.read_pointer(0x1004, pointer_width), Some(0x9abcdef0));
+        assert_eq!(memory.read_pointer(0x1006, pointer_width), None);
+        assert_eq!(
+            memory.read_pointer(0x1000, PointerWidth::Bits64),
+            Some(0x9abcdef012345678)
+        );
+        assert_eq!(memory.read_pointer(0x1000, PointerWidth::Unknown), None);
+    }
+
+    #[test]
+    fn test_follow_pointer() {
+        // A two-node linked list, with each node in its own region.
+        let node1 = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .D64(0x2000)
+                .D64(0x1122334455667788),
+            0x1000,
+        );
+        let node2 = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .D64(0)
+                .D64(0x99aabbccddeeff00),
+            0x2000,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(node1)
+            .add_memory(node2);
+        let dump = read_synth_dump(dump).unwrap();
+        let memory = dump.get_memory().unwrap();
+
+        assert_eq!(
+            memory.get_memory_at_address::<u64>(0x1008),
+            Some(0x1122334455667788)
+        );
+        assert_eq!(
+            memory.get_memory_at_address::<u32>(0x100c),
+            Some(0x11223344)
+        );
+        // Reads must fit in a region.
+        assert_eq!(memory.get_memory_at_address::<u64>(0x100c), None);
+        assert_eq!(memory.get_memory_at_address::<u64>(0x3000), None);
+
+        // Follow node1's next pointer to read node2's next pointer, then its value.
+        assert_eq!(
+            memory.follow_pointer::<u64>(0x1000, PointerWidth::Bits64),
+            Some(0)
+        );
+        let next = memory.read_pointer(0x1000, PointerWidth::Bits64).unwrap();
+        assert_eq!(
+            memory.get_memory_at_address::<u64>(next + 8),
+            Some(0x99aabbccddeeff00)
+        );
+        // node2's next pointer is null, which isn't in any region.
+        assert_eq!(
+            memory.follow_pointer::<u64>(0x2000, PointerWidth::Bits64),
+            None
+        );
+        assert_eq!(
+            memory.follow_pointer::<u64>(0x1000, PointerWidth::Unknown),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_strings() {
+        let wide = |endian| {
+            let mut section = Section::with_endian(endian);
+            for unit in "h\u{e9}llo\0".encode_utf16() {
+                section = section.D16(unit);
+            }
+            for unit in "unterminated".encode_utf16() {
+                section = section.D16(unit);
+            }
+            Memory::with_section(section, 0x2000)
+        };
+
+        for endian in [Endian::Little, Endian::Big] {
+            let dump = SynthMinidump::with_endian(endian)
+                .add_memory(Memory::with_section(
+                    Section::with_endian(endian)
+                        .append_bytes(b"hello\0")
+                        .append_bytes(b"unterminated"),
+                    0x1000,
+                ))
+                .add_memory(wide(endian));
+            let dump = read_synth_dump(dump).unwrap();
+            let memory = dump.get_memory().unwrap();
+
+            assert_eq!(memory.read_cstring(0x1000, 100), Some(&b"hello"[..]));
+            assert_eq!(memory.read_cstring(0x1002, 100), Some(&b"llo"[..]));
+            assert_eq!(memory.read_cstring(0x1000, 3), Some(&b"hel"[..]));
+            // Unterminated strings stop at the end of the region.
+            assert_eq!(memory.read_cstring(0x1006, 100), Some(&b"unterminated"[..]));
+            assert_eq!(memory.read_cstring(0x1012, 100), None);
+
+            assert_eq!(
+                memory.read_utf16z(0x2000, 100).as_deref(),
+                Some("h\u{e9}llo"),
+                "{:?}",
+                endian
+            );
+            assert_eq!(memory.read_utf16z(0x2000, 2).as_deref(), Some("h\u{e9}"));
+            assert_eq!(
+                memory.read_utf16z(0x200c, 100).as_deref(),
+                Some("unterminated")
+            );
+            assert_eq!(memory.read_utf16z(0x3000, 100), None);
+        }
+    }
+
+    #[test]
+    fn test_read_array() {
+        for endian in [Endian::Little, Endian::Big] {
+            let dump = SynthMinidump::with_endian(endian)
+                .add_memory(Memory::with_section(
+                    Section::with_endian(endian)
+                        .D32(1)
+                        .D32(2)
+                        .D32(3)
+                        .D32(4)
+                        .D16(5),
+                    0x1000,
+                ))
+                .add_memory(Memory::with_section(
+                    Section::with_endian(endian)
+                        .D32(0x10)
+                        .D32(0x100)
+                        .D32(0x20)
+                        .D32(0x200),
+                    0x2000,
+                ));
+            let dump = read_synth_dump(dump).unwrap();
+            let memory = dump.get_memory().unwrap();
+
+            assert_eq!(memory.read_array::<u32>(0x1000, 3), Some(vec![1, 2, 3]));
+            assert_eq!(memory.read_array::<u32>(0x1004, 0), Some(vec![]));
+            // Reading stops at the end of the region, even mid-value.
+            assert_eq!(memory.read_array::<u32>(0x1008, 10), Some(vec![3, 4]));
+            assert_eq!(memory.read_array::<u32>(0x1010, 10), Some(vec![]));
+            assert_eq!(memory.read_array::<u32>(0x3000, 10), None);
+
+            let descriptors = memory
+                .read_array::<md::MINIDUMP_LOCATION_DESCRIPTOR>(0x2000, 2)
+                .unwrap();
+            let descriptors = descriptors
+                .iter()
+                .map(|desc| (desc.data_size, desc.rva))
+                .collect::<Vec<_>>();
+            assert_eq!(descriptors, [(0x10, 0x100), (0x20, 0x200)]);
+        }
+    }
+
+    #[test]
+    fn test_pointer_width_from_context() {
+        let dump = read_synth_dump(SynthMinidump::with_endian(Endian::Little)).unwrap();
+        assert_eq!(dump.pointer_width(), PointerWidth::Unknown);
+
+        for endian in [Endian::Little, Endian::Big] {
+            let stack = Memory::with_section(
+                Section::with_endian(endian).append_repeated(0, 0x10),
+                0x1000,
+            );
+            let context = minidump_synth::x86_context(endian, 0xabcd1234, 0x1000);
+            let thread = Thread::new(endian, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack);
+            let dump = read_synth_dump(dump).unwrap();
+            assert_eq!(dump.pointer_width(), PointerWidth::Bits32);
+
+            let stack = Memory::with_section(
+                Section::with_endian(endian).append_repeated(0, 0x10),
+                0x1000,
+            );
+            let context = minidump_synth::amd64_context(endian, 0x1234abcd1234abcd, 0x1000);
+            let thread = Thread::new(endian, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack);
+            let dump = read_synth_dump(dump).unwrap();
+            assert_eq!(dump.pointer_width(), PointerWidth::Bits64);
+        }
+    }
+
+    #[test]
+    fn test_context_float_registers() {
+        // 1.0 as an 80-bit extended precision float.
+        const ST0: u128 = 0x3fff_8000_0000_0000_0000;
+        const XMM3: u128 = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut float = minidump_synth::FloatRegisters {
+            control_word: 0x27f,
+            status_word: 0x3800,
+            tag_word: 0x01,
+            mx_csr: 0x1fa0,
+            ..Default::default()
+        };
+        float.st_registers[0] = ST0;
+        float.xmm_registers[3] = XMM3;
+
+        for endian in [Endian::Little, Endian::Big] {
+            let scroll_endian = match endian {
+                Endian::Little => LE,
+                Endian::Big => BE,
+            };
+            let read_context = |context: Section, arch: md::ProcessorArchitecture| {
+                let stack = Memory::with_section(
+                    Section::with_endian(endian).append_repeated(0, 0x10),
+                    0x1000,
+                );
+                let thread = Thread::new(endian, 0x1234, &stack, &context);
+                let system_info = SystemInfo::new(endian).set_processor_architecture(arch as u16);
+                let dump = SynthMinidump::with_endian(endian)
+                    .add_thread(thread)
+                    .add(context)
+                    .add_memory(stack)
+                    .add_system_info(system_info);
+                let dump = read_synth_dump(dump).unwrap();
+                let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+                let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+                thread_list.threads[0]
+                    .context(&system_info, None)
+                    .unwrap()
+                    .into_owned()
+            };
+            let check_fxsave = |fxsave: &md::XMM_SAVE_AREA32| {
+                assert_eq!(fxsave.control_word, 0x27f);
+                assert_eq!(fxsave.status_word, 0x3800);
+                assert_eq!(fxsave.tag_word, 0x01);
+                assert_eq!(fxsave.mx_csr, 0x1fa0);
+                assert_eq!(fxsave.float_registers[0], ST0);
+                assert_eq!(fxsave.xmm_registers[3], XMM3);
+                assert_eq!(fxsave.xmm_registers[2], 0);
+            };
+
+            let context = read_context(
+                minidump_synth::amd64_context_with_float_registers(endian, 0x1234, 0x1000, &float),
+                md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64,
+            );
+            match &context.raw {
+                MinidumpRawContext::Amd64(raw) => {
+                    assert_eq!(raw.rip, 0x1234);
+                    assert_eq!(raw.mx_csr, 0x1fa0);
+                    check_fxsave(&raw.float_save.pread_with(0, scroll_endian).unwrap());
+                }
+                _ => panic!("Got unexpected raw context type!"),
+            }
+
+            let context = read_context(
+                minidump_synth::x86_context_with_float_registers(endian, 0x1234, 0x1000, &float),
+                md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL,
+            );
+            match &context.raw {
+                MinidumpRawContext::X86(raw) => {
+                    assert_eq!(raw.eip, 0x1234);
+                    assert_eq!(raw.float_save.control_word, 0x27f);
+                    assert_eq!(raw.float_save.status_word, 0x3800);
+                    // Only ST0 is valid, the rest are empty.
+                    assert_eq!(raw.float_save.tag_word, 0xfffc);
+                    let st0 = match endian {
+                        Endian::Little => ST0.to_le_bytes()[..10].to_vec(),
+                        Endian::Big => ST0.to_be_bytes()[6..].to_vec(),
+                    };
+                    assert_eq!(&raw.float_save.register_area[..10], &st0[..]);
+                    check_fxsave(&raw.extended_registers.pread_with(0, scroll_endian).unwrap());
+                }
+                _ => panic!("Got unexpected raw context type!"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_arm64ec_context() {
+        const XMM0: u128 = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut float = minidump_synth::FloatRegisters::default();
+        // mm0 holds lr in its low 64 bits and the low 16 bits of x16 above that.
+        float.st_registers[0] = 0x1111_0000_0000_0000_abcd;
+        float.st_registers[1] = 0x2222_0000_0000_0000_0006;
+        float.st_registers[2] = 0x3333_0000_0000_0000_0007;
+        float.st_registers[3] = 0x4444_0000_0000_0000_0009;
+        float.xmm_registers[0] = XMM0;
+        let context = minidump_synth::amd64_context_with_float_registers(
+            Endian::Little,
+            0x1234,
+            0x1000,
+            &float,
+        );
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x10),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(
+            md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let context = thread_list.threads[0].context(&system_info, None).unwrap();
+
+        // The emulated thread has an x64 context even though the system is ARM64.
+        assert_eq!(context.architecture(), Cpu::X86_64);
+        assert_eq!(context.get_instruction_pointer(), 0x1234);
+
+        let native = context.arm64ec_view().unwrap();
+        assert_eq!(native.architecture(), Cpu::Arm64);
+        assert_eq!(native.get_instruction_pointer(), 0x1234);
+        assert_eq!(native.get_stack_pointer(), 0x1000);
+        assert_eq!(native.get_register("lr"), Some(0xabcd));
+        assert_eq!(native.get_register("x6"), Some(6));
+        assert_eq!(native.get_register("x9"), Some(9));
+        assert_eq!(native.get_register("x16"), Some(0x4444_3333_2222_1111));
+        assert_eq!(native.get_register("x17"), Some(0));
+        assert_eq!(native.get_register("x13"), None);
+        match &native.raw {
+            MinidumpRawContext::Arm64(raw) => assert_eq!(raw.float_regs[0], XMM0),
+            _ => panic!("Got unexpected raw context type!"),
+        }
+
+        // Native ARM64 contexts have no ARM64EC view.
+        assert!(native.arm64ec_view().is_none());
+    }
+
     #[test]
     fn test_memory64_list() {
         const CONTENTS0: &[u8] = b"memory_contents";
@@ -6129,9 +9413,115 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(regions[0].size, CONTENTS0.len() as u64);
         assert_eq!(&regions[0].bytes, &CONTENTS0);
 
-        assert_eq!(regions[1].base_address, 0x1234);
-        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
-        assert_eq!(&regions[1].bytes, &CONTENTS1);
+        assert_eq!(regions[1].base_address, 0x1234);
+        assert_eq!(regions[1].size, CONTENTS1.len() as u64);
+        assert_eq!(&regions[1].bytes, &CONTENTS1);
+    }
+
+    #[test]
+    fn test_memory_list_resolve_overlaps() {
+        let memory0 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0xaa, 0x20),
+            0x1000,
+        );
+        let memory1 = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0xbb, 0x10),
+            0x1008,
+        );
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(memory0)
+            .add_memory(memory1);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        assert_eq!(memory_list.overlapping_regions(), &[(0, 1)]);
+
+        let read_byte = |list: &MinidumpMemoryList, addr: u64| -> u8 {
+            list.memory_at_address(addr)
+                .unwrap()
+                .get_memory_at_address(addr)
+                .unwrap()
+        };
+
+        // By default the lowest region wins.
+        assert_eq!(read_byte(&memory_list, 0x1000), 0xaa);
+        assert_eq!(read_byte(&memory_list, 0x100c), 0xaa);
+
+        // The later region wins where it overlaps, but not elsewhere.
+        memory_list.resolve_overlaps(MemoryOverlapPolicy::PreferLater);
+        assert_eq!(read_byte(&memory_list, 0x1000), 0xaa);
+        assert_eq!(read_byte(&memory_list, 0x1008), 0xbb);
+        assert_eq!(read_byte(&memory_list, 0x1017), 0xbb);
+        assert_eq!(read_byte(&memory_list, 0x1018), 0xaa);
+        assert_eq!(memory_list.by_addr().count(), 2);
+
+        memory_list.resolve_overlaps(MemoryOverlapPolicy::PreferLarger);
+        assert_eq!(read_byte(&memory_list, 0x100c), 0xaa);
+    }
+
+    #[test]
+    fn test_memory_list_nested_overlaps() {
+        // The second and third regions overlap each other, inside the first one.
+        let region = |base, size| {
+            Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0, size),
+                base,
+            )
+        };
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(region(0x1000, 100))
+            .add_memory(region(0x1010, 10))
+            .add_memory(region(0x1015, 15));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        assert_eq!(memory_list.overlapping_regions(), &[(0, 1), (0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_memory_list_regions_in_range() {
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_memory(Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0xcc, 0x10),
+                0x2000,
+            ))
+            .add_memory(Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0xaa, 0x10),
+                0x1000,
+            ))
+            .add_memory(Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0xbb, 0x10),
+                0x1010,
+            ))
+            .add_memory(Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0xdd, 0x10),
+                0x3000,
+            ));
+        let dump = read_synth_dump(dump).unwrap();
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+
+        let region = memory_list.memory_at_address(0x1000).unwrap();
+        assert!(region.contains(0x1000));
+        assert!(region.contains(0x100f));
+        assert!(!region.contains(0x1010));
+        assert!(!region.contains(0xfff));
+
+        let bases = |start: u64, len: u64| -> Vec<u64> {
+            memory_list
+                .regions_in_range(start, len)
+                .map(|region| region.base_address)
+                .collect()
+        };
+
+        // Sorted by address, skipping the gap between 0x1020 and 0x2000.
+        assert_eq!(bases(0x1008, 0x1000), vec![0x1000, 0x1010, 0x2000]);
+        // Partial overlaps at either end count.
+        assert_eq!(bases(0xff0, 0x11), vec![0x1000]);
+        assert_eq!(bases(0x200f, 0x2000), vec![0x2000, 0x3000]);
+        // Ranges that only touch a region, or are entirely in a gap, don't.
+        assert_eq!(bases(0xff0, 0x10), Vec::<u64>::new());
+        assert_eq!(bases(0x1020, 0xfe0), Vec::<u64>::new());
+        assert_eq!(bases(0x1000, 0), Vec::<u64>::new());
+        // Ranges running off the end of the address space are clamped.
+        assert_eq!(bases(0x3008, u64::MAX), vec![0x3000]);
     }
 
     #[test]
@@ -6631,6 +10021,101 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(modules[0].debug_file().unwrap(), "helpivecrashed.dylib");
     }
 
+    #[test]
+    fn test_elf_build_id_breakpad_format() {
+        // A GNU build id, as reported by `readelf -n`.
+        const BUILD_ID: &[u8] = &[
+            0xb4, 0x3d, 0x6a, 0x1c, 0x02, 0x9e, 0x7f, 0x55, 0x88, 0x13, 0xc6, 0x0a, 0x4e, 0x21,
+            0x97, 0xd0, 0x3f, 0x6b, 0xe2, 0x15,
+        ];
+        // The build id doesn't depend on the minidump's endianness, so neither should
+        // the debug id derived from it.
+        for endian in [Endian::Little, Endian::Big] {
+            let name = DumpString::new("libfoo.so", endian);
+            let cv_record = Section::with_endian(endian)
+                .D32(md::CvSignature::Elf as u32)
+                .append_bytes(BUILD_ID);
+            let module = SynthModule::new(
+                endian,
+                0x7f0000000000,
+                0x4000,
+                &name,
+                0,
+                0,
+                Some(&STOCK_VERSION_INFO),
+            )
+            .cv_record(&cv_record);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_module(module)
+                .add(name)
+                .add(cv_record);
+            let dump = read_synth_dump(dump).unwrap();
+            let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let module = module_list.iter().next().unwrap();
+
+            // The first three fields of the GUID are byte-swapped and the age is 0.
+            assert_eq!(
+                module.debug_identifier().unwrap().breakpad().to_string(),
+                "1C6A3DB49E02557F8813C60A4E2197D00"
+            );
+            assert_eq!(
+                module.code_identifier().unwrap(),
+                CodeId::new("b43d6a1c029e7f558813c60a4e2197d03f6be215".to_owned())
+            );
+        }
+    }
+
+    #[test]
+    fn test_macos_debug_id_breakpad_format() {
+        // The LC_UUID of a Mach-O binary, as reported by `dwarfdump --uuid`.
+        const UUID: [u8; 16] = [
+            0x9f, 0x2a, 0x0d, 0x5e, 0x13, 0x8c, 0x3b, 0x41, 0xa8, 0x20, 0x5d, 0x1e, 0x6b, 0x77,
+            0xc4, 0x03,
+        ];
+        for endian in [Endian::Little, Endian::Big] {
+            let name = DumpString::new("libfoo.dylib", endian);
+            // The dumper converts the UUID into GUID fields before writing them out
+            // with the minidump's endianness.
+            let cv_record = Section::with_endian(endian)
+                .D32(md::CvSignature::Pdb70 as u32)
+                .D32(u32::from_be_bytes(UUID[0..4].try_into().unwrap()))
+                .D16(u16::from_be_bytes(UUID[4..6].try_into().unwrap()))
+                .D16(u16::from_be_bytes(UUID[6..8].try_into().unwrap()))
+                .append_bytes(&UUID[8..])
+                // age
+                .D32(0)
+                .append_bytes(b"libfoo.dylib\0");
+            let module = SynthModule::new(
+                endian,
+                0x100000000,
+                0x4000,
+                &name,
+                0,
+                0,
+                Some(&STOCK_VERSION_INFO),
+            )
+            .cv_record(&cv_record);
+            let dump = SynthMinidump::with_endian(endian)
+                .add_system_info(SystemInfo::new(endian).set_platform_id(PlatformId::MacOs as u32))
+                .add_module(module)
+                .add(name)
+                .add(cv_record);
+            let dump = read_synth_dump(dump).unwrap();
+            let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let module = module_list.iter().next().unwrap();
+
+            // The id used in symbol file paths and the MODULE record of the .sym file.
+            assert_eq!(
+                module.debug_identifier().unwrap().breakpad().to_string(),
+                "9F2A0D5E138C3B41A8205D1E6B77C4030"
+            );
+            assert_eq!(
+                module.code_identifier().unwrap(),
+                CodeId::new("9F2A0D5E138C3B41A8205D1E6B77C403".to_owned())
+            );
+        }
+    }
+
     #[test]
     fn test_windows_code_id_no_cv() {
         let name = DumpString::new("windows module", Endian::Little);
@@ -6664,6 +10149,74 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         );
     }
 
+    #[test]
+    fn test_code_id_per_platform() {
+        let code_id = |platform_id: PlatformId, cv_record: Section| {
+            let name = DumpString::new("module", Endian::Little);
+            let module = SynthModule::new(
+                Endian::Little,
+                0x100000000,
+                0x1e000, // size of image
+                &name,
+                0xb1054d2a, // datetime
+                0x34571371,
+                Some(&STOCK_VERSION_INFO),
+            )
+            .cv_record(&cv_record);
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_system_info(
+                    SystemInfo::new(Endian::Little).set_platform_id(platform_id as u32),
+                )
+                .add_module(module)
+                .add(name)
+                .add(cv_record);
+            let dump = read_synth_dump(dump).unwrap();
+            let module_list = dump.get_stream::<MinidumpModuleList>().unwrap();
+            let code_id = module_list.iter().next().unwrap().code_identifier();
+            code_id.map(|id| id.as_str().to_owned())
+        };
+        let pdb70 = |name: &[u8]| {
+            Section::with_endian(Endian::Little)
+                .D32(md::CvSignature::Pdb70 as u32)
+                .D32(0xaabbccdd)
+                .D16(0xeeff)
+                .D16(0x0011)
+                .append_bytes(b"\x22\x33\x44\x55\x66\x77\x88\x99")
+                .D32(1)
+                .append_bytes(name)
+        };
+        let elf = |build_id: &[u8]| {
+            Section::with_endian(Endian::Little)
+                .D32(md::CvSignature::Elf as u32)
+                .append_bytes(build_id)
+        };
+
+        // PE: the datetime followed by the size of image.
+        assert_eq!(
+            code_id(PlatformId::VER_PLATFORM_WIN32_NT, pdb70(b"module.pdb\0")).as_deref(),
+            Some("b1054d2a1e000")
+        );
+        // ELF: the build id.
+        assert_eq!(
+            code_id(PlatformId::Linux, elf(b"\xde\xad\xbe\xef\x01\x02")).as_deref(),
+            Some("deadbeef0102")
+        );
+        assert_eq!(
+            code_id(PlatformId::Android, elf(b"\xde\xad\xbe\xef\x01\x02")).as_deref(),
+            Some("deadbeef0102")
+        );
+        assert_eq!(code_id(PlatformId::Linux, elf(&[0; 20])), None);
+        // Mach-O: the uuid, without dashes.
+        assert_eq!(
+            code_id(PlatformId::MacOs, pdb70(b"module.dylib\0")).as_deref(),
+            Some("aabbccddeeff00112233445566778899")
+        );
+        assert_eq!(
+            code_id(PlatformId::Ios, pdb70(b"module.dylib\0")).as_deref(),
+            Some("aabbccddeeff00112233445566778899")
+        );
+    }
+
     #[test]
     fn test_null_id() {
         // Add a module with an ELF build id of nothing but zeros
@@ -6798,6 +10351,409 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         assert_eq!(stack.size, 0x1000);
     }
 
+    #[test]
+    fn test_thread_xstate_amd64() {
+        // A context with the XSTATE flag, followed by an AVX-512 ZMM_Hi256 area.
+        const AVX_OFFSET: u32 = 576;
+        const ZMM_H_OFFSET: u32 = 1152;
+        let mut context_bytes =
+            minidump_synth::amd64_context(Endian::Little, 0x1234abcd1234abcd, 0x1000000010000000)
+                .get_contents()
+                .unwrap();
+        context_bytes.resize(256 + ZMM_H_OFFSET as usize + 16 * 32, 0);
+        // context_flags: CONTEXT_ALL | CONTEXT_HAS_XSTATE
+        context_bytes[48..52].copy_from_slice(&(0x10001f | md::CONTEXT_HAS_XSTATE).to_le_bytes());
+        // xmm3 in the legacy area, and the upper halves of ymm3 and zmm3.
+        context_bytes[256 + 160 + 3 * 16..][..16].copy_from_slice(&[0x11; 16]);
+        let avx = 256 + AVX_OFFSET as usize;
+        context_bytes[avx + 3 * 16..][..16].copy_from_slice(&[0x22; 16]);
+        let zmm_h = 256 + ZMM_H_OFFSET as usize;
+        context_bytes[zmm_h + 3 * 32..][..32].copy_from_slice(&[0x33; 32]);
+        // XSTATE_BV in the XSAVE header: AVX-512 state is in its initial state.
+        let xstate_bv: u64 = (1 << md::XstateFeatureIndex::LEGACY_SSE as u64)
+            | (1 << md::XstateFeatureIndex::GSSE_AND_AVX as u64);
+        context_bytes[256 + 512..][..8].copy_from_slice(&xstate_bv.to_le_bytes());
+        let context = Section::with_endian(Endian::Little).append_bytes(&context_bytes);
+
+        let mut features = [md::XSTATE_FEATURE::default(); 64];
+        features[md::XstateFeatureIndex::LEGACY_FLOATING_POINT as usize] = md::XSTATE_FEATURE {
+            offset: 0,
+            size: 160,
+        };
+        features[md::XstateFeatureIndex::LEGACY_SSE as usize] = md::XSTATE_FEATURE {
+            offset: 160,
+            size: 256,
+        };
+        features[md::XstateFeatureIndex::GSSE_AND_AVX as usize] = md::XSTATE_FEATURE {
+            offset: AVX_OFFSET,
+            size: 256,
+        };
+        features[md::XstateFeatureIndex::AVX512_ZMM_H as usize] = md::XSTATE_FEATURE {
+            offset: ZMM_H_OFFSET,
+            size: 512,
+        };
+        let enabled_features = (1 << md::XstateFeatureIndex::LEGACY_FLOATING_POINT as u64)
+            | (1 << md::XstateFeatureIndex::LEGACY_SSE as u64)
+            | (1 << md::XstateFeatureIndex::GSSE_AND_AVX as u64)
+            | (1 << md::XstateFeatureIndex::AVX512_ZMM_H as u64);
+        let mut misc = MiscStream::new(Endian::Little);
+        misc.misc_5 = Some(MiscInfo5Fields {
+            xstate_data: md::XSTATE_CONFIG_FEATURE_MSC_INFO {
+                size_of_info: mem::size_of::<md::XSTATE_CONFIG_FEATURE_MSC_INFO>() as u32,
+                context_size: context_bytes.len() as u32,
+                enabled_features,
+                features,
+            },
+            process_cookie: None,
+        });
+
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000000010000000,
+        );
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(system_info)
+            .add_stream(misc);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let misc = dump.get_stream::<MinidumpMiscInfo>().unwrap();
+        let thread = &thread_list.threads[0];
+
+        // The context itself still parses.
+        let context = thread.context(&system_info, Some(&misc)).unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0x1234abcd1234abcd);
+
+        // Without the XSTATE configuration there's nothing to read.
+        assert!(thread.xstate(&system_info, None).is_none());
+
+        let xstate = thread.xstate(&system_info, Some(&misc)).unwrap();
+        assert_eq!(xstate.enabled_features(), enabled_features);
+        assert_eq!(
+            xstate
+                .feature(md::XstateFeatureIndex::GSSE_AND_AVX)
+                .unwrap()
+                .len(),
+            256
+        );
+        assert!(xstate.feature(md::XstateFeatureIndex::ACK512_ZMM).is_none());
+        assert!(!xstate.is_initial(md::XstateFeatureIndex::GSSE_AND_AVX));
+        assert!(xstate.is_initial(md::XstateFeatureIndex::AVX512_ZMM_H));
+
+        let mut ymm3 = [0x11; 32];
+        ymm3[16..].copy_from_slice(&[0x22; 16]);
+        assert_eq!(xstate.ymm(3), Some(ymm3));
+        assert_eq!(xstate.ymm(2), Some([0; 32]));
+        assert_eq!(xstate.ymm(16), None);
+
+        // zmm3's upper half is ignored because AVX-512 is in its initial state.
+        let mut zmm3 = [0; 64];
+        zmm3[..32].copy_from_slice(&ymm3);
+        assert_eq!(xstate.zmm(3), Some(zmm3));
+        assert_eq!(xstate.zmm(16), None);
+    }
+
+    #[test]
+    fn test_stream_raw_bytes() {
+        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(SystemInfo::new(Endian::Little));
+        let dump = read_synth_dump(dump).unwrap();
+
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let raw = dump
+            .get_raw_stream(MinidumpThreadList::STREAM_TYPE)
+            .unwrap();
+        // The very same bytes, not a copy of them.
+        assert_eq!(thread_list.raw_bytes().as_ptr(), raw.as_ptr());
+        assert_eq!(thread_list.raw_bytes().len(), raw.len());
+
+        let memory_list = dump.get_stream::<MinidumpMemoryList<'_>>().unwrap();
+        let raw = dump
+            .get_raw_stream(MinidumpMemoryList::STREAM_TYPE)
+            .unwrap();
+        assert_eq!(memory_list.raw_bytes().as_ptr(), raw.as_ptr());
+        assert_eq!(memory_list.raw_bytes().len(), raw.len());
+
+        assert!(MinidumpMemoryList::new().raw_bytes().is_empty());
+
+        // Streams that don't keep their bytes can still be read raw by type.
+        let raw = dump.get_raw_stream_of::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(
+            raw.as_ptr(),
+            dump.get_raw_stream(MinidumpSystemInfo::STREAM_TYPE)
+                .unwrap()
+                .as_ptr()
+        );
+        assert_eq!(raw.len(), <md::MINIDUMP_SYSTEM_INFO>::size_with(&LE));
+        assert_eq!(
+            dump.get_raw_stream_of::<MinidumpException>().err(),
+            Some(Error::StreamNotFound)
+        );
+    }
+
+    #[test]
+    fn test_architecture() {
+        let context = minidump_synth::amd64_context(Endian::Little, 0x1234abcd, 0x1000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack);
+        let dump = read_synth_dump(dump).unwrap();
+        assert!(dump.get_stream::<MinidumpSystemInfo>().is_err());
+        assert_eq!(dump.architecture(), Some(Cpu::X86_64));
+
+        // SystemInfo wins over the contexts.
+        let context = minidump_synth::amd64_context(Endian::Little, 0x1234abcd, 0x1000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_ARM64 as u16;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(SystemInfo::new(Endian::Little).set_processor_architecture(arch));
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.architecture(), Some(Cpu::Arm64));
+
+        // Nothing to go on.
+        let dump = read_synth_dump(SynthMinidump::with_endian(Endian::Little)).unwrap();
+        assert_eq!(dump.architecture(), None);
+    }
+
+    #[test]
+    fn test_streams_present() {
+        let context = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(context)
+            .add_memory(stack)
+            .add_system_info(SystemInfo::new(Endian::Little));
+        let dump = read_synth_dump(dump).unwrap();
+
+        let expected = [
+            MINIDUMP_STREAM_TYPE::ThreadListStream as u32,
+            MINIDUMP_STREAM_TYPE::MemoryListStream as u32,
+            MINIDUMP_STREAM_TYPE::SystemInfoStream as u32,
+        ];
+        assert_eq!(dump.streams_present(), expected.iter().copied().collect());
+    }
+
+    #[test]
+    fn test_thread_list_duplicate_ids() {
+        let context1 = minidump_synth::x86_context(Endian::Little, 0xabcd1234, 0x1010);
+        let context2 = minidump_synth::x86_context(Endian::Little, 0xabcd5678, 0x1010);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x1000),
+            0x1000,
+        );
+        let system_info = SystemInfo::new(Endian::Little);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(Thread::new(Endian::Little, 0x1234, &stack, &context1))
+            .add_thread(Thread::new(Endian::Little, 0x5678, &stack, &context1))
+            .add_thread(Thread::new(Endian::Little, 0x1234, &stack, &context2))
+            .add(context1)
+            .add(context2)
+            .add_memory(stack)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let mut thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+
+        assert_eq!(thread_list.threads.len(), 3);
+        assert_eq!(thread_list.duplicate_thread_ids(), vec![0x1234]);
+        // The first of the duplicates is the one that gets looked up.
+        let context = thread_list
+            .get_thread(0x1234)
+            .unwrap()
+            .context(&system_info, None)
+            .unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0xabcd1234);
+
+        thread_list.deduplicate();
+        let ids = thread_list
+            .threads
+            .iter()
+            .map(|thread| thread.raw.thread_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0x1234, 0x5678]);
+        assert!(thread_list.duplicate_thread_ids().is_empty());
+        let context = thread_list
+            .get_thread(0x5678)
+            .unwrap()
+            .context(&system_info, None)
+            .unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0xabcd1234);
+        let context = thread_list.threads[0].context(&system_info, None).unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0xabcd1234);
+    }
+
+    #[test]
+    fn test_thread_truncated_context() {
+        let full_context = minidump_synth::amd64_context_with_frame_pointer(
+            Endian::Little,
+            0x1234abcd1234abcd,
+            0x1000000010000000,
+            0x1000000010000100,
+        )
+        .get_contents()
+        .unwrap();
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let read_context = |len: usize| {
+            let context = Section::with_endian(Endian::Little).append_bytes(&full_context[..len]);
+            let stack = Memory::with_section(
+                Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+                0x1000000010000000,
+            );
+            let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+            let thread = Thread::new(Endian::Little, 0x1234, &stack, &context);
+            let dump = SynthMinidump::with_endian(Endian::Little)
+                .add_thread(thread)
+                .add(context)
+                .add_memory(stack)
+                .add_system_info(system_info);
+            let dump = read_synth_dump(dump).unwrap();
+            let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+            let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+            thread_list.threads[0]
+                .context(&system_info, None)
+                .map(Cow::into_owned)
+        };
+
+        // Everything up to and including rip is present.
+        let context = read_context(0x100).expect("Should have a thread context");
+        assert!(matches!(context.valid, MinidumpContextValidity::Some(_)));
+        assert_eq!(context.get_register("rip"), Some(0x1234abcd1234abcd));
+        assert_eq!(context.get_register("rsp"), Some(0x1000000010000000));
+        assert_eq!(context.get_register("rbp"), Some(0x1000000010000100));
+        assert_eq!(context.get_register("r15"), Some(0));
+
+        // Cut off in the middle of rbp.
+        let context = read_context(0xa4).expect("Should have a thread context");
+        assert_eq!(context.get_register("rsp"), Some(0x1000000010000000));
+        assert_eq!(context.get_register("rax"), Some(0));
+        assert_eq!(context.get_register("rbp"), None);
+        assert_eq!(context.get_register("rip"), None);
+
+        // Without the context flags, there's no telling what this is.
+        assert!(read_context(0x30).is_none());
+
+        // A complete context is still entirely valid.
+        let context = read_context(full_context.len()).expect("Should have a thread context");
+        assert_eq!(context.valid, MinidumpContextValidity::All);
+    }
+
+    #[test]
+    fn test_thread_raw_context_bytes() {
+        let context = || minidump_synth::amd64_context(Endian::Little, 0x1234abcd, 0x10000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x10000000,
+        );
+        let expected = context().get_contents().unwrap();
+        let thread_context = context();
+        let thread = Thread::new(Endian::Little, 0x1234, &stack, &thread_context);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread)
+            .add(thread_context)
+            .add_memory(stack);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let thread = &thread_list.threads[0];
+
+        assert_eq!(thread.raw_context_bytes(), Some(&expected[..]));
+        // Without system info, the architecture is guessed from the context.
+        assert_eq!(thread.raw_context_cpu(None), Some(Cpu::X86_64));
+
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_INTEL as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = read_synth_dump(
+            SynthMinidump::with_endian(Endian::Little).add_system_info(system_info),
+        )
+        .unwrap();
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        assert_eq!(thread.raw_context_cpu(Some(&system_info)), Some(Cpu::X86));
+    }
+
+    #[test]
+    fn test_thread_teb() {
+        let context = minidump_synth::amd64_context(Endian::Little, 0x1234abcd, 0x10000000);
+        let stack = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0, 0x100),
+            0x10000000,
+        );
+        let teb = Memory::with_section(
+            Section::with_endian(Endian::Little)
+                .append_repeated(0, 0x60)
+                // ProcessEnvironmentBlock
+                .D64(0x7ffd0000)
+                // LastErrorValue: ERROR_ACCESS_DENIED
+                .D32(5)
+                .append_repeated(0, 0x14),
+            0x7ffe0000,
+        );
+        let thread1 = Thread::new(Endian::Little, 1, &stack, &context).teb(0x7ffe0000);
+        let thread2 = Thread::new(Endian::Little, 2, &stack, &context).teb(0x7ffc0000);
+        let arch = md::ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16;
+        let system_info = SystemInfo::new(Endian::Little).set_processor_architecture(arch);
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_thread(thread1)
+            .add_thread(thread2)
+            .add(context)
+            .add_memory(stack)
+            .add_memory(teb)
+            .add_system_info(system_info);
+        let dump = read_synth_dump(dump).unwrap();
+        let thread_list = dump.get_stream::<MinidumpThreadList<'_>>().unwrap();
+        let memory = dump.get_memory().unwrap();
+
+        let thread = &thread_list.threads[0];
+        assert_eq!(thread.teb(), 0x7ffe0000);
+        assert_eq!(thread.peb(Cpu::X86_64, &memory), Some(0x7ffd0000));
+        assert_eq!(thread.last_error_value(Cpu::X86_64, &memory), Some(5));
+        assert_eq!(
+            thread.last_error(Cpu::X86_64, &memory),
+            Some(CrashReason::from_windows_error(5))
+        );
+        // The fields are at different offsets in a 32-bit TEB.
+        assert_eq!(thread.peb(Cpu::X86, &memory), Some(0));
+        assert_eq!(thread.last_error_value(Cpu::X86, &memory), Some(0));
+
+        // The TEB of this one isn't in the dump.
+        let thread = &thread_list.threads[1];
+        assert_eq!(thread.teb(), 0x7ffc0000);
+        assert_eq!(thread.peb(Cpu::X86_64, &memory), None);
+        assert_eq!(thread.last_error_value(Cpu::X86_64, &memory), None);
+        assert_eq!(thread.last_error(Cpu::X86_64, &memory), None);
+    }
+
     #[test]
     fn test_crashpad_info_missing() {
         let dump = SynthMinidump::with_endian(Endian::Little);
@@ -6922,6 +10878,154 @@ c70206ca83eb2852-de0206ca83eb2852  -w-s  10bac9000 fd:05 1196511 /usr/lib64/libt
         );
     }
 
+    #[test]
+    fn test_exception_context_in_memory() {
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16);
+        // The context is only in the dumped memory, the exception stream just has its address.
+        let context = Memory::with_section(
+            minidump_synth::amd64_context(Endian::Little, 0x4000_1234, 0x8_0000),
+            0x1_0000,
+        );
+        let not_a_context = Memory::with_section(
+            Section::with_endian(Endian::Little).append_repeated(0xaa, 0x1000),
+            0x2_0000,
+        );
+
+        let mut exception = Exception::new(Endian::Little);
+        exception.exception_record.number_parameters = 4;
+        exception.exception_record.exception_information[..4].copy_from_slice(&[
+            0,
+            0xdead_0000,
+            0x2_0000,
+            0x1_0000,
+        ]);
+        // Not a parameter, so it's never looked at.
+        exception.exception_record.exception_information[4] = 0x1_0000;
+
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception)
+            .add_memory(context)
+            .add_memory(not_a_context);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let system_info = dump.get_stream::<MinidumpSystemInfo>().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        let memory = dump.get_memory().unwrap();
+        assert!(exception.context(&system_info, None).is_none());
+        let context = exception
+            .context_with_memory(&system_info, None, &memory)
+            .unwrap();
+        assert_eq!(context.get_instruction_pointer(), 0x4000_1234);
+        assert_eq!(context.get_stack_pointer(), 0x8_0000);
+
+        // Without the parameter pointing at it, there's nothing to recover.
+        let mut raw = exception.raw.clone();
+        raw.exception_record.number_parameters = 3;
+        let exception = MinidumpException { raw, ..exception };
+        assert!(exception
+            .context_with_memory(&system_info, None, &memory)
+            .is_none());
+    }
+
+    #[test]
+    fn test_chained_exceptions() {
+        // A rethrown C++ exception, linked to the access violation that caused it,
+        // which is linked to a record that wasn't captured.
+        const CPP_EXCEPTION: u32 = 0xe06d7363;
+        const ACCESS_VIOLATION: u32 = 0xc0000005;
+        let record64 = |section: Section, code: u32, next: u64, address: u64| {
+            section
+                .D32(code)
+                .D32(0) // exception_flags
+                .D64(next)
+                .D64(address)
+                .D32(2) // number_parameters
+                .D32(0) // __align
+                .D64(0)
+                .D64(0x10)
+                .append_repeated(0, 13 * 8)
+        };
+        let records = Section::with_endian(Endian::Little);
+        let records = record64(records, CPP_EXCEPTION, 0x20098, 0x7ff012345678);
+        let records = record64(records, ACCESS_VIOLATION, 0x50000, 0x7ff000001000);
+        let memory = Memory::with_section(records, 0x20000);
+
+        let system_info = SystemInfo::new(Endian::Little)
+            .set_processor_architecture(ProcessorArchitecture::PROCESSOR_ARCHITECTURE_AMD64 as u16)
+            .set_platform_id(PlatformId::VER_PLATFORM_WIN32_NT as u32);
+        let mut exception = Exception::new(Endian::Little);
+        exception.thread_id = 0x1234;
+        exception.exception_record.exception_code = CPP_EXCEPTION;
+        exception.exception_record.exception_record = 0x20000;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(system_info)
+            .add_exception(exception)
+            .add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+
+        let memory = dump.get_memory().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        let chain = exception.chained_exceptions(&memory, dump.pointer_width());
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].raw.exception_record.exception_code, CPP_EXCEPTION);
+        assert_eq!(
+            chain[0].get_crash_address(Os::Windows, Cpu::X86_64),
+            0x7ff012345678
+        );
+        assert_eq!(
+            chain[1].raw.exception_record.exception_code,
+            ACCESS_VIOLATION
+        );
+        assert_eq!(chain[1].get_crash_address(Os::Windows, Cpu::X86_64), 0x10);
+        assert_eq!(chain[1].get_crashing_thread_id(), 0x1234);
+        assert!(chain[1].context.is_none());
+    }
+
+    #[test]
+    fn test_chained_exceptions_cycle_32bit() {
+        // Two 32-bit records that link to each other.
+        let record32 = |section: Section, code: u32, next: u32| {
+            section
+                .D32(code)
+                .D32(0) // exception_flags
+                .D32(next)
+                .D32(0x401000) // exception_address
+                .D32(0) // number_parameters
+                .append_repeated(0, 15 * 4)
+        };
+        let records = Section::with_endian(Endian::Little);
+        let records = record32(records, 1, 0x20050);
+        let records = record32(records, 2, 0x20000);
+        let memory = Memory::with_section(records, 0x20000);
+
+        let mut exception = Exception::new(Endian::Little);
+        exception.exception_record.exception_record = 0x20000;
+        let dump = SynthMinidump::with_endian(Endian::Little)
+            .add_system_info(SystemInfo::new(Endian::Little))
+            .add_exception(exception)
+            .add_memory(memory);
+        let dump = read_synth_dump(dump).unwrap();
+        assert_eq!(dump.pointer_width(), PointerWidth::Bits32);
+
+        let memory = dump.get_memory().unwrap();
+        let exception = dump.get_stream::<MinidumpException>().unwrap();
+        let chain = exception.chained_exceptions(&memory, dump.pointer_width());
+        let codes: Vec<u32> = chain
+            .iter()
+            .map(|exception| exception.raw.exception_record.exception_code)
+            .collect();
+        assert_eq!(codes, [1, 2]);
+        assert_eq!(chain[1].raw.exception_record.exception_address, 0x401000);
+
+        // Without any memory there's nothing to follow.
+        let no_memory = UnifiedMemoryList::default();
+        assert!(exception
+            .chained_exceptions(&no_memory, PointerWidth::Bits32)
+            .is_empty());
+    }
+
     #[test]
     fn test_fuzzed_oom() {
         // https://github.com/rust-minidump/rust-minidump/issues/381